@@ -1,17 +1,29 @@
 use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use crate::admin::cmd::AdminCmd;
+use crate::db::bus::{self, EventBus};
+use crate::db::raft::ClusterError;
+use crate::lan::cmd::LanCmd;
+use crate::org::cmd::OrgCmd;
+use crate::rack::cmd::RackCmd;
 use crate::trunk::cmd::TrunkCmd;
 use crate::wan::cmd::WanCmd;
 use crate::util::actor::{Actor, Process};
 
 #[derive(Debug)]
 pub struct RackdCmdActor {
-    pub conn: Connection
+    pub conn: Connection,
+    pub bus: EventBus
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub enum RackdCmd {
     Trunk(TrunkCmd),
-    Wan(WanCmd)
+    Wan(WanCmd),
+    Lan(LanCmd),
+    Org(OrgCmd),
+    Rack(RackCmd),
+    Admin(AdminCmd)
 }
 
 impl Actor for RackdCmdActor {
@@ -36,18 +48,108 @@ impl Actor for RackdCmdActor {
                     let response = cmd.payload.process(self);
                     let _ = cmd.respond_to.send(response);
                 },
-                // WanCmd::SetIpv6(cmd) => {
-                //     let response = cmd.payload.process(self);
-                //     let _ = cmd.respond_to.send(response);
-                // }
+                WanCmd::SetIpv6(cmd) => {
+                    let response = cmd.payload.process(self);
+                    let _ = cmd.respond_to.send(response);
+                },
+                WanCmd::SetDhcp6Duid(cmd) => {
+                    let response = cmd.payload.process(self);
+                    let _ = cmd.respond_to.send(response);
+                },
+                WanCmd::SetDhcp6Iana(cmd) => {
+                    let response = cmd.payload.process(self);
+                    let _ = cmd.respond_to.send(response);
+                },
+                WanCmd::SetDhcp6Iapd(cmd) => {
+                    let response = cmd.payload.process(self);
+                    let _ = cmd.respond_to.send(response);
+                },
+                WanCmd::SetPppoeCredentials(cmd) => {
+                    let response = cmd.payload.process(self);
+                    let _ = cmd.respond_to.send(response);
+                },
+                WanCmd::SetMode(cmd) => {
+                    let response = cmd.payload.process(self);
+                    let _ = cmd.respond_to.send(response);
+                },
+                WanCmd::Delete(cmd) => {
+                    let response = cmd.payload.process(self);
+                    let _ = cmd.respond_to.send(response);
+                },
+                WanCmd::Restore(cmd) => {
+                    let response = cmd.payload.process(self);
+                    let _ = cmd.respond_to.send(response);
+                }
             },
             RackdCmd::Trunk(cmd) => match cmd {
                 TrunkCmd::Create(cmd) => {
                     let response = cmd.payload.process(self);
                     let _ = cmd.respond_to.send(response);
+                },
+                TrunkCmd::Rename(cmd) => {
+                    let response = cmd.payload.process(self);
+                    let _ = cmd.respond_to.send(response);
+                },
+                TrunkCmd::Delete(cmd) => {
+                    let response = cmd.payload.process(self);
+                    let _ = cmd.respond_to.send(response);
+                },
+                TrunkCmd::BindInterface(cmd) => {
+                    let response = cmd.payload.process(self);
+                    let _ = cmd.respond_to.send(response);
+                }
+            }
+            RackdCmd::Lan(cmd) => match cmd {
+                LanCmd::Create(cmd) => {
+                    let response = cmd.payload.process(self);
+                    let _ = cmd.respond_to.send(response);
+                },
+                LanCmd::Rename(cmd) => {
+                    let response = cmd.payload.process(self);
+                    let _ = cmd.respond_to.send(response);
+                },
+                LanCmd::SetPrefix(cmd) => {
+                    let response = cmd.payload.process(self);
+                    let _ = cmd.respond_to.send(response);
+                },
+                LanCmd::Delete(cmd) => {
+                    let response = cmd.payload.process(self);
+                    let _ = cmd.respond_to.send(response);
+                },
+                LanCmd::Restore(cmd) => {
+                    let response = cmd.payload.process(self);
+                    let _ = cmd.respond_to.send(response);
+                }
+            }
+            RackdCmd::Org(cmd) => match cmd {
+                OrgCmd::Create(cmd) => {
+                    let response = cmd.payload.process(self);
+                    let _ = cmd.respond_to.send(response);
+                },
+                OrgCmd::Rename(cmd) => {
+                    let response = cmd.payload.process(self);
+                    let _ = cmd.respond_to.send(response);
+                },
+                OrgCmd::Join(cmd) => {
+                    let response = cmd.payload.process(self);
+                    let _ = cmd.respond_to.send(response);
+                }
+            }
+            RackdCmd::Rack(cmd) => match cmd {
+                RackCmd::Init(cmd) => {
+                    let response = cmd.payload.process(self);
+                    let _ = cmd.respond_to.send(response);
+                }
+            }
+            RackdCmd::Admin(cmd) => match cmd {
+                AdminCmd::RebuildProjections(cmd) => {
+                    let response = cmd.payload.process(self);
+                    let _ = cmd.respond_to.send(response);
                 }
             }
         }
+        // Transactions are committed by the time a command has been processed
+        self.bus.publish_staged();
         
         // match message {
         //     AppMessage::Initialize(msg) => {
@@ -118,9 +220,56 @@ impl Actor for RackdCmdActor {
     }
 }
 
+impl RackdCmd {
+    /// Responds with the error without processing the command
+    pub fn reject(self, e: ClusterError) {
+        match self {
+            RackdCmd::Wan(cmd) => match cmd {
+                WanCmd::Create(cmd) => { let _ = cmd.respond_to.send(Err(e.into())); },
+                WanCmd::Rename(cmd) => { let _ = cmd.respond_to.send(Err(e.into())); },
+                WanCmd::SetMacAddr(cmd) => { let _ = cmd.respond_to.send(Err(e.into())); },
+                WanCmd::SetIpv4Params(cmd) => { let _ = cmd.respond_to.send(Err(e.into())); },
+                WanCmd::SetIpv6(cmd) => { let _ = cmd.respond_to.send(Err(e.into())); },
+                WanCmd::SetDhcp6Duid(cmd) => { let _ = cmd.respond_to.send(Err(e.into())); },
+                WanCmd::SetDhcp6Iana(cmd) => { let _ = cmd.respond_to.send(Err(e.into())); },
+                WanCmd::SetDhcp6Iapd(cmd) => { let _ = cmd.respond_to.send(Err(e.into())); },
+                WanCmd::SetPppoeCredentials(cmd) => { let _ = cmd.respond_to.send(Err(e.into())); },
+                WanCmd::SetMode(cmd) => { let _ = cmd.respond_to.send(Err(e.into())); },
+                WanCmd::Delete(cmd) => { let _ = cmd.respond_to.send(Err(e.into())); },
+                WanCmd::Restore(cmd) => { let _ = cmd.respond_to.send(Err(e.into())); }
+            },
+            RackdCmd::Trunk(cmd) => match cmd {
+                TrunkCmd::Create(cmd) => { let _ = cmd.respond_to.send(Err(e.into())); },
+                TrunkCmd::Rename(cmd) => { let _ = cmd.respond_to.send(Err(e.into())); },
+                TrunkCmd::Delete(cmd) => { let _ = cmd.respond_to.send(Err(e.into())); },
+                TrunkCmd::BindInterface(cmd) => { let _ = cmd.respond_to.send(Err(e.into())); }
+            },
+            RackdCmd::Lan(cmd) => match cmd {
+                LanCmd::Create(cmd) => { let _ = cmd.respond_to.send(Err(e.into())); },
+                LanCmd::Rename(cmd) => { let _ = cmd.respond_to.send(Err(e.into())); },
+                LanCmd::SetPrefix(cmd) => { let _ = cmd.respond_to.send(Err(e.into())); },
+                LanCmd::Delete(cmd) => { let _ = cmd.respond_to.send(Err(e.into())); },
+                LanCmd::Restore(cmd) => { let _ = cmd.respond_to.send(Err(e.into())); }
+            },
+            RackdCmd::Org(cmd) => match cmd {
+                OrgCmd::Create(cmd) => { let _ = cmd.respond_to.send(Err(e.into())); },
+                OrgCmd::Rename(cmd) => { let _ = cmd.respond_to.send(Err(e.into())); },
+                OrgCmd::Join(cmd) => { let _ = cmd.respond_to.send(Err(e.into())); }
+            },
+            RackdCmd::Rack(cmd) => match cmd {
+                RackCmd::Init(cmd) => { let _ = cmd.respond_to.send(Err(e.into())); }
+            },
+            RackdCmd::Admin(cmd) => match cmd {
+                AdminCmd::RebuildProjections(cmd) => { let _ = cmd.respond_to.send(Err(e.into())); }
+            }
+        }
+    }
+}
+
 impl RackdCmdActor {
-    pub fn new(conn: Connection) -> Self {
-        Self { conn }
+    pub fn new(conn: Connection, bus: EventBus) -> Self {
+        conn.rollback_hook(Some(bus::discard_staged));
+        Self { conn, bus }
     }
 }
 
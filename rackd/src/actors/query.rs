@@ -1,5 +1,5 @@
 use rusqlite::Connection;
-use crate::{util::actor::{Actor, Process}, wan::query::WanQuery};
+use crate::{event::query::EventQuery, lan::query::LanQuery, org::query::OrgQuery, rack::query::RackQuery, trunk::query::TrunkQuery, util::actor::{Actor, Process}, wan::query::WanQuery};
 
 #[derive(Debug)]
 pub struct RackdQueryActor {
@@ -8,7 +8,12 @@ pub struct RackdQueryActor {
 
 #[derive(Debug)]
 pub enum RackdQuery {
-    Wan(WanQuery)
+    Wan(WanQuery),
+    Trunk(TrunkQuery),
+    Lan(LanQuery),
+    Org(OrgQuery),
+    Rack(RackQuery),
+    Event(EventQuery)
 }
 
 impl Actor for RackdQueryActor {
@@ -17,6 +22,10 @@ impl Actor for RackdQueryActor {
     fn receive(&mut self, query: RackdQuery) {
         match query {
             RackdQuery::Wan(query) => match query {
+                WanQuery::GetAllWans(query) => {
+                    let response = query.payload.process(self);
+                    let _ = query.respond_to.send(response);
+                },
                 WanQuery::GetWanById(query) => {
                     let response = query.payload.process(self);
                     let _ = query.respond_to.send(response);
@@ -24,6 +33,68 @@ impl Actor for RackdQueryActor {
                 WanQuery::GetWanByName(query) => {
                     let response = query.payload.process(self);
                     let _ = query.respond_to.send(response);
+                },
+                WanQuery::GetWanDuid(query) => {
+                    let response = query.payload.process(self);
+                    let _ = query.respond_to.send(response);
+                },
+                WanQuery::GetWanHistory(query) => {
+                    let response = query.payload.process(self);
+                    let _ = query.respond_to.send(response);
+                },
+                WanQuery::GetPppoePeer(query) => {
+                    let response = query.payload.process(self);
+                    let _ = query.respond_to.send(response);
+                },
+                WanQuery::GetWanHostLinks(query) => {
+                    let response = query.payload.process(self);
+                    let _ = query.respond_to.send(response);
+                }
+            }
+            RackdQuery::Trunk(query) => match query {
+                TrunkQuery::GetAllTrunks(query) => {
+                    let response = query.payload.process(self);
+                    let _ = query.respond_to.send(response);
+                },
+                TrunkQuery::GetTrunkById(query) => {
+                    let response = query.payload.process(self);
+                    let _ = query.respond_to.send(response);
+                },
+                TrunkQuery::GetTrunkByName(query) => {
+                    let response = query.payload.process(self);
+                    let _ = query.respond_to.send(response);
+                },
+                TrunkQuery::GetTrunkNetworks(query) => {
+                    let response = query.payload.process(self);
+                    let _ = query.respond_to.send(response);
+                }
+            }
+            RackdQuery::Lan(query) => match query {
+                LanQuery::GetLanById(query) => {
+                    let response = query.payload.process(self);
+                    let _ = query.respond_to.send(response);
+                },
+                LanQuery::GetLanByName(query) => {
+                    let response = query.payload.process(self);
+                    let _ = query.respond_to.send(response);
+                }
+            }
+            RackdQuery::Org(query) => match query {
+                OrgQuery::GetOrg(query) => {
+                    let response = query.payload.process(self);
+                    let _ = query.respond_to.send(response);
+                }
+            }
+            RackdQuery::Rack(query) => match query {
+                RackQuery::GetRack(query) => {
+                    let response = query.payload.process(self);
+                    let _ = query.respond_to.send(response);
+                }
+            }
+            RackdQuery::Event(query) => match query {
+                EventQuery::GetEvents(query) => {
+                    let response = query.payload.process(self);
+                    let _ = query.respond_to.send(response);
                 }
             }
         }
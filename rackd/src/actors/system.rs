@@ -1,8 +1,14 @@
+use std::net::SocketAddr;
 use log::error;
+use openraft::{error::Fatal, network::RaftNetworkFactory};
 use rusqlite::Connection;
-use crate::util::actor::{Actor, Handle, Msg, Payload};
+use tokio::{net::TcpListener, sync::mpsc};
+use tokio_util::sync::CancellationToken;
+use crate::util::{actor::{Actor, AsyncActor, Handle, Msg, Payload}, models::Id, secret::{self, SecretKeyError}};
 use super::{cmd::{RackdCmd, RackdCmdActor}, query::{RackdQuery, RackdQueryActor}};
-use crate::db;
+use crate::sys::{actor::{SysActor, SysMessage}, hydrate::Hydrate, reconciler::WanReconciler, util::{monitor::NlMonitor, netlink::Netlink}};
+use crate::conf::settings;
+use crate::db::{self, bus::EventBus, raft::{self, log_store::LogStore, network::{ClusterSecret, Http}, proposer::RaftProposer, state_machine::StateMachine, ClusterError, ClusterMode, NodeId, Pending, Raft, TypeConfig}};
 use thiserror::Error;
 
 // The API Actor (Handling REST/gRPC request) is going to front the
@@ -12,7 +18,7 @@ use thiserror::Error;
 
 // Our Error Kernel (Components of the program that should NEVER fail) includes:
 // - Internal EventBus
-// - Supervision (Restart, Resume, Reinitialize Actors)
+// - Supervision (Restart, Resume, Reinitialize Actors), see AsyncActor::supervise (ie: the SysActor)
 
 // pub struct ActorSystem {
 //     pub app: Handle<AppMessage>,
@@ -47,13 +53,36 @@ pub enum ActorStatus {
 #[derive(Clone)]
 pub struct Rackd {
     pub cmd: Handle<RackdCmd>,
-    pub query: Handle<RackdQuery>
+    pub query: Handle<RackdQuery>,
+    pub events: EventBus,
+    /// Node of the rack the host belongs to
+    pub node: NodeId,
+    /// Host of the node, it isn't replicated
+    pub sys: Handle<SysMessage>,
+    /// Only set on nodes of a replicated rack
+    pub raft: Option<Raft>
 }
 
 #[derive(Debug, Error)]
 pub enum RackdError {
     #[error("{}", .0)]
-    Db(#[from] rusqlite::Error)
+    Db(#[from] rusqlite::Error),
+    #[error("{}", .0)]
+    Raft(Box<Fatal<NodeId>>),
+    #[error("{}", .0)]
+    Cluster(#[from] ClusterError),
+    #[error("{}", .0)]
+    Secrets(#[from] SecretKeyError),
+    #[error("{}", .0)]
+    Netlink(#[from] std::io::Error),
+    #[error("Failed to listen for raft RPCs: {}", .0)]
+    Listener(std::io::Error)
+}
+
+impl From<Fatal<NodeId>> for RackdError {
+    fn from(error: Fatal<NodeId>) -> Self {
+        Self::Raft(Box::new(error))
+    }
 }
 
 impl Rackd {
@@ -66,23 +95,79 @@ impl Rackd {
         self.query.send(query).await
     }
 
+    /// Runs the query and completes its result with the live state of the host
+    pub async fn query_hydrated<P>(&self, query: P) -> Result<P::Ok, P::Err> where P: Payload, P::Ok: Hydrate, RackdQuery: From<Msg<P>> {
+        let view = self.query(query).await?;
+        Ok(view.hydrate(self).await)
+    }
+
+    /// In-memory node whose host has no links, see SysActor::detached
     pub fn mock() -> Result<Self, RackdError> {
+        Self::mock_with(SysActor::detached())
+    }
+
+    /// In-memory node running on the given host (ie: a SysActor on the host running the tests)
+    pub fn mock_with(sys: Handle<SysMessage>) -> Result<Self, RackdError> {
         let _ = env_logger::builder()
             .filter_level(log::LevelFilter::Error)
             .format_target(false)
             .format_timestamp(None)
             .try_init();
-        db::cmd::migrations::runner().run(Connection::open_in_memory()?);
-        let cmd = RackdCmdActor::spawn(RackdCmdActor::new(Connection::open_in_memory()?));
-        let query = RackdQueryActor::spawn(RackdQueryActor::new(Connection::open_in_memory()?));
-        Ok(Self { cmd, query })
+        secret::load_random();
+        // Shared-cache in-memory DB so both actors see the same data, unique per mock
+        let path = format!("file:{}?mode=memory&cache=shared", Id::new());
+        let conn = db::cmd::migrations::runner().run(Connection::open(&path)?)?;
+        let events = EventBus::new();
+        let cmd = RackdCmdActor::spawn(RackdCmdActor::new(conn, events.clone()));
+        let query = RackdQueryActor::spawn(RackdQueryActor::new(Connection::open(&path)?));
+        Ok(Self { cmd, query, events, node: 1, sys, raft: None })
     }
 
+    /// Node of a single node rack
     pub fn new(path: &str) -> Result<Self, RackdError> {
-        db::cmd::migrations::runner().run(Connection::open(path).unwrap());
-        let cmd = RackdCmdActor::spawn(RackdCmdActor::new(Connection::open(path)?)); 
+        db::cmd::migrations::runner().run(Connection::open(path)?)?;
+        let events = EventBus::new();
+        let cmd = RackdCmdActor::spawn(RackdCmdActor::new(Connection::open(path)?, events.clone())); 
         let query = RackdQueryActor::spawn(RackdQueryActor::new(Connection::open(path)?)); 
-        Ok(Self { cmd, query })
+        let sys = SysActor::spawn(Netlink::connect()?, NlMonitor::connect()?);
+        Ok(Self { cmd, query, events, node: 1, sys, raft: None })
+    }
+
+    /// Node the daemon runs, its command DB and raft log are the ones of `settings().database`
+    /// and it reaches the other nodes of the rack at the addresses of `settings().cluster`, where it serves their RPCs too.
+    /// The wan links of the node are kept in sync with the rack by a WanReconciler
+    pub async fn start() -> Result<Self, RackdError> {
+        let (database, cluster) = (&settings().database, &settings().cluster);
+        secret::load(&settings().secrets.key)?;
+        let secret = ClusterSecret::load(&settings().secrets.cluster)?;
+        let rackd = Self::replicated(cluster.node, cluster.mode, &database.cmd, &database.raft, Http::new(secret.clone())).await?;
+        let listener = TcpListener::bind(&cluster.addr).await.map_err(RackdError::Listener)?;
+        let peers = raft::api::peer_router(rackd.clone(), &cluster.peers, secret);
+        tokio::spawn(axum::serve(listener, peers.into_make_service_with_connect_info::<SocketAddr>()).into_future());
+        if let Some(raft) = &rackd.raft {
+            raft::bootstrap(raft, cluster).await?;
+        }
+        let reconciler = WanReconciler::new(rackd.clone(), Netlink::connect()?, cluster.node);
+        tokio::spawn(WanReconciler::run(reconciler, CancellationToken::new()));
+        Ok(rackd)
+    }
+
+    /// Node of a replicated rack, commands are processed once they've been committed to the raft log stored at raft_path
+    pub async fn replicated<N>(node: NodeId, mode: ClusterMode, path: &str, raft_path: &str, network: N) -> Result<Self, RackdError> where N: RaftNetworkFactory<TypeConfig> {
+        let conn = db::cmd::migrations::runner().run(Connection::open(path)?)?;
+        let events = EventBus::new();
+        let pending = Pending::default();
+        let log = LogStore::new(Connection::open(raft_path)?)?;
+        let state_machine = StateMachine::new(RackdCmdActor::new(conn, events.clone()), pending.clone());
+        let raft = Raft::new(node, raft::config(), network, log, state_machine).await?;
+
+        let (sender, receiver) = mpsc::channel(10);
+        let proposer = RaftProposer::new(raft.clone(), pending, mode);
+        tokio::spawn(RaftProposer::run(proposer, receiver, CancellationToken::new()));
+        let cmd = Handle { sender };
+        let query = RackdQueryActor::spawn(RackdQueryActor::new(Connection::open(path)?));
+        let sys = SysActor::spawn(Netlink::connect()?, NlMonitor::connect()?);
+        Ok(Self { cmd, query, events, node, sys, raft: Some(raft) })
 
         // let (tx_app, rx_app) = mpsc::channel::<AppMessage>(10);
         // let (tx_sys, rx_sys) = mpsc::channel::<SysMessage>(10);
@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+use crate::util::actor::Msg;
+pub mod rebuild_projections;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum AdminCmd {
+    RebuildProjections(Msg<rebuild_projections::RebuildProjections>)
+}
@@ -0,0 +1,138 @@
+use field_types::FieldName;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use crate::{actors::cmd::RackdCmdActor, db::{cmd::projectors, raft::ClusterError, util::RebuildReport, Tx}, util::actor::{Payload, Process}};
+
+/// Rebuilds the given views from the event log, an empty list rebuilds all of them
+#[derive(Debug, Serialize, Deserialize, ToSchema, FieldName)]
+pub struct RebuildProjections {
+    pub views: Vec<String>
+}
+
+#[derive(Debug, Error)]
+pub enum RebuildProjectionsError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
+    #[error("Unknown views: {}", .0.join(","))]
+    UnknownViews(Vec<String>)
+}
+
+impl Payload for RebuildProjections {
+    type Ok = Vec<RebuildReport>;
+    type Err = RebuildProjectionsError;
+}
+
+impl Process for RebuildProjections {
+    type Actor = RackdCmdActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let projectors = projectors();
+        let unknown: Vec<String> = self.views.iter().filter(|v| projectors.get(v).is_none()).cloned().collect();
+        if !unknown.is_empty() {
+            Err(RebuildProjectionsError::UnknownViews(unknown))?
+        }
+        let tx = actor.conn.tx()?;
+        let reports = if self.views.is_empty() {
+            projectors.rebuild_all(&tx)?
+        } else {
+            self.views.iter()
+                .map(|v| projectors.get(v).unwrap().rebuild(&tx))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+        tx.commit()?;
+        Ok(reports)
+    }
+}
+
+pub mod casts {
+    use crate::{actors::cmd::RackdCmd, admin::cmd::AdminCmd, util::actor::Msg};
+    use super::RebuildProjections;
+
+    impl From<Msg<RebuildProjections>> for RackdCmd {
+        fn from(cmd: Msg<RebuildProjections>) -> Self {
+            Self::Admin(AdminCmd::RebuildProjections(cmd))
+        }
+    }
+}
+
+pub mod api {
+    use std::collections::HashMap;
+    use serde_json::Value;
+    use axum::{extract::{OriginalUri, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, util::api::{Error, Json, Response, TryFromJson}};
+    use super::{RebuildProjections, RebuildProjectionsError, RebuildProjectionsFieldName};
+
+    #[utoipa::path(post, path = "/admin/rebuild_projections", tag = "admin",
+        request_body = RebuildProjections,
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn rebuild_projections(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Json(cmd): Json<RebuildProjections>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.exec(cmd).await
+            .map(|reports| Response::ok(reports, path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl TryFromJson for RebuildProjections {
+        fn try_from(mut map: HashMap<String, Value>) -> Result<Self, Vec<Error>> {
+            Self::check_keys(&map, RebuildProjections::as_field_name_array().map(|f| f.name()))?;
+            let views = map.remove(RebuildProjectionsFieldName::Views.name()).unwrap_or_default();
+            match serde_json::from_value::<Vec<String>>(views) {
+                Ok(views) => Ok(Self { views }),
+                Err(e) => Err(vec![Error::new("REBUILD_PROJECTIONS_INVALID_VIEWS", e.to_string())])
+            }
+        }
+    }
+
+    impl From<RebuildProjectionsError> for Error {
+        fn from(error: RebuildProjectionsError) -> Self {
+            let msg = error.to_string();
+            match error {
+                RebuildProjectionsError::Db(_) => Error::new("REBUILD_PROJECTIONS_DB_ERROR", msg),
+                RebuildProjectionsError::Cluster(e) => Error::from(e),
+                RebuildProjectionsError::UnknownViews(_) => Error::new("REBUILD_PROJECTIONS_UNKNOWN_VIEWS", msg)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::{actors::system::Rackd, admin::cmd::rebuild_projections::{RebuildProjections, RebuildProjectionsError}, lan::{cmd::create::CreateLan, query::get_by_key::GetLanByName}, net::{NetName, Prefix, VlanId}};
+    use crate::util::test_support::init_rack;
+
+    #[tokio::test]
+    async fn cant_rebuild_unknown_view() {
+        let rackd = Rackd::mock().unwrap();
+        let cmd = RebuildProjections {
+            views: vec![String::from("lan_view"), String::from("isp_view")]
+        };
+        assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, RebuildProjectionsError::UnknownViews(v) if v == vec![String::from("isp_view")])));
+    }
+
+    #[tokio::test]
+    async fn can_rebuild_views_from_events() {
+        let rackd = Rackd::mock().unwrap();
+        let trunk_id = init_rack(&rackd).await;
+        let cmd = CreateLan {
+            trunk: trunk_id,
+            vlan: VlanId::try_from(100).unwrap(),
+            name: NetName::from_str("lab").unwrap(),
+            prefix: Prefix::from_str("fd00:0:0:100::/64").unwrap()
+        };
+        rackd.exec(cmd).await.unwrap();
+
+        let reports = rackd.exec(RebuildProjections { views: vec![] }).await.unwrap();
+        assert_eq!(reports.len(), 6);
+        assert!(reports.iter().all(|r| r.failures.is_empty()));
+        let lan_view = reports.iter().find(|r| r.view == "lan_view").unwrap();
+        assert_eq!(lan_view.events, 3);
+        assert!(rackd.query(GetLanByName { name: NetName::from_str("lab").unwrap() }).await.is_ok());
+    }
+}
@@ -1,12 +1,48 @@
 use utoipa_axum::router::OpenApiRouter;
 use utoipa_axum::routes;
 use crate::actors::system::Rackd;
-use crate::{trunk, wan};
+use crate::util::api::request_id;
+use crate::{admin, db, event, lan, org, rack, trunk, wan};
 
-pub fn router() -> OpenApiRouter {
-    let rackd = Rackd::new("/data/lab/rust/rackd/rackd/tmp/rackd.db").unwrap();
+pub fn router(rackd: Rackd) -> OpenApiRouter {
     OpenApiRouter::new()
-        .routes(routes!(wan::cmd::create::api::create, wan::query::get_by_key::api::get_wan_by_id))
+        .routes(routes!(wan::cmd::create::api::create))
+        .routes(routes!(wan::query::get_by_key::api::get_wan_by_id, wan::cmd::delete::api::delete))
+        .routes(routes!(wan::cmd::restore::api::restore))
         .routes(routes!(trunk::cmd::create::api::create))
+        .routes(routes!(trunk::cmd::rename::api::rename))
+        .routes(routes!(trunk::cmd::delete::api::delete))
+        .routes(routes!(trunk::cmd::bind::api::bind))
+        .routes(routes!(trunk::query::get_all::api::get_all_trunks))
+        .routes(routes!(trunk::query::get_by_key::api::get_trunk_by_id))
+        .routes(routes!(trunk::query::get_networks::api::get_trunk_networks))
+        .routes(routes!(lan::cmd::create::api::create))
+        .routes(routes!(lan::cmd::rename::api::rename))
+        .routes(routes!(lan::cmd::set_prefix::api::set_prefix))
+        .routes(routes!(lan::query::get_by_key::api::get_lan_by_id, lan::cmd::delete::api::delete))
+        .routes(routes!(lan::cmd::restore::api::restore))
+        .routes(routes!(org::cmd::create::api::create))
+        .routes(routes!(org::cmd::rename::api::rename))
+        .routes(routes!(org::cmd::join::api::join))
+        .routes(routes!(org::query::get_org::api::get_org))
+        .routes(routes!(rack::cmd::init::api::init))
+        .routes(routes!(rack::query::get_rack::api::get_rack))
+        .routes(routes!(admin::cmd::rebuild_projections::api::rebuild_projections))
+        .routes(routes!(wan::query::get_all::api::get_all_wans))
+        .routes(routes!(wan::cmd::rename::api::rename))
+        .routes(routes!(wan::cmd::set_mac::api::set_mac))
+        .routes(routes!(wan::cmd::set_ipv4::api::set_ipv4))
+        .routes(routes!(wan::cmd::set_ipv6::api::set_ipv6))
+        .routes(routes!(wan::cmd::set_dhcp6_duid::api::set_duid))
+        .routes(routes!(wan::cmd::set_dhcp6_iana::api::set_iana))
+        .routes(routes!(wan::cmd::set_dhcp6_iapd::api::set_iapd))
+        .routes(routes!(wan::query::get_duid::api::get_wan_duid))
+        .routes(routes!(wan::query::get_history::api::get_wan_history))
+        .routes(routes!(wan::cmd::set_pppoe::api::set_pppoe_credentials))
+        .routes(routes!(wan::cmd::set_mode::api::set_mode))
+        .routes(routes!(event::stream::api::stream))
+        .routes(routes!(event::query::get_events::api::get_events))
+        .routes(routes!(db::raft::api::switchover))
         .with_state(rackd)
+        .layer(axum::middleware::from_fn(request_id))
 }
\ No newline at end of file
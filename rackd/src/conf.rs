@@ -2,10 +2,14 @@ use std::{env, sync::OnceLock};
 use config::{Config, File};
 use log::error;
 use serde::Deserialize;
+use crate::{db::raft::{ClusterMode, NodeId}, net::probe::ProbeConfig};
 
 #[derive(Debug, Deserialize)]
 pub struct Settings {
-    pub database: Database
+    pub database: Database,
+    pub secrets: Secrets,
+    pub probes: ProbeConfig,
+    pub cluster: Cluster
 }
 
 #[derive(Debug, Deserialize)]
@@ -16,6 +20,33 @@ pub struct Database {
     pub raft: String
 }
 
+#[derive(Debug, Deserialize)]
+pub struct Secrets {
+    /// File holding the hex encoded key secrets are sealed with
+    pub key: String,
+    /// File holding the secret the nodes of the rack present to each other on raft RPCs
+    pub cluster: String
+}
+
+/// Nodes of the rack, every node lists the same members
+#[derive(Debug, Deserialize)]
+pub struct Cluster {
+    /// Id of this node, it must never change once the node joined
+    pub node: NodeId,
+    pub mode: ClusterMode,
+    /// Address other nodes reach this node's raft RPCs at (ie: [fd00::1]:8081), it's served apart from the API
+    pub addr: String,
+    /// Every other node of the rack, none on a single node rack
+    #[serde(default)]
+    pub peers: Vec<Peer>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Peer {
+    pub id: NodeId,
+    pub addr: String
+}
+
 impl Settings {
     pub fn new() -> Self {
         let run_mode = env::var("RUN_MODE").unwrap_or_default();
@@ -0,0 +1,53 @@
+use std::cell::RefCell;
+use tokio::sync::broadcast;
+use crate::util::models::Event;
+
+const CAPACITY: usize = 1024;
+
+thread_local! {
+    // Events stored by the transaction running on this thread, they are held
+    // back until the transaction is committed so subscribers never see rolled back events
+    static OUTBOX: RefCell<Vec<Event>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Called by the EventStore for every stored event
+pub fn stage(e: Event) {
+    OUTBOX.with_borrow_mut(|outbox| outbox.push(e));
+}
+
+/// Called when a transaction is rolled back, its events were never stored
+pub fn discard_staged() {
+    OUTBOX.with_borrow_mut(|outbox| outbox.clear());
+}
+
+/// In-process bus fed with every committed event.
+/// Subscribers that lag behind lose events and need to catch up from the event table by `seq`
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<Event>
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        Self { sender }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.sender.subscribe()
+    }
+
+    /// Publishes the events staged on this thread, must only be called after the transaction has been committed
+    pub fn publish_staged(&self) {
+        for e in OUTBOX.take() {
+            // No subscribers isn't an error
+            let _ = self.sender.send(e);
+        }
+    }
+}
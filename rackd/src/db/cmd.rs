@@ -1,7 +1,7 @@
 pub mod migrations;
 pub mod traits;
 use std::sync::OnceLock;
-use crate::{net::views::NetworkView, trunk::views::TrunkView, wan::views::WanView};
+use crate::{lan::views::LanView, net::views::NetworkView, org::views::OrgView, rack::views::RackView, trunk::views::TrunkView, wan::views::WanView};
 
 use super::util::Projectors;
 
@@ -12,7 +12,10 @@ pub fn projectors() -> &'static Projectors {
         
         projectors.register::<NetworkView>();
         projectors.register::<WanView>();
+        projectors.register::<LanView>();
         projectors.register::<TrunkView>();
+        projectors.register::<OrgView>();
+        projectors.register::<RackView>();
         projectors
     })
 }
@@ -16,12 +16,39 @@ pub fn runner() -> &'static MigrationRunner<'static> {
     })
 }
 
-pub fn up_v0_1_0(tx: &Transaction) {
-    
+pub fn up_v0_1_0(_tx: &Transaction) -> Result<(), rusqlite::Error> {
+    Ok(())
 }
 
-pub fn up_v0_2_0(tx: &Transaction) {
-    
+pub fn up_v0_2_0(tx: &Transaction) -> Result<(), rusqlite::Error> {
+    // View tables are owned by their projectors, a rebuild creates them and replays any existing events
+    super::projectors().rebuild_all(tx)?;
+    Ok(())
 }
 
+#[cfg(test)]
+mod tests {
+    use include_dir::include_dir;
+    use rusqlite::{params, Connection};
+    use crate::{db::util::{Migration, MigrationRunner}, trunk::model::TrunkEvent, util::models::{EventData, Id}};
 
+    #[test]
+    fn databases_created_by_0_1_0_are_upgraded() {
+        let conn = MigrationRunner::new(include_dir!("$CARGO_MANIFEST_DIR/src/db/cmd/schemas"))
+            .register([Migration::to("0.1.0").up(super::up_v0_1_0)])
+            .run(Connection::open_in_memory().unwrap()).unwrap();
+        let (stream, first, data) = (Id::new(), Id::new(), EventData::Trunk(TrunkEvent::Deleted));
+        conn.execute("INSERT INTO event (id, stream_id, version, data) VALUES (?1, ?2, 1, ?4), (?3, ?2, 2, ?4)", params! { first, stream, Id::new(), data }).unwrap();
+        conn.execute("INSERT INTO entity (id, value) VALUES (?1, '{}')", params! { stream }).unwrap();
+
+        let conn = super::runner().run(conn).unwrap();
+        let version: u32 = conn.query_row("SELECT version FROM entity WHERE id = ?1", params! { stream }, |row| row.get(0)).unwrap();
+        assert_eq!(version, 2);
+        let (timestamp, origin): (i64, Option<String>) = conn.query_row("SELECT timestamp, origin FROM event WHERE id = ?1", params! { first }, |row| Ok((row.get(0)?, row.get(1)?))).unwrap();
+        assert_eq!((timestamp, origin), (0, None));
+        // Views are recreated by their projectors with the current columns
+        conn.query_row("SELECT COUNT(*) FROM wan_view WHERE mode IS NOT NULL", (), |row| row.get::<_, u32>(0)).unwrap();
+        let duplicate = conn.execute("INSERT INTO event (id, stream_id, version, data) VALUES (?1, ?2, 2, ?3)", params! { Id::new(), stream, data });
+        assert!(duplicate.is_err());
+    }
+}
@@ -0,0 +1,12 @@
+ALTER TABLE event ADD COLUMN timestamp INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE event ADD COLUMN origin TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS event_stream_version ON event (stream_id, version);
+CREATE INDEX IF NOT EXISTS event_timestamp ON event (timestamp);
+
+ALTER TABLE entity ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
+UPDATE entity SET version = (SELECT COALESCE(MAX(version), 0) FROM event WHERE event.stream_id = entity.id);
+
+DROP TABLE IF EXISTS network_view;
+DROP TABLE IF EXISTS wan_view;
+DROP TABLE IF EXISTS trunk_view;
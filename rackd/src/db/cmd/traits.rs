@@ -1,38 +1,108 @@
-use log::error;
-use rusqlite::{params, types::FromSql, ToSql, Transaction};
+use log::{error, warn};
+use rusqlite::{params, types::{FromSql, Type}, ToSql, Transaction};
 use serde::{de::DeserializeOwned, Serialize};
-use crate::util::models::{Entity, Event, Id};
+use thiserror::Error;
+use crate::{db::{bus, raft}, util::models::{casts::UnexpectedEvent, sqlite::timestamp_to_sql, Entity, Event, EventData, Id}};
+
+#[derive(Debug, Error)]
+pub enum EntityStoreError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Conflict(#[from] VersionConflict)
+}
+
+#[derive(Debug, Error)]
+#[error("Entity {stream_id} was modified concurrently: loaded at version {expected}, stored version is {found}")]
+pub struct VersionConflict {
+    pub stream_id: Id,
+    pub expected: u32,
+    pub found: u32
+}
+
+/// A snapshot of the entity is stored every time its stream crosses a multiple of this many events
+pub const SNAPSHOT_EVERY: u32 = 20;
 
 pub trait EntityStore {
-    fn save<T>(&self, entity: &mut T) -> Result<(), rusqlite::Error> where T: Entity + Serialize;
-    fn load<T, K>(&self, id: K) -> Result<Option<T>, rusqlite::Error> where T: Entity + DeserializeOwned, K: Into<Id>;
+    fn save<T>(&self, entity: &mut T) -> Result<(), EntityStoreError> where T: Entity + Serialize;
+    fn load<T, K>(&self, id: K) -> Result<Option<T>, rusqlite::Error> 
+        where T: Entity + Default + Serialize + DeserializeOwned, T::E: TryFrom<EventData, Error = UnexpectedEvent>, K: Into<Id>;
 }
 
 impl<'a> EntityStore for Transaction<'a> {
-    fn save<T>(&self, entity: &mut T) -> Result<(), rusqlite::Error> where T: Entity + Serialize {
-        let mut stmt = self.prepare("INSERT INTO entity (id, value) VALUES (?1, ?2)")
-            .map_err(|e| { error!("prepare() in EntityStore::save() failed: {}", e); e })?;
-        
-        stmt.execute(params! { entity.id(), serde_json::to_string(&entity).unwrap() })
-            .map_err(|e| { error!("execute() in EntityStore::save() failed: {}", e); e })?;
-
+    fn save<T>(&self, entity: &mut T) -> Result<(), EntityStoreError> where T: Entity + Serialize {
+        let stream_id = entity.id();
+        // Pending events must not end up in the snapshot
         let events = std::mem::take(&mut entity.metadata().events);
+        let version = entity.metadata().version;
+        let expected = version - events.len() as u32;
+
+        let found: u32 = self.query_row("SELECT COALESCE(MAX(version), 0) FROM event WHERE stream_id = ?1", params! { stream_id }, |row| row.get(0))
+            .map_err(|e| { error!("query_row() in EntityStore::save() failed: {}", e); e })?;
+        if found != expected {
+            Err(VersionConflict { stream_id, expected, found })?
+        }
+
         EventStore::save_many(self, &events)?;
+        if expected / SNAPSHOT_EVERY != version / SNAPSHOT_EVERY {
+            save_snapshot(self, stream_id, version, entity)?;
+        }
         Ok(())
     }
 
-    fn load<T, K>(&self, id: K) -> Result<Option<T>, rusqlite::Error> where T: Entity + DeserializeOwned, K: Into<Id> {
+    fn load<T, K>(&self, id: K) -> Result<Option<T>, rusqlite::Error> 
+        where T: Entity + Default + Serialize + DeserializeOwned, T::E: TryFrom<EventData, Error = UnexpectedEvent>, K: Into<Id> {
         let id: Id = id.into();
-        let mut stmt = self.prepare("SELECT value FROM entity WHERE id = :id")
-            .map_err(|e| { error!("prepare() in EntityStore::load() failed: {}", e); e })?;
-        match stmt.query_row(&[(":id", &id.to_string())], |row| row.get::<_, String>(0)) {
-            Ok(value) => Ok(Some(serde_json::from_str(value.as_str()).unwrap())),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        let snapshot = match self.query_row("SELECT version, value FROM entity WHERE id = ?1", params! { id }, |row| Ok((row.get::<_, u32>(0)?, row.get::<_, String>(1)?))) {
+            Ok(snapshot) => Some(snapshot),
+            Err(rusqlite::Error::QueryReturnedNoRows) => None,
             Err(e) => Err(e).map_err(|e| { error!("query_row() in EntityStore::load() failed: {}", e); e })?
+        };
+
+        // A snapshot that can't be deserialized (ie: the struct changed) or doesn't match its version is discarded
+        let mut stale = false;
+        let mut entity = snapshot.and_then(|(version, value)| match serde_json::from_str::<T>(&value) {
+            Ok(mut entity) => match entity.metadata().version == version {
+                true => Some(entity),
+                false => { warn!("Snapshot of {} at version {} has a mismatching version, replaying stream", id, version); stale = true; None }
+            },
+            Err(e) => { warn!("Snapshot of {} at version {} is unreadable, replaying stream: {}", id, version, e); stale = true; None }
+        });
+
+        let from = entity.as_mut().map(|e| e.metadata().version).unwrap_or(0);
+        let mut stmt = self.prepare("SELECT version, data FROM event WHERE stream_id = ?1 AND version > ?2 ORDER BY version")
+            .map_err(|e| { error!("prepare() in EntityStore::load() failed: {}", e); e })?;
+        let mut rows = stmt.query(params! { id, from })?;
+        while let Some(row) = rows.next()? {
+            let version: u32 = row.get(0)?;
+            let e = T::E::try_from(row.get(1)?)
+                .map_err(|e| rusqlite::Error::FromSqlConversionFailure(1, Type::Text, Box::new(e)))?;
+            let entity = entity.get_or_insert_with(T::default);
+            entity.apply(&e);
+            entity.metadata().version = version;
         }
+
+        if let Some(entity) = entity.as_mut().filter(|_| stale) {
+            let version = entity.metadata().version;
+            save_snapshot(self, id, version, entity).map_err(|e| match e {
+                EntityStoreError::Db(e) => e,
+                e => rusqlite::Error::ToSqlConversionFailure(Box::new(e))
+            })?;
+        }
+        Ok(entity)
     }
 }
 
+fn save_snapshot<T>(tx: &Transaction, id: Id, version: u32, entity: &T) -> Result<(), EntityStoreError> where T: Serialize {
+    let value = serde_json::to_string(entity)
+        .map_err(|e| { error!("to_string() in EntityStore::save_snapshot() failed: {}", e); rusqlite::Error::ToSqlConversionFailure(Box::new(e)) })?;
+    let mut stmt = tx.prepare("INSERT INTO entity (id, version, value) VALUES (?1, ?2, ?3) ON CONFLICT(id) DO UPDATE SET version = excluded.version, value = excluded.value")
+        .map_err(|e| { error!("prepare() in EntityStore::save_snapshot() failed: {}", e); e })?;
+    stmt.execute(params! { id, version, value })
+        .map_err(|e| { error!("execute() in EntityStore::save_snapshot() failed: {}", e); e })?;
+    Ok(())
+}
+
 
 pub trait EventStore {
     fn save(&self, e: &Event) -> Result<(), rusqlite::Error>;
@@ -41,15 +111,18 @@ pub trait EventStore {
 
 impl<'a> EventStore for Transaction<'a> {
     fn save(&self, e: &Event) -> Result<(), rusqlite::Error> {
-        // SET DATETIME AND TRANSACION ID
         // Don't prepare the SQL Statement every time ()
-        let mut stmt = self.prepare("INSERT INTO event (id, stream_id, version, data) VALUES (?1, ?2, ?3, ?4)")
+        let mut stmt = self.prepare("INSERT INTO event (id, stream_id, version, data, timestamp, origin) VALUES (?1, ?2, ?3, ?4, ?5, ?6) RETURNING seq")
             .map_err(|e| { error!("prepare() in EventStore::save() failed: {}", e); e })?;
-        stmt.execute(params! { e.id, e.stream_id, e.version, e.data })
-            .map_err(|e| { error!("execute() in EventStore::save() failed: {}", e); e })?;
+        let timestamp = timestamp_to_sql(&e.timestamp);
+        let seq = stmt.query_row(params! { e.id, e.stream_id, e.version, e.data, timestamp, e.origin }, |row| row.get(0))
+            .map_err(|e| { error!("query_row() in EventStore::save() failed: {}", e); e })?;
+        let e = Event { seq, ..e.clone() };
 
-        super::projectors().exec(self, &e);
+        super::projectors().exec(self, &e)?;
+        raft::state_machine::record_applied(self);
         // super::reactors::reactors().exec(self, &e);
+        bus::stage(e);
         Ok(())
     }    
 
@@ -145,3 +218,97 @@ impl<'a> KeyValueStore for Transaction<'a> {
 //     // }
 // }
 
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use rusqlite::Connection;
+    use crate::{db::{cmd::migrations, Tx}, lan::model::{entity::{Lan, LanEvent}, values::LanId}, net::{NetName, Prefix, VlanId}, util::models::Entity};
+    use super::{EntityStore, EntityStoreError, SNAPSHOT_EVERY};
+
+    fn created() -> LanEvent {
+        LanEvent::Created {
+            id: LanId::new(),
+            rack: Box::default(),
+            trunk: Box::default(),
+            vlan: VlanId::try_from(100).unwrap(),
+            name: NetName::from_str("lab").unwrap(),
+            prefix: Prefix::from_str("fd00:0:0:100::/64").unwrap()
+        }
+    }
+
+    #[test]
+    fn can_save_an_entity_more_than_once() {
+        let mut conn = migrations::runner().run(Connection::open_in_memory().unwrap()).unwrap();
+        let tx = conn.tx().unwrap();
+        let mut lan = Lan::default();
+        lan.process(created());
+        tx.save(&mut lan).unwrap();
+        lan.process(LanEvent::Renamed { from: lan.name.clone(), to: NetName::from_str("home").unwrap() });
+        tx.save(&mut lan).unwrap();
+
+        let lan: Lan = tx.load(lan.id).unwrap().unwrap();
+        assert_eq!(lan.name, NetName::from_str("home").unwrap());
+        assert_eq!(lan.meta.version, 2);
+        assert!(lan.meta.events.is_empty());
+    }
+
+    #[test]
+    fn cant_save_a_stale_entity() {
+        let mut conn = migrations::runner().run(Connection::open_in_memory().unwrap()).unwrap();
+        let tx = conn.tx().unwrap();
+        let mut lan = Lan::default();
+        lan.process(created());
+        tx.save(&mut lan).unwrap();
+
+        let mut first: Lan = tx.load(lan.id).unwrap().unwrap();
+        let mut second: Lan = tx.load(lan.id).unwrap().unwrap();
+        first.process(LanEvent::Renamed { from: first.name.clone(), to: NetName::from_str("home").unwrap() });
+        second.process(LanEvent::Renamed { from: second.name.clone(), to: NetName::from_str("office").unwrap() });
+        tx.save(&mut first).unwrap();
+        assert!(tx.save(&mut second).is_err_and(|e| matches!(e, EntityStoreError::Conflict(c) if c.expected == 1 && c.found == 2)));
+    }
+
+    #[test]
+    fn takes_a_snapshot_every_n_events() {
+        let mut conn = migrations::runner().run(Connection::open_in_memory().unwrap()).unwrap();
+        let tx = conn.tx().unwrap();
+        let mut lan = Lan::default();
+        lan.process(created());
+        tx.save(&mut lan).unwrap();
+        let snapshots = |tx: &rusqlite::Transaction| tx.query_row("SELECT COUNT(*) FROM entity", [], |row| row.get::<_, u32>(0)).unwrap();
+        assert_eq!(snapshots(&tx), 0);
+
+        for i in 1..SNAPSHOT_EVERY {
+            lan.process(LanEvent::Renamed { from: lan.name.clone(), to: NetName::from_str(&format!("lab{i}")).unwrap() });
+            tx.save(&mut lan).unwrap();
+        }
+        let version: u32 = tx.query_row("SELECT version FROM entity WHERE id = ?1", [lan.id], |row| row.get(0)).unwrap();
+        assert_eq!(version, SNAPSHOT_EVERY);
+
+        lan.process(LanEvent::Renamed { from: lan.name.clone(), to: NetName::from_str("home").unwrap() });
+        tx.save(&mut lan).unwrap();
+        let lan: Lan = tx.load(lan.id).unwrap().unwrap();
+        assert_eq!(lan.name, NetName::from_str("home").unwrap());
+        assert_eq!(lan.meta.version, SNAPSHOT_EVERY + 1);
+    }
+
+    #[test]
+    fn regenerates_an_unreadable_snapshot() {
+        let mut conn = migrations::runner().run(Connection::open_in_memory().unwrap()).unwrap();
+        let tx = conn.tx().unwrap();
+        let mut lan = Lan::default();
+        lan.process(created());
+        for i in 1..SNAPSHOT_EVERY {
+            lan.process(LanEvent::Renamed { from: lan.name.clone(), to: NetName::from_str(&format!("lab{i}")).unwrap() });
+        }
+        tx.save(&mut lan).unwrap();
+        tx.execute("UPDATE entity SET value = '{\"id\": 1}' WHERE id = ?1", [lan.id]).unwrap();
+
+        let loaded: Lan = tx.load(lan.id).unwrap().unwrap();
+        assert_eq!(loaded.name, lan.name);
+        assert_eq!(loaded.meta.version, SNAPSHOT_EVERY);
+        let value: String = tx.query_row("SELECT value FROM entity WHERE id = ?1", [lan.id], |row| row.get(0)).unwrap();
+        assert!(serde_json::from_str::<Lan>(&value).is_ok());
+    }
+}
@@ -1,9 +1,11 @@
 pub mod util;
+pub mod bus;
 pub mod cmd;
 pub mod query;
-// mod raft;
+pub mod raft;
 use rusqlite::{Connection, Transaction};
 
+/// Transactions are rolled back when dropped, callers commit them explicitly once they succeed
 pub trait Tx {
     fn tx(&mut self) -> Result<Transaction, rusqlite::Error>;
 }
@@ -11,7 +13,7 @@ pub trait Tx {
 impl Tx for Connection {
     fn tx(&mut self) -> Result<Transaction, rusqlite::Error> {
         let mut tx = self.transaction()?;
-        tx.set_drop_behavior(rusqlite::DropBehavior::Commit);
+        tx.set_drop_behavior(rusqlite::DropBehavior::Rollback);
         Ok(tx)
     }
 }
\ No newline at end of file
@@ -1,19 +1,22 @@
 use std::{any::type_name, marker::PhantomData};
 use log::error;
-use rusqlite::{named_params, types::FromSql, ToSql, Transaction};
-use crate::util::models::Event;
+use rusqlite::{named_params, params_from_iter, types::FromSql, ToSql, Transaction};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use crate::util::models::{Event, Id};
 
 pub trait DbView: Sized {
     // fn meta(&mut self) -> &mut Metadata;
     fn name() -> &'static str;
-    fn update(tx: &Transaction, e: &Event);
+    fn update(tx: &Transaction, e: &Event) -> Result<(), rusqlite::Error>;
     // /// Convert SqliteRow to View 
     fn try_from(row: &rusqlite::Row) -> Result<Self, rusqlite::Error>;
     fn select_fields() -> &'static str;
     fn sql_select() -> String {
         format!("SELECT {} FROM {}", Self::select_fields(), Self::name())
     }
-    // fn sql_create() -> &'static str;
+    /// Table definition used when the projection is (re)built
+    fn sql_create() -> &'static str;
 }
 
 // #[derive(Debug, Serialize, Deserialize, Default)]
@@ -89,22 +92,25 @@ impl<T> DbQuery for GetAll<T> where T: DbView {
     }
 }
 
-pub struct GetCount<T> {
+pub struct GetCount<'a, T> {
+    pub filters: &'a [Filter],
+    pub include_deleted: bool,
     pub view: PhantomData<T>
 }
 
-impl<T> DbQuery for GetCount<T> where T: DbView {
+impl<'a, T> DbQuery for GetCount<'a, T> where T: DbView {
     type Ok = usize;
 
     fn run(&self, tx: &Transaction) -> Result<Self::Ok, rusqlite::Error>  {
-        let sql = format!("SELECT COUNT(*) FROM {} WHERE deleted = :deleted", T::name());
+        let (conditions, params) = Filter::sql(self.filters);
+        let sql = format!("SELECT COUNT(*) FROM {} WHERE {}{}", T::name(), not_deleted(self.include_deleted), conditions);
         let mut stmt = tx.prepare(&sql)
             .map_err(|e| { 
                 error!("prepare() in GetCount<{}> failed: {}", type_name::<T>(), e);
                 e
             })?;
             
-        match stmt.query_row(named_params! { ":deleted": false }, |row| row.get::<_, usize>(0)) {
+        match stmt.query_row(params_from_iter(params), |row| row.get::<_, usize>(0)) {
             Ok(count) => Ok(count),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(0),
             Err(e) => Err(e).map_err(|e| { 
@@ -115,3 +121,112 @@ impl<T> DbQuery for GetCount<T> where T: DbView {
     }
 }
 
+/// Soft deleted rows stay in the views with `deleted = 1` so they can be restored
+fn not_deleted(include_deleted: bool) -> &'static str {
+    match include_deleted {
+        true => "1 = 1",
+        false => "deleted = 0"
+    }
+}
+
+/// Condition on a column of the view, the column names are never user input
+pub enum Filter {
+    Eq(&'static str, Box<dyn ToSql + Send>),
+    /// Case insensitive (ASCII only, as LIKE is) prefix of a text column
+    Prefix(&'static str, String)
+}
+
+impl Filter {
+    /// `AND ...` clause for every filter and the parameters it binds, in order
+    fn sql(filters: &[Filter]) -> (String, Vec<&dyn ToSql>) {
+        let mut sql = String::new();
+        let mut params: Vec<&dyn ToSql> = vec![];
+        for filter in filters {
+            match filter {
+                Filter::Eq(column, value) => {
+                    sql.push_str(&format!(" AND {} = ?", column));
+                    params.push(value.as_ref());
+                },
+                Filter::Prefix(column, prefix) => {
+                    // Case insensitive, the wildcards of the prefix are matched as they are
+                    sql.push_str(&format!(" AND {} LIKE replace(replace(replace(?, '\\', '\\\\'), '%', '\\%'), '_', '\\_') || '%' ESCAPE '\\'", column));
+                    params.push(prefix);
+                }
+            }
+        }
+        (sql, params)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Order {
+    #[default]
+    Asc,
+    Desc
+}
+
+/// Keyset pagination over a view sorted by `sort` then id, `cursor` is the id of the
+/// last item of the previous page so pages don't shift when rows are added or removed
+pub struct GetPage<T> {
+    pub filters: Vec<Filter>,
+    pub sort: &'static str,
+    pub order: Order,
+    pub cursor: Option<Id>,
+    pub limit: usize,
+    pub include_deleted: bool,
+    pub view: PhantomData<T>
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Items matching the filters across all pages
+    pub total: usize,
+    /// Cursor of the following page, if there is one
+    pub next: Option<Id>
+}
+
+impl<T> DbQuery for GetPage<T> where T: DbView {
+    type Ok = Page<T>;
+
+    fn run(&self, tx: &Transaction) -> Result<Self::Ok, rusqlite::Error> {
+        let (conditions, mut params) = Filter::sql(&self.filters);
+        let (cmp, order) = match self.order {
+            Order::Asc => (">", "ASC"),
+            Order::Desc => ("<", "DESC")
+        };
+        let after = match &self.cursor {
+            Some(cursor) => {
+                params.push(cursor);
+                params.push(cursor);
+                format!(" AND ({sort}, id) {cmp} ((SELECT {sort} FROM {table} WHERE id = ?), ?)", sort = self.sort, table = T::name())
+            },
+            None => String::new()
+        };
+        // One extra row tells whether there's a next page
+        let limit = self.limit + 1;
+        params.push(&limit);
+        let sql = format!("{} WHERE {}{}{} ORDER BY {sort} {order}, id {order} LIMIT ?", T::sql_select(), not_deleted(self.include_deleted), conditions, after, sort = self.sort);
+        let mut stmt = tx.prepare(&sql)
+            .map_err(|e| {
+                error!("prepare() in GetPage<{}> failed: {}", type_name::<T>(), e);
+                e
+            })?;
+        let mut rows = stmt.query_map(params_from_iter(params), |row| Ok((T::try_from(row)?, row.get::<_, Id>("id")?)))
+            .map_err(|e| {
+                error!("query_map() in GetPage<{}> failed: {}", type_name::<T>(), e);
+                e
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        let next = match rows.len() > self.limit {
+            true => {
+                rows.truncate(self.limit);
+                rows.last().map(|(_, id)| *id)
+            },
+            false => None
+        };
+        let total = GetCount { filters: &self.filters, include_deleted: self.include_deleted, view: PhantomData::<T> }.run(tx)?;
+        Ok(Page { items: rows.into_iter().map(|(item, _)| item).collect(), total, next })
+    }
+}
@@ -0,0 +1,478 @@
+use std::{collections::BTreeMap, io::Cursor, sync::{Arc, Mutex}, time::Duration};
+use chrono::{DateTime, Utc};
+use openraft::{error::{ClientWriteError, RaftError}, BasicNode};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use crate::{actors::cmd::RackdCmd, conf::Cluster, util::models::Id};
+pub mod log_store;
+pub mod state_machine;
+pub mod network;
+pub mod proposer;
+
+// Commands are replicated, not events: every node applies the committed RackdCmds
+// to its own RackdCmdActor, the Ids and timestamps are seeded from the log entry
+// so all replicas end up with the exact same event log.
+
+pub type NodeId = u64;
+
+/// Spelled out instead of declared with `openraft::declare_raft_types!`, the macro derives serde
+/// behind a `feature = "serde"` this crate doesn't have
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd)]
+pub struct TypeConfig;
+
+impl openraft::RaftTypeConfig for TypeConfig {
+    type D = LogCmd;
+    type R = ();
+    type NodeId = NodeId;
+    type Node = BasicNode;
+    type Entry = openraft::Entry<Self>;
+    type SnapshotData = Cursor<Vec<u8>>;
+    type Responder = openraft::impls::OneshotResponder<Self>;
+    type AsyncRuntime = openraft::TokioRuntime;
+}
+
+pub type Raft = openraft::Raft<TypeConfig>;
+
+/// A RackdCmd as stored in the replicated log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogCmd {
+    pub id: Id,
+    pub origin: Option<Id>,
+    pub timestamp: DateTime<Utc>,
+    /// Serialized RackdCmd
+    pub cmd: String
+}
+
+/// How leadership is decided
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClusterMode {
+    /// Every node is a voter and the leader is elected, requires at least 3 nodes
+    Elected,
+    /// 2-node rack: the leader is the only voter and the follower a learner so there's no election,
+    /// if the leader goes down the rack is read-only until it's back or roles are switched over
+    Manual
+}
+
+/// Commands proposed by this node that haven't been applied yet, they hold the channel to respond to
+pub type Pending = Arc<Mutex<BTreeMap<Id, RackdCmd>>>;
+
+#[derive(Debug, Error)]
+pub enum ClusterError {
+    #[error("Not the leader, current leader is {:?}", .0)]
+    NotLeader(Option<NodeId>),
+    #[error("Rack is read-only on this node, current leader is {:?}", .0)]
+    ReadOnly(Option<NodeId>),
+    #[error("Cluster unavailable: {}", .0)]
+    Unavailable(String),
+    #[error("Rack isn't replicated")]
+    NotReplicated,
+    #[error("Switchover requires a leader and a single follower, cluster has {} voters and {} learners", .0, .1)]
+    SwitchoverUnsupported(usize, usize)
+}
+
+impl From<RaftError<NodeId, ClientWriteError<NodeId, BasicNode>>> for ClusterError {
+    fn from(error: RaftError<NodeId, ClientWriteError<NodeId, BasicNode>>) -> Self {
+        match error {
+            RaftError::APIError(ClientWriteError::ForwardToLeader(e)) => Self::NotLeader(e.leader_id),
+            e => Self::Unavailable(e.to_string())
+        }
+    }
+}
+
+pub fn config() -> Arc<openraft::Config> {
+    let config = openraft::Config {
+        cluster_name: String::from("rackd"),
+        heartbeat_interval: 100,
+        election_timeout_min: 300,
+        election_timeout_max: 600,
+        ..Default::default()
+    };
+    Arc::new(config.validate().expect("[BUG] Invalid raft config"))
+}
+
+/// Result of a switchover
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct Switchover {
+    pub leader: NodeId,
+    pub follower: NodeId
+}
+
+/// Hands leadership of a manual 2-node cluster over to the follower, must run on the leader.
+/// The follower becomes a voter (joint config) before the leader steps down to learner
+/// so it's caught up with every committed command by the time it takes over.
+pub async fn switchover(raft: &Raft, timeout: Duration) -> Result<Switchover, ClusterError> {
+    let metrics = raft.metrics().borrow().clone();
+    let membership = metrics.membership_config.membership();
+    let voters: Vec<NodeId> = membership.voter_ids().collect();
+    let learners: Vec<NodeId> = membership.learner_ids().collect();
+    let follower = match (voters.as_slice(), learners.as_slice()) {
+        ([_], [follower]) => *follower,
+        _ => return Err(ClusterError::SwitchoverUnsupported(voters.len(), learners.len()))
+    };
+    if metrics.current_leader != Some(metrics.id) {
+        return Err(ClusterError::ReadOnly(metrics.current_leader));
+    }
+    raft.change_membership([follower], true).await?;
+    raft.wait(Some(timeout)).metrics(|m| m.current_leader == Some(follower), "switchover").await
+        .map_err(|e| ClusterError::Unavailable(e.to_string()))?;
+    Ok(Switchover { leader: follower, follower: metrics.id })
+}
+
+/// Forms the cluster out of the configured nodes the first time the rack starts, it's up to the node with
+/// the lowest id. Manual clusters start with that node as the only voter and the other one as a learner
+pub async fn bootstrap(raft: &Raft, cluster: &Cluster) -> Result<(), ClusterError> {
+    let unavailable = |e: &dyn std::fmt::Display| ClusterError::Unavailable(e.to_string());
+    let first = cluster.peers.iter().all(|peer| cluster.node < peer.id);
+    if !first || raft.is_initialized().await.map_err(|e| unavailable(&e))? {
+        return Ok(());
+    }
+    let node = BasicNode::new(&cluster.addr);
+    let peers = cluster.peers.iter().map(|peer| (peer.id, BasicNode::new(&peer.addr)));
+    match cluster.mode {
+        ClusterMode::Elected => {
+            let members = BTreeMap::from_iter(peers.chain([(cluster.node, node)]));
+            raft.initialize(members).await.map_err(|e| unavailable(&e))?;
+        },
+        ClusterMode::Manual => {
+            raft.initialize(BTreeMap::from([(cluster.node, node)])).await.map_err(|e| unavailable(&e))?;
+            raft.wait(None).metrics(|m| m.current_leader == Some(cluster.node), "bootstrap").await.map_err(|e| unavailable(&e))?;
+            for (id, peer) in peers {
+                // Followers catch up whenever they're reachable, the rack doesn't wait for them
+                raft.add_learner(id, peer, false).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Waits until the node has applied every entry up to the given log index
+pub async fn wait_applied(raft: &Raft, index: u64, timeout: Duration) -> Result<(), ClusterError> {
+    raft.wait(Some(timeout)).applied_index_at_least(Some(index), "wait_applied").await
+        .map(|_| ())
+        .map_err(|e| ClusterError::Unavailable(e.to_string()))
+}
+
+pub mod api {
+    use std::{collections::HashSet, net::{IpAddr, SocketAddr}, sync::Arc, time::Duration};
+    use axum::{extract::{ConnectInfo, OriginalUri, Request, State}, http::StatusCode, middleware::{self, Next}, response::IntoResponse, routing::post, Json, Router};
+    use log::{error, warn};
+    use openraft::raft::{AppendEntriesRequest, InstallSnapshotRequest, VoteRequest};
+    use crate::{actors::system::Rackd, conf::Peer, util::api::{Error, Response}};
+    use super::{network::{ClusterSecret, SECRET_HEADER}, switchover as switch, ClusterError, NodeId, Switchover, TypeConfig};
+
+    /// Peers allowed to call the raft RPCs of this node
+    #[derive(Debug)]
+    struct PeerAuth {
+        peers: HashSet<IpAddr>,
+        secret: ClusterSecret
+    }
+
+    /// Raft RPCs, served to the other nodes of the rack on their own listener (conf::Cluster::addr) apart from the API.
+    /// Requests are only let through from the address of a peer presenting the cluster secret,
+    /// the listener must be served with `into_make_service_with_connect_info::<SocketAddr>()`
+    pub fn peer_router(rackd: Rackd, peers: &[Peer], secret: ClusterSecret) -> Router {
+        let peers = peers.iter()
+            .filter_map(|peer| match peer.addr.parse::<SocketAddr>() {
+                Ok(addr) => Some(addr.ip().to_canonical()),
+                Err(e) => { error!("Address {} of node {} isn't an ip:port, its RPCs will be rejected: {}", peer.addr, peer.id, e); None }
+            })
+            .collect();
+        let auth = Arc::new(PeerAuth { peers, secret });
+        Router::new()
+            .route("/raft/append", post(append))
+            .route("/raft/vote", post(vote))
+            .route("/raft/snapshot", post(snapshot))
+            .with_state(rackd)
+            .layer(middleware::from_fn_with_state(auth, authorize))
+    }
+
+    async fn authorize(State(auth): State<Arc<PeerAuth>>, ConnectInfo(remote): ConnectInfo<SocketAddr>, request: Request, next: Next) -> axum::response::Response {
+        let secret = request.headers().get(SECRET_HEADER).map(|secret| secret.as_bytes());
+        if !auth.peers.contains(&remote.ip().to_canonical()) || !secret.is_some_and(|secret| auth.secret.matches(secret)) {
+            warn!("Rejected raft RPC {} from {}", request.uri().path(), remote);
+            return StatusCode::UNAUTHORIZED.into_response();
+        }
+        next.run(request).await
+    }
+
+    #[utoipa::path(post, path = "/cluster/switchover", tag = "cluster",
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn switchover(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri) -> impl IntoResponse {
+        let path = uri.path();
+        let result = match &rackd.raft {
+            Some(raft) => switch(raft, Duration::from_secs(5)).await,
+            None => Err(ClusterError::NotReplicated)
+        };
+        let response = result
+            .map(|switchover| Response::<Switchover>::ok(switchover, path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    /// Node to node RPCs of the raft network (see network::Http), they aren't part of the documented API
+    async fn append(State(rackd): State<Rackd>, Json(rpc): Json<AppendEntriesRequest<TypeConfig>>) -> impl IntoResponse {
+        match &rackd.raft {
+            Some(raft) => Json(raft.append_entries(rpc).await).into_response(),
+            None => StatusCode::NOT_FOUND.into_response()
+        }
+    }
+
+    async fn vote(State(rackd): State<Rackd>, Json(rpc): Json<VoteRequest<NodeId>>) -> impl IntoResponse {
+        match &rackd.raft {
+            Some(raft) => Json(raft.vote(rpc).await).into_response(),
+            None => StatusCode::NOT_FOUND.into_response()
+        }
+    }
+
+    async fn snapshot(State(rackd): State<Rackd>, Json(rpc): Json<InstallSnapshotRequest<TypeConfig>>) -> impl IntoResponse {
+        match &rackd.raft {
+            Some(raft) => Json(raft.install_snapshot(rpc).await).into_response(),
+            None => StatusCode::NOT_FOUND.into_response()
+        }
+    }
+
+    impl From<ClusterError> for Error {
+        fn from(error: ClusterError) -> Self {
+            let msg = error.to_string();
+            match error {
+                ClusterError::NotLeader(_) => Error::new("CLUSTER_NOT_LEADER", msg),
+                ClusterError::ReadOnly(_) => Error::new("CLUSTER_READ_ONLY", msg),
+                ClusterError::Unavailable(_) => Error::new("CLUSTER_UNAVAILABLE", msg),
+                ClusterError::NotReplicated => Error::new("CLUSTER_NOT_REPLICATED", msg),
+                ClusterError::SwitchoverUnsupported(..) => Error::new("CLUSTER_SWITCHOVER_UNSUPPORTED", msg)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, net::SocketAddr, str::FromStr, time::Duration};
+    use openraft::{raft::VoteRequest, BasicNode, Vote};
+    use tokio::{io::{AsyncReadExt, AsyncWriteExt}, net::{TcpListener, TcpSocket}};
+    use crate::{actors::system::Rackd, conf::{Cluster, Peer}, db::raft::{api, bootstrap, network::{ClusterSecret, Http, Loopback, SECRET_HEADER}, switchover, wait_applied, ClusterError, ClusterMode}, event::{model::EventFilter, query::get_events::GetEvents}, lan::{cmd::create::CreateLan, query::get_by_key::GetLanByName}, net::{NetName, Prefix, VlanId}, trunk::{cmd::create::{CreateTrunk, CreateTrunkError}, model::TrunkName}, util::models::Id};
+    use crate::util::test_support::init_rack;
+
+    async fn node(id: u64, mode: ClusterMode, network: &Loopback) -> Rackd {
+        let path = format!("file:{}?mode=memory&cache=shared", Id::new());
+        let raft_path = format!("file:{}?mode=memory&cache=shared", Id::new());
+        let rackd = Rackd::replicated(id, mode, &path, &raft_path, network.clone()).await.unwrap();
+        network.connect(id, rackd.raft.clone().unwrap());
+        rackd
+    }
+
+    async fn cluster(size: u64) -> (Loopback, BTreeMap<u64, Rackd>) {
+        let network = Loopback::default();
+        let mut nodes = BTreeMap::new();
+        for id in 1..=size {
+            nodes.insert(id, node(id, ClusterMode::Elected, &network).await);
+        }
+        let members: BTreeMap<u64, BasicNode> = nodes.keys().map(|id| (*id, BasicNode::default())).collect();
+        nodes[&1].raft.as_ref().unwrap().initialize(members).await.unwrap();
+        (network, nodes)
+    }
+
+    /// Manual 2-node cluster, node 1 leads and node 2 follows
+    async fn pair() -> (Loopback, BTreeMap<u64, Rackd>) {
+        let network = Loopback::default();
+        let mut nodes = BTreeMap::new();
+        for id in 1..=2 {
+            nodes.insert(id, node(id, ClusterMode::Manual, &network).await);
+        }
+        let leader = nodes[&1].raft.as_ref().unwrap();
+        leader.initialize(BTreeMap::from([(1, BasicNode::default())])).await.unwrap();
+        leader.wait(Some(Duration::from_secs(5))).metrics(|m| m.current_leader == Some(1), "leader").await.unwrap();
+        leader.add_learner(2, BasicNode::default(), true).await.unwrap();
+        (network, nodes)
+    }
+
+    async fn leader(nodes: &BTreeMap<u64, Rackd>) -> u64 {
+        let metrics = nodes[&1].raft.as_ref().unwrap()
+            .wait(Some(Duration::from_secs(5)))
+            .metrics(|m| m.current_leader.is_some(), "leader elected").await
+            .unwrap();
+        metrics.current_leader.unwrap()
+    }
+
+    #[tokio::test]
+    async fn replicates_commands_to_every_node() {
+        let (_network, nodes) = cluster(3).await;
+        let leader = &nodes[&leader(&nodes).await];
+        let trunk_id = init_rack(leader).await;
+        let cmd = CreateLan {
+            trunk: trunk_id,
+            vlan: VlanId::try_from(100).unwrap(),
+            name: NetName::from_str("lab").unwrap(),
+            prefix: Prefix::from_str("fd00:0:0:100::/64").unwrap()
+        };
+        let lan_id = leader.exec(cmd).await.unwrap();
+
+        let applied = leader.raft.as_ref().unwrap().metrics().borrow().last_log_index.unwrap();
+        let expected = leader.query(GetEvents { after: 0, filter: EventFilter::default(), limit: 100 }).await.unwrap();
+        assert_eq!(expected.len(), 3);
+        for rackd in nodes.values() {
+            wait_applied(rackd.raft.as_ref().unwrap(), applied, Duration::from_secs(5)).await.unwrap();
+            let events = rackd.query(GetEvents { after: 0, filter: EventFilter::default(), limit: 100 }).await.unwrap();
+            assert_eq!(events.len(), expected.len());
+            assert!(events.iter().zip(expected.iter()).all(|(a, b)| a.id == b.id && a.seq == b.seq && a.timestamp == b.timestamp));
+            let lan = rackd.query(GetLanByName { name: NetName::from_str("lab").unwrap() }).await.unwrap();
+            assert_eq!(lan.id.0, lan_id.0);
+        }
+    }
+
+    #[tokio::test]
+    async fn followers_reject_commands() {
+        let (_network, nodes) = cluster(3).await;
+        let leader = leader(&nodes).await;
+        let follower = nodes.iter().find(|(id, _)| **id != leader).map(|(_, rackd)| rackd).unwrap();
+        let cmd = CreateTrunk {
+            name: TrunkName::from_str("trunk1").unwrap()
+        };
+        assert!(follower.exec(cmd).await.is_err_and(|e| matches!(e, CreateTrunkError::Cluster(ClusterError::NotLeader(Some(id))) if id == leader)));
+    }
+
+    #[tokio::test]
+    async fn new_nodes_catch_up_from_a_snapshot() {
+        let (network, nodes) = cluster(3).await;
+        let leader = &nodes[&leader(&nodes).await];
+        let raft = leader.raft.as_ref().unwrap();
+        let trunk_id = init_rack(leader).await;
+        let cmd = CreateLan {
+            trunk: trunk_id,
+            vlan: VlanId::try_from(100).unwrap(),
+            name: NetName::from_str("lab").unwrap(),
+            prefix: Prefix::from_str("fd00:0:0:100::/64").unwrap()
+        };
+        leader.exec(cmd).await.unwrap();
+
+        // The command is answered while it's applied, before the metrics catch up with it
+        let last = raft.metrics().borrow().last_log_index.unwrap();
+        wait_applied(raft, last, Duration::from_secs(5)).await.unwrap();
+        let applied = raft.metrics().borrow().last_applied.unwrap();
+        raft.trigger().snapshot().await.unwrap();
+        raft.wait(Some(Duration::from_secs(5))).snapshot(applied, "snapshot built").await.unwrap();
+        raft.trigger().purge_log(applied.index).await.unwrap();
+        raft.wait(Some(Duration::from_secs(5))).purged(Some(applied), "log purged").await.unwrap();
+
+        let learner = node(4, ClusterMode::Elected, &network).await;
+        raft.add_learner(4, BasicNode::default(), true).await.unwrap();
+        wait_applied(learner.raft.as_ref().unwrap(), applied.index, Duration::from_secs(5)).await.unwrap();
+        assert!(learner.query(GetLanByName { name: NetName::from_str("lab").unwrap() }).await.is_ok());
+        let events = learner.query(GetEvents { after: 0, filter: EventFilter::default(), limit: 100 }).await.unwrap();
+        assert_eq!(events.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn followers_of_a_manual_cluster_are_read_only() {
+        let (_network, nodes) = pair().await;
+        let trunk_id = init_rack(&nodes[&1]).await;
+        let cmd = CreateLan {
+            trunk: trunk_id,
+            vlan: VlanId::try_from(100).unwrap(),
+            name: NetName::from_str("lab").unwrap(),
+            prefix: Prefix::from_str("fd00:0:0:100::/64").unwrap()
+        };
+        nodes[&1].exec(cmd).await.unwrap();
+
+        let follower = &nodes[&2];
+        let applied = nodes[&1].raft.as_ref().unwrap().metrics().borrow().last_log_index.unwrap();
+        wait_applied(follower.raft.as_ref().unwrap(), applied, Duration::from_secs(5)).await.unwrap();
+        assert!(follower.query(GetLanByName { name: NetName::from_str("lab").unwrap() }).await.is_ok());
+        let cmd = CreateTrunk {
+            name: TrunkName::from_str("trunk2").unwrap()
+        };
+        assert!(follower.exec(cmd).await.is_err_and(|e| matches!(e, CreateTrunkError::Cluster(ClusterError::ReadOnly(Some(1))))));
+    }
+
+    #[tokio::test]
+    async fn switchover_hands_leadership_to_the_follower() {
+        let (_network, nodes) = pair().await;
+        init_rack(&nodes[&1]).await;
+        assert!(switchover(nodes[&2].raft.as_ref().unwrap(), Duration::from_secs(5)).await.is_err_and(|e| matches!(e, ClusterError::ReadOnly(Some(1)))));
+
+        let result = switchover(nodes[&1].raft.as_ref().unwrap(), Duration::from_secs(5)).await.unwrap();
+        assert_eq!((result.leader, result.follower), (2, 1));
+        let cmd = CreateTrunk {
+            name: TrunkName::from_str("trunk2").unwrap()
+        };
+        nodes[&2].exec(cmd).await.unwrap();
+        let cmd = CreateTrunk {
+            name: TrunkName::from_str("trunk3").unwrap()
+        };
+        assert!(nodes[&1].exec(cmd).await.is_err_and(|e| matches!(e, CreateTrunkError::Cluster(ClusterError::ReadOnly(Some(2))))));
+
+        let applied = nodes[&2].raft.as_ref().unwrap().metrics().borrow().last_log_index.unwrap();
+        wait_applied(nodes[&1].raft.as_ref().unwrap(), applied, Duration::from_secs(5)).await.unwrap();
+        let events = nodes[&1].query(GetEvents { after: 0, filter: EventFilter::default(), limit: 100 }).await.unwrap();
+        assert_eq!(events.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn manual_clusters_bootstrap_over_http() {
+        // Listeners are bound first so each node knows the address of the other
+        let listeners = [TcpListener::bind("127.0.0.1:0").await.unwrap(), TcpListener::bind("127.0.0.1:0").await.unwrap()];
+        let addrs = listeners.iter().map(|listener| listener.local_addr().unwrap().to_string()).collect::<Vec<_>>();
+        let mut nodes = BTreeMap::new();
+        for (id, listener) in (1..=2).zip(listeners) {
+            let path = format!("file:{}?mode=memory&cache=shared", Id::new());
+            let raft_path = format!("file:{}?mode=memory&cache=shared", Id::new());
+            let rackd = Rackd::replicated(id, ClusterMode::Manual, &path, &raft_path, Http::new(ClusterSecret::new("s3cret"))).await.unwrap();
+            let peers = [Peer { id: 3 - id, addr: addrs[2 - id as usize].clone() }];
+            let router = api::peer_router(rackd.clone(), &peers, ClusterSecret::new("s3cret"));
+            tokio::spawn(async move { axum::serve(listener, router.into_make_service_with_connect_info::<SocketAddr>()).await });
+            nodes.insert(id, rackd);
+        }
+        let cluster = |node: u64| Cluster {
+            node, mode: ClusterMode::Manual, addr: addrs[node as usize - 1].clone(),
+            peers: vec![Peer { id: 3 - node, addr: addrs[2 - node as usize].clone() }]
+        };
+        bootstrap(nodes[&2].raft.as_ref().unwrap(), &cluster(2)).await.unwrap();
+        bootstrap(nodes[&1].raft.as_ref().unwrap(), &cluster(1)).await.unwrap();
+
+        init_rack(&nodes[&1]).await;
+        let applied = nodes[&1].raft.as_ref().unwrap().metrics().borrow().last_log_index.unwrap();
+        wait_applied(nodes[&2].raft.as_ref().unwrap(), applied, Duration::from_secs(5)).await.unwrap();
+        let events = nodes[&2].query(GetEvents { after: 0, filter: EventFilter::default(), limit: 100 }).await.unwrap();
+        assert_eq!(events.len(), 2);
+        let cmd = CreateTrunk {
+            name: TrunkName::from_str("trunk2").unwrap()
+        };
+        assert!(nodes[&2].exec(cmd).await.is_err_and(|e| matches!(e, CreateTrunkError::Cluster(ClusterError::ReadOnly(Some(1))))));
+    }
+
+    /// Status line of a vote RPC posted to addr from a socket bound to the given ip
+    async fn vote_from(ip: &str, addr: SocketAddr, secret: Option<&str>) -> String {
+        let socket = TcpSocket::new_v4().unwrap();
+        socket.bind(format!("{ip}:0").parse().unwrap()).unwrap();
+        let mut stream = socket.connect(addr).await.unwrap();
+        let body = serde_json::to_string(&VoteRequest::<u64>::new(Vote::new(1, 2), None)).unwrap();
+        let secret = secret.map(|secret| format!("{SECRET_HEADER}: {secret}\r\n")).unwrap_or_default();
+        let request = format!("POST /raft/vote HTTP/1.1\r\nHost: {addr}\r\nContent-Type: application/json\r\n{secret}Content-Length: {}\r\nConnection: close\r\n\r\n{body}", body.len());
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response.lines().next().unwrap_or_default().to_string()
+    }
+
+    #[tokio::test]
+    async fn raft_rpcs_are_only_served_to_peers_presenting_the_secret() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let path = format!("file:{}?mode=memory&cache=shared", Id::new());
+        let raft_path = format!("file:{}?mode=memory&cache=shared", Id::new());
+        let rackd = Rackd::replicated(1, ClusterMode::Elected, &path, &raft_path, Http::new(ClusterSecret::new("s3cret"))).await.unwrap();
+        let peers = [Peer { id: 2, addr: String::from("127.0.0.1:8081") }];
+        let router = api::peer_router(rackd, &peers, ClusterSecret::new("s3cret"));
+        tokio::spawn(async move { axum::serve(listener, router.into_make_service_with_connect_info::<SocketAddr>()).await });
+
+        assert!(vote_from("127.0.0.1", addr, None).await.contains("401"));
+        assert!(vote_from("127.0.0.1", addr, Some("s3cre7")).await.contains("401"));
+        // Not the address of a peer
+        assert!(vote_from("127.0.0.2", addr, Some("s3cret")).await.contains("401"));
+        assert!(vote_from("127.0.0.1", addr, Some("s3cret")).await.contains("200"));
+    }
+}
@@ -0,0 +1,151 @@
+// Helpers return the errors of openraft's storage traits as they are
+#![allow(clippy::result_large_err)]
+use std::{fmt::Debug, ops::{Bound, RangeBounds}, sync::{Arc, Mutex}};
+use openraft::{storage::{LogFlushed, RaftLogStorage}, Entry, LogId, LogState, RaftLogReader, StorageError, StorageIOError, Vote};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{de::DeserializeOwned, Serialize};
+use super::{NodeId, TypeConfig};
+
+const SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS raft_log (
+        idx     INTEGER     PRIMARY KEY,
+        entry   TEXT        NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS raft_meta (
+        key     TEXT        PRIMARY KEY,
+        value   TEXT        NOT NULL
+    );
+"#;
+
+const VOTE: &str = "vote";
+const COMMITTED: &str = "committed";
+const LAST_PURGED: &str = "last_purged";
+
+/// Raft log stored in its own SQLite database, the daemon's is at `settings().database.raft` (see Rackd::start)
+#[derive(Debug, Clone)]
+pub struct LogStore {
+    conn: Arc<Mutex<Connection>>
+}
+
+impl LogStore {
+    pub fn new(conn: Connection) -> Result<Self, rusqlite::Error> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn: Arc::new(Mutex::new(conn)) })
+    }
+
+    fn read_meta<T>(&self, key: &str) -> Result<Option<T>, StorageIOError<NodeId>> where T: DeserializeOwned {
+        let conn = self.conn.lock().unwrap();
+        let value: Option<String> = conn.query_row("SELECT value FROM raft_meta WHERE key = ?1", params![key], |row| row.get(0))
+            .optional()
+            .map_err(|e| StorageIOError::read(&e))?;
+        value.map(|v| serde_json::from_str(&v))
+            .transpose()
+            .map_err(|e| StorageIOError::read(&e))
+    }
+
+    fn write_meta<T>(&self, key: &str, value: &T) -> Result<(), StorageIOError<NodeId>> where T: Serialize {
+        let value = serde_json::to_string(value).map_err(|e| StorageIOError::write(&e))?;
+        let conn = self.conn.lock().unwrap();
+        conn.execute("INSERT INTO raft_meta (key, value) VALUES (?1, ?2) ON CONFLICT (key) DO UPDATE SET value = excluded.value", params![key, value])
+            .map_err(|e| StorageIOError::write(&e))?;
+        Ok(())
+    }
+
+    fn last_entry(&self) -> Result<Option<Entry<TypeConfig>>, StorageIOError<NodeId>> {
+        let conn = self.conn.lock().unwrap();
+        let entry: Option<String> = conn.query_row("SELECT entry FROM raft_log ORDER BY idx DESC LIMIT 1", (), |row| row.get(0))
+            .optional()
+            .map_err(|e| StorageIOError::read_logs(&e))?;
+        entry.map(|e| serde_json::from_str(&e))
+            .transpose()
+            .map_err(|e| StorageIOError::read_logs(&e))
+    }
+}
+
+impl RaftLogReader<TypeConfig> for LogStore {
+    async fn try_get_log_entries<RB>(&mut self, range: RB) -> Result<Vec<Entry<TypeConfig>>, StorageError<NodeId>>
+        where RB: RangeBounds<u64> + Clone + Debug + Send {
+        let start = match range.start_bound() {
+            Bound::Included(i) => *i as i64,
+            Bound::Excluded(i) => *i as i64 + 1,
+            Bound::Unbounded => 0
+        };
+        let end = match range.end_bound() {
+            Bound::Included(i) => (*i).min(i64::MAX as u64 - 1) as i64 + 1,
+            Bound::Excluded(i) => (*i).min(i64::MAX as u64) as i64,
+            Bound::Unbounded => i64::MAX
+        };
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT entry FROM raft_log WHERE idx >= ?1 AND idx < ?2 ORDER BY idx")
+            .map_err(|e| StorageIOError::read_logs(&e))?;
+        let entries = stmt.query_map(params![start, end], |row| row.get::<_, String>(0))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| StorageIOError::read_logs(&e))?;
+        let entries = entries.iter()
+            .map(|e| serde_json::from_str(e))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageIOError::read_logs(&e))?;
+        Ok(entries)
+    }
+}
+
+impl RaftLogStorage<TypeConfig> for LogStore {
+    type LogReader = Self;
+
+    async fn get_log_state(&mut self) -> Result<LogState<TypeConfig>, StorageError<NodeId>> {
+        let last_purged_log_id = self.read_meta::<LogId<NodeId>>(LAST_PURGED)?;
+        let last_log_id = self.last_entry()?.map(|e| e.log_id).or(last_purged_log_id);
+        Ok(LogState { last_purged_log_id, last_log_id })
+    }
+
+    async fn get_log_reader(&mut self) -> Self::LogReader {
+        self.clone()
+    }
+
+    async fn save_vote(&mut self, vote: &Vote<NodeId>) -> Result<(), StorageError<NodeId>> {
+        Ok(self.write_meta(VOTE, vote)?)
+    }
+
+    async fn read_vote(&mut self) -> Result<Option<Vote<NodeId>>, StorageError<NodeId>> {
+        Ok(self.read_meta(VOTE)?)
+    }
+
+    async fn save_committed(&mut self, committed: Option<LogId<NodeId>>) -> Result<(), StorageError<NodeId>> {
+        Ok(self.write_meta(COMMITTED, &committed)?)
+    }
+
+    async fn read_committed(&mut self) -> Result<Option<LogId<NodeId>>, StorageError<NodeId>> {
+        Ok(self.read_meta::<Option<LogId<NodeId>>>(COMMITTED)?.flatten())
+    }
+
+    async fn append<I>(&mut self, entries: I, callback: LogFlushed<TypeConfig>) -> Result<(), StorageError<NodeId>>
+        where I: IntoIterator<Item = Entry<TypeConfig>> + Send, I::IntoIter: Send {
+        {
+            let mut conn = self.conn.lock().unwrap();
+            let tx = conn.transaction().map_err(|e| StorageIOError::write_logs(&e))?;
+            for entry in entries {
+                let value = serde_json::to_string(&entry).map_err(|e| StorageIOError::write_log_entry(entry.log_id, &e))?;
+                tx.execute("INSERT OR REPLACE INTO raft_log (idx, entry) VALUES (?1, ?2)", params![entry.log_id.index, value])
+                    .map_err(|e| StorageIOError::write_log_entry(entry.log_id, &e))?;
+            }
+            tx.commit().map_err(|e| StorageIOError::write_logs(&e))?;
+        }
+        callback.log_io_completed(Ok(()));
+        Ok(())
+    }
+
+    async fn truncate(&mut self, log_id: LogId<NodeId>) -> Result<(), StorageError<NodeId>> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM raft_log WHERE idx >= ?1", params![log_id.index])
+            .map_err(|e| StorageIOError::write_logs(&e))?;
+        Ok(())
+    }
+
+    async fn purge(&mut self, log_id: LogId<NodeId>) -> Result<(), StorageError<NodeId>> {
+        self.write_meta(LAST_PURGED, &log_id)?;
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM raft_log WHERE idx <= ?1", params![log_id.index])
+            .map_err(|e| StorageIOError::write_logs(&e))?;
+        Ok(())
+    }
+}
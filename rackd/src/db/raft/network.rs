@@ -0,0 +1,169 @@
+use std::{collections::BTreeMap, fmt::Debug, fs, sync::{Arc, Mutex}};
+use http_body_util::{BodyExt, Full};
+use hyper::{body::Bytes, header::CONTENT_TYPE, Request};
+use hyper_util::{client::legacy::{connect::HttpConnector, Client}, rt::TokioExecutor};
+use openraft::{error::{InstallSnapshotError, NetworkError, RPCError, RaftError, RemoteError, Unreachable}, network::{RPCOption, RaftNetwork, RaftNetworkFactory}, raft::{AppendEntriesRequest, AppendEntriesResponse, InstallSnapshotRequest, InstallSnapshotResponse, VoteRequest, VoteResponse}, BasicNode};
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+use crate::util::secret::SecretKeyError;
+use super::{NodeId, Raft, TypeConfig};
+
+/// Header the nodes of a rack present their shared secret in on raft RPCs
+pub const SECRET_HEADER: &str = "x-rackd-cluster-secret";
+
+/// Secret shared by every node of the rack, RPCs that don't present it are rejected (see api::peer_router)
+#[derive(Clone)]
+pub struct ClusterSecret(Arc<str>);
+
+impl ClusterSecret {
+    pub fn new(secret: &str) -> Self {
+        Self(Arc::from(secret))
+    }
+
+    /// Reads the secret from the file at path (conf::Secrets::cluster), called once at startup
+    pub fn load(path: &str) -> Result<Self, SecretKeyError> {
+        let secret = fs::read_to_string(path).map_err(|e| SecretKeyError::Read(path.to_string(), e))?;
+        match secret.trim() {
+            "" => Err(SecretKeyError::Empty(path.to_string())),
+            secret => Ok(Self::new(secret))
+        }
+    }
+
+    /// Constant time comparison, a mismatch doesn't tell how much of the secret was right
+    pub fn matches(&self, presented: &[u8]) -> bool {
+        let secret = self.0.as_bytes();
+        secret.len() == presented.len() && secret.iter().zip(presented).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+    }
+}
+
+impl Debug for ClusterSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ClusterSecret(<redacted>)")
+    }
+}
+
+/// In-process network, RPCs are direct calls to the target's Raft handle.
+/// Used to run a multi-node rack in a single process (e.g. tests)
+#[derive(Clone, Default)]
+pub struct Loopback {
+    nodes: Arc<Mutex<BTreeMap<NodeId, Raft>>>
+}
+
+#[derive(Debug, Error)]
+#[error("Node {} isn't connected", .0)]
+pub struct Disconnected(NodeId);
+
+impl Loopback {
+    pub fn connect(&self, id: NodeId, raft: Raft) {
+        self.nodes.lock().unwrap().insert(id, raft);
+    }
+
+    /// The node stops receiving RPCs, as if its link went down
+    pub fn disconnect(&self, id: NodeId) {
+        self.nodes.lock().unwrap().remove(&id);
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn get<E>(&self, target: NodeId) -> Result<Raft, RPCError<NodeId, BasicNode, E>> where E: std::error::Error {
+        self.nodes.lock().unwrap().get(&target).cloned()
+            .ok_or_else(|| RPCError::Unreachable(Unreachable::new(&Disconnected(target))))
+    }
+}
+
+pub struct LoopbackConnection {
+    network: Loopback,
+    target: NodeId
+}
+
+impl RaftNetworkFactory<TypeConfig> for Loopback {
+    type Network = LoopbackConnection;
+
+    async fn new_client(&mut self, target: NodeId, _node: &BasicNode) -> Self::Network {
+        LoopbackConnection { network: self.clone(), target }
+    }
+}
+
+impl RaftNetwork<TypeConfig> for LoopbackConnection {
+    async fn append_entries(&mut self, rpc: AppendEntriesRequest<TypeConfig>, _option: RPCOption)
+        -> Result<AppendEntriesResponse<NodeId>, RPCError<NodeId, BasicNode, RaftError<NodeId>>> {
+        self.network.get(self.target)?.append_entries(rpc).await
+            .map_err(|e| RPCError::RemoteError(RemoteError::new(self.target, e)))
+    }
+
+    async fn install_snapshot(&mut self, rpc: InstallSnapshotRequest<TypeConfig>, _option: RPCOption)
+        -> Result<InstallSnapshotResponse<NodeId>, RPCError<NodeId, BasicNode, RaftError<NodeId, InstallSnapshotError>>> {
+        self.network.get(self.target)?.install_snapshot(rpc).await
+            .map_err(|e| RPCError::RemoteError(RemoteError::new(self.target, e)))
+    }
+
+    async fn vote(&mut self, rpc: VoteRequest<NodeId>, _option: RPCOption)
+        -> Result<VoteResponse<NodeId>, RPCError<NodeId, BasicNode, RaftError<NodeId>>> {
+        self.network.get(self.target)?.vote(rpc).await
+            .map_err(|e| RPCError::RemoteError(RemoteError::new(self.target, e)))
+    }
+}
+
+/// Network of a rack whose nodes are separate hosts, RPCs are posted to the `/raft/*` routes the target
+/// serves to its peers at the address of its BasicNode (see conf::Cluster and api::peer_router)
+#[derive(Clone)]
+pub struct Http {
+    client: Client<HttpConnector, Full<Bytes>>,
+    secret: ClusterSecret
+}
+
+impl Http {
+    pub fn new(secret: ClusterSecret) -> Self {
+        Self { client: Client::builder(TokioExecutor::new()).build_http(), secret }
+    }
+}
+
+pub struct HttpConnection {
+    client: Client<HttpConnector, Full<Bytes>>,
+    secret: ClusterSecret,
+    target: NodeId,
+    addr: String
+}
+
+impl RaftNetworkFactory<TypeConfig> for Http {
+    type Network = HttpConnection;
+
+    async fn new_client(&mut self, target: NodeId, node: &BasicNode) -> Self::Network {
+        HttpConnection { client: self.client.clone(), secret: self.secret.clone(), target, addr: node.addr.clone() }
+    }
+}
+
+impl HttpConnection {
+    async fn post<T, R, E>(&self, route: &str, rpc: T, option: RPCOption) -> Result<R, RPCError<NodeId, BasicNode, RaftError<NodeId, E>>>
+        where T: Serialize, R: DeserializeOwned, E: std::error::Error + DeserializeOwned {
+        let body = serde_json::to_vec(&rpc).map_err(|e| RPCError::Network(NetworkError::new(&e)))?;
+        let request = Request::post(format!("http://{}/raft/{}", self.addr, route))
+            .header(CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .header(SECRET_HEADER, self.secret.0.as_ref())
+            .body(Full::new(Bytes::from(body)))
+            .map_err(|e| RPCError::Network(NetworkError::new(&e)))?;
+        let response = match tokio::time::timeout(option.hard_ttl(), self.client.request(request)).await {
+            Ok(response) => response.map_err(|e| RPCError::Unreachable(Unreachable::new(&e)))?,
+            Err(e) => Err(RPCError::Unreachable(Unreachable::new(&e)))?
+        };
+        let body = response.into_body().collect().await.map_err(|e| RPCError::Network(NetworkError::new(&e)))?.to_bytes();
+        let result: Result<R, RaftError<NodeId, E>> = serde_json::from_slice(&body).map_err(|e| RPCError::Network(NetworkError::new(&e)))?;
+        result.map_err(|e| RPCError::RemoteError(RemoteError::new(self.target, e)))
+    }
+}
+
+impl RaftNetwork<TypeConfig> for HttpConnection {
+    async fn append_entries(&mut self, rpc: AppendEntriesRequest<TypeConfig>, option: RPCOption)
+        -> Result<AppendEntriesResponse<NodeId>, RPCError<NodeId, BasicNode, RaftError<NodeId>>> {
+        self.post("append", rpc, option).await
+    }
+
+    async fn install_snapshot(&mut self, rpc: InstallSnapshotRequest<TypeConfig>, option: RPCOption)
+        -> Result<InstallSnapshotResponse<NodeId>, RPCError<NodeId, BasicNode, RaftError<NodeId, InstallSnapshotError>>> {
+        self.post("snapshot", rpc, option).await
+    }
+
+    async fn vote(&mut self, rpc: VoteRequest<NodeId>, option: RPCOption)
+        -> Result<VoteResponse<NodeId>, RPCError<NodeId, BasicNode, RaftError<NodeId>>> {
+        self.post("vote", rpc, option).await
+    }
+}
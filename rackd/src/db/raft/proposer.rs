@@ -0,0 +1,44 @@
+use log::error;
+use crate::{actors::cmd::RackdCmd, util::{actor::{origin, AsyncActor}, models::{now, Id}}};
+use super::{ClusterError, ClusterMode, LogCmd, Pending, Raft};
+
+/// Fronts the RackdCmdActor of a replicated rack: commands are appended to the raft log
+/// and only processed once committed, by the state machine of every node
+pub struct RaftProposer {
+    pub raft: Raft,
+    pub pending: Pending,
+    pub mode: ClusterMode
+}
+
+impl RaftProposer {
+    pub fn new(raft: Raft, pending: Pending, mode: ClusterMode) -> Self {
+        Self { raft, pending, mode }
+    }
+}
+
+impl AsyncActor for RaftProposer {
+    type Message = RackdCmd;
+
+    async fn receive(&mut self, cmd: RackdCmd) {
+        let log = match serde_json::to_string(&cmd) {
+            Ok(serialized) => LogCmd { id: Id::new(), origin: origin(), timestamp: now(), cmd: serialized },
+            Err(e) => {
+                error!("[BUG] Failed to serialize command {:?}: {}", cmd, e);
+                return cmd.reject(ClusterError::Unavailable(e.to_string()));
+            }
+        };
+        let id = log.id;
+        self.pending.lock().unwrap().insert(id, cmd);
+        if let Err(e) = self.raft.client_write(log).await {
+            // Still pending means it wasn't applied, the state machine responds otherwise
+            if let Some(cmd) = self.pending.lock().unwrap().remove(&id) {
+                // Followers of a manual cluster never take over on their own
+                let error = match (ClusterError::from(e), self.mode) {
+                    (ClusterError::NotLeader(leader), ClusterMode::Manual) => ClusterError::ReadOnly(leader),
+                    (error, _) => error
+                };
+                cmd.reject(error);
+            }
+        }
+    }
+}
@@ -0,0 +1,275 @@
+// Helpers return the errors of openraft's storage traits as they are
+#![allow(clippy::result_large_err)]
+use std::{cell::RefCell, io::Cursor, sync::{Arc, Mutex}};
+use openraft::{storage::RaftStateMachine, BasicNode, Entry, EntryPayload, LogId, RaftSnapshotBuilder, Snapshot, SnapshotMeta, StorageError, StorageIOError, StoredMembership};
+use rusqlite::{params, Transaction};
+use serde::{Deserialize, Serialize};
+use crate::{actors::cmd::{RackdCmd, RackdCmdActor}, db::{cmd::{projectors, traits::KeyValueStore}, Tx}, util::{actor::{Actor, Envelope}, models::{seeded, sqlite::timestamp_to_sql, Event, Id}}};
+use super::{NodeId, Pending, TypeConfig};
+
+const LAST_APPLIED: &str = "raft_last_applied";
+const MEMBERSHIP: &str = "raft_membership";
+
+thread_local! {
+    // Serialized log id of the entry being applied on this thread, recorded by every transaction storing its events
+    static APPLYING: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Called by the EventStore for every stored event, a command's events and the log id
+/// it was applied from are committed together so a replayed entry is always recognized
+pub fn record_applied(tx: &Transaction) {
+    APPLYING.with_borrow(|applying| if let Some(log_id) = applying {
+        tx.set(LAST_APPLIED, log_id);
+    });
+}
+
+/// Applies committed commands to the RackdCmdActor, the command DB is the state machine
+#[derive(Debug, Clone)]
+pub struct StateMachine {
+    actor: Arc<Mutex<RackdCmdActor>>,
+    pending: Pending,
+    snapshot: Arc<Mutex<Option<StoredSnapshot>>>
+}
+
+#[derive(Debug, Clone)]
+pub struct StoredSnapshot {
+    meta: SnapshotMeta<NodeId, BasicNode>,
+    data: Vec<u8>
+}
+
+/// Snapshots hold the event log, entities and views are rebuilt from it
+#[derive(Debug, Serialize, Deserialize)]
+struct SnapshotData {
+    events: Vec<Event>
+}
+
+type AppliedState = (Option<LogId<NodeId>>, StoredMembership<NodeId, BasicNode>);
+
+impl StateMachine {
+    pub fn new(actor: RackdCmdActor, pending: Pending) -> Self {
+        Self {
+            actor: Arc::new(Mutex::new(actor)),
+            pending,
+            snapshot: Arc::new(Mutex::new(None))
+        }
+    }
+
+    /// Runs f on a blocking thread with exclusive access to the actor
+    async fn with_actor<T, F>(&self, f: F) -> Result<T, StorageIOError<NodeId>>
+        where F: FnOnce(&mut RackdCmdActor) -> Result<T, StorageIOError<NodeId>> + Send + 'static, T: Send + 'static {
+        let actor = self.actor.clone();
+        tokio::task::spawn_blocking(move || f(&mut actor.lock().unwrap()))
+            .await
+            .map_err(|e| StorageIOError::write_state_machine(&e))?
+    }
+}
+
+fn applied_state(tx: &Transaction) -> Result<AppliedState, StorageIOError<NodeId>> {
+    let last_applied = tx.get::<String>(LAST_APPLIED)
+        .map(|v| serde_json::from_str(&v))
+        .transpose()
+        .map_err(|e| StorageIOError::read_state_machine(&e))?;
+    let membership = tx.get::<String>(MEMBERSHIP)
+        .map(|v| serde_json::from_str(&v))
+        .transpose()
+        .map_err(|e| StorageIOError::read_state_machine(&e))?
+        .unwrap_or_default();
+    Ok((last_applied, membership))
+}
+
+fn set_applied_state(tx: &Transaction, last_applied: &Option<LogId<NodeId>>, membership: Option<&StoredMembership<NodeId, BasicNode>>) -> Result<(), StorageIOError<NodeId>> {
+    let last_applied = serde_json::to_string(last_applied).map_err(|e| StorageIOError::write_state_machine(&e))?;
+    tx.set(LAST_APPLIED, &last_applied);
+    if let Some(membership) = membership {
+        let membership = serde_json::to_string(membership).map_err(|e| StorageIOError::write_state_machine(&e))?;
+        tx.set(MEMBERSHIP, &membership);
+    }
+    Ok(())
+}
+
+impl RaftStateMachine<TypeConfig> for StateMachine {
+    type SnapshotBuilder = Self;
+
+    async fn applied_state(&mut self) -> Result<AppliedState, StorageError<NodeId>> {
+        Ok(self.with_actor(|actor| {
+            let tx = actor.conn.tx().map_err(|e| StorageIOError::read_state_machine(&e))?;
+            applied_state(&tx)
+        }).await?)
+    }
+
+    async fn apply<I>(&mut self, entries: I) -> Result<Vec<()>, StorageError<NodeId>>
+        where I: IntoIterator<Item = Entry<TypeConfig>> + Send, I::IntoIter: Send {
+        let mut responses = Vec::new();
+        for entry in entries {
+            let pending = self.pending.clone();
+            self.with_actor(move |actor| {
+                let log_id = entry.log_id;
+                let (last_applied, _) = applied_state(&actor.conn.tx().map_err(|e| StorageIOError::apply(log_id, &e))?)?;
+                if last_applied >= Some(log_id) {
+                    // Applied before a crash, its events are already stored
+                    if let EntryPayload::Normal(log) = &entry.payload {
+                        pending.lock().unwrap().remove(&log.id);
+                    }
+                    return Ok(());
+                }
+                let membership = match entry.payload {
+                    EntryPayload::Blank => None,
+                    EntryPayload::Normal(log) => {
+                        // The node that proposed the command still holds the caller's channel
+                        let cmd = match pending.lock().unwrap().remove(&log.id) {
+                            Some(cmd) => cmd,
+                            None => serde_json::from_str::<RackdCmd>(&log.cmd).map_err(|e| StorageIOError::apply(log_id, &e))?
+                        };
+                        let applying = serde_json::to_string(&Some(log_id)).map_err(|e| StorageIOError::apply(log_id, &e))?;
+                        APPLYING.set(Some(applying));
+                        seeded(log.id, log.timestamp, || actor.deliver(Envelope { origin: log.origin, message: cmd }));
+                        APPLYING.set(None);
+                        None
+                    },
+                    EntryPayload::Membership(membership) => Some(StoredMembership::new(Some(log_id), membership))
+                };
+                // Commands that stored events already recorded it, this covers the ones that didn't
+                // (rejected commands, blank and membership entries) which have nothing to duplicate
+                let tx = actor.conn.tx().map_err(|e| StorageIOError::apply(log_id, &e))?;
+                set_applied_state(&tx, &Some(log_id), membership.as_ref())?;
+                tx.commit().map_err(|e| StorageIOError::apply(log_id, &e))
+            }).await?;
+            responses.push(());
+        }
+        Ok(responses)
+    }
+
+    async fn get_snapshot_builder(&mut self) -> Self::SnapshotBuilder {
+        self.clone()
+    }
+
+    async fn begin_receiving_snapshot(&mut self) -> Result<Box<Cursor<Vec<u8>>>, StorageError<NodeId>> {
+        Ok(Box::new(Cursor::new(Vec::new())))
+    }
+
+    async fn install_snapshot(&mut self, meta: &SnapshotMeta<NodeId, BasicNode>, snapshot: Box<Cursor<Vec<u8>>>) -> Result<(), StorageError<NodeId>> {
+        let data = snapshot.into_inner();
+        let snapshot: SnapshotData = serde_json::from_slice(&data)
+            .map_err(|e| StorageIOError::read_snapshot(Some(meta.signature()), &e))?;
+        let stored = meta.clone();
+        self.with_actor(move |actor| {
+            let signature = Some(stored.signature());
+            let tx = actor.conn.tx().map_err(|e| StorageIOError::write_snapshot(signature.clone(), &e))?;
+            let install = || -> Result<(), rusqlite::Error> {
+                tx.execute("DELETE FROM event", ())?;
+                tx.execute("DELETE FROM entity", ())?;
+                let mut stmt = tx.prepare("INSERT INTO event (seq, id, stream_id, version, data, timestamp, origin) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)")?;
+                for e in &snapshot.events {
+                    stmt.execute(params![e.seq, e.id, e.stream_id, e.version, e.data, timestamp_to_sql(&e.timestamp), e.origin])?;
+                }
+                projectors().rebuild_all(&tx)?;
+                Ok(())
+            };
+            install().map_err(|e| StorageIOError::write_snapshot(signature.clone(), &e))?;
+            set_applied_state(&tx, &stored.last_log_id, Some(&stored.last_membership))?;
+            tx.commit().map_err(|e| StorageIOError::write_snapshot(signature, &e))
+        }).await?;
+        *self.snapshot.lock().unwrap() = Some(StoredSnapshot { meta: meta.clone(), data });
+        Ok(())
+    }
+
+    async fn get_current_snapshot(&mut self) -> Result<Option<Snapshot<TypeConfig>>, StorageError<NodeId>> {
+        let snapshot = self.snapshot.lock().unwrap().clone();
+        Ok(snapshot.map(|s| Snapshot { meta: s.meta, snapshot: Box::new(Cursor::new(s.data)) }))
+    }
+}
+
+impl RaftSnapshotBuilder<TypeConfig> for StateMachine {
+    async fn build_snapshot(&mut self) -> Result<Snapshot<TypeConfig>, StorageError<NodeId>> {
+        let (meta, data) = self.with_actor(|actor| {
+            let tx = actor.conn.tx().map_err(|e| StorageIOError::read_state_machine(&e))?;
+            let (last_log_id, last_membership) = applied_state(&tx)?;
+            let read = || -> Result<Vec<Event>, rusqlite::Error> {
+                let mut stmt = tx.prepare(&format!("SELECT {} FROM event ORDER BY seq", Event::select_fields()))?;
+                let events = stmt.query_map((), |row| Event::try_from(row))?;
+                events.collect()
+            };
+            let events = read().map_err(|e| StorageIOError::read_state_machine(&e))?;
+            let data = serde_json::to_vec(&SnapshotData { events }).map_err(|e| StorageIOError::read_state_machine(&e))?;
+            let snapshot_id = match last_log_id {
+                Some(log_id) => format!("{}-{}", log_id, Id::new()),
+                None => format!("0-{}", Id::new())
+            };
+            Ok((SnapshotMeta { last_log_id, last_membership, snapshot_id }, data))
+        }).await?;
+        *self.snapshot.lock().unwrap() = Some(StoredSnapshot { meta: meta.clone(), data: data.clone() });
+        Ok(Snapshot { meta, snapshot: Box::new(Cursor::new(data)) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use openraft::{storage::RaftStateMachine, CommittedLeaderId, Entry, EntryPayload, LogId};
+    use rusqlite::Connection;
+    use tokio::sync::oneshot;
+    use crate::{actors::cmd::{RackdCmd, RackdCmdActor}, db::{self, bus::EventBus, cmd::traits::KeyValueStore, raft::{LogCmd, Pending, TypeConfig}, Tx}, trunk::{cmd::rename::RenameTrunk, model::{TrunkId, TrunkName}}, util::{actor::{Msg, Process}, models::{now, Id}}};
+    use super::{StateMachine, APPLYING, LAST_APPLIED};
+    use crate::util::test_support::lim15109;
+
+    fn entry(index: u64, cmd: &RackdCmd) -> Entry<TypeConfig> {
+        let log = LogCmd { id: Id::new(), origin: None, timestamp: now(), cmd: serde_json::to_string(cmd).unwrap() };
+        Entry { log_id: LogId::new(CommittedLeaderId::new(1, 1), index), payload: EntryPayload::Normal(log) }
+    }
+
+    fn rename(index: u64, id: TrunkId, name: &str) -> Entry<TypeConfig> {
+        let (respond_to, _) = oneshot::channel();
+        entry(index, &RackdCmd::from(Msg { payload: RenameTrunk { id, name: TrunkName::from_str(name).unwrap() }, respond_to }))
+    }
+
+    fn events(state_machine: &StateMachine) -> u32 {
+        let mut actor = state_machine.actor.lock().unwrap();
+        let tx = actor.conn.tx().unwrap();
+        tx.query_row("SELECT COUNT(*) FROM event", (), |row| row.get(0)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn entries_applied_before_a_crash_are_skipped() {
+        let path = format!("file:{}?mode=memory&cache=shared", Id::new());
+        let conn = db::cmd::migrations::runner().run(Connection::open(&path).unwrap()).unwrap();
+        let pending = Pending::default();
+        let mut state_machine = StateMachine::new(RackdCmdActor::new(conn, EventBus::new()), pending.clone());
+
+        let (respond_to, initialized) = oneshot::channel();
+        let cmd = lim15109();
+        let cmd = RackdCmd::from(Msg { payload: cmd, respond_to });
+        let init = entry(1, &cmd);
+        if let EntryPayload::Normal(log) = &init.payload {
+            pending.lock().unwrap().insert(log.id, cmd);
+        }
+        state_machine.apply([init]).await.unwrap();
+        let trunk = initialized.await.unwrap().unwrap().trunk;
+        state_machine.apply([rename(2, trunk, "trunk2"), rename(3, trunk, "trunk1")]).await.unwrap();
+
+        // Renaming to trunk2 is valid again, only the log id tells it was already applied
+        let stored = events(&state_machine);
+        state_machine.apply([rename(2, trunk, "trunk2")]).await.unwrap();
+        assert_eq!(events(&state_machine), stored);
+    }
+
+    #[test]
+    fn commands_whose_projections_fail_store_nothing() {
+        let conn = db::cmd::migrations::runner().run(Connection::open_in_memory().unwrap()).unwrap();
+        // The rack is stored before the trunk, whose projection can't be updated anymore
+        conn.execute("DROP TABLE trunk_view", ()).unwrap();
+        let bus = EventBus::new();
+        let mut feed = bus.subscribe();
+        let mut actor = RackdCmdActor::new(conn, bus);
+
+        APPLYING.set(Some(serde_json::to_string(&Some(LogId::new(CommittedLeaderId::new(1, 1), 1))).unwrap()));
+        assert!(lim15109().process(&mut actor).is_err());
+        APPLYING.set(None);
+        actor.bus.publish_staged();
+
+        let tx = actor.conn.tx().unwrap();
+        let count = |table: &str| tx.query_row(&format!("SELECT COUNT(*) FROM {table}"), (), |row| row.get::<_, u32>(0)).unwrap();
+        assert_eq!((count("event"), count("entity"), count("rack_view")), (0, 0, 0));
+        assert_eq!(tx.get::<String>(LAST_APPLIED), None);
+        assert!(feed.try_recv().is_err());
+    }
+}
@@ -5,7 +5,8 @@ use include_dir::Dir;
 use log::{error, info};
 use std::{collections::BTreeMap, ops::Bound::{Excluded, Included}};
 use semver::Version;
-use crate::{db::cmd::traits::*, util::models::Event};
+use utoipa::ToSchema;
+use crate::{db::cmd::traits::*, util::models::{Event, Id}};
 use super::query::traits::DbView;
 
 pub struct Projectors(pub HashMap<String, Projector>);
@@ -19,38 +20,86 @@ impl Projectors {
         let projector = Projector {
             table: T::name(),
             apply: T::update,
-            // sql_create: T::sql_create()
+            sql_create: T::sql_create()
         };
         self.0.insert(String::from(projector.table), projector);
-        // save 
     }
 
-    pub fn exec(&self, tx: &Transaction, e: &Event) {
+    pub fn get(&self, table: &str) -> Option<&Projector> {
+        self.0.get(table)
+    }
+
+    pub fn tables(&self) -> Vec<&'static str> {
+        let mut tables: Vec<&'static str> = self.0.values().map(|p| p.table).collect();
+        tables.sort();
+        tables
+    }
+
+    pub fn exec(&self, tx: &Transaction, e: &Event) -> Result<(), rusqlite::Error> {
         for (_, projector) in &self.0 {
-            (projector.apply)(tx, e)
+            (projector.apply)(tx, e).map_err(|error| { 
+                error!("[BUG] Projector {} failed on event {}: {}", projector.table, e.id, error); 
+                error 
+            })?;
         }
+        Ok(())
     }
 
-    // pub fn rebuild<T: View>(&self, tx: &Transaction) {
-    //     info!("[REBUILD_PROJECTION] Rebuilding Projection for View {}", T::name());
-    //     if let Err(e) = tx.execute(&format!("DROP TABLE IF EXISTS {}", T::name()), ()) {
-    //         error!("Failed to Drop Table {} when rebuilding projection: {}", T::name(), e);
-    //         std::process::exit(-1)
-    //     }
-    //     info!("[REBUILD_PROJECTION] View Table Dropped");
-    //     if let Err(e) = tx.execute(T::sql_create(), ()) {
-    //         error!("Failed to Recreate {} when rebuilding projection: {}", T::name(), e);
-    //         std::process::exit(-1)
-    //     }
-    //     info!("[REBUILD_PROJECTION] View Table Recreated");
-    //     // TBD
-    // }
+    /// Rebuilds every registered projection, used by migrations that change view tables
+    pub fn rebuild_all(&self, tx: &Transaction) -> Result<Vec<RebuildReport>, rusqlite::Error> {
+        self.tables().into_iter()
+            .map(|table| self.0[table].rebuild(tx))
+            .collect()
+    }
 }
 
 pub struct Projector {
     pub table: &'static str,
-    pub apply: fn(&Transaction, &Event)
-    // pub sql_create: &'static str
+    pub apply: fn(&Transaction, &Event) -> Result<(), rusqlite::Error>,
+    pub sql_create: &'static str
+}
+
+impl Projector {
+    /// Drops and recreates the view table, then replays the whole event log through it
+    pub fn rebuild(&self, tx: &Transaction) -> Result<RebuildReport, rusqlite::Error> {
+        info!("[REBUILD_PROJECTION] Rebuilding Projection for View {}", self.table);
+        tx.execute(&format!("DROP TABLE IF EXISTS {}", self.table), ())
+            .map_err(|e| { error!("Failed to Drop Table {} when rebuilding projection: {}", self.table, e); e })?;
+        tx.execute(self.sql_create, ())
+            .map_err(|e| { error!("Failed to Recreate {} when rebuilding projection: {}", self.table, e); e })?;
+        info!("[REBUILD_PROJECTION] View Table {} Recreated", self.table);
+
+        let mut report = RebuildReport { view: String::from(self.table), ..Default::default() };
+        let mut stmt = tx.prepare(&format!("SELECT {} FROM event ORDER BY seq", Event::select_fields()))?;
+        let mut rows = stmt.query(())?;
+        while let Some(row) = rows.next()? {
+            let e = Event::try_from(row)?;
+            if let Err(error) = (self.apply)(tx, &e) {
+                error!("[REBUILD_PROJECTION] View {} failed on event {} (seq {}): {}", self.table, e.id, e.seq, error);
+                report.failures.push(RebuildFailure { seq: e.seq, event: e.id, error: error.to_string() });
+            }
+            report.events += 1;
+            if report.events.is_multiple_of(1000) {
+                info!("[REBUILD_PROJECTION] View {}: {} events replayed", self.table, report.events);
+            }
+        }
+        info!("[REBUILD_PROJECTION] View {} rebuilt: {} events replayed, {} failures", self.table, report.events, report.failures.len());
+        Ok(report)
+    }
+}
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct RebuildReport {
+    pub view: String,
+    pub events: usize,
+    pub failures: Vec<RebuildFailure>
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RebuildFailure {
+    pub seq: u64,
+    pub event: Id,
+    pub error: String
 }
 
 pub type DbAction = fn(&Transaction) -> Result<(), rusqlite::Error>;
@@ -62,7 +111,7 @@ pub struct Migration {
     down: Option<MigrationFn>
 }
 
-pub type MigrationFn = fn(&Transaction) -> ();
+pub type MigrationFn = fn(&Transaction) -> Result<(), rusqlite::Error>;
 
 impl Migration {
     pub fn to(version: &str) -> Self {
@@ -100,7 +149,7 @@ impl<'a> MigrationRunner<'a> {
         // Self(BTreeMap::from_iter(migrations.into_iter().map(|m| (m.version.clone(), m))))
     }
 
-    pub fn run(&self, mut conn: Connection) -> Connection {
+    pub fn run(&self, mut conn: Connection) -> Result<Connection, rusqlite::Error> {
         let tx = conn.transaction().map_err(|e| error!("Failed to open tx: {}", e)).unwrap();
         let table_count = match tx.query_row("SELECT COUNT(*) FROM sqlite_master WHERE type='table'", [], |row| row.get::<_, usize>(0)) {
             Ok(count) => count,
@@ -131,13 +180,15 @@ impl<'a> MigrationRunner<'a> {
         
         for (version, migration) in self.migrations.range((Excluded(db_version), Included(bin_version))) {
             let tx = conn.transaction().map_err(|e| error!("{}", e)).unwrap();
-            Self::deploy(tx, migration, &metadata, &self.schemas);
+            Self::deploy(tx, migration, &metadata, &self.schemas)
+                .map_err(|e| { error!("Migration to {} failed: {}", version, e); e })?;
             info!("FINISHED RUNNING MIGRATION TO {}", version)
         }
-        conn
+        Ok(conn)
     }
 
-    fn deploy(tx: Transaction, migration: &Migration, metadata: &DbMetadata, schemas: &Dir) {
+    /// The migration is rolled back if its script or its up action fail
+    fn deploy(tx: Transaction, migration: &Migration, metadata: &DbMetadata, schemas: &Dir) -> Result<(), rusqlite::Error> {
         let target_version = migration.version.clone();
         let sql_path = format!("{}_up.sql", target_version.to_string());
         
@@ -146,19 +197,19 @@ impl<'a> MigrationRunner<'a> {
                 Some(sql) => sql,
                 None => panic!("Failed to read migrations file")
             };
-            if let Err(e) = tx.execute_batch(sql) {
-                println!("Failed to execute migration script: {e:?}");
-            } 
+            tx.execute_batch(sql)
+                .map_err(|e| { error!("Failed to execute migration script {}: {}", sql_path, e); e })?;
         }
 
         if let Some(up) = migration.up {
-            up(&tx);
+            up(&tx)?;
         }
         let mut metadata = metadata.clone();
         metadata.version = target_version;
         tx.set::<DbMetadata>("metadata", &metadata);
-        tx.commit();
+        tx.commit()?;
         info!("Finished Deploying Migration");
+        Ok(())
     }
 }
 
@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use utoipa::IntoParams;
+use crate::util::models::{Event, EventKind, Id};
+
+#[derive(Debug, Default, Clone, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EventFilter {
+    /// Only events of this EventData variant
+    pub kind: Option<EventKind>,
+    /// Only events of this entity stream
+    #[param(value_type = Option<String>)]
+    pub stream_id: Option<Id>,
+    /// Only events recorded at or after this time (RFC 3339)
+    #[param(value_type = Option<String>)]
+    pub from: Option<DateTime<Utc>>,
+    /// Only events recorded before this time (RFC 3339)
+    #[param(value_type = Option<String>)]
+    pub to: Option<DateTime<Utc>>
+}
+
+impl EventFilter {
+    pub fn matches(&self, e: &Event) -> bool {
+        self.kind.is_none_or(|kind| kind == e.data.kind())
+            && self.stream_id.is_none_or(|id| id == e.stream_id)
+            && self.from.is_none_or(|from| e.timestamp >= from)
+            && self.to.is_none_or(|to| e.timestamp < to)
+    }
+}
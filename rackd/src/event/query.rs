@@ -0,0 +1,7 @@
+use crate::util::actor::Msg;
+pub mod get_events;
+
+#[derive(Debug)]
+pub enum EventQuery {
+    GetEvents(Msg<get_events::GetEvents>)
+}
@@ -0,0 +1,209 @@
+use rusqlite::named_params;
+use serde::Serialize;
+use thiserror::Error;
+use crate::{actors::query::RackdQueryActor, db::{query::traits::DbQuery, Tx}, event::model::EventFilter, util::{actor::{Payload, Process}, models::{sqlite::timestamp_to_sql, Event}}};
+
+pub const MAX_LIMIT: u32 = 1000;
+
+/// Events stored after `after` (by seq) matching the filter, in log order
+#[derive(Debug)]
+pub struct GetEvents {
+    pub after: u64,
+    pub filter: EventFilter,
+    pub limit: u32
+}
+
+/// A page of the event log, `next` is the seq to resume from when there may be more events
+#[derive(Debug, Default, Serialize)]
+pub struct EventPage {
+    pub events: Vec<Event>,
+    pub next: Option<u64>
+}
+
+#[derive(Debug, Error)]
+pub enum GetEventsError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error("Limit must be between 1 and {}", MAX_LIMIT)]
+    InvalidLimit
+}
+
+impl Payload for GetEvents {
+    type Ok = Vec<Event>;
+    type Err = GetEventsError;
+}
+
+impl DbQuery for GetEvents {
+    type Ok = Vec<Event>;
+
+    fn run(&self, tx: &rusqlite::Transaction) -> Result<Self::Ok, rusqlite::Error> {
+        let sql = format!(r#"
+            SELECT {} FROM event
+            WHERE seq > :after
+                AND (:stream_id IS NULL OR stream_id = :stream_id)
+                AND (:kind IS NULL OR json_type(data, '$.' || :kind) IS NOT NULL)
+                AND (:from IS NULL OR timestamp >= :from)
+                AND (:to IS NULL OR timestamp < :to)
+            ORDER BY seq
+            LIMIT :limit
+        "#, Event::select_fields());
+        let mut stmt = tx.prepare(&sql)?;
+        let kind = self.filter.kind.map(|kind| kind.to_string());
+        let events = stmt.query_map(named_params! {
+            ":after": self.after,
+            ":stream_id": self.filter.stream_id,
+            ":kind": kind,
+            ":from": self.filter.from.as_ref().map(timestamp_to_sql),
+            ":to": self.filter.to.as_ref().map(timestamp_to_sql),
+            ":limit": self.limit
+        }, |row| Event::try_from(row))?;
+        events.collect()
+    }
+}
+
+impl Process for GetEvents {
+    type Actor = RackdQueryActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        if self.limit == 0 || self.limit > MAX_LIMIT {
+            Err(GetEventsError::InvalidLimit)?
+        }
+        let tx = actor.conn.tx()?;
+        Ok(self.run(&tx)?)
+    }
+}
+
+pub mod casts {
+    use crate::{actors::query::RackdQuery, event::query::EventQuery, util::actor::Msg};
+    use super::GetEvents;
+
+    impl From<Msg<GetEvents>> for RackdQuery {
+        fn from(query: Msg<GetEvents>) -> Self {
+            Self::Event(EventQuery::GetEvents(query))
+        }
+    }
+}
+
+pub mod api {
+    use axum::{extract::{OriginalUri, Query, State}, response::IntoResponse};
+    use serde::Deserialize;
+    use utoipa::IntoParams;
+    use crate::{actors::system::Rackd, event::model::EventFilter, util::api::{Error, Response}};
+    use super::{EventPage, GetEvents, GetEventsError};
+
+    #[derive(Debug, Deserialize, IntoParams)]
+    #[into_params(parameter_in = Query)]
+    pub struct PageParams {
+        /// Only events with a greater seq, use the `next` of the previous page
+        pub after: Option<u64>,
+        /// Defaults to 100, at most 1000
+        pub limit: Option<u32>
+    }
+
+    #[utoipa::path(get, path = "/events", tag = "events",
+        params(PageParams, EventFilter),
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn get_events(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Query(page): Query<PageParams>, Query(filter): Query<EventFilter>) -> impl IntoResponse {
+        let path = uri.path();
+        let limit = page.limit.unwrap_or(100);
+        let query = GetEvents { after: page.after.unwrap_or(0), filter, limit };
+        let response = rackd.query(query).await
+            .map(|events| {
+                let next = match events.last() {
+                    Some(last) if events.len() == limit as usize => Some(last.seq),
+                    _ => None
+                };
+                Response::ok(EventPage { events, next }, path).to_axum_json()
+            })
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl From<GetEventsError> for Error {
+        fn from(error: GetEventsError) -> Self {
+            let msg = error.to_string();
+            match error {
+                GetEventsError::Db(_) => Error::new("GET_EVENTS_DB_ERROR", msg),
+                GetEventsError::InvalidLimit => Error::new("GET_EVENTS_INVALID_LIMIT", msg)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use chrono::{TimeDelta, Utc};
+    use crate::{actors::system::Rackd, event::{model::EventFilter, query::get_events::{GetEvents, GetEventsError}}, lan::cmd::{create::CreateLan, rename::RenameLan}, net::{NetName, Prefix, VlanId}, trunk::{cmd::create::CreateTrunk, model::TrunkName}, util::{actor::with_origin, models::{EventKind, Id}}};
+    use crate::util::test_support::{init_rack, lim15109};
+
+    #[tokio::test]
+    async fn can_filter_and_resume_events() {
+        let rackd = Rackd::mock().unwrap();
+        let trunk_id = init_rack(&rackd).await;
+        let cmd = CreateLan {
+            trunk: trunk_id,
+            vlan: VlanId::try_from(100).unwrap(),
+            name: NetName::from_str("lab").unwrap(),
+            prefix: Prefix::from_str("fd00:0:0:100::/64").unwrap()
+        };
+        let lan_id = rackd.exec(cmd).await.unwrap();
+        let cmd = RenameLan {
+            id: lan_id,
+            name: NetName::from_str("lab2").unwrap()
+        };
+        rackd.exec(cmd).await.unwrap();
+
+        let all = rackd.query(GetEvents { after: 0, filter: EventFilter::default(), limit: 100 }).await.unwrap();
+        assert_eq!(all.len(), 4);
+        assert!(all.windows(2).all(|w| w[0].seq < w[1].seq));
+
+        let filter = EventFilter { kind: Some(EventKind::Lan), ..Default::default() };
+        let lans = rackd.query(GetEvents { after: 0, filter, limit: 100 }).await.unwrap();
+        assert_eq!(lans.len(), 2);
+        assert!(lans.iter().all(|e| e.stream_id == lan_id.0));
+
+        let filter = EventFilter { stream_id: Some(lan_id.0), ..Default::default() };
+        let resumed = rackd.query(GetEvents { after: lans[0].seq, filter, limit: 100 }).await.unwrap();
+        assert_eq!(resumed.len(), 1);
+        assert_eq!(resumed[0].seq, lans[1].seq);
+    }
+
+    #[tokio::test]
+    async fn can_filter_events_by_time_range() {
+        let rackd = Rackd::mock().unwrap();
+        init_rack(&rackd).await;
+        let now = Utc::now();
+
+        let filter = EventFilter { from: Some(now - TimeDelta::minutes(1)), to: Some(now + TimeDelta::minutes(1)), ..Default::default() };
+        assert_eq!(rackd.query(GetEvents { after: 0, filter, limit: 100 }).await.unwrap().len(), 2);
+        let filter = EventFilter { from: Some(now + TimeDelta::minutes(1)), ..Default::default() };
+        assert!(rackd.query(GetEvents { after: 0, filter, limit: 100 }).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn records_the_origin_of_events() {
+        let rackd = Rackd::mock().unwrap();
+        let origin = Id::new();
+        let cmd = lim15109();
+        with_origin(origin, rackd.exec(cmd)).await.unwrap();
+        let cmd = CreateTrunk {
+            name: TrunkName::from_str("trunk2").unwrap()
+        };
+        rackd.exec(cmd).await.unwrap();
+
+        let events = rackd.query(GetEvents { after: 0, filter: EventFilter::default(), limit: 100 }).await.unwrap();
+        assert_eq!(events[0].origin, Some(origin));
+        assert_eq!(events[1].origin, Some(origin));
+        assert_eq!(events[2].origin, None);
+    }
+
+    #[tokio::test]
+    async fn cant_get_more_than_max_limit_events() {
+        let rackd = Rackd::mock().unwrap();
+        let query = GetEvents { after: 0, filter: EventFilter::default(), limit: 5000 };
+        assert!(rackd.query(query).await.is_err_and(|e| matches!(e, GetEventsError::InvalidLimit)));
+    }
+}
@@ -0,0 +1,153 @@
+use log::{error, warn};
+use tokio::sync::{broadcast::{self, error::RecvError}, mpsc};
+use crate::{actors::system::Rackd, event::{model::EventFilter, query::get_events::GetEvents}, util::models::Event};
+
+const PAGE: u32 = 500;
+
+/// Streams the events matching the filter.
+/// When `after` is set, the events stored after that seq are replayed from the event log first,
+/// otherwise only events committed from now on are sent.
+pub fn subscribe(rackd: Rackd, filter: EventFilter, after: Option<u64>) -> mpsc::Receiver<Event> {
+    let (sender, receiver) = mpsc::channel(PAGE as usize);
+    // Subscribe before catching up (and before the feed gets to run) so no event committed in between is missed
+    let live = rackd.events.subscribe();
+    tokio::spawn(feed(rackd, filter, after, live, sender));
+    receiver
+}
+
+async fn feed(rackd: Rackd, filter: EventFilter, after: Option<u64>, mut live: broadcast::Receiver<Event>, sender: mpsc::Sender<Event>) {
+    let mut last = match after {
+        Some(after) => match catch_up(&rackd, &filter, after, &sender).await {
+            Some(last) => Some(last),
+            None => return
+        },
+        None => None
+    };
+    loop {
+        match live.recv().await {
+            Ok(e) => {
+                if last.is_some_and(|last| e.seq <= last) {
+                    continue;
+                }
+                last = Some(e.seq);
+                if filter.matches(&e) && sender.send(e).await.is_err() {
+                    return;
+                }
+            },
+            Err(RecvError::Lagged(n)) => match last {
+                Some(after) => match catch_up(&rackd, &filter, after, &sender).await {
+                    Some(after) => last = Some(after),
+                    None => return
+                },
+                None => warn!("[EVENT_STREAM] Subscriber lagged behind, {} events were dropped", n)
+            },
+            Err(RecvError::Closed) => return
+        }
+    }
+}
+
+/// Sends every stored event after the given seq, returns the last seq read or None if the stream is over
+async fn catch_up(rackd: &Rackd, filter: &EventFilter, mut after: u64, sender: &mpsc::Sender<Event>) -> Option<u64> {
+    loop {
+        let query = GetEvents { after, filter: filter.clone(), limit: PAGE };
+        let events = match rackd.query(query).await {
+            Ok(events) => events,
+            Err(e) => {
+                error!("[EVENT_STREAM] Failed to read events after seq {}: {}", after, e);
+                return None;
+            }
+        };
+        let done = events.len() < PAGE as usize;
+        for e in events {
+            after = e.seq;
+            sender.send(e).await.ok()?;
+        }
+        if done {
+            return Some(after);
+        }
+    }
+}
+
+pub mod api {
+    use axum::{extract::{Query, State}, http::HeaderMap, response::{sse::{Event as SseEvent, KeepAlive}, Sse}};
+    use futures::Stream;
+    use serde::Deserialize;
+    use utoipa::IntoParams;
+    use crate::{actors::system::Rackd, event::model::EventFilter};
+
+    #[derive(Debug, Deserialize, IntoParams)]
+    #[into_params(parameter_in = Query)]
+    pub struct StreamParams {
+        /// Resume after this seq, the Last-Event-ID header takes precedence
+        pub from_seq: Option<u64>
+    }
+
+    #[utoipa::path(get, path = "/events/stream", tag = "events",
+        params(StreamParams, EventFilter),
+        responses((status = OK, description = "Server-Sent Events, the id of each event is its seq", content_type = "text/event-stream"))
+    )]
+    pub async fn stream(State(rackd): State<Rackd>, headers: HeaderMap, Query(params): Query<StreamParams>, Query(filter): Query<EventFilter>) -> Sse<impl Stream<Item = Result<SseEvent, axum::Error>>> {
+        let last_event_id = headers.get("last-event-id")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok());
+        let receiver = super::subscribe(rackd, filter, last_event_id.or(params.from_seq));
+        let stream = futures::stream::unfold(receiver, |mut receiver| async move {
+            let e = receiver.recv().await?;
+            let sse = SseEvent::default()
+                .id(e.seq.to_string())
+                .event(e.data.kind().to_string())
+                .json_data(&e);
+            Some((sse, receiver))
+        });
+        Sse::new(stream).keep_alive(KeepAlive::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{str::FromStr, time::Duration};
+    use tokio::time::timeout;
+    use crate::{actors::system::Rackd, event::{model::EventFilter, stream::subscribe}, lan::cmd::{create::CreateLan, rename::RenameLan}, net::{NetName, Prefix, VlanId}, trunk::{cmd::create::CreateTrunk, model::TrunkName}, util::models::EventKind};
+    use crate::util::test_support::init_rack;
+
+    #[tokio::test]
+    async fn streams_committed_events_after_a_seq() {
+        let rackd = Rackd::mock().unwrap();
+        let trunk_id = init_rack(&rackd).await;
+        let cmd = CreateLan {
+            trunk: trunk_id,
+            vlan: VlanId::try_from(100).unwrap(),
+            name: NetName::from_str("lab").unwrap(),
+            prefix: Prefix::from_str("fd00:0:0:100::/64").unwrap()
+        };
+        let lan_id = rackd.exec(cmd).await.unwrap();
+
+        let filter = EventFilter { kind: Some(EventKind::Lan), ..Default::default() };
+        let mut receiver = subscribe(rackd.clone(), filter, Some(0));
+        let created = timeout(Duration::from_secs(1), receiver.recv()).await.unwrap().unwrap();
+        assert_eq!(created.stream_id, lan_id.0);
+
+        let cmd = RenameLan {
+            id: lan_id,
+            name: NetName::from_str("lab2").unwrap()
+        };
+        rackd.exec(cmd).await.unwrap();
+        let renamed = timeout(Duration::from_secs(1), receiver.recv()).await.unwrap().unwrap();
+        assert_eq!(renamed.version, 2);
+        assert!(renamed.seq > created.seq);
+    }
+
+    #[tokio::test]
+    async fn doesnt_stream_failed_commands() {
+        let rackd = Rackd::mock().unwrap();
+        let mut receiver = subscribe(rackd.clone(), EventFilter::default(), None);
+        init_rack(&rackd).await;
+        let cmd = CreateTrunk {
+            name: TrunkName::from_str("trunk1").unwrap()
+        };
+        assert!(rackd.exec(cmd).await.is_err());
+        assert!(timeout(Duration::from_secs(1), receiver.recv()).await.unwrap().is_some());
+        assert!(timeout(Duration::from_secs(1), receiver.recv()).await.unwrap().is_some());
+        assert!(timeout(Duration::from_millis(200), receiver.recv()).await.is_err());
+    }
+}
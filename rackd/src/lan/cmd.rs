@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+use crate::util::actor::Msg;
+pub mod create;
+pub mod rename;
+pub mod set_prefix;
+pub mod delete;
+pub mod restore;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum LanCmd {
+    Create(Msg<create::CreateLan>),
+    Rename(Msg<rename::RenameLan>),
+    SetPrefix(Msg<set_prefix::SetLanPrefix>),
+    Delete(Msg<delete::DeleteLan>),
+    Restore(Msg<restore::RestoreLan>)
+}
\ No newline at end of file
@@ -0,0 +1,216 @@
+use field_types::FieldName;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::{EntityStore, VersionConflict}, raft::ClusterError, query::traits::QueryRunner, Tx}, lan::model::{entity::{Lan, LanEvent}, values::LanId}, net::{query::{GetNetworkByName, GetNetworkByTrunkVlan}, views::NetworkView, NetName, Prefix, VlanId}, rack::{query::get_rack::LoadRack, Rack}, trunk::model::{Trunk, TrunkId}, util::{actor::{Payload, Process}, models::Entity, traits::OptionExt}};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, FieldName)]
+pub struct CreateLan {
+    pub trunk: TrunkId,
+    pub vlan: VlanId,
+    pub name: NetName,
+    #[schema(value_type = String, example = "fd00:1:2:3::/64")]
+    pub prefix: Prefix
+}
+
+#[derive(Debug, Error)]
+pub enum CreateLanError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Conflict(#[from] VersionConflict),
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
+    #[error("Rack hasn't been initialized")]
+    RackNotFound,
+    #[error("Trunk with ID not found")]
+    TrunkNotFound,
+    #[error("Lan Name already in use")]
+    NameAlreadyInUse,
+    #[error("VLAN/Trunk already in use")]
+    TrunkVlanAlreadyInUse
+}
+
+impl Payload for CreateLan {
+    type Ok = LanId;
+    type Err = CreateLanError;
+}
+
+impl CreateLan {
+    fn exec(&self, rack: Option<Rack>, trunk: Option<Trunk>, name_twin: Option<NetworkView>, trunk_vlan_twin: Option<NetworkView>) -> Result<Lan, CreateLanError> {
+        let rack = rack.ok_or(CreateLanError::RackNotFound)?;
+        let trunk = trunk.ok_or(CreateLanError::TrunkNotFound)?;
+        name_twin.err_or(CreateLanError::NameAlreadyInUse)?;
+        trunk_vlan_twin.err_or(CreateLanError::TrunkVlanAlreadyInUse)?;
+        let mut lan = Lan::default();
+        lan.process(LanEvent::Created {
+            id: LanId::new(),
+            rack: Box::new(rack),
+            trunk: Box::new(trunk),
+            vlan: self.vlan,
+            name: self.name.clone(),
+            prefix: self.prefix
+        });
+        Ok(lan)
+    }
+}
+
+impl Process for CreateLan {
+    type Actor = RackdCmdActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let rack = tx.run(LoadRack)?;
+        let trunk = tx.load(self.trunk)?;
+        let name_twin = tx.run(GetNetworkByName { name: self.name.clone() })?;
+        let trunk_vlan_twin = tx.run(GetNetworkByTrunkVlan { trunk: self.trunk, vlan: self.vlan })?;
+        self.exec(rack, trunk, name_twin, trunk_vlan_twin).map(|mut lan| {
+            tx.save(&mut lan)?;
+            tx.commit()?;
+            Ok(lan.id)
+        })?
+    }
+}
+
+pub mod casts {
+    use crate::{actors::cmd::RackdCmd, db::cmd::traits::EntityStoreError, lan::cmd::LanCmd, util::actor::Msg};
+    use super::{CreateLan, CreateLanError};
+
+    impl From<EntityStoreError> for CreateLanError {
+        fn from(error: EntityStoreError) -> Self {
+            match error {
+                EntityStoreError::Db(e) => Self::Db(e),
+                EntityStoreError::Conflict(e) => Self::Conflict(e)
+            }
+        }
+    }
+
+    impl From<Msg<CreateLan>> for RackdCmd {
+        fn from(cmd: Msg<CreateLan>) -> Self {
+            Self::Lan(LanCmd::Create(cmd))
+        }
+    }
+}
+
+pub mod api {
+    use std::collections::HashMap;
+    use serde_json::Value;
+    use axum::{extract::{OriginalUri, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, net::{NetName, Prefix, VlanId}, trunk::model::TrunkId, util::api::{Error, Json, Response, TryFromJson}};
+    use super::{CreateLan, CreateLanError, CreateLanFieldName};
+
+    #[utoipa::path(post, path = "/lan/create", tag = "lan",
+        request_body = CreateLan,
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn create(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Json(cmd): Json<CreateLan>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.exec(cmd).await
+            .map(|lan_id| Response::ok(lan_id, path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl TryFromJson for CreateLan {
+        fn try_from(mut map: HashMap<String, Value>) -> Result<Self, Vec<Error>> {
+            Self::check_keys(&map, CreateLan::as_field_name_array().map(|f| f.name()))?;
+            let trunk = map.remove(CreateLanFieldName::Trunk.name()).unwrap_or_default();
+            let vlan = map.remove(CreateLanFieldName::Vlan.name()).unwrap_or_default();
+            let name = map.remove(CreateLanFieldName::Name.name()).unwrap_or_default();
+            let prefix = map.remove(CreateLanFieldName::Prefix.name()).unwrap_or_default();
+
+            match (TrunkId::try_from(trunk), VlanId::try_from(vlan), NetName::try_from(name), Prefix::try_from(prefix)) {
+                (Ok(trunk), Ok(vlan), Ok(name), Ok(prefix)) => Ok(Self { trunk, vlan, name, prefix }),
+                (r1, r2, r3, r4) => {
+                    let e1 = r1.map_err(Error::from).err();
+                    let e2 = r2.map_err(Error::from).err();
+                    let e3 = r3.map_err(Error::from).err();
+                    let e4 = r4.map_err(Error::from).err();
+
+                    let errors: Vec<Error> = [e1, e2, e3, e4].into_iter().flatten().collect();
+                    Err(errors)
+                }
+            }
+        }
+    }
+
+    impl From<CreateLanError> for Error {
+        fn from(error: CreateLanError) -> Self {
+            let msg = error.to_string();
+            match error {
+                CreateLanError::Db(_) => Error::new("CREATE_LAN_DB_ERROR", msg),
+                CreateLanError::Conflict(_) => Error::new("CREATE_LAN_CONFLICT", msg),
+                CreateLanError::Cluster(e) => Error::from(e),
+                CreateLanError::RackNotFound => Error::new("CREATE_LAN_RACK_NOT_FOUND", msg),
+                CreateLanError::TrunkNotFound => Error::new("CREATE_LAN_TRUNK_NOT_FOUND", msg),
+                CreateLanError::NameAlreadyInUse => Error::new("CREATE_LAN_NAME_ALREADY_IN_USE", msg),
+                CreateLanError::TrunkVlanAlreadyInUse => Error::new("CREATE_LAN_TRUNK_VLAN_ALREADY_IN_USE", msg)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::{actors::system::Rackd, lan::cmd::create::{CreateLan, CreateLanError}, net::{NetName, Prefix, VlanId}, trunk::model::TrunkId, wan::{cmd::create::CreateWan, model::values::WanMode}};
+    use crate::util::test_support::{create_wan, init_rack};
+
+    #[tokio::test]
+    async fn cant_create_if_trunk_doesnt_exist() {
+        let rackd = Rackd::mock().unwrap();
+        init_rack(&rackd).await;
+        let cmd = CreateLan {
+            trunk: TrunkId::new(),
+            vlan: VlanId::try_from(100).unwrap(),
+            name: NetName::from_str("lab").unwrap(),
+            prefix: Prefix::from_str("fd00:0:0:100::/64").unwrap()
+        };
+        assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, CreateLanError::TrunkNotFound)));
+    }
+
+    #[tokio::test]
+    async fn cant_create_if_trunk_vlan_is_used_by_a_wan() {
+        let (rackd, trunk_id, _) = create_wan(WanMode::IPoE).await;
+        let cmd = CreateLan {
+            trunk: trunk_id,
+            vlan: VlanId::try_from(4001).unwrap(),
+            name: NetName::from_str("lab").unwrap(),
+            prefix: Prefix::from_str("fd00:0:0:100::/64").unwrap()
+        };
+        assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, CreateLanError::TrunkVlanAlreadyInUse)));
+    }
+
+    #[tokio::test]
+    async fn cant_create_if_name_is_used_by_a_wan() {
+        let (rackd, trunk_id, _) = create_wan(WanMode::IPoE).await;
+        let cmd = CreateLan {
+            trunk: trunk_id,
+            vlan: VlanId::try_from(101).unwrap(),
+            name: NetName::from_str("movistar").unwrap(),
+            prefix: Prefix::from_str("10.0.101.0/24").unwrap()
+        };
+        assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, CreateLanError::NameAlreadyInUse)));
+    }
+
+    #[tokio::test]
+    async fn wan_cant_reuse_lan_trunk_vlan() {
+        let rackd = Rackd::mock().unwrap();
+        let trunk_id = init_rack(&rackd).await;
+        let cmd = CreateLan {
+            trunk: trunk_id,
+            vlan: VlanId::try_from(200).unwrap(),
+            name: NetName::from_str("lab").unwrap(),
+            prefix: Prefix::from_str("fd00:0:0:200::/64").unwrap()
+        };
+        rackd.exec(cmd).await.unwrap();
+        let cmd = CreateWan {
+            trunk: trunk_id,
+            vlan: VlanId::try_from(200).unwrap(),
+            name: NetName::from_str("verizon").unwrap(),
+            mode: WanMode::IPoE
+        };
+        assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, crate::wan::cmd::create::CreateWanError::TrunkVlanAlreadyInUse)));
+    }
+}
\ No newline at end of file
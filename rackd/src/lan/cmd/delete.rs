@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::{EntityStore, VersionConflict}, raft::ClusterError, Tx}, lan::model::{entity::{Lan, LanEvent}, values::LanId}, util::{actor::{Payload, Process}, models::Entity, traits::SoftDeleteExt}};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DeleteLan {
+    pub id: LanId
+}
+
+#[derive(Debug, Error)]
+pub enum DeleteLanError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Conflict(#[from] VersionConflict),
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
+    #[error("Lan with id x Not Found")]
+    LanNotFound
+}
+
+impl Payload for DeleteLan {
+    type Ok = ();
+    type Err = DeleteLanError;
+}
+
+impl DeleteLan {
+    fn exec(&self, lan: Option<Lan>) -> Result<Lan, DeleteLanError> {
+        let mut lan = lan.live_or(DeleteLanError::LanNotFound)?;
+        lan.process(LanEvent::Deleted);
+        Ok(lan)
+    }
+}
+
+impl Process for DeleteLan {
+    type Actor = RackdCmdActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let lan = tx.load(self.id)?;
+        self.exec(lan).map(|mut lan| {
+            tx.save(&mut lan)?;
+            tx.commit()?;
+            Ok(())
+        })?
+    }
+}
+
+pub mod casts {
+    use crate::{actors::cmd::RackdCmd, db::cmd::traits::EntityStoreError, lan::cmd::LanCmd, util::actor::Msg};
+    use super::{DeleteLan, DeleteLanError};
+
+    impl From<EntityStoreError> for DeleteLanError {
+        fn from(error: EntityStoreError) -> Self {
+            match error {
+                EntityStoreError::Db(e) => Self::Db(e),
+                EntityStoreError::Conflict(e) => Self::Conflict(e)
+            }
+        }
+    }
+
+    impl From<Msg<DeleteLan>> for RackdCmd {
+        fn from(cmd: Msg<DeleteLan>) -> Self {
+            Self::Lan(LanCmd::Delete(cmd))
+        }
+    }
+}
+
+pub mod api {
+    use axum::{extract::{OriginalUri, Path, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, lan::model::values::LanId, util::api::{Error, Response}};
+    use super::{DeleteLan, DeleteLanError};
+
+    #[utoipa::path(delete, path = "/lan/{lan_id}", tag = "lan",
+        params(("lan_id" = LanId, Path, description = "Lan UUID")),
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn delete(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Path(lan_id): Path<LanId>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.exec(DeleteLan { id: lan_id }).await
+            .map(|_| Response::ok((), path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl From<DeleteLanError> for Error {
+        fn from(error: DeleteLanError) -> Self {
+            let msg = error.to_string();
+            match error {
+                DeleteLanError::Db(_) => Error::new("DELETE_LAN_DB_ERROR", msg),
+                DeleteLanError::Conflict(_) => Error::new("DELETE_LAN_CONFLICT", msg),
+                DeleteLanError::Cluster(e) => Error::from(e),
+                DeleteLanError::LanNotFound => Error::new("DELETE_LAN_NOT_FOUND", msg)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{actors::system::Rackd, lan::{cmd::delete::{DeleteLan, DeleteLanError}, model::values::LanId}};
+
+    #[tokio::test]
+    async fn cant_delete_if_lan_doesnt_exist() {
+        let rackd = Rackd::mock().unwrap();
+        let cmd = DeleteLan {
+            id: LanId::new()
+        };
+        assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, DeleteLanError::LanNotFound)));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,193 @@
+use field_types::FieldName;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::{EntityStore, VersionConflict}, raft::ClusterError, query::traits::QueryRunner, Tx}, lan::model::{entity::{Lan, LanEvent}, values::LanId}, net::{query::GetNetworkByName, views::NetworkView, NetName}, util::{actor::{Payload, Process}, models::Entity, traits::OptionExt}};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, FieldName)]
+pub struct RenameLan {
+    pub id: LanId,
+    pub name: NetName
+}
+
+#[derive(Debug, Error)]
+pub enum RenameLanError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Conflict(#[from] VersionConflict),
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
+    #[error("Lan with id x Not Found")]
+    LanNotFound,
+    #[error("Lan Name already in use")]
+    NameAlreadyInUse
+}
+
+impl Payload for RenameLan {
+    type Ok = ();
+    type Err = RenameLanError;
+}
+
+impl RenameLan {
+    fn exec(&self, lan: Option<Lan>, name_twin: Option<NetworkView>) -> Result<Lan, RenameLanError> {
+        let mut lan = lan.filter(|lan| !lan.deleted).ok_or(RenameLanError::LanNotFound)?;
+        name_twin.err_or(RenameLanError::NameAlreadyInUse)?;
+        lan.process(LanEvent::Renamed { from: lan.name.clone(), to: self.name.clone() });
+        Ok(lan)
+    }
+}
+
+impl Process for RenameLan {
+    type Actor = RackdCmdActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let lan = tx.load(self.id)?;
+        let name_twin = tx.run(GetNetworkByName { name: self.name.clone() })?;
+        self.exec(lan, name_twin).map(|mut lan| {
+            tx.save(&mut lan)?;
+            tx.commit()?;
+            Ok(())
+        })?
+    }
+}
+
+pub mod casts {
+    use crate::{actors::cmd::RackdCmd, db::cmd::traits::EntityStoreError, lan::cmd::LanCmd, util::actor::Msg};
+    use super::{RenameLan, RenameLanError};
+
+    impl From<EntityStoreError> for RenameLanError {
+        fn from(error: EntityStoreError) -> Self {
+            match error {
+                EntityStoreError::Db(e) => Self::Db(e),
+                EntityStoreError::Conflict(e) => Self::Conflict(e)
+            }
+        }
+    }
+
+    impl From<Msg<RenameLan>> for RackdCmd {
+        fn from(cmd: Msg<RenameLan>) -> Self {
+            Self::Lan(LanCmd::Rename(cmd))
+        }
+    }
+}
+
+pub mod api {
+    use std::collections::HashMap;
+    use serde_json::Value;
+    use axum::{extract::{OriginalUri, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, lan::model::values::LanId, net::NetName, util::api::{Error, Json, Response, TryFromJson}};
+    use super::{RenameLan, RenameLanError, RenameLanFieldName};
+
+    #[utoipa::path(patch, path = "/lan/rename", tag = "lan",
+        request_body = RenameLan,
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn rename(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Json(cmd): Json<RenameLan>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.exec(cmd).await
+            .map(|_| Response::ok((), path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl TryFromJson for RenameLan {
+        fn try_from(mut map: HashMap<String, Value>) -> Result<Self, Vec<Error>> {
+            Self::check_keys(&map, RenameLan::as_field_name_array().map(|f| f.name()))?;
+            let id = map.remove(RenameLanFieldName::Id.name()).unwrap_or_default();
+            let name = map.remove(RenameLanFieldName::Name.name()).unwrap_or_default();
+
+            match (LanId::try_from(id), NetName::try_from(name)) {
+                (Ok(id), Ok(name)) => Ok(Self { id, name }),
+                (r1, r2) => {
+                    let e1 = r1.map_err(Error::from).err();
+                    let e2 = r2.map_err(Error::from).err();
+
+                    let errors: Vec<Error> = [e1, e2].into_iter().flatten().collect();
+                    Err(errors)
+                }
+            }
+        }
+    }
+
+    impl From<RenameLanError> for Error {
+        fn from(error: RenameLanError) -> Self {
+            let msg = error.to_string();
+            match error {
+                RenameLanError::Db(_) => Error::new("RENAME_LAN_DB_ERROR", msg),
+                RenameLanError::Conflict(_) => Error::new("RENAME_LAN_CONFLICT", msg),
+                RenameLanError::Cluster(e) => Error::from(e),
+                RenameLanError::LanNotFound => Error::new("RENAME_LAN_NOT_FOUND", msg),
+                RenameLanError::NameAlreadyInUse => Error::new("RENAME_LAN_NAME_ALREADY_IN_USE", msg)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::{actors::system::Rackd, lan::{cmd::{create::CreateLan, rename::{RenameLan, RenameLanError}}, model::values::LanId}, net::{NetName, Prefix, VlanId}};
+    use crate::util::test_support::init_rack;
+
+    #[tokio::test]
+    async fn cant_rename_if_lan_doesnt_exist() {
+        let rackd = Rackd::mock().unwrap();
+        let cmd = RenameLan {
+            id: LanId::new(),
+            name: NetName::from_str("lab").unwrap()
+        };
+        assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, RenameLanError::LanNotFound)));
+    }
+
+    #[tokio::test]
+    async fn cant_rename_if_new_name_is_already_in_use() {
+        let rackd = Rackd::mock().unwrap();
+        let trunk_id = init_rack(&rackd).await;
+        let cmd = CreateLan {
+            trunk: trunk_id,
+            vlan: VlanId::try_from(100).unwrap(),
+            name: NetName::from_str("lab").unwrap(),
+            prefix: Prefix::from_str("fd00:0:0:100::/64").unwrap()
+        };
+        let lab_id = rackd.exec(cmd).await.unwrap();
+        let cmd = CreateLan {
+            trunk: trunk_id,
+            vlan: VlanId::try_from(101).unwrap(),
+            name: NetName::from_str("home").unwrap(),
+            prefix: Prefix::from_str("fd00:0:0:101::/64").unwrap()
+        };
+        rackd.exec(cmd).await.unwrap();
+
+        let cmd = RenameLan {
+            id: lab_id,
+            name: NetName::from_str("home").unwrap()
+        };
+        assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, RenameLanError::NameAlreadyInUse)));
+    }
+
+    #[tokio::test]
+    async fn can_rename_lan_more_than_once() {
+        let rackd = Rackd::mock().unwrap();
+        let trunk_id = init_rack(&rackd).await;
+        let cmd = CreateLan {
+            trunk: trunk_id,
+            vlan: VlanId::try_from(100).unwrap(),
+            name: NetName::from_str("lab").unwrap(),
+            prefix: Prefix::from_str("fd00:0:0:100::/64").unwrap()
+        };
+        let lan_id = rackd.exec(cmd).await.unwrap();
+        let cmd = RenameLan {
+            id: lan_id,
+            name: NetName::from_str("home").unwrap()
+        };
+        assert!(rackd.exec(cmd).await.is_ok());
+        let cmd = RenameLan {
+            id: lan_id,
+            name: NetName::from_str("office").unwrap()
+        };
+        assert!(rackd.exec(cmd).await.is_ok());
+    }
+}
@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::{EntityStore, VersionConflict}, raft::ClusterError, query::traits::QueryRunner, Tx}, lan::model::{entity::{Lan, LanEvent}, values::LanId}, net::query::{GetRestoreTwins, RestoreTwins}, util::{actor::{Payload, Process}, models::Entity, traits::SoftDeleteExt}};
+
+/// Brings back a deleted lan, as long as its name and trunk/VLAN haven't been reused since
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RestoreLan {
+    pub id: LanId
+}
+
+#[derive(Debug, Error)]
+pub enum RestoreLanError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Conflict(#[from] VersionConflict),
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
+    #[error("Lan with id x Not Found")]
+    LanNotFound,
+    #[error("Lan isn't deleted")]
+    NotDeleted,
+    #[error("Trunk of the lan has been deleted")]
+    TrunkNotFound,
+    #[error("Lan Name already in use")]
+    NameAlreadyInUse,
+    #[error("VLAN/Trunk already in use")]
+    TrunkVlanAlreadyInUse
+}
+
+impl Payload for RestoreLan {
+    type Ok = ();
+    type Err = RestoreLanError;
+}
+
+impl RestoreLan {
+    fn exec(&self, lan: Option<Lan>, twins: Option<RestoreTwins>) -> Result<Lan, RestoreLanError> {
+        let mut lan = lan.deleted_or(RestoreLanError::LanNotFound, RestoreLanError::NotDeleted)?;
+        if let Some(twins) = twins {
+            twins.check()?;
+        }
+        lan.process(LanEvent::Restored);
+        Ok(lan)
+    }
+}
+
+impl Process for RestoreLan {
+    type Actor = RackdCmdActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let lan: Option<Lan> = tx.load(self.id)?;
+        let twins = match &lan {
+            Some(lan) => Some(tx.run(GetRestoreTwins { trunk: lan.trunk, vlan: lan.vlan, name: lan.name.clone() })?),
+            None => None
+        };
+        self.exec(lan, twins).map(|mut lan| {
+            tx.save(&mut lan)?;
+            tx.commit()?;
+            Ok(())
+        })?
+    }
+}
+
+pub mod casts {
+    use crate::{actors::cmd::RackdCmd, db::cmd::traits::EntityStoreError, lan::cmd::LanCmd, net::query::RestoreConflict, util::actor::Msg};
+    use super::{RestoreLan, RestoreLanError};
+
+    impl From<RestoreConflict> for RestoreLanError {
+        fn from(conflict: RestoreConflict) -> Self {
+            match conflict {
+                RestoreConflict::TrunkNotFound => Self::TrunkNotFound,
+                RestoreConflict::NameAlreadyInUse => Self::NameAlreadyInUse,
+                RestoreConflict::TrunkVlanAlreadyInUse => Self::TrunkVlanAlreadyInUse
+            }
+        }
+    }
+
+    impl From<EntityStoreError> for RestoreLanError {
+        fn from(error: EntityStoreError) -> Self {
+            match error {
+                EntityStoreError::Db(e) => Self::Db(e),
+                EntityStoreError::Conflict(e) => Self::Conflict(e)
+            }
+        }
+    }
+
+    impl From<Msg<RestoreLan>> for RackdCmd {
+        fn from(cmd: Msg<RestoreLan>) -> Self {
+            Self::Lan(LanCmd::Restore(cmd))
+        }
+    }
+}
+
+pub mod api {
+    use axum::{extract::{OriginalUri, Path, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, lan::model::values::LanId, util::api::{Error, Response}};
+    use super::{RestoreLan, RestoreLanError};
+
+    #[utoipa::path(post, path = "/lan/{lan_id}/restore", tag = "lan",
+        params(("lan_id" = LanId, Path, description = "Lan UUID")),
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn restore(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Path(lan_id): Path<LanId>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.exec(RestoreLan { id: lan_id }).await
+            .map(|_| Response::ok((), path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl From<RestoreLanError> for Error {
+        fn from(error: RestoreLanError) -> Self {
+            let msg = error.to_string();
+            match error {
+                RestoreLanError::Db(_) => Error::new("RESTORE_LAN_DB_ERROR", msg),
+                RestoreLanError::Conflict(_) => Error::new("RESTORE_LAN_CONFLICT", msg),
+                RestoreLanError::Cluster(e) => Error::from(e),
+                RestoreLanError::LanNotFound => Error::new("RESTORE_LAN_NOT_FOUND", msg),
+                RestoreLanError::NotDeleted => Error::new("RESTORE_LAN_NOT_DELETED", msg),
+                RestoreLanError::TrunkNotFound => Error::new("RESTORE_LAN_TRUNK_NOT_FOUND", msg),
+                RestoreLanError::NameAlreadyInUse => Error::new("RESTORE_LAN_NAME_ALREADY_IN_USE", msg),
+                RestoreLanError::TrunkVlanAlreadyInUse => Error::new("RESTORE_LAN_TRUNK_VLAN_ALREADY_IN_USE", msg)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::{actors::system::Rackd, lan::{cmd::{create::CreateLan, delete::DeleteLan, restore::{RestoreLan, RestoreLanError}}, query::get_by_key::GetLanById}, net::{NetName, Prefix, VlanId}};
+    use crate::util::test_support::init_rack;
+
+    #[tokio::test]
+    async fn restore_only_if_name_and_trunk_vlan_are_free() {
+        let rackd = Rackd::mock().unwrap();
+        let trunk = init_rack(&rackd).await;
+        let cmd = CreateLan {
+            trunk,
+            vlan: VlanId::try_from(100).unwrap(),
+            name: NetName::from_str("lab").unwrap(),
+            prefix: Prefix::from_str("fd00:0:0:100::/64").unwrap()
+        };
+        let id = rackd.exec(cmd).await.unwrap();
+        assert!(rackd.exec(RestoreLan { id }).await.is_err_and(|e| matches!(e, RestoreLanError::NotDeleted)));
+        rackd.exec(DeleteLan { id }).await.unwrap();
+
+        let cmd = CreateLan {
+            trunk,
+            vlan: VlanId::try_from(100).unwrap(),
+            name: NetName::from_str("office").unwrap(),
+            prefix: Prefix::from_str("fd00:0:0:101::/64").unwrap()
+        };
+        let twin = rackd.exec(cmd).await.unwrap();
+        assert!(rackd.exec(RestoreLan { id }).await.is_err_and(|e| matches!(e, RestoreLanError::TrunkVlanAlreadyInUse)));
+        rackd.exec(DeleteLan { id: twin }).await.unwrap();
+
+        rackd.exec(RestoreLan { id }).await.unwrap();
+        assert_eq!(rackd.query(GetLanById { id }).await.unwrap().name.to_string(), "lab");
+    }
+}
@@ -0,0 +1,164 @@
+use field_types::FieldName;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::{EntityStore, VersionConflict}, raft::ClusterError, Tx}, lan::model::{entity::{Lan, LanEvent}, values::LanId}, net::Prefix, util::{actor::{Payload, Process}, models::Entity}};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, FieldName)]
+pub struct SetLanPrefix {
+    pub id: LanId,
+    #[schema(value_type = String, example = "fd00:1:2:3::/64")]
+    pub prefix: Prefix
+}
+
+#[derive(Debug, Error)]
+pub enum SetLanPrefixError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Conflict(#[from] VersionConflict),
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
+    #[error("Lan with id x Not Found")]
+    LanNotFound,
+    #[error("Prefix is already set")]
+    AlreadySet
+}
+
+impl Payload for SetLanPrefix {
+    type Ok = ();
+    type Err = SetLanPrefixError;
+}
+
+impl SetLanPrefix {
+    fn exec(&self, lan: Option<Lan>) -> Result<Lan, SetLanPrefixError> {
+        let mut lan = lan.filter(|lan| !lan.deleted).ok_or(SetLanPrefixError::LanNotFound)?;
+        if lan.prefix == self.prefix {
+            return Err(SetLanPrefixError::AlreadySet);
+        }
+        lan.process(LanEvent::PrefixSet { from: lan.prefix, to: self.prefix });
+        Ok(lan)
+    }
+}
+
+impl Process for SetLanPrefix {
+    type Actor = RackdCmdActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let lan = tx.load(self.id)?;
+        self.exec(lan).map(|mut lan| {
+            tx.save(&mut lan)?;
+            tx.commit()?;
+            Ok(())
+        })?
+    }
+}
+
+pub mod casts {
+    use crate::{actors::cmd::RackdCmd, db::cmd::traits::EntityStoreError, lan::cmd::LanCmd, util::actor::Msg};
+    use super::{SetLanPrefix, SetLanPrefixError};
+
+    impl From<EntityStoreError> for SetLanPrefixError {
+        fn from(error: EntityStoreError) -> Self {
+            match error {
+                EntityStoreError::Db(e) => Self::Db(e),
+                EntityStoreError::Conflict(e) => Self::Conflict(e)
+            }
+        }
+    }
+
+    impl From<Msg<SetLanPrefix>> for RackdCmd {
+        fn from(cmd: Msg<SetLanPrefix>) -> Self {
+            Self::Lan(LanCmd::SetPrefix(cmd))
+        }
+    }
+}
+
+pub mod api {
+    use std::collections::HashMap;
+    use serde_json::Value;
+    use axum::{extract::{OriginalUri, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, lan::model::values::LanId, net::Prefix, util::api::{Error, Json, Response, TryFromJson}};
+    use super::{SetLanPrefix, SetLanPrefixError, SetLanPrefixFieldName};
+
+    #[utoipa::path(patch, path = "/lan/set_prefix", tag = "lan",
+        request_body = SetLanPrefix,
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn set_prefix(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Json(cmd): Json<SetLanPrefix>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.exec(cmd).await
+            .map(|_| Response::ok((), path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl TryFromJson for SetLanPrefix {
+        fn try_from(mut map: HashMap<String, Value>) -> Result<Self, Vec<Error>> {
+            Self::check_keys(&map, SetLanPrefix::as_field_name_array().map(|f| f.name()))?;
+            let id = map.remove(SetLanPrefixFieldName::Id.name()).unwrap_or_default();
+            let prefix = map.remove(SetLanPrefixFieldName::Prefix.name()).unwrap_or_default();
+
+            match (LanId::try_from(id), Prefix::try_from(prefix)) {
+                (Ok(id), Ok(prefix)) => Ok(Self { id, prefix }),
+                (r1, r2) => {
+                    let e1 = r1.map_err(Error::from).err();
+                    let e2 = r2.map_err(Error::from).err();
+
+                    let errors: Vec<Error> = [e1, e2].into_iter().flatten().collect();
+                    Err(errors)
+                }
+            }
+        }
+    }
+
+    impl From<SetLanPrefixError> for Error {
+        fn from(error: SetLanPrefixError) -> Self {
+            let msg = error.to_string();
+            match error {
+                SetLanPrefixError::Db(_) => Error::new("SET_LAN_PREFIX_DB_ERROR", msg),
+                SetLanPrefixError::Conflict(_) => Error::new("SET_LAN_PREFIX_CONFLICT", msg),
+                SetLanPrefixError::Cluster(e) => Error::from(e),
+                SetLanPrefixError::LanNotFound => Error::new("SET_LAN_PREFIX_LAN_NOT_FOUND", msg),
+                SetLanPrefixError::AlreadySet => Error::new("SET_LAN_PREFIX_ALREADY_SET", msg)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::{actors::system::Rackd, lan::{cmd::{create::CreateLan, set_prefix::{SetLanPrefix, SetLanPrefixError}}, model::values::LanId}, net::{NetName, Prefix, VlanId}};
+    use crate::util::test_support::init_rack;
+
+    #[tokio::test]
+    async fn cant_set_prefix_if_lan_doesnt_exist() {
+        let rackd = Rackd::mock().unwrap();
+        let cmd = SetLanPrefix {
+            id: LanId::new(),
+            prefix: Prefix::from_str("fd00:0:0:100::/64").unwrap()
+        };
+        assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, SetLanPrefixError::LanNotFound)));
+    }
+
+    #[tokio::test]
+    async fn cant_set_prefix_if_its_already_set() {
+        let rackd = Rackd::mock().unwrap();
+        let trunk_id = init_rack(&rackd).await;
+        let cmd = CreateLan {
+            trunk: trunk_id,
+            vlan: VlanId::try_from(100).unwrap(),
+            name: NetName::from_str("lab").unwrap(),
+            prefix: Prefix::from_str("fd00:0:0:100::/64").unwrap()
+        };
+        let lan_id = rackd.exec(cmd).await.unwrap();
+        let cmd = SetLanPrefix {
+            id: lan_id,
+            prefix: Prefix::from_str("fd00:0:0:100::/64").unwrap()
+        };
+        assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, SetLanPrefixError::AlreadySet)));
+    }
+}
\ No newline at end of file
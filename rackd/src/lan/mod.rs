@@ -0,0 +1,4 @@
+pub mod cmd;
+pub mod model;
+pub mod query;
+pub mod views;
\ No newline at end of file
@@ -0,0 +1,2 @@
+pub mod entity;
+pub mod values;
\ No newline at end of file
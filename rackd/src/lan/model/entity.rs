@@ -0,0 +1,89 @@
+use serde::{Serialize, Deserialize};
+use crate::{net::{NetName, Prefix, VlanId}, rack::{Rack, RackId}, trunk::model::{Trunk, TrunkId}, util::models::{Entity, Id, Metadata, SoftDelete}};
+use super::values::*;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Lan {
+    pub meta: Metadata,
+    pub id: LanId,
+    pub rack: RackId,
+    pub trunk: TrunkId,
+    pub vlan: VlanId,
+    pub name: NetName,
+    pub prefix: Prefix,
+    pub deleted: bool
+}
+
+impl Entity for Lan {
+    type E = LanEvent;
+
+    fn id(&self) -> Id {
+        self.id.into()
+    }
+
+    fn metadata(&mut self) -> &mut Metadata {
+        &mut self.meta
+    }
+
+    fn apply(&mut self, event: &Self::E) {
+        match event {
+            LanEvent::Created { id, rack, trunk, vlan, name, prefix } => {
+                self.id = *id;
+                self.rack = rack.id;
+                self.trunk = trunk.id;
+                self.vlan = *vlan;
+                self.name = name.clone();
+                self.prefix = *prefix;
+            },
+            LanEvent::Renamed { to, .. } => {
+                self.name = to.clone();
+            },
+            LanEvent::PrefixSet { to, .. } => {
+                self.prefix = *to;
+            },
+            LanEvent::Deleted => {
+                self.deleted = true;
+            },
+            LanEvent::Restored => {
+                self.deleted = false;
+            }
+        }
+    }
+}
+
+impl SoftDelete for Lan {
+    fn deleted(&self) -> bool {
+        self.deleted
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum LanEvent {
+    Created { id: LanId, rack: Box<Rack>, trunk: Box<Trunk>, vlan: VlanId, name: NetName, prefix: Prefix },
+    Renamed { from: NetName, to: NetName },
+    PrefixSet { from: Prefix, to: Prefix },
+    Deleted,
+    Restored
+}
+
+pub mod casts {
+    use crate::util::models::{casts::UnexpectedEvent, EventData};
+    use super::LanEvent;
+
+    impl From<LanEvent> for EventData {
+        fn from(e: LanEvent) -> Self {
+            Self::Lan(e)
+        }
+    }
+
+    impl TryFrom<EventData> for LanEvent {
+        type Error = UnexpectedEvent;
+
+        fn try_from(e: EventData) -> Result<Self, Self::Error> {
+            match e {
+                EventData::Lan(e) => Ok(e),
+                e => Err(UnexpectedEvent(e))
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,72 @@
+use std::fmt::Display;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use crate::util::models::Id;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, ToSchema)]
+pub struct LanId(pub Id);
+
+impl LanId {
+    pub fn new() -> Self {
+        Self(Id::new())
+    }
+}
+
+impl Display for LanId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "lan with id: {}", self.0)
+    }
+}
+
+pub mod casts {
+    use serde_json::Value;
+    use thiserror::Error;
+    use crate::util::models::{casts::IdError, Id};
+    use super::LanId;
+
+    impl From<LanId> for Id {
+        fn from(value: LanId) -> Self {
+            value.0
+        }
+    }
+
+    #[derive(Debug, Error)]
+    #[error("LanIdError: {:?}", .0)]
+    pub struct LanIdError(#[from]IdError);
+
+    impl TryFrom<Value> for LanId {
+        type Error = LanIdError;
+
+        fn try_from(value: Value) -> Result<Self, Self::Error> {
+            Ok(Self(Id::try_from(value)?))
+        }
+    }
+}
+
+pub mod api {
+    use crate::util::api::Error;
+    use super::casts::LanIdError;
+
+    impl From<LanIdError> for Error {
+        fn from(error: LanIdError) -> Self {
+            Error::new("LAN_ID_ERROR", error.to_string())
+        }
+    }
+}
+
+pub mod sqlite {
+    use rusqlite::{types::{FromSql, FromSqlResult, ToSqlOutput, ValueRef}, Result, ToSql};
+    use super::*;
+
+    impl ToSql for LanId {
+        fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+            self.0.to_sql()
+        }
+    }
+
+    impl FromSql for LanId {
+        fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+            Ok(Self(Id::column_result(value)?))
+        }
+    }
+}
\ No newline at end of file
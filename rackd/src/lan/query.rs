@@ -0,0 +1,8 @@
+use crate::util::actor::Msg;
+pub mod get_by_key;
+
+#[derive(Debug)]
+pub enum LanQuery {
+    GetLanById(Msg<get_by_key::GetLanById>),
+    GetLanByName(Msg<get_by_key::GetLanByName>)
+}
\ No newline at end of file
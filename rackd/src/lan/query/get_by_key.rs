@@ -0,0 +1,131 @@
+use std::marker::PhantomData;
+use serde::{Deserialize, Serialize};
+use crate::{actors::query::RackdQueryActor, db::{query::traits::{DbQuery, GetByKey, QueryRunner}, Tx}, lan::{model::values::LanId, views::LanView}, net::{query::GetByName, NetName}, util::{actor::{Payload, Process}, query::GetByKeyError}};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetLanById {
+    pub id: LanId
+}
+
+impl Payload for GetLanById {
+    type Ok = LanView;
+    type Err = GetByKeyError<LanId>;
+}
+
+impl DbQuery for GetLanById {
+    type Ok = Option<LanView>;
+
+    fn run(&self, tx: &rusqlite::Transaction) -> Result<Self::Ok, rusqlite::Error> {
+        let query = GetByKey {
+            key: "id",
+            value: &self.id,
+            view: PhantomData::<LanView>
+        };
+        query.run(tx)
+    }
+}
+
+impl Process for GetLanById {
+    type Actor = RackdQueryActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let lan = self.run(&tx)?;
+        let lan = match lan {
+            Some(lan) => lan,
+            None => Err(GetByKeyError::NotFound(self.id))?
+        };
+        Ok(lan)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetLanByName {
+    pub name: NetName
+}
+
+impl Payload for GetLanByName {
+    type Ok = LanView;
+    type Err = GetByKeyError<NetName>;
+}
+
+impl DbQuery for GetLanByName {
+    type Ok = Option<LanView>;
+
+    fn run(&self, tx: &rusqlite::Transaction) -> Result<Self::Ok, rusqlite::Error> {
+        tx.run(GetByName { name: &self.name, view: PhantomData::<LanView> })
+    }
+}
+
+impl Process for GetLanByName {
+    type Actor = RackdQueryActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let lan = self.run(&tx)?;
+        let lan = match lan {
+            Some(lan) => lan,
+            None => Err(GetByKeyError::NotFound(self.name))?
+        };
+        Ok(lan)
+    }
+}
+
+pub mod casts {
+    use crate::{actors::query::RackdQuery, lan::query::LanQuery, util::actor::Msg};
+    use super::{GetLanById, GetLanByName};
+
+    impl From<Msg<GetLanById>> for RackdQuery {
+        fn from(query: Msg<GetLanById>) -> Self {
+            Self::Lan(LanQuery::GetLanById(query))
+        }
+    }
+
+    impl From<Msg<GetLanByName>> for RackdQuery {
+        fn from(query: Msg<GetLanByName>) -> Self {
+            Self::Lan(LanQuery::GetLanByName(query))
+        }
+    }
+}
+
+pub mod api {
+    use axum::{extract::{OriginalUri, Path, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, lan::model::values::LanId, util::api::Response};
+
+    #[utoipa::path(get, path = "/lan/{lan_id}", tag = "lan",
+        params(("lan_id" = LanId, Path, description = "Lan UUID")),
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn get_lan_by_id(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Path(lan_id): Path<LanId>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.query(super::GetLanById { id: lan_id }).await
+            .map(|lan| Response::ok(lan, path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::{actors::system::Rackd, lan::{cmd::create::CreateLan, query::get_by_key::{GetLanById, GetLanByName}}, net::{NetName, Prefix, VlanId}, trunk::model::TrunkName};
+    use crate::util::test_support::init_rack;
+
+    #[tokio::test]
+    async fn can_get_created_lan_by_id_and_name() {
+        let rackd = Rackd::mock().unwrap();
+        let trunk_id = init_rack(&rackd).await;
+        let cmd = CreateLan {
+            trunk: trunk_id,
+            vlan: VlanId::try_from(100).unwrap(),
+            name: NetName::from_str("lab").unwrap(),
+            prefix: Prefix::from_str("fd00:0:0:100::/64").unwrap()
+        };
+        let lan_id = rackd.exec(cmd).await.unwrap();
+        let lan = rackd.query(GetLanById { id: lan_id }).await.unwrap();
+        assert_eq!(lan.name, NetName::from_str("lab").unwrap());
+        let lan = rackd.query(GetLanByName { name: NetName::from_str("lab").unwrap() }).await.unwrap();
+        assert_eq!(lan.trunk.name, TrunkName::from_str("trunk1").unwrap());
+    }
+}
\ No newline at end of file
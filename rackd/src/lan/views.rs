@@ -0,0 +1,86 @@
+use rusqlite::{named_params, params, Row, Transaction};
+use serde::{Deserialize, Serialize};
+use crate::{db::query::traits::DbView, net::{NetName, Prefix, VlanId}, rack::RackId, trunk::{model::{TrunkEvent, TrunkId}, views::TrunkIdView}, util::models::{Event, EventData}};
+use super::model::{entity::LanEvent, values::LanId};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct LanView {
+    pub id: LanId,
+    pub rack: RackId,
+    pub trunk: TrunkIdView,
+    pub vlan: VlanId,
+    pub name: NetName,
+    pub prefix: Prefix
+}
+
+impl DbView for LanView {
+    fn name() -> &'static str {
+        "lan_view"
+    }
+
+    fn update(tx: &Transaction, e: &Event) -> Result<(), rusqlite::Error> {
+        match &e.data {
+            EventData::Lan(data) => match data {
+                LanEvent::Created { rack, trunk, vlan, name, prefix, .. } => {
+                    let sql = format!("INSERT INTO {} (id, rack_id, trunk_id, trunk_name, vlan, name, prefix) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)", Self::name());
+                    tx.execute(&sql, params![e.stream_id, rack.id, trunk.id, trunk.name, vlan, name, prefix])?;
+                },
+                LanEvent::Renamed { to, .. } => {
+                    let sql = format!("UPDATE {} SET name = :name WHERE id = :id", Self::name());
+                    tx.execute(&sql, named_params! { ":id": LanId(e.stream_id), ":name": to })?;
+                },
+                LanEvent::PrefixSet { to, .. } => {
+                    let sql = format!("UPDATE {} SET prefix = :prefix WHERE id = :id", Self::name());
+                    tx.execute(&sql, named_params! { ":id": LanId(e.stream_id), ":prefix": to })?;
+                },
+                LanEvent::Deleted => {
+                    let sql = format!("UPDATE {} SET deleted = 1 WHERE id = :id", Self::name());
+                    tx.execute(&sql, named_params! { ":id": LanId(e.stream_id) })?;
+                },
+                LanEvent::Restored => {
+                    let sql = format!("UPDATE {} SET deleted = 0 WHERE id = :id", Self::name());
+                    tx.execute(&sql, named_params! { ":id": LanId(e.stream_id) })?;
+                }
+            },
+            EventData::Trunk(TrunkEvent::Renamed { to, .. }) => {
+                let sql = format!("UPDATE {} SET trunk_name = :trunk_name WHERE trunk_id = :trunk_id", Self::name());
+                tx.execute(&sql, named_params! { ":trunk_id": TrunkId(e.stream_id), ":trunk_name": to })?;
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn sql_create() -> &'static str {
+        r#"
+        CREATE TABLE IF NOT EXISTS lan_view (
+            id              TEXT        PRIMARY KEY,
+            rack_id         TEXT        NOT NULL,
+            trunk_id        TEXT        NOT NULL,
+            trunk_name      TEXT        NOT NULL,
+            vlan            INTEGER     NOT NULL,
+            name            TEXT        NOT NULL,
+            prefix          TEXT        NOT NULL,
+            deleted         INTEGER     NOT NULL DEFAULT 0
+        )
+        "#
+    }
+
+    fn select_fields() -> &'static str {
+        "id, rack_id, trunk_id, trunk_name, vlan, name, prefix"
+    }
+
+    fn try_from(row: &Row) -> Result<Self, rusqlite::Error> {
+        Ok(Self {
+            id: row.get(0)?,
+            rack: row.get(1)?,
+            trunk: TrunkIdView {
+                id: TrunkId(row.get(2)?),
+                name: row.get(3)?
+            },
+            vlan: row.get(4)?,
+            name: row.get(5)?,
+            prefix: row.get(6)?
+        })
+    }
+}
\ No newline at end of file
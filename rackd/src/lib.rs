@@ -5,8 +5,12 @@ pub mod db;
 pub mod trunk;
 pub mod net;    
 pub mod wan;
+pub mod lan;
 pub mod rack;
 pub mod org;
 pub mod util;
 pub mod actors;
+pub mod admin;
+pub mod event;
+pub mod sys;
 pub mod api;
\ No newline at end of file
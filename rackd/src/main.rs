@@ -200,7 +200,7 @@ use aya_log_ebpf::info;
 use aya::{maps::Array, programs::{Xdp, XdpFlags}};
 use aya_log::EbpfLogger;
 use log::{debug, warn};
-use rackd::api;
+use rackd::{actors::system::Rackd, api};
 // use crate::{actors::{self, system::ActorSystem}, net::{shared::models::NetName, wan::cmd::Create}};
 use serde::{Deserialize, Serialize};
 use tokio::{net::TcpListener, signal};
@@ -235,8 +235,9 @@ async fn main() -> Result<(), std::io::Error> {
     #[openapi(info(description = "API DESCRIPTION HERE"))]
     struct ApiDoc;
 
+    let rackd = Rackd::start().await.map_err(std::io::Error::other)?;
     let (router, api) = OpenApiRouter::with_openapi(ApiDoc::openapi())
-        .nest("/v1", api::router())
+        .nest("/v1", api::router(rackd))
         .split_for_parts();
 
     let router = router
@@ -1,4 +1,5 @@
 pub mod model;
+pub mod probe;
 pub mod query;
 pub mod views;
 pub use model::values::*;
\ No newline at end of file
@@ -1,6 +1,7 @@
 use std::{fmt::Display, net::{Ipv4Addr, Ipv6Addr}, str::FromStr};
 use macaddr::MacAddr6;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use thiserror::Error;
 use utoipa::ToSchema;
 use uuid::Uuid;
@@ -11,7 +12,43 @@ pub struct NetworkId(pub Uuid);
 #[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Copy, Clone)]
 pub struct LinkId(u32);
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, ToSchema)]
+impl Display for LinkId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Host interface name (ie: eth0, enp1s0), at most 15 characters long (IFNAMSIZ - 1)
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq, Hash, ToSchema)]
+pub struct LinkName(String);
+
+impl LinkName {
+    pub const MAX_LEN: usize = 15;
+
+    /// Hex digits of the parent's hash kept when its name is cut short
+    const HASH_LEN: usize = 5;
+
+    /// 802.1Q link of the VLAN on top of this link (ie: eth0.4001). When the whole name wouldn't fit
+    /// in MAX_LEN the parent's name is cut short and ends with a hash of the full name, otherwise parents
+    /// sharing a prefix (ie: enp0s31f6abcd1 and enp0s31f6abcd2) would get the same link on a VLAN
+    pub fn vlan(&self, vlan: VlanId) -> LinkName {
+        let suffix = format!(".{}", vlan);
+        if self.0.len() + suffix.len() <= Self::MAX_LEN {
+            return LinkName(format!("{}{}", self.0, suffix));
+        }
+        let hash = format!("{:x}", Sha256::digest(self.0.as_bytes()));
+        let parent: String = self.0.chars().take(Self::MAX_LEN - suffix.len() - Self::HASH_LEN).collect();
+        LinkName(format!("{}{}{}", parent, &hash[..Self::HASH_LEN], suffix))
+    }
+}
+
+impl Display for LinkName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, ToSchema)]
 pub struct VlanId(u16);
 
 impl Display for VlanId {
@@ -29,12 +66,13 @@ impl Display for NetName {
     }
 }
 
+/// {"mode": "auto"} uses the link's own MAC, {"mode": "spoofed", "mac": "76:dc:3a:78:af:d0"} spoofs it
 #[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq)]
 #[serde(tag = "mode", content = "mac")]
 #[serde(rename_all = "snake_case")] 
 pub enum MacAddr {
     Auto,
-    Spoofed(MacAddr6)
+    Spoofed(#[serde(with = "mac_str")] MacAddr6)
 }
 
 impl Default for MacAddr {
@@ -43,6 +81,21 @@ impl Default for MacAddr {
     }
 }
 
+pub mod mac_str {
+    use std::str::FromStr;
+    use macaddr::MacAddr6;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(value: &MacAddr6, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        serializer.serialize_str(&value.to_string().to_lowercase())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<MacAddr6, D::Error> where D: Deserializer<'de> {
+        let s = String::deserialize(deserializer)?;
+        MacAddr6::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub enum Ipv4Params { 
     DHCP,
@@ -99,7 +152,7 @@ pub struct Ipv6Prefix {
     pub len: u8
 }
 
-#[derive(Debug, Serialize, Deserialize, Copy, Clone)]
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq)]
 pub enum Prefix {
     V4(Ipv4Prefix),
     V6(Ipv6Prefix),
@@ -112,6 +165,22 @@ impl Default for Prefix {
     }
 }
 
+impl Prefix {
+    pub fn v4(&self) -> Option<Ipv4Prefix> {
+        match self {
+            Prefix::V4(prefix) | Prefix::DualStack(prefix, _) => Some(*prefix),
+            Prefix::V6(_) => None
+        }
+    }
+
+    pub fn v6(&self) -> Option<Ipv6Prefix> {
+        match self {
+            Prefix::V6(prefix) | Prefix::DualStack(_, prefix) => Some(*prefix),
+            Prefix::V4(_) => None
+        }
+    }
+}
+
 pub trait IpPrefix where Self: Default + Copy + Sized + PartialEq + Eq {
     type Addr;
     fn new(addr: Self::Addr, len: u8) -> Self;
@@ -428,6 +497,16 @@ impl FromStr for Ipv4Prefix {
     }
 }
 
+impl FromStr for Prefix {
+    type Err = PrefixParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.contains(':') {
+            true => Ok(Prefix::V6(Ipv6Prefix::from_str(s)?)),
+            false => Ok(Prefix::V4(Ipv4Prefix::from_str(s)?))
+        }
+    }
+}
+
 pub mod casts {
     use std::str::FromStr;
     use serde_json::{Number, Value};
@@ -440,6 +519,18 @@ pub mod casts {
         }
     }
 
+    impl From<u32> for LinkId {
+        fn from(value: u32) -> Self {
+            LinkId(value)
+        }
+    }
+
+    impl From<LinkId> for u32 {
+        fn from(id: LinkId) -> Self {
+            id.0
+        }
+    }
+
     impl FromStr for NetName {
         type Err = InvalidChars;
     
@@ -473,6 +564,45 @@ pub mod casts {
         }
     }
 
+    #[derive(Debug, Error)]
+    pub enum LinkNameError {
+        #[error("Value is not a String [{}]", .0)]
+        InvalidType(Value),
+        #[error("Value contains the following invalid characters: {} [{}]", .0.chars.join(","), .0.value)]
+        InvalidCharacters(InvalidChars),
+        #[error("Value is longer than {} characters [{}]", LinkName::MAX_LEN, .0)]
+        TooLong(String),
+        #[error("No value provided")]
+        MissingValue
+    }
+
+    impl FromStr for LinkName {
+        type Err = LinkNameError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            if let Some(chars) = InvalidChars::from(s, &[]) {
+                Err(LinkNameError::InvalidCharacters(chars))?
+            }
+            match s.len() {
+                0 => Err(LinkNameError::MissingValue),
+                len if len > LinkName::MAX_LEN => Err(LinkNameError::TooLong(String::from(s))),
+                _ => Ok(LinkName(String::from(s)))
+            }
+        }
+    }
+
+    impl TryFrom<Value> for LinkName {
+        type Error = LinkNameError;
+
+        fn try_from(value: Value) -> Result<Self, Self::Error> {
+            match value {
+                Value::String(value) => LinkName::from_str(&value),
+                Value::Null => Err(LinkNameError::MissingValue),
+                _ => Err(LinkNameError::InvalidType(value))
+            }
+        }
+    }
+
     #[derive(Debug, Error)]
     pub enum VlanIdError {
         #[error("Value is not a number [{}]", .0)]
@@ -515,6 +645,96 @@ pub mod casts {
         }
     }
 
+    impl From<VlanId> for u16 {
+        fn from(value: VlanId) -> Self {
+            value.0
+        }
+    }
+
+    #[derive(Debug, Error)]
+    pub enum MacAddrError {
+        #[error("Value is not a String or an Object [{}]", .0)]
+        InvalidType(Value),
+        #[error("Value is not \"auto\" or a MAC address [{}]", .0)]
+        InvalidFormat(String),
+        #[error("Value is a multicast MAC address [{}]", .0)]
+        Multicast(String),
+        #[error("No value provided")]
+        MissingValue
+    }
+
+    /// Either the serialized form ({"mode": "auto"} or {"mode": "spoofed", "mac": "76:dc:3a:78:af:d0"})
+    /// or its shorthand, "auto" to use the link's own MAC or the static MAC to spoof
+    impl TryFrom<Value> for MacAddr {
+        type Error = MacAddrError;
+
+        fn try_from(value: Value) -> Result<Self, Self::Error> {
+            match value {
+                Value::String(s) if s.eq_ignore_ascii_case("auto") => Ok(MacAddr::Auto),
+                Value::String(s) => match MacAddr6::from_str(&s) {
+                    Ok(mac) if mac.is_multicast() => Err(MacAddrError::Multicast(s)),
+                    Ok(mac) => Ok(MacAddr::Spoofed(mac)),
+                    Err(_) => Err(MacAddrError::InvalidFormat(s))
+                },
+                Value::Object(_) => match serde_json::from_value::<MacAddr>(value.clone()) {
+                    Ok(MacAddr::Spoofed(mac)) if mac.is_multicast() => Err(MacAddrError::Multicast(mac.to_string())),
+                    Ok(mac) => Ok(mac),
+                    Err(_) => Err(MacAddrError::InvalidFormat(value.to_string()))
+                },
+                Value::Null => Err(MacAddrError::MissingValue),
+                _ => Err(MacAddrError::InvalidType(value))
+            }
+        }
+    }
+
+    #[derive(Debug, Error)]
+    pub enum Ipv4ParamsError {
+        #[error("Value is not \"dhcp\" or an Object [{}]", .0)]
+        InvalidType(Value),
+        #[error("Value is not a valid IPv4 address [{}]", .0)]
+        InvalidAddr(Value),
+        #[error("Value is not a valid IPv4 mask length [{}]", .0)]
+        InvalidMaskLen(Value),
+        #[error("Gateway {} is outside of {}/{}", .gateway, .addr, .mask_len)]
+        GatewayOutsideSubnet { addr: Ipv4Addr, mask_len: u8, gateway: Ipv4Addr },
+        #[error("No value provided")]
+        MissingValue
+    }
+
+    /// Either "dhcp" or {"addr": "192.0.2.2", "mask_len": 24, "gateway": "192.0.2.1"}
+    impl TryFrom<Value> for Ipv4Params {
+        type Error = Ipv4ParamsError;
+
+        fn try_from(value: Value) -> Result<Self, Self::Error> {
+            let addr = |value: Option<Value>| match value {
+                Some(Value::String(s)) => Ipv4Addr::from_str(&s).map_err(|_| Ipv4ParamsError::InvalidAddr(Value::String(s))),
+                Some(value) => Err(Ipv4ParamsError::InvalidAddr(value)),
+                None => Err(Ipv4ParamsError::MissingValue)
+            };
+            match value {
+                Value::String(s) if s.eq_ignore_ascii_case("dhcp") => Ok(Ipv4Params::DHCP),
+                Value::Object(mut map) => {
+                    let mask_len = match map.remove("mask_len") {
+                        Some(Value::Number(n)) => n.as_u64()
+                            .and_then(|len| u8::try_from(len).ok())
+                            .and_then(|len| Ipv4PrefixLen::try_from(len).ok())
+                            .ok_or(Ipv4ParamsError::InvalidMaskLen(Value::Number(n)))?,
+                        Some(value) => Err(Ipv4ParamsError::InvalidMaskLen(value))?,
+                        None => Err(Ipv4ParamsError::MissingValue)?
+                    };
+                    let (addr, gateway) = (addr(map.remove("addr"))?, addr(map.remove("gateway"))?);
+                    let mask = u32::MAX.checked_shl(32 - u8::from(mask_len) as u32).unwrap_or(0);
+                    if u32::from(addr) & mask != u32::from(gateway) & mask {
+                        Err(Ipv4ParamsError::GatewayOutsideSubnet { addr, mask_len: mask_len.into(), gateway })?
+                    }
+                    Ok(Ipv4Params::Static { addr, mask_len, gateway })
+                },
+                Value::Null => Err(Ipv4ParamsError::MissingValue),
+                _ => Err(Ipv4ParamsError::InvalidType(value))
+            }
+        }
+    }
+
     // impl From<VlanIdError> for ApiError {
     //     fn from(error: VlanIdError) -> Self {
     //         match error {
@@ -525,6 +745,50 @@ pub mod casts {
     //     }
     // }
 
+    #[derive(Debug, Error)]
+    pub enum PrefixError {
+        #[error("Value is not a String or an Array of Strings [{}]", .0)]
+        InvalidType(Value),
+        #[error("Value is not a valid prefix [{}]: {}", .0, .1)]
+        InvalidFormat(String, PrefixParseError),
+        #[error("Dual-stack prefixes need to be passed as [<ipv4-prefix>, <ipv6-prefix>] [{}]", .0)]
+        InvalidDualStack(Value),
+        #[error("No value provided")]
+        MissingValue
+    }
+
+    impl TryFrom<Value> for Prefix {
+        type Error = PrefixError;
+
+        fn try_from(value: Value) -> Result<Self, Self::Error> {
+            match value {
+                Value::String(s) => Prefix::from_str(&s).map_err(|e| PrefixError::InvalidFormat(s, e)),
+                Value::Array(ref items) => match items.as_slice() {
+                    [Value::String(v4), Value::String(v6)] => {
+                        let v4 = Ipv4Prefix::from_str(v4).map_err(|e| PrefixError::InvalidFormat(v4.clone(), e))?;
+                        let v6 = Ipv6Prefix::from_str(v6).map_err(|e| PrefixError::InvalidFormat(v6.clone(), e))?;
+                        Ok(Prefix::DualStack(v4, v6))
+                    },
+                    _ => Err(PrefixError::InvalidDualStack(value))
+                },
+                Value::Null => Err(PrefixError::MissingValue),
+                _ => Err(PrefixError::InvalidType(value))
+            }
+        }
+    }
+
+    impl TryFrom<Value> for Ipv6Prefix {
+        type Error = PrefixError;
+
+        fn try_from(value: Value) -> Result<Self, Self::Error> {
+            match value {
+                Value::String(s) => Ipv6Prefix::from_str(&s).map_err(|e| PrefixError::InvalidFormat(s, e)),
+                Value::Null => Err(PrefixError::MissingValue),
+                _ => Err(PrefixError::InvalidType(value))
+            }
+        }
+    }
+
     #[derive(Debug)]
     pub enum Ipv4PrefixLenError {
         OutsideBounds
@@ -571,7 +835,7 @@ pub mod casts {
 
 pub mod api {
     use crate::util::api::Error;
-    use super::casts::{NetNameError, VlanIdError};
+    use super::casts::{Ipv4ParamsError, LinkNameError, MacAddrError, NetNameError, PrefixError, VlanIdError};
 
     impl From<VlanIdError> for Error {
         fn from(error: VlanIdError) -> Self {
@@ -584,6 +848,30 @@ pub mod api {
             Error::new("NET_NAME_ERROR", error.to_string())
         }
     }
+
+    impl From<PrefixError> for Error {
+        fn from(error: PrefixError) -> Self {
+            Error::new("PREFIX_ERROR", error.to_string())
+        }
+    }
+
+    impl From<LinkNameError> for Error {
+        fn from(error: LinkNameError) -> Self {
+            Error::new("LINK_NAME_ERROR", error.to_string())
+        }
+    }
+
+    impl From<MacAddrError> for Error {
+        fn from(error: MacAddrError) -> Self {
+            Error::new("MAC_ADDR_ERROR", error.to_string())
+        }
+    }
+
+    impl From<Ipv4ParamsError> for Error {
+        fn from(error: Ipv4ParamsError) -> Self {
+            Error::new("IPV4_PARAMS_ERROR", error.to_string())
+        }
+    }
 }
 
 pub mod sqlite {
@@ -603,6 +891,18 @@ pub mod sqlite {
         }
     }
 
+    impl FromSql for LinkName {
+        fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+            Ok(LinkName(String::from(value.as_str()?)))
+        }
+    }
+
+    impl ToSql for LinkName {
+        fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+            Ok(self.0.as_str().into())
+        }
+    }
+
     impl FromSql for VlanId {
         fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
             Ok(VlanId(value.as_i64()? as u16))
@@ -715,6 +1015,22 @@ pub mod sqlite {
 mod tests {
     use super::*;
 
+    #[test]
+    fn vlan_links_fit_in_an_interface_name() {
+        let vlan = VlanId::try_from(4001).unwrap();
+        assert_eq!(LinkName::from_str("eth0").unwrap().vlan(vlan).to_string(), "eth0.4001");
+        assert_eq!(LinkName::from_str("eth0123456").unwrap().vlan(vlan).to_string(), "eth0123456.4001");
+        let long = LinkName::from_str("enx0123456789ab").unwrap().vlan(vlan).to_string();
+        assert_eq!(long.len(), LinkName::MAX_LEN);
+        assert!(long.starts_with("enx01") && long.ends_with(".4001"));
+
+        // Parents only told apart past the cut get links of their own
+        let first = LinkName::from_str("enp0s31f6abcd1").unwrap().vlan(vlan);
+        let second = LinkName::from_str("enp0s31f6abcd2").unwrap().vlan(vlan);
+        assert_ne!(first, second);
+        assert_eq!(first, LinkName::from_str("enp0s31f6abcd1").unwrap().vlan(vlan));
+    }
+
     #[test]
     fn can_create_ipv4_prefix() {
         let addr = Ipv4Addr::new(172, 24, 16, 255);
@@ -741,6 +1057,37 @@ mod tests {
         assert_eq!(prefix.last().to_string(), "172.24.16.255");
     }
 
+    #[test]
+    fn parse_link_name() {
+        assert!(LinkName::from_str("enp1s0").is_ok());
+        assert!(LinkName::from_str("enx00e04c680001a").is_err_and(|e| matches!(e, casts::LinkNameError::TooLong(_))));
+        assert!(LinkName::from_str("eth0.100").is_err_and(|e| matches!(e, casts::LinkNameError::InvalidCharacters(_))));
+    }
+
+    #[test]
+    fn parse_mac_addr_and_ipv4_params() {
+        use serde_json::json;
+        assert_eq!(MacAddr::try_from(json!("auto")).unwrap(), MacAddr::Auto);
+        assert_eq!(MacAddr::try_from(json!("76:dc:3a:78:af:d0")).unwrap(), MacAddr::Spoofed(MacAddr6::new(0x76, 0xdc, 0x3a, 0x78, 0xaf, 0xd0)));
+        assert!(MacAddr::try_from(json!("01:00:5e:00:00:01")).is_err_and(|e| matches!(e, casts::MacAddrError::Multicast(_))));
+        assert!(MacAddr::try_from(json!(42)).is_err_and(|e| matches!(e, casts::MacAddrError::InvalidType(_))));
+        // The serialized form round trips through the API as well
+        let spoofed = MacAddr::Spoofed(MacAddr6::new(0x76, 0xdc, 0x3a, 0x78, 0xaf, 0xd0));
+        assert_eq!(MacAddr::try_from(json!({"mode": "auto"})).unwrap(), MacAddr::Auto);
+        assert_eq!(serde_json::to_value(spoofed).unwrap(), json!({"mode": "spoofed", "mac": "76:dc:3a:78:af:d0"}));
+        assert_eq!(MacAddr::try_from(serde_json::to_value(spoofed).unwrap()).unwrap(), spoofed);
+        assert!(MacAddr::try_from(json!({"mode": "spoofed", "mac": "01:00:5e:00:00:01"})).is_err_and(|e| matches!(e, casts::MacAddrError::Multicast(_))));
+        assert!(MacAddr::try_from(json!({"mode": "spoofed"})).is_err_and(|e| matches!(e, casts::MacAddrError::InvalidFormat(_))));
+
+        assert_eq!(Ipv4Params::try_from(json!("dhcp")).unwrap(), Ipv4Params::DHCP);
+        let ip = Ipv4Params::try_from(json!({ "addr": "192.0.2.2", "mask_len": 24, "gateway": "192.0.2.1" })).unwrap();
+        assert!(matches!(ip, Ipv4Params::Static { .. }));
+        let ip = Ipv4Params::try_from(json!({ "addr": "192.0.2.2", "mask_len": 24, "gateway": "198.51.100.1" }));
+        assert!(ip.is_err_and(|e| matches!(e, casts::Ipv4ParamsError::GatewayOutsideSubnet { .. })));
+        let ip = Ipv4Params::try_from(json!({ "addr": "192.0.2.2", "mask_len": 33, "gateway": "192.0.2.1" }));
+        assert!(ip.is_err_and(|e| matches!(e, casts::Ipv4ParamsError::InvalidMaskLen(_))));
+    }
+
     #[test]
     fn ipv6_prefix_type() {
 
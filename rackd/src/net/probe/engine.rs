@@ -0,0 +1,118 @@
+use std::{net::IpAddr, time::Duration};
+use futures::future::join_all;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use super::{sockets::probe, stats::{Hysteresis, ProbeStats, Samples}, InternetUp, ProbeConfig, ProbeSource, ProbeTarget};
+
+/// Probes the targets of the config from a source, each family is decided on its own
+pub struct ProbeEngine {
+    pub source: ProbeSource,
+    config: ProbeConfig,
+    /// Same order as the targets of the config
+    samples: Vec<Samples>,
+    v4: Hysteresis,
+    v6: Hysteresis
+}
+
+/// Stats of a target, they're reported as part of the telemetry of the link probed from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TargetStats {
+    pub target: ProbeTarget,
+    pub stats: ProbeStats
+}
+
+impl ProbeEngine {
+    pub fn new(source: ProbeSource, config: ProbeConfig) -> Self {
+        let samples = config.targets.iter().map(|_| Samples::new(config.window)).collect();
+        let hysteresis = Hysteresis::new(config.up_after, config.down_after);
+        Self { source, config, samples, v4: hysteresis, v6: hysteresis }
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.interval)
+    }
+
+    /// Probes every target at once, a family succeeds when a quorum of its targets replied.
+    /// Nothing changes until every probe is done so a round can be dropped midway
+    pub async fn round(&mut self) -> Option<InternetUp> {
+        let within = Duration::from_millis(self.config.timeout);
+        let rtts = join_all(self.config.targets.iter().map(|target| probe(&self.source, target, within))).await;
+        let (mut targets4, mut targets6, mut replied4, mut replied6) = (0, 0, 0, 0);
+        for ((target, samples), rtt) in self.config.targets.iter().zip(self.samples.iter_mut()).zip(rtts) {
+            let (targets, replied) = match target.addr() {
+                IpAddr::V4(_) => (&mut targets4, &mut replied4),
+                IpAddr::V6(_) => (&mut targets6, &mut replied6)
+            };
+            *targets += 1;
+            match rtt {
+                Ok(rtt) => {
+                    *replied += 1;
+                    samples.push(Some(rtt));
+                },
+                Err(e) => {
+                    debug!("[PROBE] {:?} from {:?} failed: {}", target, self.source, e);
+                    samples.push(None);
+                }
+            }
+        }
+        // Families without targets are never up, nor is one whose targets are fewer than the quorum and all lost
+        let v4 = self.v4.update(targets4 > 0 && replied4 >= self.config.quorum.min(targets4));
+        let v6 = self.v6.update(targets6 > 0 && replied6 >= self.config.quorum.min(targets6));
+        match (v4, v6) {
+            (true, true) => Some(InternetUp::DualStack),
+            (false, true) => Some(InternetUp::V6),
+            (true, false) => Some(InternetUp::V4),
+            (false, false) => None
+        }
+    }
+
+    /// Decisions start over from down, the samples are kept
+    pub fn reset(&mut self) {
+        let hysteresis = Hysteresis::new(self.config.up_after, self.config.down_after);
+        (self.v4, self.v6) = (hysteresis, hysteresis);
+    }
+
+    /// A decision is about to flip, the next round shouldn't wait for the interval
+    pub fn pending(&self) -> bool {
+        self.v4.pending() || self.v6.pending()
+    }
+
+    pub fn stats(&self) -> Vec<TargetStats> {
+        self.config.targets.iter().zip(&self.samples).map(|(target, samples)| TargetStats { target: target.clone(), stats: samples.stats() }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+    use tokio::net::{TcpListener, UdpSocket};
+    use crate::net::probe::{InternetUp, ProbeConfig, ProbeSource, ProbeTarget};
+    use super::ProbeEngine;
+
+    const LOCALHOST: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+    #[tokio::test]
+    async fn families_go_up_after_enough_rounds() {
+        let listener = TcpListener::bind(SocketAddr::new(LOCALHOST, 0)).await.unwrap();
+        // Bound but never answered, its probes time out (a closed port would answer with a reset or a port unreachable)
+        let silent = UdpSocket::bind(SocketAddr::new(LOCALHOST, 0)).await.unwrap();
+        let config = ProbeConfig {
+            interval: 30, timeout: 200, window: 10, quorum: 2, up_after: 2, down_after: 2,
+            targets: vec![
+                ProbeTarget::Tcp { addr: LOCALHOST, port: listener.local_addr().unwrap().port() },
+                ProbeTarget::Udp { addr: LOCALHOST, port: silent.local_addr().unwrap().port() }
+            ]
+        };
+        let mut engine = ProbeEngine::new(ProbeSource::default(), config.clone());
+        assert_eq!(engine.round().await, None);
+        assert!(!engine.pending());
+
+        let mut engine = ProbeEngine::new(ProbeSource::default(), ProbeConfig { quorum: 1, ..config });
+        assert_eq!(engine.round().await, None);
+        assert!(engine.pending());
+        assert_eq!(engine.round().await, Some(InternetUp::V4));
+        let stats = engine.stats();
+        assert_eq!((stats[0].stats.sent, stats[0].stats.lost), (2, 0));
+        assert_eq!((stats[1].stats.sent, stats[1].stats.lost), (2, 2));
+    }
+}
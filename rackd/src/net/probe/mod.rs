@@ -0,0 +1,75 @@
+pub mod engine;
+pub mod sockets;
+pub mod stats;
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use crate::net::LinkName;
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Copy, Clone)]
+pub enum InternetUp {
+    V4, V6, DualStack
+}
+
+/// How Internet reachability is probed, see the `[probes]` section of the settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProbeConfig {
+    /// Seconds between two rounds once the decision settled
+    pub interval: u64,
+    /// Milliseconds a target has to reply
+    pub timeout: u64,
+    /// Probes per target the stats are computed over
+    pub window: usize,
+    /// Targets of a family that must reply for a round to succeed
+    pub quorum: usize,
+    /// Consecutive successful rounds it takes to go up
+    pub up_after: u8,
+    /// Consecutive failed rounds it takes to go down
+    pub down_after: u8,
+    pub targets: Vec<ProbeTarget>
+}
+
+/// Reachability probe, a probe succeeds as soon as the target proves the path to it works
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum ProbeTarget {
+    /// Echo request answered by an echo reply
+    Icmp { addr: IpAddr },
+    /// Datagram answered by anything, a port unreachable included
+    Udp { addr: IpAddr, port: u16 },
+    /// Connection accepted or reset
+    Tcp { addr: IpAddr, port: u16 },
+    /// Query for the A record of `name` answered, whatever the response code
+    Dns { addr: IpAddr, #[serde(default = "dns_port")] port: u16, name: String }
+}
+
+fn dns_port() -> u16 {
+    53
+}
+
+impl ProbeTarget {
+    pub fn addr(&self) -> IpAddr {
+        match self {
+            Self::Icmp { addr } | Self::Udp { addr, .. } | Self::Tcp { addr, .. } | Self::Dns { addr, .. } => *addr
+        }
+    }
+}
+
+/// Where probes leave from, binding them to the link of a wan makes them skip the routes of other wans
+#[derive(Debug, Clone, Default)]
+pub struct ProbeSource {
+    pub device: Option<LinkName>,
+    pub ipv4: Option<Ipv4Addr>,
+    pub ipv6: Option<Ipv6Addr>
+}
+
+#[derive(Debug, Error)]
+pub enum ProbeError {
+    #[error("Target didn't reply in time")]
+    TimedOut,
+    #[error("{} can't be queried", .0)]
+    InvalidDnsName(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error)
+}
@@ -0,0 +1,190 @@
+use std::{io::{self, ErrorKind}, net::{IpAddr, SocketAddr}, sync::atomic::{AtomicU16, Ordering}, time::Duration};
+use socket2::{Domain, Protocol, SockRef, Socket, Type};
+use tokio::{io::Interest, net::{TcpSocket, UdpSocket}, time::{timeout, Instant}};
+use super::{ProbeError, ProbeSource, ProbeTarget};
+
+/// ICMP sequence numbers and DNS ids, replies to earlier probes are told apart by them
+static SEQUENCE: AtomicU16 = AtomicU16::new(0);
+
+/// Probes the target from the source, the RTT is returned when it proved reachable
+pub async fn probe(source: &ProbeSource, target: &ProbeTarget, within: Duration) -> Result<Duration, ProbeError> {
+    let rtt = match target {
+        ProbeTarget::Icmp { addr } => timeout(within, icmp(source, *addr)).await,
+        ProbeTarget::Udp { addr, port } => timeout(within, udp(source, SocketAddr::new(*addr, *port))).await,
+        ProbeTarget::Tcp { addr, port } => timeout(within, tcp(source, SocketAddr::new(*addr, *port))).await,
+        ProbeTarget::Dns { addr, port, name } => timeout(within, dns(source, SocketAddr::new(*addr, *port), name)).await
+    };
+    rtt.map_err(|_| ProbeError::TimedOut)?
+}
+
+fn bind(socket: SockRef, source: &ProbeSource, to: IpAddr) -> Result<(), ProbeError> {
+    if let Some(device) = &source.device {
+        socket.bind_device(Some(device.to_string().as_bytes()))?;
+    }
+    let from = match to {
+        IpAddr::V4(_) => source.ipv4.map(IpAddr::V4),
+        IpAddr::V6(_) => source.ipv6.map(IpAddr::V6)
+    };
+    if let Some(from) = from {
+        socket.bind(&SocketAddr::new(from, 0).into())?;
+    }
+    Ok(())
+}
+
+fn datagram(socket: Socket) -> Result<UdpSocket, ProbeError> {
+    socket.set_nonblocking(true)?;
+    Ok(UdpSocket::from_std(socket.into())?)
+}
+
+/// Errors queued on a socket (ie: ICMP unreachables) don't make it readable, the pending error is taken
+/// as soon as it's reported. Tokio never clears the error readiness, it must end the receive
+async fn recv(socket: &UdpSocket, buf: &mut [u8]) -> io::Result<usize> {
+    loop {
+        let ready = socket.ready(Interest::READABLE | Interest::ERROR).await?;
+        if ready.is_error() {
+            return Err(socket.take_error()?.unwrap_or_else(|| io::Error::other("socket reported an error without a pending one")));
+        }
+        match socket.try_recv(buf) {
+            Err(e) if e.kind() == ErrorKind::WouldBlock => continue,
+            result => return result
+        }
+    }
+}
+
+/// Ping sockets don't need CAP_NET_RAW (see net.ipv4.ping_group_range), the kernel fills in
+/// the identifier and the checksum and only hands back the replies to this socket
+async fn icmp(source: &ProbeSource, addr: IpAddr) -> Result<Duration, ProbeError> {
+    let (domain, protocol, request, reply) = match addr {
+        IpAddr::V4(_) => (Domain::IPV4, Protocol::ICMPV4, 8, 0),
+        IpAddr::V6(_) => (Domain::IPV6, Protocol::ICMPV6, 128, 129)
+    };
+    let socket = Socket::new(domain, Type::DGRAM, Some(protocol))?;
+    bind(SockRef::from(&socket), source, addr)?;
+    let socket = datagram(socket)?;
+    socket.connect(SocketAddr::new(addr, 0)).await?;
+
+    let sequence = SEQUENCE.fetch_add(1, Ordering::Relaxed).to_be_bytes();
+    let echo = [request, 0, 0, 0, 0, 0, sequence[0], sequence[1], b'r', b'a', b'c', b'k', b'd', 0, 0, 0];
+    let sent = Instant::now();
+    socket.send(&echo).await?;
+    let mut buf = [0; 1500];
+    loop {
+        let len = recv(&socket, &mut buf).await?;
+        if len >= 8 && buf[0] == reply && buf[6..8] == sequence {
+            return Ok(sent.elapsed());
+        }
+    }
+}
+
+/// A port unreachable comes from the target, it proves the path works as well as a reply
+async fn udp(source: &ProbeSource, addr: SocketAddr) -> Result<Duration, ProbeError> {
+    let socket = Socket::new(Domain::for_address(addr), Type::DGRAM, Some(Protocol::UDP))?;
+    bind(SockRef::from(&socket), source, addr.ip())?;
+    let socket = datagram(socket)?;
+    socket.connect(addr).await?;
+    let sent = Instant::now();
+    socket.send(b"rackd").await?;
+    let mut buf = [0; 1500];
+    match recv(&socket, &mut buf).await {
+        Ok(_) => Ok(sent.elapsed()),
+        Err(e) if e.kind() == ErrorKind::ConnectionRefused => Ok(sent.elapsed()),
+        Err(e) => Err(e)?
+    }
+}
+
+/// A reset comes from the target, it proves the path works as well as an accepted connection
+async fn tcp(source: &ProbeSource, addr: SocketAddr) -> Result<Duration, ProbeError> {
+    let socket = match addr {
+        SocketAddr::V4(_) => TcpSocket::new_v4()?,
+        SocketAddr::V6(_) => TcpSocket::new_v6()?
+    };
+    bind(SockRef::from(&socket), source, addr.ip())?;
+    let sent = Instant::now();
+    match socket.connect(addr).await {
+        Ok(_) => Ok(sent.elapsed()),
+        Err(e) if e.kind() == ErrorKind::ConnectionRefused => Ok(sent.elapsed()),
+        Err(e) => Err(e)?
+    }
+}
+
+async fn dns(source: &ProbeSource, addr: SocketAddr, name: &str) -> Result<Duration, ProbeError> {
+    let id = SEQUENCE.fetch_add(1, Ordering::Relaxed).to_be_bytes();
+    let query = dns_query(id, name)?;
+    let socket = Socket::new(Domain::for_address(addr), Type::DGRAM, Some(Protocol::UDP))?;
+    bind(SockRef::from(&socket), source, addr.ip())?;
+    let socket = datagram(socket)?;
+    socket.connect(addr).await?;
+    let sent = Instant::now();
+    socket.send(&query).await?;
+    let mut buf = [0; 1500];
+    loop {
+        let len = recv(&socket, &mut buf).await?;
+        // Same id with the QR bit set
+        if len >= 12 && buf[0..2] == id && buf[2] & 0x80 != 0 {
+            return Ok(sent.elapsed());
+        }
+    }
+}
+
+/// Recursive query for the A record of the name (RFC 1035 4.1)
+fn dns_query(id: [u8; 2], name: &str) -> Result<Vec<u8>, ProbeError> {
+    let mut query = vec![id[0], id[1], 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0];
+    for label in name.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(ProbeError::InvalidDnsName(String::from(name)));
+        }
+        query.push(label.len() as u8);
+        query.extend_from_slice(label.as_bytes());
+    }
+    query.extend_from_slice(&[0, 0, 1, 0, 1]);
+    Ok(query)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::{IpAddr, Ipv4Addr, SocketAddr}, time::Duration};
+    use tokio::net::{TcpListener, UdpSocket};
+    use crate::net::probe::{ProbeSource, ProbeTarget};
+    use super::{dns_query, probe};
+
+    const LOCALHOST: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+    #[tokio::test]
+    async fn probes_prove_the_loopback_reachable() {
+        let source = ProbeSource { ipv4: Some(Ipv4Addr::LOCALHOST), ..Default::default() };
+        let within = Duration::from_secs(1);
+
+        let listener = TcpListener::bind(SocketAddr::new(LOCALHOST, 0)).await.unwrap();
+        let target = ProbeTarget::Tcp { addr: LOCALHOST, port: listener.local_addr().unwrap().port() };
+        assert!(probe(&source, &target, within).await.is_ok());
+
+        // Nothing listens on a port that was just released, the port unreachable is enough
+        let port = UdpSocket::bind(SocketAddr::new(LOCALHOST, 0)).await.unwrap().local_addr().unwrap().port();
+        assert!(probe(&source, &ProbeTarget::Udp { addr: LOCALHOST, port }, within).await.is_ok());
+
+        let server = UdpSocket::bind(SocketAddr::new(LOCALHOST, 0)).await.unwrap();
+        let target = ProbeTarget::Dns { addr: LOCALHOST, port: server.local_addr().unwrap().port(), name: String::from("example.com") };
+        tokio::spawn(async move {
+            let mut buf = [0; 512];
+            let (len, from) = server.recv_from(&mut buf).await.unwrap();
+            buf[2] |= 0x80;
+            server.send_to(&buf[..len], from).await.unwrap();
+        });
+        assert!(probe(&source, &target, within).await.is_ok());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs ping sockets, the group running the tests must be in net.ipv4.ping_group_range"]
+    async fn icmp_probes_prove_the_loopback_reachable() {
+        let source = ProbeSource { ipv4: Some(Ipv4Addr::LOCALHOST), ..Default::default() };
+        assert!(probe(&source, &ProbeTarget::Icmp { addr: LOCALHOST }, Duration::from_secs(1)).await.is_ok());
+    }
+
+    #[test]
+    fn dns_queries_are_encoded_label_by_label() {
+        let query = dns_query([0xab, 0xcd], "example.com.").unwrap();
+        assert_eq!(&query[..4], &[0xab, 0xcd, 0x01, 0x00]);
+        assert_eq!(&query[12..], b"\x07example\x03com\x00\x00\x01\x00\x01");
+        assert!(dns_query([0, 0], "example..com").is_err());
+    }
+}
@@ -0,0 +1,114 @@
+use std::{collections::VecDeque, time::Duration};
+use serde::{Deserialize, Serialize};
+
+/// Outcome of the last probes of a target, a lost probe has no RTT
+#[derive(Debug, Clone)]
+pub struct Samples {
+    window: usize,
+    rtts: VecDeque<Option<Duration>>
+}
+
+impl Samples {
+    pub fn new(window: usize) -> Self {
+        Self { window: window.max(1), rtts: VecDeque::with_capacity(window.max(1)) }
+    }
+
+    pub fn push(&mut self, rtt: Option<Duration>) {
+        if self.rtts.len() == self.window {
+            self.rtts.pop_front();
+        }
+        self.rtts.push_back(rtt);
+    }
+
+    pub fn stats(&self) -> ProbeStats {
+        let replies = self.rtts.iter().flatten().copied().collect::<Vec<_>>();
+        let sent = self.rtts.len() as u32;
+        let lost = sent - replies.len() as u32;
+        let loss = (lost * 100).checked_div(sent).unwrap_or_default() as u8;
+        let rtt_avg = (!replies.is_empty()).then(|| replies.iter().sum::<Duration>() / replies.len() as u32);
+        // Mean variation between consecutive replies (RFC 3550 6.4.1 without the smoothing)
+        let jitter = (replies.len() > 1).then(|| {
+            replies.windows(2).map(|pair| pair[0].abs_diff(pair[1])).sum::<Duration>() / (replies.len() - 1) as u32
+        });
+        ProbeStats { sent, lost, loss, rtt_min: replies.iter().min().copied(), rtt_avg, rtt_max: replies.iter().max().copied(), jitter }
+    }
+}
+
+/// Stats over the window of a target
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProbeStats {
+    pub sent: u32,
+    pub lost: u32,
+    /// Percentage of the probes that were lost
+    pub loss: u8,
+    pub rtt_min: Option<Duration>,
+    pub rtt_avg: Option<Duration>,
+    pub rtt_max: Option<Duration>,
+    pub jitter: Option<Duration>
+}
+
+/// Up/down decision that only flips after enough consecutive rounds disagree with it,
+/// a single lost round doesn't take a wan down nor does a single reply bring it up
+#[derive(Debug, Clone, Copy)]
+pub struct Hysteresis {
+    up_after: u8,
+    down_after: u8,
+    up: bool,
+    streak: u8
+}
+
+impl Hysteresis {
+    pub fn new(up_after: u8, down_after: u8) -> Self {
+        Self { up_after: up_after.max(1), down_after: down_after.max(1), up: false, streak: 0 }
+    }
+
+    /// Feeds the outcome of a round and returns the decision
+    pub fn update(&mut self, ok: bool) -> bool {
+        if ok == self.up {
+            self.streak = 0;
+            return self.up;
+        }
+        self.streak += 1;
+        if self.streak >= if ok { self.up_after } else { self.down_after } {
+            self.up = ok;
+            self.streak = 0;
+        }
+        self.up
+    }
+
+    /// Rounds disagree with the decision, more of them will tell whether it flips
+    pub fn pending(&self) -> bool {
+        self.streak > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use super::{Hysteresis, Samples};
+
+    #[test]
+    fn stats_cover_the_window() {
+        let mut samples = Samples::new(4);
+        for rtt in [None, Some(10), Some(20), None, Some(18)] {
+            samples.push(rtt.map(Duration::from_millis));
+        }
+        let stats = samples.stats();
+        assert_eq!((stats.sent, stats.lost, stats.loss), (4, 1, 25));
+        assert_eq!(stats.rtt_min, Some(Duration::from_millis(10)));
+        assert_eq!(stats.rtt_max, Some(Duration::from_millis(20)));
+        assert_eq!(stats.rtt_avg, Some(Duration::from_millis(16)));
+        assert_eq!(stats.jitter, Some(Duration::from_millis(6)));
+    }
+
+    #[test]
+    fn decisions_flip_after_consecutive_rounds() {
+        let mut hysteresis = Hysteresis::new(2, 3);
+        assert!(!hysteresis.update(true) && hysteresis.pending());
+        assert!(hysteresis.update(true) && !hysteresis.pending());
+        assert!(hysteresis.update(false));
+        assert!(hysteresis.update(true));
+        assert!(hysteresis.update(false) && hysteresis.update(false));
+        assert!(!hysteresis.update(false));
+    }
+}
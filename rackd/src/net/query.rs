@@ -1,7 +1,8 @@
 use std::{any::type_name, marker::PhantomData};
 use log::error;
 use rusqlite::{named_params, Transaction};
-use crate::{db::query::traits::{DbQuery, GetByKey, QueryRunner, DbView}, trunk::model::TrunkId};
+use thiserror::Error;
+use crate::{db::{cmd::traits::EntityStore, query::traits::{DbQuery, GetByKey, QueryRunner, DbView}}, trunk::model::{Trunk, TrunkId}, util::traits::{OptionExt, SoftDeleteExt}};
 use super::{model::values::{NetName, VlanId}, views::NetworkView};
 
 pub struct GetByName<'a, T> where T: DbView {
@@ -67,4 +68,67 @@ impl DbQuery for GetNetworkByTrunkVlan {
     fn run(&self, tx: &Transaction) -> Result<Self::Ok, rusqlite::Error> {
         tx.run(GetByTrunkVlan { trunk: self.trunk, vlan: self.vlan, view: PhantomData::<NetworkView> })
     }
-}
\ No newline at end of file
+}
+
+/// Networks (Lans and Wans) riding a trunk, deleted ones are only included on request
+pub struct GetNetworksByTrunk {
+    pub trunk: TrunkId,
+    pub include_deleted: bool
+}
+
+impl DbQuery for GetNetworksByTrunk {
+    type Ok = Vec<NetworkView>;
+
+    fn run(&self, tx: &Transaction) -> Result<Self::Ok, rusqlite::Error> {
+        let sql = format!("{} WHERE trunk_id = :trunk_id AND (deleted = 0 OR :include_deleted) ORDER BY vlan", NetworkView::sql_select());
+        let mut stmt = tx.prepare(&sql)?;
+        let networks = stmt.query_map(named_params! { ":trunk_id": self.trunk, ":include_deleted": self.include_deleted }, <NetworkView as DbView>::try_from)?;
+        networks.collect()
+    }
+}
+
+/// What a deleted network (Lan or Wan) would clash with if it were restored
+pub struct GetRestoreTwins {
+    pub trunk: TrunkId,
+    pub vlan: VlanId,
+    pub name: NetName
+}
+
+pub struct RestoreTwins {
+    pub trunk: Option<Trunk>,
+    /// Live network using the name of the deleted one
+    pub name: Option<NetworkView>,
+    /// Live network on the trunk/VLAN of the deleted one
+    pub trunk_vlan: Option<NetworkView>
+}
+
+#[derive(Debug, Error)]
+pub enum RestoreConflict {
+    #[error("Trunk of the network has been deleted")]
+    TrunkNotFound,
+    #[error("Name already in use")]
+    NameAlreadyInUse,
+    #[error("VLAN/Trunk already in use")]
+    TrunkVlanAlreadyInUse
+}
+
+impl DbQuery for GetRestoreTwins {
+    type Ok = RestoreTwins;
+
+    fn run(&self, tx: &Transaction) -> Result<Self::Ok, rusqlite::Error> {
+        Ok(RestoreTwins {
+            trunk: tx.load(self.trunk)?,
+            name: tx.run(GetNetworkByName { name: self.name.clone() })?,
+            trunk_vlan: tx.run(GetNetworkByTrunkVlan { trunk: self.trunk, vlan: self.vlan })?
+        })
+    }
+}
+
+impl RestoreTwins {
+    /// A network can only come back onto a live trunk, as long as its name and trunk/VLAN haven't been reused since
+    pub fn check(self) -> Result<(), RestoreConflict> {
+        self.trunk.live_or(RestoreConflict::TrunkNotFound)?;
+        self.name.err_or(RestoreConflict::NameAlreadyInUse)?;
+        self.trunk_vlan.err_or(RestoreConflict::TrunkVlanAlreadyInUse)
+    }
+}
@@ -1,14 +1,16 @@
+use crate::lan::model::entity::LanEvent;
 use crate::wan::model::entity::WanEvent;
-use crate::{db::query::traits::DbView, trunk::views::TrunkIdView, util::models::EventData};
+use crate::{db::query::traits::DbView, trunk::{model::{TrunkEvent, TrunkId}, views::TrunkIdView}, util::models::EventData};
 use crate::util::models::{Event, Id};
 use rusqlite::{named_params, params, Error, Row, Transaction};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use super::model::values::{NetName, VlanId};
 
 // #[derive(Debug, Serialize, Deserialize)]
 // pub struct NetworkId(pub Uuid);
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct NetworkView {
     pub id: Id,
     pub name: NetName,
@@ -17,29 +19,75 @@ pub struct NetworkView {
     pub kind: NetworkKind
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub enum NetworkKind {
     Lan, Wan
 }
 
 impl DbView for NetworkView {
     fn name() -> &'static str { "network_view" }
-    fn update(tx: &Transaction, e: &Event) {
+    fn update(tx: &Transaction, e: &Event) -> Result<(), rusqlite::Error> {
         match &e.data {
             EventData::Wan(inner) => match inner {
                 WanEvent::Created { id, trunk, vlan, name, .. } => {
-                    let sql = format!("INSERT INTO {} (id, trunk_id, trunk_name, vlan, name, kind) VALUES (?1, ?2, ?3, ?4, ?5, ?6)", Self::name());
-                    tx.execute(&sql, params![id.0, trunk.id, trunk.name, vlan, name, NetworkKind::Wan]).unwrap();                    
+                    let sql = format!("INSERT INTO {} (id, trunk_id, trunk_name, vlan, name, kind) VALUES (?1, ?2, ?3, ?4, ?5, ?6)", <Self as DbView>::name());
+                    tx.execute(&sql, params![id.0, trunk.id, trunk.name, vlan, name, NetworkKind::Wan])?;
                 },
                 WanEvent::Renamed { to, .. } => {
-                    let sql = format!("UPDATE {} set name = :name WHERE id = :id", Self::name());
-                    tx.execute(&sql, named_params! { ":id": e.stream_id, ":name": to }).unwrap();
+                    let sql = format!("UPDATE {} set name = :name WHERE id = :id", <Self as DbView>::name());
+                    tx.execute(&sql, named_params! { ":id": e.stream_id, ":name": to })?;
+                },
+                WanEvent::Deleted => {
+                    let sql = format!("UPDATE {} set deleted = 1 WHERE id = :id", <Self as DbView>::name());
+                    tx.execute(&sql, named_params! { ":id": e.stream_id })?;
+                },
+                WanEvent::Restored => {
+                    let sql = format!("UPDATE {} set deleted = 0 WHERE id = :id", <Self as DbView>::name());
+                    tx.execute(&sql, named_params! { ":id": e.stream_id })?;
                 },
                 _ => { }
             },
+            EventData::Lan(inner) => match inner {
+                LanEvent::Created { id, trunk, vlan, name, .. } => {
+                    let sql = format!("INSERT INTO {} (id, trunk_id, trunk_name, vlan, name, kind) VALUES (?1, ?2, ?3, ?4, ?5, ?6)", <Self as DbView>::name());
+                    tx.execute(&sql, params![id.0, trunk.id, trunk.name, vlan, name, NetworkKind::Lan])?;
+                },
+                LanEvent::Renamed { to, .. } => {
+                    let sql = format!("UPDATE {} set name = :name WHERE id = :id", <Self as DbView>::name());
+                    tx.execute(&sql, named_params! { ":id": e.stream_id, ":name": to })?;
+                },
+                LanEvent::Deleted => {
+                    let sql = format!("UPDATE {} set deleted = 1 WHERE id = :id", <Self as DbView>::name());
+                    tx.execute(&sql, named_params! { ":id": e.stream_id })?;
+                },
+                LanEvent::Restored => {
+                    let sql = format!("UPDATE {} set deleted = 0 WHERE id = :id", <Self as DbView>::name());
+                    tx.execute(&sql, named_params! { ":id": e.stream_id })?;
+                },
+                _ => { }
+            },
+            EventData::Trunk(TrunkEvent::Renamed { to, .. }) => {
+                let sql = format!("UPDATE {} set trunk_name = :trunk_name WHERE trunk_id = :trunk_id", <Self as DbView>::name());
+                tx.execute(&sql, named_params! { ":trunk_id": TrunkId(e.stream_id), ":trunk_name": to })?;
+            },
             // TBD
             _ => {}
         }
+        Ok(())
+    }
+
+    fn sql_create() -> &'static str {
+        r#"
+        CREATE TABLE IF NOT EXISTS network_view (
+            id              TEXT        PRIMARY KEY,
+            trunk_id        TEXT        NOT NULL,
+            trunk_name      TEXT        NOT NULL,
+            vlan            INTEGER     NOT NULL,
+            name            TEXT        NOT NULL COLLATE NOCASE,
+            kind            TEXT        NOT NULL,
+            deleted         INTEGER     NOT NULL DEFAULT 0
+        )
+        "#
     }
 
     fn select_fields() -> &'static str {
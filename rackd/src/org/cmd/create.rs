@@ -0,0 +1,232 @@
+use field_types::FieldName;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::{EntityStore, VersionConflict}, raft::ClusterError, query::traits::QueryRunner, Tx}, net::Ipv6Prefix, org::{model::{NewOrgToken, Org, OrgDomain, OrgEvent, OrgId, OrgName, OrgToken}, query::get_org::GetOrg, views::OrgView}, util::{actor::{Payload, Process}, models::Entity, traits::OptionExt}};
+
+/// Longest prefix an organization can use, every rack gets its own /56 out of it
+pub const MAX_PREFIX_LEN: u8 = 48;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, FieldName)]
+pub struct CreateOrg {
+    pub name: OrgName,
+    #[schema(value_type = String, example = "chomba.org")]
+    pub domain: OrgDomain,
+    #[schema(value_type = String, example = "2a0f:85c1:83f::/48")]
+    pub prefix: Ipv6Prefix,
+    /// Generated when the request is parsed, before the command is replicated
+    #[field_name(skip)]
+    #[schema(value_type = String, ignore)]
+    pub token: NewOrgToken
+}
+
+/// The token is only handed out here by the node the org was created on, racks present it to join the org
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct OrgCreated {
+    pub id: OrgId,
+    pub token: Option<OrgToken>
+}
+
+#[derive(Debug, Error)]
+pub enum CreateOrgError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Conflict(#[from] VersionConflict),
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
+    #[error("Rack already belongs to an organization")]
+    OrgAlreadyExists,
+    #[error("Prefix Length {0} is too large (expected <= {max})", max = MAX_PREFIX_LEN)]
+    PrefixTooLarge(u8)
+}
+
+impl Payload for CreateOrg {
+    type Ok = OrgCreated;
+    type Err = CreateOrgError;
+}
+
+impl CreateOrg {
+    fn exec(&self, twin: Option<OrgView>) -> Result<Org, CreateOrgError> {
+        twin.err_or(CreateOrgError::OrgAlreadyExists)?;
+        if self.prefix.len > MAX_PREFIX_LEN {
+            Err(CreateOrgError::PrefixTooLarge(self.prefix.len))?
+        }
+        let mut org = Org::default();
+        org.process(OrgEvent::Created {
+            id: OrgId::new(),
+            name: self.name.clone(),
+            domain: self.domain.clone(),
+            prefix: self.prefix,
+            token: self.token.hash()
+        });
+        Ok(org)
+    }
+}
+
+impl Process for CreateOrg {
+    type Actor = RackdCmdActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let twin = tx.run(GetOrg)?;
+        self.exec(twin).map(|mut org| {
+            tx.save(&mut org)?;
+            tx.commit()?;
+            Ok(OrgCreated { id: org.id, token: self.token.token() })
+        })?
+    }
+}
+
+pub mod casts {
+    use crate::{actors::cmd::RackdCmd, db::cmd::traits::EntityStoreError, org::cmd::OrgCmd, util::actor::Msg};
+    use super::{CreateOrg, CreateOrgError};
+
+    impl From<EntityStoreError> for CreateOrgError {
+        fn from(error: EntityStoreError) -> Self {
+            match error {
+                EntityStoreError::Db(e) => Self::Db(e),
+                EntityStoreError::Conflict(e) => Self::Conflict(e)
+            }
+        }
+    }
+
+    impl From<Msg<CreateOrg>> for RackdCmd {
+        fn from(cmd: Msg<CreateOrg>) -> Self {
+            Self::Org(OrgCmd::Create(cmd))
+        }
+    }
+}
+
+pub mod api {
+    use std::collections::HashMap;
+    use serde_json::Value;
+    use axum::{extract::{OriginalUri, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, net::Ipv6Prefix, org::model::{NewOrgToken, OrgDomain, OrgName}, util::api::{Error, Json, Response, TryFromJson}};
+    use super::{CreateOrg, CreateOrgError, CreateOrgFieldName};
+
+    #[utoipa::path(post, path = "/org/create", tag = "org",
+        request_body = CreateOrg,
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn create(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Json(cmd): Json<CreateOrg>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.exec(cmd).await
+            .map(|created| Response::ok(created, path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl TryFromJson for CreateOrg {
+        fn try_from(mut map: HashMap<String, Value>) -> Result<Self, Vec<Error>> {
+            Self::check_keys(&map, CreateOrg::as_field_name_array().map(|f| f.name()))?;
+            let name = map.remove(CreateOrgFieldName::Name.name()).unwrap_or_default();
+            let domain = map.remove(CreateOrgFieldName::Domain.name()).unwrap_or_default();
+            let prefix = map.remove(CreateOrgFieldName::Prefix.name()).unwrap_or_default();
+
+            match (OrgName::try_from(name), OrgDomain::try_from(domain), Ipv6Prefix::try_from(prefix)) {
+                (Ok(name), Ok(domain), Ok(prefix)) => Ok(Self { name, domain, prefix, token: NewOrgToken::new() }),
+                (r1, r2, r3) => {
+                    let e1 = r1.map_err(Error::from).err();
+                    let e2 = r2.map_err(Error::from).err();
+                    let e3 = r3.map_err(Error::from).err();
+
+                    let errors: Vec<Error> = [e1, e2, e3].into_iter().flatten().collect();
+                    Err(errors)
+                }
+            }
+        }
+    }
+
+    impl From<CreateOrgError> for Error {
+        fn from(error: CreateOrgError) -> Self {
+            let msg = error.to_string();
+            match error {
+                CreateOrgError::Db(_) => Error::new("CREATE_ORG_DB_ERROR", msg),
+                CreateOrgError::Conflict(_) => Error::new("CREATE_ORG_CONFLICT", msg),
+                CreateOrgError::Cluster(e) => Error::from(e),
+                CreateOrgError::OrgAlreadyExists => Error::new("CREATE_ORG_ALREADY_EXISTS", msg),
+                CreateOrgError::PrefixTooLarge(_) => Error::new("CREATE_ORG_PREFIX_TOO_LARGE", msg)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::{actors::system::Rackd, event::{model::EventFilter, query::get_events::GetEvents}, net::Ipv6Prefix, org::{cmd::create::{CreateOrg, CreateOrgError}, model::{NewOrgToken, OrgDomain, OrgName, OrgToken}}, util::models::{now, seeded, Id}};
+
+    fn chomba() -> CreateOrg {
+        CreateOrg {
+            name: OrgName::from_str("chomba").unwrap(),
+            domain: OrgDomain::from_str("chomba.org").unwrap(),
+            prefix: Ipv6Prefix::from_str("2a0f:85c1:83f::/48").unwrap(),
+            token: NewOrgToken::new()
+        }
+    }
+
+    #[tokio::test]
+    async fn cant_create_with_prefix_longer_than_48() {
+        let rackd = Rackd::mock().unwrap();
+        let cmd = CreateOrg {
+            name: OrgName::from_str("chomba").unwrap(),
+            domain: OrgDomain::from_str("chomba.org").unwrap(),
+            prefix: Ipv6Prefix::from_str("2a0f:85c1:83f:100::/56").unwrap(),
+            token: NewOrgToken::new()
+        };
+        assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, CreateOrgError::PrefixTooLarge(56))));
+    }
+
+    #[tokio::test]
+    async fn cant_create_more_than_one_org() {
+        let rackd = Rackd::mock().unwrap();
+        let cmd = CreateOrg {
+            name: OrgName::from_str("chomba").unwrap(),
+            domain: OrgDomain::from_str("chomba.org").unwrap(),
+            prefix: Ipv6Prefix::from_str("2a0f:85c1:83f::/48").unwrap(),
+            token: NewOrgToken::new()
+        };
+        rackd.exec(cmd).await.unwrap();
+        let cmd = CreateOrg {
+            name: OrgName::from_str("acme").unwrap(),
+            domain: OrgDomain::from_str("acme.com").unwrap(),
+            prefix: Ipv6Prefix::from_str("2a0f:85c1:840::/44").unwrap(),
+            token: NewOrgToken::new()
+        };
+        assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, CreateOrgError::OrgAlreadyExists)));
+    }
+
+    #[tokio::test]
+    async fn token_is_left_out_of_the_events() {
+        let rackd = Rackd::mock().unwrap();
+        let cmd = CreateOrg {
+            name: OrgName::from_str("chomba").unwrap(),
+            domain: OrgDomain::from_str("chomba.org").unwrap(),
+            prefix: Ipv6Prefix::from_str("2a0f:85c1:83f::/48").unwrap(),
+            token: NewOrgToken::new()
+        };
+        let created = rackd.exec(cmd).await.unwrap();
+        let events = rackd.query(GetEvents { after: 0, filter: EventFilter::default(), limit: 100 }).await.unwrap();
+        let events = serde_json::to_string(&events).unwrap();
+        assert!(!events.contains(&created.token.unwrap().to_string()));
+    }
+
+    #[tokio::test]
+    async fn token_cant_be_derived_from_the_org_or_event_ids() {
+        // Replicas derive every id from the log id, a token made out of them would be known to anyone reading the events
+        let (seed, timestamp) = (Id::new(), now());
+        assert_ne!(seeded(seed, timestamp, OrgToken::new), seeded(seed, timestamp, OrgToken::new));
+
+        // The log carries the hash, the nodes applying the command from it can't hand the token out
+        let cmd = chomba();
+        let token = cmd.token.clone().token().unwrap();
+        let log = serde_json::to_string(&cmd).unwrap();
+        assert!(!log.contains(&token.to_string()));
+        assert_eq!(serde_json::to_value(&cmd).unwrap()["token"], serde_json::to_value(token.hash()).unwrap());
+        let replicated: CreateOrg = serde_json::from_str(&log).unwrap();
+        let rackd = Rackd::mock().unwrap();
+        assert!(rackd.exec(replicated).await.unwrap().token.is_none());
+    }
+}
@@ -0,0 +1,178 @@
+use field_types::FieldName;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::{EntityStore, VersionConflict}, raft::ClusterError, query::traits::QueryRunner, Tx}, org::{model::{Org, OrgEvent, OrgToken}, query::get_org::GetOrg, views::OrgView}, rack::RackId, util::{actor::{Payload, Process}, models::Entity}};
+
+/// Processed by a rack of the organization when another rack presents the org's token to join it,
+/// the joining rack gets the org back so it can set up its own side (ie: `rack join <rack> --token <token>`)
+#[derive(Debug, Serialize, Deserialize, ToSchema, FieldName)]
+pub struct JoinOrg {
+    pub rack: RackId,
+    pub token: OrgToken
+}
+
+#[derive(Debug, Error)]
+pub enum JoinOrgError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Conflict(#[from] VersionConflict),
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
+    #[error("Rack doesn't belong to an organization")]
+    OrgNotFound,
+    #[error("Invalid organization token")]
+    InvalidToken,
+    #[error("Rack already joined the organization")]
+    AlreadyJoined
+}
+
+impl Payload for JoinOrg {
+    type Ok = OrgView;
+    type Err = JoinOrgError;
+}
+
+impl JoinOrg {
+    fn exec(&self, org: Option<Org>) -> Result<Org, JoinOrgError> {
+        let mut org = org.ok_or(JoinOrgError::OrgNotFound)?;
+        if org.token != self.token.hash() {
+            Err(JoinOrgError::InvalidToken)?
+        }
+        if org.racks.contains(&self.rack) {
+            Err(JoinOrgError::AlreadyJoined)?
+        }
+        org.process(OrgEvent::RackJoined { rack: self.rack });
+        Ok(org)
+    }
+}
+
+impl Process for JoinOrg {
+    type Actor = RackdCmdActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let org = match tx.run(GetOrg)? {
+            Some(view) => tx.load(view.id)?,
+            None => None
+        };
+        let mut org = self.exec(org)?;
+        tx.save(&mut org)?;
+        let view = tx.run(GetOrg)?.ok_or(JoinOrgError::OrgNotFound)?;
+        tx.commit()?;
+        Ok(view)
+    }
+}
+
+pub mod casts {
+    use crate::{actors::cmd::RackdCmd, db::cmd::traits::EntityStoreError, org::cmd::OrgCmd, util::actor::Msg};
+    use super::{JoinOrg, JoinOrgError};
+
+    impl From<EntityStoreError> for JoinOrgError {
+        fn from(error: EntityStoreError) -> Self {
+            match error {
+                EntityStoreError::Db(e) => Self::Db(e),
+                EntityStoreError::Conflict(e) => Self::Conflict(e)
+            }
+        }
+    }
+
+    impl From<Msg<JoinOrg>> for RackdCmd {
+        fn from(cmd: Msg<JoinOrg>) -> Self {
+            Self::Org(OrgCmd::Join(cmd))
+        }
+    }
+}
+
+pub mod api {
+    use std::collections::HashMap;
+    use serde_json::Value;
+    use axum::{extract::{OriginalUri, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, org::model::OrgToken, rack::RackId, util::api::{Error, Json, Response, TryFromJson}};
+    use super::{JoinOrg, JoinOrgError, JoinOrgFieldName};
+
+    #[utoipa::path(post, path = "/org/join", tag = "org",
+        request_body = JoinOrg,
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn join(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Json(cmd): Json<JoinOrg>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.exec(cmd).await
+            .map(|org| Response::ok(org, path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl TryFromJson for JoinOrg {
+        fn try_from(mut map: HashMap<String, Value>) -> Result<Self, Vec<Error>> {
+            Self::check_keys(&map, JoinOrg::as_field_name_array().map(|f| f.name()))?;
+            let rack = map.remove(JoinOrgFieldName::Rack.name()).unwrap_or_default();
+            let token = map.remove(JoinOrgFieldName::Token.name()).unwrap_or_default();
+
+            match (RackId::try_from(rack), OrgToken::try_from(token)) {
+                (Ok(rack), Ok(token)) => Ok(Self { rack, token }),
+                (r1, r2) => {
+                    let e1 = r1.map_err(Error::from).err();
+                    let e2 = r2.map_err(Error::from).err();
+
+                    let errors: Vec<Error> = [e1, e2].into_iter().flatten().collect();
+                    Err(errors)
+                }
+            }
+        }
+    }
+
+    impl From<JoinOrgError> for Error {
+        fn from(error: JoinOrgError) -> Self {
+            let msg = error.to_string();
+            match error {
+                JoinOrgError::Db(_) => Error::new("JOIN_ORG_DB_ERROR", msg),
+                JoinOrgError::Conflict(_) => Error::new("JOIN_ORG_CONFLICT", msg),
+                JoinOrgError::Cluster(e) => Error::from(e),
+                JoinOrgError::OrgNotFound => Error::new("JOIN_ORG_NOT_FOUND", msg),
+                JoinOrgError::InvalidToken => Error::new("JOIN_ORG_INVALID_TOKEN", msg),
+                JoinOrgError::AlreadyJoined => Error::new("JOIN_ORG_ALREADY_JOINED", msg)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::{actors::system::Rackd, net::Ipv6Prefix, org::{cmd::{create::CreateOrg, join::{JoinOrg, JoinOrgError}}, model::{NewOrgToken, OrgDomain, OrgName, OrgToken}}, rack::RackId};
+
+    #[tokio::test]
+    async fn cant_join_with_invalid_token() {
+        let rackd = Rackd::mock().unwrap();
+        let cmd = CreateOrg {
+            name: OrgName::from_str("chomba").unwrap(),
+            domain: OrgDomain::from_str("chomba.org").unwrap(),
+            prefix: Ipv6Prefix::from_str("2a0f:85c1:83f::/48").unwrap(),
+            token: NewOrgToken::new()
+        };
+        rackd.exec(cmd).await.unwrap();
+        let cmd = JoinOrg {
+            rack: RackId::new(),
+            token: OrgToken::new()
+        };
+        assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, JoinOrgError::InvalidToken)));
+    }
+
+    #[tokio::test]
+    async fn rack_can_join_only_once() {
+        let rackd = Rackd::mock().unwrap();
+        let cmd = CreateOrg {
+            name: OrgName::from_str("chomba").unwrap(),
+            domain: OrgDomain::from_str("chomba.org").unwrap(),
+            prefix: Ipv6Prefix::from_str("2a0f:85c1:83f::/48").unwrap(),
+            token: NewOrgToken::new()
+        };
+        let created = rackd.exec(cmd).await.unwrap();
+        let rack = RackId::new();
+        let org = rackd.exec(JoinOrg { rack, token: created.token.clone().unwrap() }).await.unwrap();
+        assert_eq!(org.racks, vec![rack]);
+        assert!(rackd.exec(JoinOrg { rack, token: created.token.unwrap() }).await.is_err_and(|e| matches!(e, JoinOrgError::AlreadyJoined)));
+    }
+}
@@ -0,0 +1,150 @@
+use field_types::FieldName;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::{EntityStore, VersionConflict}, raft::ClusterError, query::traits::QueryRunner, Tx}, org::{model::{Org, OrgEvent, OrgName}, query::get_org::GetOrg}, util::{actor::{Payload, Process}, models::Entity}};
+
+/// Renames the organization this rack belongs to
+#[derive(Debug, Serialize, Deserialize, ToSchema, FieldName)]
+pub struct RenameOrg {
+    pub name: OrgName
+}
+
+#[derive(Debug, Error)]
+pub enum RenameOrgError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Conflict(#[from] VersionConflict),
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
+    #[error("Rack doesn't belong to an organization")]
+    OrgNotFound
+}
+
+impl Payload for RenameOrg {
+    type Ok = ();
+    type Err = RenameOrgError;
+}
+
+impl RenameOrg {
+    fn exec(&self, org: Option<Org>) -> Result<Org, RenameOrgError> {
+        let mut org = org.ok_or(RenameOrgError::OrgNotFound)?;
+        org.process(OrgEvent::Renamed { from: org.name.clone(), to: self.name.clone() });
+        Ok(org)
+    }
+}
+
+impl Process for RenameOrg {
+    type Actor = RackdCmdActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let org = match tx.run(GetOrg)? {
+            Some(view) => tx.load(view.id)?,
+            None => None
+        };
+        self.exec(org).map(|mut org| {
+            tx.save(&mut org)?;
+            tx.commit()?;
+            Ok(())
+        })?
+    }
+}
+
+pub mod casts {
+    use crate::{actors::cmd::RackdCmd, db::cmd::traits::EntityStoreError, org::cmd::OrgCmd, util::actor::Msg};
+    use super::{RenameOrg, RenameOrgError};
+
+    impl From<EntityStoreError> for RenameOrgError {
+        fn from(error: EntityStoreError) -> Self {
+            match error {
+                EntityStoreError::Db(e) => Self::Db(e),
+                EntityStoreError::Conflict(e) => Self::Conflict(e)
+            }
+        }
+    }
+
+    impl From<Msg<RenameOrg>> for RackdCmd {
+        fn from(cmd: Msg<RenameOrg>) -> Self {
+            Self::Org(OrgCmd::Rename(cmd))
+        }
+    }
+}
+
+pub mod api {
+    use std::collections::HashMap;
+    use serde_json::Value;
+    use axum::{extract::{OriginalUri, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, org::model::OrgName, util::api::{Error, Json, Response, TryFromJson}};
+    use super::{RenameOrg, RenameOrgError, RenameOrgFieldName};
+
+    #[utoipa::path(patch, path = "/org/rename", tag = "org",
+        request_body = RenameOrg,
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn rename(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Json(cmd): Json<RenameOrg>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.exec(cmd).await
+            .map(|_| Response::ok((), path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl TryFromJson for RenameOrg {
+        fn try_from(mut map: HashMap<String, Value>) -> Result<Self, Vec<Error>> {
+            Self::check_keys(&map, RenameOrg::as_field_name_array().map(|f| f.name()))?;
+            let name = map.remove(RenameOrgFieldName::Name.name()).unwrap_or_default();
+            match OrgName::try_from(name) {
+                Ok(name) => Ok(Self { name }),
+                Err(e) => Err(vec![Error::from(e)])
+            }
+        }
+    }
+
+    impl From<RenameOrgError> for Error {
+        fn from(error: RenameOrgError) -> Self {
+            let msg = error.to_string();
+            match error {
+                RenameOrgError::Db(_) => Error::new("RENAME_ORG_DB_ERROR", msg),
+                RenameOrgError::Conflict(_) => Error::new("RENAME_ORG_CONFLICT", msg),
+                RenameOrgError::Cluster(e) => Error::from(e),
+                RenameOrgError::OrgNotFound => Error::new("RENAME_ORG_NOT_FOUND", msg)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::{actors::system::Rackd, net::Ipv6Prefix, org::{cmd::{create::CreateOrg, rename::{RenameOrg, RenameOrgError}}, model::{NewOrgToken, OrgDomain, OrgName}, query::get_org::GetOrg}};
+
+    #[tokio::test]
+    async fn cant_rename_if_rack_has_no_org() {
+        let rackd = Rackd::mock().unwrap();
+        let cmd = RenameOrg {
+            name: OrgName::from_str("acme").unwrap()
+        };
+        assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, RenameOrgError::OrgNotFound)));
+    }
+
+    #[tokio::test]
+    async fn can_rename_org() {
+        let rackd = Rackd::mock().unwrap();
+        let cmd = CreateOrg {
+            name: OrgName::from_str("chomba").unwrap(),
+            domain: OrgDomain::from_str("chomba.org").unwrap(),
+            prefix: Ipv6Prefix::from_str("2a0f:85c1:83f::/48").unwrap(),
+            token: NewOrgToken::new()
+        };
+        rackd.exec(cmd).await.unwrap();
+        let cmd = RenameOrg {
+            name: OrgName::from_str("acme").unwrap()
+        };
+        rackd.exec(cmd).await.unwrap();
+        let org = rackd.query(GetOrg).await.unwrap();
+        assert_eq!(org.name, OrgName::from_str("acme").unwrap());
+    }
+}
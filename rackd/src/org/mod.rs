@@ -1,2 +1,4 @@
 pub mod model;
-pub mod cmd;
\ No newline at end of file
+pub mod cmd;
+pub mod views;
+pub mod query;
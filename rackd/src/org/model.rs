@@ -1,4 +1,4 @@
 pub mod entity;
 pub mod values;
-pub use entity::*;
+pub use entity::{Org, OrgEvent};
 pub use values::*;
\ No newline at end of file
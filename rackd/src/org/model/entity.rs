@@ -1,49 +1,85 @@
 use serde::{Deserialize, Serialize};
-use crate::{net::Ipv6Prefix, rack::RackId, util::models::{Entity, Metadata}};
-use super::{Asn, OrgDomain, OrgId, OrgName};
+use crate::{net::Ipv6Prefix, rack::RackId, util::models::{Entity, Id, Metadata}};
+use super::{Asn, OrgDomain, OrgId, OrgName, OrgTokenHash};
 
 /// A struct that represents a **Company/Organization** that spans across *multiple geographical Sites*.
-/// It has a one-to-many relationship with the **Site** struct and contains the following fields: 
+/// It has a one-to-many relationship with the **Site** struct and contains the following fields:
 /// - **name**: The organization's display name.
 /// - **prefix**: IPv6 GUA Prefix used by the Organization. It needs to be /48 or shorter (e.g. /40, /44)
 /// - **domain**: Root Domain used by the Organization.
 /// - **asn**: The organization's AS Number.
-#[derive(Debug, Default)]
+/// - **token**: Hash of the token shared by every rack in the organization, a rack presents the token to join.
+/// - **racks**: Racks that joined the organization.
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Org {
     pub meta: Metadata,
-    pub id: OrgId, 
+    pub id: OrgId,
     pub asn: Asn,
     pub name: OrgName,
-    pub domain: OrgDomain, 
+    pub domain: OrgDomain,
     pub prefix: Ipv6Prefix, // /48 GUA Prefix
+    pub token: OrgTokenHash,
+    pub racks: Vec<RackId>
 }
 
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum OrgEvent {
-    Created { id: RackId, name: OrgName, domain: OrgDomain, prefix: Ipv6Prefix },
-    Renamed { name: OrgName }
+    Created { id: OrgId, name: OrgName, domain: OrgDomain, prefix: Ipv6Prefix, token: OrgTokenHash },
+    Renamed { from: OrgName, to: OrgName },
+    RackJoined { rack: RackId }
     // DomainUpdated
     // PrefixUpdated
 }
 
-// impl Entity for Org {
-//     type E = OrgEvent;
-
-//     fn apply(&mut self, e: &Self::Event) {
-//         match e {
-//             OrgEvent::Created { id, name, domain, prefix } => {
-//                 self.id = *id;
-//                 self.name = name.clone();
-//                 self.domain = domain.clone();
-//                 self.prefix = *prefix;
-//             },
-//             OrgEvent::Renamed { name } => {
-//                 self.name = name.clone();
-//             }
-//         }
-//     }
-
-//     fn id(&self) -> Id { self.id }
-//     fn metadata(&mut self) -> &mut Metadata { &mut self.meta }
-// }
+impl Entity for Org {
+    type E = OrgEvent;
+
+    fn id(&self) -> Id {
+        self.id.into()
+    }
+
+    fn metadata(&mut self) -> &mut Metadata {
+        &mut self.meta
+    }
+
+    fn apply(&mut self, e: &Self::E) {
+        match e {
+            OrgEvent::Created { id, name, domain, prefix, token } => {
+                self.id = *id;
+                self.name = name.clone();
+                self.domain = domain.clone();
+                self.prefix = *prefix;
+                self.token = token.clone();
+            },
+            OrgEvent::Renamed { to, .. } => {
+                self.name = to.clone();
+            },
+            OrgEvent::RackJoined { rack } => {
+                self.racks.push(*rack);
+            }
+        }
+    }
+}
+
+pub mod casts {
+    use crate::util::models::{casts::UnexpectedEvent, EventData};
+    use super::OrgEvent;
+
+    impl From<OrgEvent> for EventData {
+        fn from(e: OrgEvent) -> Self {
+            Self::Org(e)
+        }
+    }
+
+    impl TryFrom<EventData> for OrgEvent {
+        type Error = UnexpectedEvent;
+
+        fn try_from(e: EventData) -> Result<Self, Self::Error> {
+            match e {
+                EventData::Org(e) => Ok(e),
+                e => Err(UnexpectedEvent(e))
+            }
+        }
+    }
+}
@@ -1,17 +1,26 @@
-use std::fmt::Display;
-use serde::{Deserialize, Serialize};
-use uuid::Uuid;
+use std::{fmt::Display, ops::RangeInclusive};
+use chacha20poly1305::aead::{rand_core::RngCore, OsRng};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use utoipa::ToSchema;
+use crate::util::models::Id;
 
-#[derive(Debug, Default)]
-pub struct OrgId(pub Uuid);
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, ToSchema)]
+pub struct OrgId(pub Id);
 
 impl OrgId {
     pub fn new() -> Self {
-        Self(Uuid::new_v4())
+        Self(Id::new())
     }
 }
 
-#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq)]
+impl Display for OrgId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "org with id: {}", self.0)
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
 pub struct OrgName(String);
 
 impl Display for OrgName {
@@ -20,7 +29,7 @@ impl Display for OrgName {
     }
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub struct OrgDomain {
     pub name: String,
     pub tld: String
@@ -32,33 +41,362 @@ impl Display for OrgDomain {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Copy, Clone, Default)]
-pub struct Asn(u16);
+/// Shared secret a rack presents to join the organization
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct OrgToken(String);
+
+impl OrgToken {
+    /// 32 bytes out of the OS RNG (hex encoded), nothing stored in the rack can be used to guess it
+    pub fn new() -> Self {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        Self(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+    }
+
+    pub fn hash(&self) -> OrgTokenHash {
+        OrgTokenHash(format!("{:x}", Sha256::digest(self.0.as_bytes())))
+    }
+}
+
+/// SHA-256 of the org's token (hex encoded), the token itself is never stored nor replicated
+/// since events are published as they are (ie: `GET /v1/events`)
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OrgTokenHash(String);
+
+/// Token of an org being created, it's generated by the node the command is sent to and only its hash
+/// is serialized (ie: into the raft log) so the nodes applying the command from the log never see it
+#[derive(Debug, Clone)]
+pub enum NewOrgToken {
+    Generated(OrgToken),
+    Replicated(OrgTokenHash)
+}
+
+impl NewOrgToken {
+    pub fn new() -> Self {
+        Self::Generated(OrgToken::new())
+    }
+
+    pub fn hash(&self) -> OrgTokenHash {
+        match self {
+            Self::Generated(token) => token.hash(),
+            Self::Replicated(hash) => hash.clone()
+        }
+    }
+
+    /// Only the node that generated the token can hand it out
+    pub fn token(self) -> Option<OrgToken> {
+        match self {
+            Self::Generated(token) => Some(token),
+            Self::Replicated(_) => None
+        }
+    }
+}
+
+impl Default for NewOrgToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Serialize for NewOrgToken {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        self.hash().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for NewOrgToken {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        Ok(Self::Replicated(OrgTokenHash::deserialize(deserializer)?))
+    }
+}
+
+impl Display for OrgToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// 4-byte AS Number (RFC6793), racks are expected to use private ones (ie: AS4200000001)
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, Default, PartialEq, Eq, ToSchema)]
+#[schema(value_type = u32, example = 4200000001_u32)]
+pub struct Asn(u32);
+
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq, ToSchema)]
+pub enum AsnKind {
+    Public,
+    /// 64512 - 65534 (RFC6996)
+    Private,
+    /// 4200000000 - 4294967294 (RFC6996)
+    PrivateExt,
+    /// AS0, AS_TRANS, Documentation and Last ASNs (RFC7607, RFC6793, RFC5398, RFC7300)
+    Reserved
+}
+
+impl Asn {
+    pub const PRIVATE: RangeInclusive<u32> = 64512..=65534;
+    pub const PRIVATE_EXT: RangeInclusive<u32> = 4200000000..=4294967294;
+
+    pub fn kind(&self) -> AsnKind {
+        match self.0 {
+            0 | 23456 | 64496..=64511 | 65535..=131071 | u32::MAX => AsnKind::Reserved,
+            asn if Self::PRIVATE.contains(&asn) => AsnKind::Private,
+            asn if Self::PRIVATE_EXT.contains(&asn) => AsnKind::PrivateExt,
+            _ => AsnKind::Public
+        }
+    }
+
+    pub fn is_private(&self) -> bool {
+        matches!(self.kind(), AsnKind::Private | AsnKind::PrivateExt)
+    }
+}
+
+impl Display for Asn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "AS{}", self.0)
+    }
+}
 
 pub mod casts {
-    use super::Asn;
+    use std::str::FromStr;
+    use serde_json::Value;
+    use thiserror::Error;
+    use crate::util::models::casts::{IdError, InvalidChars};
+    use super::*;
+
+    impl From<OrgId> for Id {
+        fn from(value: OrgId) -> Self {
+            value.0
+        }
+    }
+
+    #[derive(Debug, Error)]
+    #[error("OrgIdError: {:?}", .0)]
+    pub struct OrgIdError(#[from]IdError);
+
+    impl TryFrom<Value> for OrgId {
+        type Error = OrgIdError;
+
+        fn try_from(value: Value) -> Result<Self, Self::Error> {
+            Ok(Self(Id::try_from(value)?))
+        }
+    }
+
+    impl FromStr for OrgName {
+        type Err = InvalidChars;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match InvalidChars::from(s, &[]) {
+                Some(error) => Err(error),
+                None => Ok(OrgName(String::from(s)))
+            }
+        }
+    }
+
+    #[derive(Debug, Error)]
+    pub enum OrgNameError {
+        #[error("Value is not a String [{}]", .0)]
+        InvalidType(Value),
+        #[error("Value contains the following invalid characters: {} [{}]", .0.chars.join(","), .0.value)]
+        InvalidCharacters(InvalidChars),
+        #[error("No value provided")]
+        MissingValue
+    }
+
+    impl TryFrom<Value> for OrgName {
+        type Error = OrgNameError;
+
+        fn try_from(value: Value) -> Result<Self, Self::Error> {
+            match value {
+                Value::String(value) => Ok(OrgName::from_str(&value).map_err(OrgNameError::InvalidCharacters)?),
+                Value::Null => Err(OrgNameError::MissingValue),
+                _ => Err(OrgNameError::InvalidType(value))
+            }
+        }
+    }
 
-    #[derive(Debug)]
+    #[derive(Debug, Error)]
+    #[error("Invalid label [{}], labels are made of letters, digits and inner hyphens", .0)]
+    pub struct InvalidDomain(pub String);
+
+    impl FromStr for OrgDomain {
+        type Err = InvalidDomain;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let valid = |label: &str| !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+            if let Some(label) = s.split('.').find(|label| !valid(label)) {
+                return Err(InvalidDomain(String::from(label)));
+            }
+            match s.rsplit_once('.') {
+                Some((name, tld)) => Ok(OrgDomain { name: name.to_lowercase(), tld: tld.to_lowercase() }),
+                None => Err(InvalidDomain(String::from(s)))
+            }
+        }
+    }
+
+    #[derive(Debug, Error)]
+    pub enum OrgDomainError {
+        #[error("Value is not a String [{}]", .0)]
+        InvalidType(Value),
+        #[error("Value is not a valid domain [{}]: {}", .0, .1)]
+        InvalidFormat(String, InvalidDomain),
+        #[error("No value provided")]
+        MissingValue
+    }
+
+    impl TryFrom<Value> for OrgDomain {
+        type Error = OrgDomainError;
+
+        fn try_from(value: Value) -> Result<Self, Self::Error> {
+            match value {
+                Value::String(s) => OrgDomain::from_str(&s).map_err(|e| OrgDomainError::InvalidFormat(s, e)),
+                Value::Null => Err(OrgDomainError::MissingValue),
+                _ => Err(OrgDomainError::InvalidType(value))
+            }
+        }
+    }
+
+    #[derive(Debug, Error)]
+    pub enum OrgTokenError {
+        #[error("Value is not a String [{}]", .0)]
+        InvalidType(Value),
+        #[error("No value provided")]
+        MissingValue
+    }
+
+    impl TryFrom<Value> for OrgToken {
+        type Error = OrgTokenError;
+
+        fn try_from(value: Value) -> Result<Self, Self::Error> {
+            match value {
+                Value::String(s) => Ok(OrgToken(s)),
+                Value::Null => Err(OrgTokenError::MissingValue),
+                _ => Err(OrgTokenError::InvalidType(value))
+            }
+        }
+    }
+
+    #[derive(Debug, Error)]
     pub enum AsnError {
-        InvalidRange
+        #[error("Value is not a Number [{}]", .0)]
+        InvalidType(Value),
+        #[error("Value is out of range [{}] (expected 1 - {})", .0, u32::MAX - 1)]
+        InvalidRange(u64),
+        #[error("{} is reserved", Asn(*.0))]
+        Reserved(u32),
+        #[error("No value provided")]
+        MissingValue
     }
 
     impl TryFrom<u32> for Asn {
         type Error = AsnError;
+
         fn try_from(value: u32) -> Result<Self, Self::Error> {
-            // TBD
-            Ok(Asn(value as u16))
+            let asn = Asn(value);
+            match asn.kind() {
+                AsnKind::Reserved => Err(AsnError::Reserved(value)),
+                _ => Ok(asn)
+            }
+        }
+    }
+
+    impl TryFrom<Value> for Asn {
+        type Error = AsnError;
+
+        fn try_from(value: Value) -> Result<Self, Self::Error> {
+            match value {
+                Value::Number(ref n) => match n.as_u64() {
+                    Some(n) => Asn::try_from(u32::try_from(n).map_err(|_| AsnError::InvalidRange(n))?),
+                    None => Err(AsnError::InvalidType(value))
+                },
+                Value::Null => Err(AsnError::MissingValue),
+                _ => Err(AsnError::InvalidType(value))
+            }
+        }
+    }
+}
+
+pub mod api {
+    use crate::util::api::Error;
+    use super::casts::{AsnError, OrgDomainError, OrgIdError, OrgNameError, OrgTokenError};
+
+    impl From<OrgIdError> for Error {
+        fn from(error: OrgIdError) -> Self {
+            Error::new("ORG_ID_ERROR", error.to_string())
+        }
+    }
+
+    impl From<OrgNameError> for Error {
+        fn from(error: OrgNameError) -> Self {
+            Error::new("ORG_NAME_ERROR", error.to_string())
+        }
+    }
+
+    impl From<OrgDomainError> for Error {
+        fn from(error: OrgDomainError) -> Self {
+            Error::new("ORG_DOMAIN_ERROR", error.to_string())
+        }
+    }
+
+    impl From<OrgTokenError> for Error {
+        fn from(error: OrgTokenError) -> Self {
+            Error::new("ORG_TOKEN_ERROR", error.to_string())
+        }
+    }
+
+    impl From<AsnError> for Error {
+        fn from(error: AsnError) -> Self {
+            Error::new("ASN_ERROR", error.to_string())
         }
     }
 }
 
 pub mod sqlite {
+    use std::str::FromStr;
     use rusqlite::{Result, types::{FromSql, FromSqlError, FromSqlResult, ValueRef, ToSqlOutput}, ToSql};
     use super::*;
 
+    impl ToSql for OrgId {
+        fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+            self.0.to_sql()
+        }
+    }
+
+    impl FromSql for OrgId {
+        fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+            Ok(Self(Id::column_result(value)?))
+        }
+    }
+
+    impl ToSql for OrgName {
+        fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+            Ok(self.0.as_str().into())
+        }
+    }
+
+    impl FromSql for OrgName {
+        fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+            Ok(OrgName(String::from(value.as_str()?)))
+        }
+    }
+
+    impl ToSql for OrgDomain {
+        fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+            Ok(self.to_string().into())
+        }
+    }
+
+    impl FromSql for OrgDomain {
+        fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+            OrgDomain::from_str(value.as_str()?).map_err(|e| FromSqlError::Other(Box::new(e)))
+        }
+    }
+
     impl FromSql for Asn {
         fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
-            let asn = u16::try_from(value.as_i64()?).map_err(|e| FromSqlError::Other(Box::new(e)))?;
+            let asn = u32::try_from(value.as_i64()?).map_err(|e| FromSqlError::Other(Box::new(e)))?;
             Ok(Asn(asn))
         }
     }
@@ -69,3 +407,34 @@ pub mod sqlite {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::Connection;
+    use serde_json::json;
+    use super::{casts::AsnError, *};
+
+    #[test]
+    fn classifies_asn_ranges() {
+        assert_eq!(Asn::try_from(15169).unwrap().kind(), AsnKind::Public);
+        assert_eq!(Asn::try_from(64512).unwrap().kind(), AsnKind::Private);
+        assert_eq!(Asn::try_from(4200000001).unwrap().kind(), AsnKind::PrivateExt);
+        assert_eq!(Asn::try_from(396982).unwrap().kind(), AsnKind::Public);
+        for reserved in [0, 23456, 64496, 65535, 65536, 131071, u32::MAX] {
+            assert!(Asn::try_from(reserved).is_err_and(|e| matches!(e, AsnError::Reserved(asn) if asn == reserved)));
+        }
+        assert!(Asn::try_from(json!(4294967296_u64)).is_err_and(|e| matches!(e, AsnError::InvalidRange(_))));
+        assert!(Asn::try_from(json!("AS4200000001")).is_err_and(|e| matches!(e, AsnError::InvalidType(_))));
+    }
+
+    #[test]
+    fn asn_round_trips_through_sqlite_and_json() {
+        let asn = Asn::try_from(4294967294).unwrap();
+        let conn = Connection::open_in_memory().unwrap();
+        let stored: Asn = conn.query_row("SELECT ?1", [asn], |row| row.get(0)).unwrap();
+        assert_eq!(stored, asn);
+        let json = serde_json::to_value(asn).unwrap();
+        assert_eq!(json, json!(4294967294_u32));
+        assert_eq!(serde_json::from_value::<Asn>(json).unwrap(), asn);
+    }
+}
@@ -0,0 +1,7 @@
+use crate::util::actor::Msg;
+pub mod get_org;
+
+#[derive(Debug)]
+pub enum OrgQuery {
+    GetOrg(Msg<get_org::GetOrg>)
+}
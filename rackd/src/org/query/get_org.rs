@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use crate::{actors::query::RackdQueryActor, db::{query::traits::{DbQuery, DbView}, Tx}, org::views::OrgView, util::actor::{Payload, Process}};
+
+/// The organization this rack belongs to
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetOrg;
+
+#[derive(Debug, Error)]
+pub enum GetOrgError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error("Rack doesn't belong to an organization")]
+    OrgNotFound
+}
+
+impl Payload for GetOrg {
+    type Ok = OrgView;
+    type Err = GetOrgError;
+}
+
+impl DbQuery for GetOrg {
+    type Ok = Option<OrgView>;
+
+    fn run(&self, tx: &rusqlite::Transaction) -> Result<Self::Ok, rusqlite::Error> {
+        let sql = format!("{} WHERE deleted = 0 LIMIT 1", OrgView::sql_select());
+        match tx.query_row(&sql, (), <OrgView as DbView>::try_from) {
+            Ok(org) => Ok(Some(org)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e)
+        }
+    }
+}
+
+impl Process for GetOrg {
+    type Actor = RackdQueryActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let org = self.run(&tx)?;
+        org.ok_or(GetOrgError::OrgNotFound)
+    }
+}
+
+pub mod casts {
+    use crate::{actors::query::RackdQuery, org::query::OrgQuery, util::actor::Msg};
+    use super::GetOrg;
+
+    impl From<Msg<GetOrg>> for RackdQuery {
+        fn from(query: Msg<GetOrg>) -> Self {
+            Self::Org(OrgQuery::GetOrg(query))
+        }
+    }
+}
+
+pub mod api {
+    use axum::{extract::{OriginalUri, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, util::api::{Error, Response}};
+    use super::{GetOrg, GetOrgError};
+
+    #[utoipa::path(get, path = "/org", tag = "org",
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn get_org(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.query(GetOrg).await
+            .map(|org| Response::ok(org, path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl From<GetOrgError> for Error {
+        fn from(error: GetOrgError) -> Self {
+            let msg = error.to_string();
+            match error {
+                GetOrgError::Db(_) => Error::new("GET_ORG_DB_ERROR", msg),
+                GetOrgError::OrgNotFound => Error::new("GET_ORG_NOT_FOUND", msg)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::{actors::system::Rackd, net::Ipv6Prefix, org::{cmd::create::CreateOrg, model::{NewOrgToken, OrgDomain, OrgName}, query::get_org::{GetOrg, GetOrgError}}};
+
+    #[tokio::test]
+    async fn cant_get_org_if_rack_hasnt_joined_one() {
+        let rackd = Rackd::mock().unwrap();
+        assert!(rackd.query(GetOrg).await.is_err_and(|e| matches!(e, GetOrgError::OrgNotFound)));
+    }
+
+    #[tokio::test]
+    async fn can_get_created_org() {
+        let rackd = Rackd::mock().unwrap();
+        let cmd = CreateOrg {
+            name: OrgName::from_str("chomba").unwrap(),
+            domain: OrgDomain::from_str("chomba.org").unwrap(),
+            prefix: Ipv6Prefix::from_str("2a0f:85c1:83f::/48").unwrap(),
+            token: NewOrgToken::new()
+        };
+        let created = rackd.exec(cmd).await.unwrap();
+        let org = rackd.query(GetOrg).await.unwrap();
+        assert_eq!(org.id.0, created.id.0);
+        assert_eq!(org.domain.to_string(), "chomba.org");
+        assert!(org.racks.is_empty());
+    }
+}
@@ -0,0 +1,68 @@
+use rusqlite::{named_params, params, types::Type, Row, Transaction};
+use serde::{Deserialize, Serialize};
+use crate::{db::query::traits::DbView, net::Ipv6Prefix, rack::RackId, util::models::{Event, EventData}};
+use super::model::{OrgDomain, OrgEvent, OrgId, OrgName};
+
+/// The org's token is left out, it's only handed out when the org is created
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct OrgView {
+    pub id: OrgId,
+    pub name: OrgName,
+    pub domain: OrgDomain,
+    pub prefix: Ipv6Prefix,
+    pub racks: Vec<RackId>
+}
+
+impl DbView for OrgView {
+    fn name() -> &'static str {
+        "org_view"
+    }
+
+    fn update(tx: &Transaction, e: &Event) -> Result<(), rusqlite::Error> {
+        if let EventData::Org(data) = &e.data {
+            match data {
+                OrgEvent::Created { name, domain, prefix, .. } => {
+                    let sql = format!("INSERT INTO {} (id, name, domain, prefix) VALUES (?1, ?2, ?3, ?4)", Self::name());
+                    tx.execute(&sql, params![e.stream_id, name, domain, prefix])?;
+                },
+                OrgEvent::Renamed { to, .. } => {
+                    let sql = format!("UPDATE {} SET name = :name WHERE id = :id", Self::name());
+                    tx.execute(&sql, named_params! { ":id": e.stream_id, ":name": to })?;
+                },
+                OrgEvent::RackJoined { rack } => {
+                    let sql = format!("UPDATE {} SET racks = json_insert(racks, '$[#]', :rack) WHERE id = :id", Self::name());
+                    tx.execute(&sql, named_params! { ":id": e.stream_id, ":rack": rack })?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn sql_create() -> &'static str {
+        r#"
+        CREATE TABLE IF NOT EXISTS org_view (
+            id              TEXT        PRIMARY KEY,
+            name            TEXT        NOT NULL,
+            domain          TEXT        NOT NULL,
+            prefix          TEXT        NOT NULL,
+            racks           TEXT        NOT NULL DEFAULT '[]',
+            deleted         INTEGER     NOT NULL DEFAULT 0
+        )
+        "#
+    }
+
+    fn select_fields() -> &'static str {
+        "id, name, domain, prefix, racks"
+    }
+
+    fn try_from(row: &Row) -> Result<Self, rusqlite::Error> {
+        let racks: String = row.get(4)?;
+        Ok(Self {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            domain: row.get(2)?,
+            prefix: row.get(3)?,
+            racks: serde_json::from_str(&racks).map_err(|e| rusqlite::Error::FromSqlConversionFailure(4, Type::Text, Box::new(e)))?
+        })
+    }
+}
@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+use crate::util::actor::Msg;
+pub mod init;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum RackCmd {
+    Init(Msg<init::InitRack>)
+}
@@ -0,0 +1,190 @@
+use field_types::FieldName;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::{EntityStore, VersionConflict}, raft::ClusterError, query::traits::QueryRunner, Tx}, net::Ipv6Prefix, org::model::Asn, rack::{query::get_rack::GetRack, views::RackView, Rack, RackEvent, RackId, RackName}, trunk::model::{Trunk, TrunkEvent, TrunkId, TrunkName}, util::{actor::{Payload, Process}, models::Entity, traits::OptionExt}};
+
+/// Every rack gets a /56 out of its organization's prefix
+pub const PREFIX_LEN: u8 = 56;
+
+/// Bootstraps the rack along with its first trunk (ie: `rack new lim15109 --asn 4200000001 --prefix 2a0f:85c1:83f:100::/56 --trunk trunk1`)
+#[derive(Debug, Serialize, Deserialize, ToSchema, FieldName)]
+pub struct InitRack {
+    pub asn: Asn,
+    pub name: RackName,
+    #[schema(value_type = String, example = "2a0f:85c1:83f:100::/56")]
+    pub prefix: Ipv6Prefix,
+    pub trunk: TrunkName
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct RackInitialized {
+    pub rack: RackId,
+    pub trunk: TrunkId
+}
+
+#[derive(Debug, Error)]
+pub enum InitRackError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Conflict(#[from] VersionConflict),
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
+    #[error("Rack has already been initialized")]
+    AlreadyInitialized,
+    #[error("Invalid Prefix Length {0} (expected {len})", len = PREFIX_LEN)]
+    InvalidPrefixLen(u8)
+}
+
+impl Payload for InitRack {
+    type Ok = RackInitialized;
+    type Err = InitRackError;
+}
+
+impl InitRack {
+    fn exec(&self, twin: Option<RackView>) -> Result<(Rack, Trunk), InitRackError> {
+        twin.err_or(InitRackError::AlreadyInitialized)?;
+        if self.prefix.len != PREFIX_LEN {
+            Err(InitRackError::InvalidPrefixLen(self.prefix.len))?
+        }
+        let mut rack = Rack::default();
+        rack.process(RackEvent::Initialized {
+            id: RackId::new(),
+            asn: self.asn,
+            name: self.name.clone(),
+            prefix: self.prefix
+        });
+        let mut trunk = Trunk::default();
+        trunk.process(TrunkEvent::Created {
+            rack: rack.clone(),
+            id: TrunkId::new(),
+            name: self.trunk.clone()
+        });
+        Ok((rack, trunk))
+    }
+}
+
+impl Process for InitRack {
+    type Actor = RackdCmdActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let twin = tx.run(GetRack)?;
+        self.exec(twin).map(|(mut rack, mut trunk)| {
+            tx.save(&mut rack)?;
+            tx.save(&mut trunk)?;
+            tx.commit()?;
+            Ok(RackInitialized { rack: rack.id, trunk: trunk.id })
+        })?
+    }
+}
+
+pub mod casts {
+    use crate::{actors::cmd::RackdCmd, db::cmd::traits::EntityStoreError, rack::cmd::RackCmd, util::actor::Msg};
+    use super::{InitRack, InitRackError};
+
+    impl From<EntityStoreError> for InitRackError {
+        fn from(error: EntityStoreError) -> Self {
+            match error {
+                EntityStoreError::Db(e) => Self::Db(e),
+                EntityStoreError::Conflict(e) => Self::Conflict(e)
+            }
+        }
+    }
+
+    impl From<Msg<InitRack>> for RackdCmd {
+        fn from(cmd: Msg<InitRack>) -> Self {
+            Self::Rack(RackCmd::Init(cmd))
+        }
+    }
+}
+
+pub mod api {
+    use std::collections::HashMap;
+    use serde_json::Value;
+    use axum::{extract::{OriginalUri, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, net::Ipv6Prefix, org::model::Asn, rack::RackName, trunk::model::TrunkName, util::api::{Error, Json, Response, TryFromJson}};
+    use super::{InitRack, InitRackError, InitRackFieldName};
+
+    #[utoipa::path(post, path = "/rack/init", tag = "rack",
+        request_body = InitRack,
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn init(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Json(cmd): Json<InitRack>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.exec(cmd).await
+            .map(|initialized| Response::ok(initialized, path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl TryFromJson for InitRack {
+        fn try_from(mut map: HashMap<String, Value>) -> Result<Self, Vec<Error>> {
+            Self::check_keys(&map, InitRack::as_field_name_array().map(|f| f.name()))?;
+            let asn = map.remove(InitRackFieldName::Asn.name()).unwrap_or_default();
+            let name = map.remove(InitRackFieldName::Name.name()).unwrap_or_default();
+            let prefix = map.remove(InitRackFieldName::Prefix.name()).unwrap_or_default();
+            let trunk = map.remove(InitRackFieldName::Trunk.name()).unwrap_or_default();
+
+            match (Asn::try_from(asn), RackName::try_from(name), Ipv6Prefix::try_from(prefix), TrunkName::try_from(trunk)) {
+                (Ok(asn), Ok(name), Ok(prefix), Ok(trunk)) => Ok(Self { asn, name, prefix, trunk }),
+                (r1, r2, r3, r4) => {
+                    let e1 = r1.map_err(Error::from).err();
+                    let e2 = r2.map_err(Error::from).err();
+                    let e3 = r3.map_err(Error::from).err();
+                    let e4 = r4.map_err(Error::from).err();
+
+                    let errors: Vec<Error> = [e1, e2, e3, e4].into_iter().flatten().collect();
+                    Err(errors)
+                }
+            }
+        }
+    }
+
+    impl From<InitRackError> for Error {
+        fn from(error: InitRackError) -> Self {
+            let msg = error.to_string();
+            match error {
+                InitRackError::Db(_) => Error::new("INIT_RACK_DB_ERROR", msg),
+                InitRackError::Conflict(_) => Error::new("INIT_RACK_CONFLICT", msg),
+                InitRackError::Cluster(e) => Error::from(e),
+                InitRackError::AlreadyInitialized => Error::new("INIT_RACK_ALREADY_INITIALIZED", msg),
+                InitRackError::InvalidPrefixLen(_) => Error::new("INIT_RACK_INVALID_PREFIX_LEN", msg)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::{actors::system::Rackd, net::Ipv6Prefix, org::model::Asn, rack::{cmd::init::{InitRack, InitRackError}, RackName}, trunk::model::TrunkName};
+    use crate::util::test_support::init_rack;
+
+    #[tokio::test]
+    async fn cant_init_with_prefix_other_than_56() {
+        let rackd = Rackd::mock().unwrap();
+        let cmd = InitRack {
+            asn: Asn::try_from(4200000001).unwrap(),
+            name: RackName::from_str("lim15109").unwrap(),
+            prefix: Ipv6Prefix::from_str("2a0f:85c1:83f::/48").unwrap(),
+            trunk: TrunkName::from_str("trunk1").unwrap()
+        };
+        assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, InitRackError::InvalidPrefixLen(48))));
+    }
+
+    #[tokio::test]
+    async fn cant_init_more_than_once() {
+        let rackd = Rackd::mock().unwrap();
+        init_rack(&rackd).await;
+        let cmd = InitRack {
+            asn: Asn::try_from(4200000002).unwrap(),
+            name: RackName::from_str("lim15110").unwrap(),
+            prefix: Ipv6Prefix::from_str("2a0f:85c1:83f:200::/56").unwrap(),
+            trunk: TrunkName::from_str("trunk1").unwrap()
+        };
+        assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, InitRackError::AlreadyInitialized)));
+    }
+}
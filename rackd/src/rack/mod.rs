@@ -1,3 +1,6 @@
 pub mod model;
-pub use model::entity::*;
+pub mod cmd;
+pub mod views;
+pub mod query;
+pub use model::entity::{Rack, RackEvent};
 pub use model::values::*;
\ No newline at end of file
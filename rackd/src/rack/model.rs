@@ -1,7 +1,7 @@
 pub mod entity;
 pub mod values;
 
-pub use entity::*;
+pub use entity::{Rack, RackEvent};
 pub use values::*;
 
 // Rack Commands (RackCmd) and Org Commands (OrgCmd) as well as Virtual/Overlay Network Commands (VNetCmd)
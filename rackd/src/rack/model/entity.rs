@@ -1,16 +1,18 @@
 use serde::{Deserialize, Serialize};
-use crate::org::model::Asn;
-use super::RackId;
+use crate::{net::Ipv6Prefix, org::model::Asn, util::models::{Entity, Id, Metadata}};
+use super::{RackId, RackName};
 
 // Racks will implement ANYCAST DNS 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct Rack {
-    // pub meta: Metadata<Self>,
+    pub meta: Metadata,
     // PE-LIM-1 (Country-City-Sequential Number)
     // pe-lim-1.chomba.org
     // pub seq: u32,
     pub id: RackId,
-    pub asn: Asn // ZIP Code
+    pub asn: Asn, // ZIP Code
+    pub name: RackName,
+    pub prefix: Ipv6Prefix // /56 GUA Prefix
 
     // pub nodes: HashMap<Id, RackNode>,
     // pub trunks: BTreeSet<TrunkId>,
@@ -18,11 +20,52 @@ pub struct Rack {
     // pub status: RackStatus
 }
 
-impl Default for Rack {
-    fn default() -> Self {
-        Self {
-            id: RackId::new(),
-            asn: Asn::try_from(4001).unwrap()
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum RackEvent {
+    Initialized { id: RackId, asn: Asn, name: RackName, prefix: Ipv6Prefix }
+}
+
+impl Entity for Rack {
+    type E = RackEvent;
+
+    fn id(&self) -> Id {
+        self.id.into()
+    }
+
+    fn metadata(&mut self) -> &mut Metadata {
+        &mut self.meta
+    }
+
+    fn apply(&mut self, e: &Self::E) {
+        match e {
+            RackEvent::Initialized { id, asn, name, prefix } => {
+                self.id = *id;
+                self.asn = *asn;
+                self.name = name.clone();
+                self.prefix = *prefix;
+            }
+        }
+    }
+}
+
+pub mod casts {
+    use crate::util::models::{casts::UnexpectedEvent, EventData};
+    use super::RackEvent;
+
+    impl From<RackEvent> for EventData {
+        fn from(e: RackEvent) -> Self {
+            Self::Rack(e)
+        }
+    }
+
+    impl TryFrom<EventData> for RackEvent {
+        type Error = UnexpectedEvent;
+
+        fn try_from(e: EventData) -> Result<Self, Self::Error> {
+            match e {
+                EventData::Rack(e) => Ok(e),
+                e => Err(UnexpectedEvent(e))
+            }
         }
     }
 }
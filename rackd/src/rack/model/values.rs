@@ -1,13 +1,31 @@
+use std::fmt::Display;
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
+use utoipa::ToSchema;
+use crate::util::models::Id;
 
 // Commands: Create(rackId, trunkNAME) / Rename(rackId, newName) / Set_Trunk_Interface(nodeId, newName) -> from nodeId get rackId
-#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
-pub struct RackId(pub Uuid);
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct RackId(pub Id);
 
 impl RackId {
     pub fn new() -> Self {
-        Self(Uuid::new_v4())
+        Self(Id::new())
+    }
+}
+
+impl Display for RackId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "rack with id: {}", self.0)
+    }
+}
+
+// lim15109 (Country-City-Sequential Number)
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct RackName(String);
+
+impl Display for RackName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
     }
 }
 
@@ -17,20 +35,107 @@ pub enum RackStatus {
     Offline
 }
 
+pub mod casts {
+    use std::str::FromStr;
+    use serde_json::Value;
+    use thiserror::Error;
+    use crate::util::models::casts::{IdError, InvalidChars};
+    use super::*;
+
+    impl From<RackId> for Id {
+        fn from(value: RackId) -> Self {
+            value.0
+        }
+    }
+
+    #[derive(Debug, Error)]
+    #[error("RackIdError: {:?}", .0)]
+    pub struct RackIdError(#[from]IdError);
+
+    impl TryFrom<Value> for RackId {
+        type Error = RackIdError;
+
+        fn try_from(value: Value) -> Result<Self, Self::Error> {
+            Ok(Self(Id::try_from(value)?))
+        }
+    }
+
+    impl FromStr for RackName {
+        type Err = InvalidChars;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match InvalidChars::from(s, &[]) {
+                Some(error) => Err(error),
+                None => Ok(RackName(s.to_lowercase()))
+            }
+        }
+    }
+
+    #[derive(Debug, Error)]
+    pub enum RackNameError {
+        #[error("Value is not a String [{}]", .0)]
+        InvalidType(Value),
+        #[error("Value contains the following invalid characters: {} [{}]", .0.chars.join(","), .0.value)]
+        InvalidCharacters(InvalidChars),
+        #[error("No value provided")]
+        MissingValue
+    }
+
+    impl TryFrom<Value> for RackName {
+        type Error = RackNameError;
+
+        fn try_from(value: Value) -> Result<Self, Self::Error> {
+            match value {
+                Value::String(value) => Ok(RackName::from_str(&value).map_err(RackNameError::InvalidCharacters)?),
+                Value::Null => Err(RackNameError::MissingValue),
+                _ => Err(RackNameError::InvalidType(value))
+            }
+        }
+    }
+}
+
+pub mod api {
+    use crate::util::api::Error;
+    use super::casts::{RackIdError, RackNameError};
+
+    impl From<RackIdError> for Error {
+        fn from(error: RackIdError) -> Self {
+            Error::new("RACK_ID_ERROR", error.to_string())
+        }
+    }
+
+    impl From<RackNameError> for Error {
+        fn from(error: RackNameError) -> Self {
+            Error::new("RACK_NAME_ERROR", error.to_string())
+        }
+    }
+}
+
 pub mod sqlite {
-    use rusqlite::{Result, types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef}, ToSql};
+    use rusqlite::{Result, types::{FromSql, FromSqlResult, ToSqlOutput, ValueRef}, ToSql};
     use super::*;
-    
+
     impl ToSql for RackId {
         fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
-            Ok(self.0.to_string().into())
+            self.0.to_sql()
         }
     }
-    
+
     impl FromSql for RackId {
         fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
-            let id = Uuid::try_from(value.as_str()?).map_err(|e| FromSqlError::Other(Box::new(e)))?;
-            Ok(Self(id))
+            Ok(Self(Id::column_result(value)?))
+        }
+    }
+
+    impl ToSql for RackName {
+        fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+            Ok(self.0.as_str().into())
         }
     }
-}
\ No newline at end of file
+
+    impl FromSql for RackName {
+        fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+            Ok(RackName(String::from(value.as_str()?)))
+        }
+    }
+}
@@ -0,0 +1,7 @@
+use crate::util::actor::Msg;
+pub mod get_rack;
+
+#[derive(Debug)]
+pub enum RackQuery {
+    GetRack(Msg<get_rack::GetRack>)
+}
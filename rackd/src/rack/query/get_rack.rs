@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use crate::{actors::query::RackdQueryActor, db::{cmd::traits::EntityStore, query::traits::{DbQuery, DbView}, Tx}, rack::{views::RackView, Rack}, util::actor::{Payload, Process}};
+
+/// The rack this node belongs to, set up by `InitRack`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetRack;
+
+#[derive(Debug, Error)]
+pub enum GetRackError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error("Rack hasn't been initialized")]
+    RackNotFound
+}
+
+impl Payload for GetRack {
+    type Ok = RackView;
+    type Err = GetRackError;
+}
+
+impl DbQuery for GetRack {
+    type Ok = Option<RackView>;
+
+    fn run(&self, tx: &rusqlite::Transaction) -> Result<Self::Ok, rusqlite::Error> {
+        let sql = format!("{} WHERE deleted = 0 LIMIT 1", RackView::sql_select());
+        match tx.query_row(&sql, (), <RackView as DbView>::try_from) {
+            Ok(rack) => Ok(Some(rack)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e)
+        }
+    }
+}
+
+/// Loads the rack entity so commands can embed it in their events
+#[derive(Debug)]
+pub struct LoadRack;
+
+impl DbQuery for LoadRack {
+    type Ok = Option<Rack>;
+
+    fn run(&self, tx: &rusqlite::Transaction) -> Result<Self::Ok, rusqlite::Error> {
+        match GetRack.run(tx)? {
+            Some(view) => tx.load(view.id),
+            None => Ok(None)
+        }
+    }
+}
+
+impl Process for GetRack {
+    type Actor = RackdQueryActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let rack = self.run(&tx)?;
+        rack.ok_or(GetRackError::RackNotFound)
+    }
+}
+
+pub mod casts {
+    use crate::{actors::query::RackdQuery, rack::query::RackQuery, util::actor::Msg};
+    use super::GetRack;
+
+    impl From<Msg<GetRack>> for RackdQuery {
+        fn from(query: Msg<GetRack>) -> Self {
+            Self::Rack(RackQuery::GetRack(query))
+        }
+    }
+}
+
+pub mod api {
+    use axum::{extract::{OriginalUri, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, util::api::{Error, Response}};
+    use super::{GetRack, GetRackError};
+
+    #[utoipa::path(get, path = "/rack", tag = "rack",
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn get_rack(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.query(GetRack).await
+            .map(|rack| Response::ok(rack, path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl From<GetRackError> for Error {
+        fn from(error: GetRackError) -> Self {
+            let msg = error.to_string();
+            match error {
+                GetRackError::Db(_) => Error::new("GET_RACK_DB_ERROR", msg),
+                GetRackError::RackNotFound => Error::new("GET_RACK_NOT_FOUND", msg)
+            }
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use rusqlite::{params, Row, Transaction};
+use serde::{Deserialize, Serialize};
+use crate::{db::query::traits::DbView, net::Ipv6Prefix, org::model::Asn, util::models::{Event, EventData}};
+use super::{RackEvent, RackId, RackName};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RackView {
+    pub id: RackId,
+    pub asn: Asn,
+    pub name: RackName,
+    pub prefix: Ipv6Prefix
+}
+
+impl DbView for RackView {
+    fn name() -> &'static str {
+        "rack_view"
+    }
+
+    fn update(tx: &Transaction, e: &Event) -> Result<(), rusqlite::Error> {
+        if let EventData::Rack(RackEvent::Initialized { asn, name, prefix, .. }) = &e.data {
+            let sql = format!("INSERT INTO {} (id, asn, name, prefix) VALUES (?1, ?2, ?3, ?4)", Self::name());
+            tx.execute(&sql, params![e.stream_id, asn, name, prefix])?;
+        }
+        Ok(())
+    }
+
+    fn sql_create() -> &'static str {
+        r#"
+        CREATE TABLE IF NOT EXISTS rack_view (
+            id              TEXT        PRIMARY KEY,
+            asn             INTEGER     NOT NULL,
+            name            TEXT        NOT NULL,
+            prefix          TEXT        NOT NULL,
+            deleted         INTEGER     NOT NULL DEFAULT 0
+        )
+        "#
+    }
+
+    fn select_fields() -> &'static str {
+        "id, asn, name, prefix"
+    }
+
+    fn try_from(row: &Row) -> Result<Self, rusqlite::Error> {
+        Ok(Self {
+            id: row.get(0)?,
+            asn: row.get(1)?,
+            name: row.get(2)?,
+            prefix: row.get(3)?
+        })
+    }
+}
@@ -1,12 +1,58 @@
-use std::sync::Arc;
-use aya::Ebpf;
-use crate::{app::actor::AppMessage, util::actor::*};
-use super::{link::{cmd::*, query::*}, util::{netlink::Netlink, trackers::LinkTrackers}};
+use tokio::sync::{broadcast, mpsc};
+use tokio_util::sync::CancellationToken;
+use crate::util::actor::*;
+use super::{error::SysError, link::{cmd::*, domain::{LinkDown, LinkStatus, LinkTelemetry, TrackedLinks}, events::LinkEvent, query::*}, util::{monitor::NlMonitor, netlink::Netlink, trackers::LinkTrackers}};
 
+const CAPACITY: usize = 1024;
+
+/// Actor in charge of the host (links, addresses, routes) of the node it runs on
 pub struct SysActor {
     pub netlink: Netlink,
+    /// Kernel notifications the trackers follow their links with
+    pub monitor: NlMonitor,
     pub trackers: LinkTrackers,
-    pub app: Handle<AppMessage>
+    pub events: broadcast::Sender<LinkEvent>,
+    pub tracked: TrackedLinks
+}
+
+impl SysActor {
+    pub fn new(netlink: Netlink, monitor: NlMonitor, events: broadcast::Sender<LinkEvent>) -> Self {
+        Self { netlink, monitor, trackers: LinkTrackers::new(), events, tracked: TrackedLinks::default() }
+    }
+
+    /// Spawns a supervised SysActor, a panicking actor is replaced by a new one (without trackers)
+    /// while the netlink connections and the subscribers of its LinkEvents are kept
+    pub fn spawn(netlink: Netlink, monitor: NlMonitor) -> Handle<SysMessage> {
+        let (events, _) = broadcast::channel(CAPACITY);
+        let (sender, receiver) = mpsc::channel(10);
+        let init = move || SysActor::new(netlink.clone(), monitor.clone(), events.clone());
+        tokio::spawn(SysActor::supervise(init, receiver, CancellationToken::new()));
+        Handle { sender }
+    }
+
+    /// Handle of a host without links that's never changed, every link is missing (ie: tests that mustn't touch the host)
+    pub fn detached() -> Handle<SysMessage> {
+        let (events, _) = broadcast::channel(CAPACITY);
+        let (sender, mut receiver) = mpsc::channel::<Envelope<SysMessage>>(10);
+        tokio::spawn(async move {
+            while let Some(envelope) = receiver.recv().await {
+                match envelope.message {
+                    SysMessage::GetLinkById(msg) => { let _ = msg.respond_to.send(Err(SysError::NotFound)); },
+                    SysMessage::GetLinkByName(msg) => { let _ = msg.respond_to.send(Err(SysError::NotFound)); },
+                    SysMessage::GetLinksTelemetry(msg) => {
+                        let missing = LinkTelemetry { status: LinkStatus::Down(LinkDown::WentMissing), ..Default::default() };
+                        let telemetry = msg.payload.names.into_iter().map(|name| (name, missing.clone())).collect();
+                        let _ = msg.respond_to.send(Ok(telemetry));
+                    },
+                    SysMessage::TrackLink(msg) => { let _ = msg.respond_to.send(Err(SysError::NotFound)); },
+                    SysMessage::WatchLinks(msg) => { let _ = msg.respond_to.send(Ok(events.subscribe())); },
+                    SysMessage::EnableLink(msg) => { let _ = msg.respond_to.send(Err(SysError::NotFound)); },
+                    SysMessage::DisableLink(msg) => { let _ = msg.respond_to.send(Err(SysError::NotFound)); }
+                }
+            }
+        });
+        Handle { sender }
+    }
 }
 
 impl AsyncActor for SysActor {
@@ -22,7 +68,15 @@ impl AsyncActor for SysActor {
                 let response = msg.payload.process(self).await;
                 let _ = msg.respond_to.send(response);
             },
-            SysMessage::TrackIspLink(msg) => {
+            SysMessage::GetLinksTelemetry(msg) => {
+                let response = msg.payload.process(self).await;
+                let _ = msg.respond_to.send(response);
+            },
+            SysMessage::TrackLink(msg) => {
+                let response = msg.payload.process(self);
+                let _ = msg.respond_to.send(response);
+            },
+            SysMessage::WatchLinks(msg) => {
                 let response = msg.payload.process(self);
                 let _ = msg.respond_to.send(response);
             },
@@ -38,18 +92,38 @@ impl AsyncActor for SysActor {
     }
 }
 
+/// Subscribes to the LinkEvents of the tracked links
+pub struct WatchLinks;
+
+impl Payload for WatchLinks {
+    type Ok = broadcast::Receiver<LinkEvent>;
+    type Err = ();
+}
+
+impl Process for WatchLinks {
+    type Actor = SysActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        Ok(actor.events.subscribe())
+    }
+}
+
 pub type EnableLinkCmd = Msg<EnableLink>;
 pub type DisableLinkCmd = Msg<DisableLink>;
-pub type TrackIspLinkCmd = Msg<TrackIspLink>;
+pub type TrackLinkCmd = Msg<TrackLink>;
+pub type WatchLinksQuery = Msg<WatchLinks>;
 pub type GetLinkByIdQuery = Msg<GetLinkById>;
 pub type GetLinkByNameQuery = Msg<GetLinkByName>;
+pub type GetLinksTelemetryQuery = Msg<GetLinksTelemetry>;
 
 pub enum SysMessage {
     EnableLink(EnableLinkCmd),
     DisableLink(DisableLinkCmd),
-    TrackIspLink(TrackIspLinkCmd),
+    TrackLink(TrackLinkCmd),
+    WatchLinks(WatchLinksQuery),
     GetLinkById(GetLinkByIdQuery),
     GetLinkByName(GetLinkByNameQuery),
+    GetLinksTelemetry(GetLinksTelemetryQuery),
 }
 
 impl From<GetLinkByIdQuery> for SysMessage {
@@ -64,9 +138,21 @@ impl From<GetLinkByNameQuery> for SysMessage {
     }
 }
 
-impl From<TrackIspLinkCmd> for SysMessage {
-    fn from(value: TrackIspLinkCmd) -> Self {
-        SysMessage::TrackIspLink(value)
+impl From<GetLinksTelemetryQuery> for SysMessage {
+    fn from(value: GetLinksTelemetryQuery) -> Self {
+        SysMessage::GetLinksTelemetry(value)
+    }
+}
+
+impl From<TrackLinkCmd> for SysMessage {
+    fn from(value: TrackLinkCmd) -> Self {
+        SysMessage::TrackLink(value)
+    }
+}
+
+impl From<WatchLinksQuery> for SysMessage {
+    fn from(value: WatchLinksQuery) -> Self {
+        SysMessage::WatchLinks(value)
     }
 }
 
@@ -80,4 +166,26 @@ impl From<DisableLinkCmd> for SysMessage {
     fn from(value: DisableLinkCmd) -> Self {
         SysMessage::DisableLink(value)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::{actors::system::Rackd, net::LinkName, sys::{actor::SysActor, error::SysError, link::query::GetLinkByName, util::{monitor::NlMonitor, netlink::Netlink}}};
+
+    #[tokio::test]
+    async fn sys_actor_answers_through_rackd() {
+        let rackd = Rackd::mock_with(SysActor::spawn(Netlink::connect().unwrap(), NlMonitor::connect().unwrap())).unwrap();
+        let query = GetLinkByName { name: LinkName::from_str("rkdmissing0").unwrap() };
+        assert!(rackd.sys.send(query).await.is_err_and(|e| matches!(e, SysError::NotFound)));
+        let link = rackd.sys.send(GetLinkByName { name: LinkName::from_str("lo").unwrap() }).await.unwrap();
+        assert_eq!(link.name.to_string(), "lo");
+    }
+
+    #[tokio::test]
+    async fn detached_hosts_have_no_links() {
+        let rackd = Rackd::mock().unwrap();
+        let name = LinkName::from_str("lo").unwrap();
+        assert!(rackd.sys.send(GetLinkByName { name }).await.is_err_and(|e| matches!(e, SysError::NotFound)));
+    }
+}
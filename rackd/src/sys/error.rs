@@ -1,12 +1,11 @@
-#[derive(Debug)]
+use thiserror::Error;
+
+#[derive(Debug, Error)]
 pub enum SysError {
+    #[error("Link not found")]
     NotFound,
-    Netlink(rtnetlink::Error)
+    #[error("eBPF Error: {}", .0)]
+    Ebpf(String),
+    #[error(transparent)]
+    Netlink(#[from] rtnetlink::Error)
 }
-
-
-impl From<rtnetlink::Error> for SysError {
-    fn from(error: rtnetlink::Error) -> Self {
-        SysError::Netlink(error)
-    }
-}
\ No newline at end of file
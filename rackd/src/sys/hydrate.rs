@@ -0,0 +1,8 @@
+use std::future::Future;
+use crate::actors::system::Rackd;
+
+/// Views completed with the live state of the host the node runs on, hydration happens once the query actor
+/// answered so netlink is never dumped while the SQLite connection is held
+pub trait Hydrate where Self: Sized {
+    fn hydrate(self, rackd: &Rackd) -> impl Future<Output = Self> + Send;
+}
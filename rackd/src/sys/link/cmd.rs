@@ -1,7 +1,8 @@
 use std::sync::Arc;
 use aya::programs::{Xdp, XdpFlags};
 use log::warn;
-use crate::{isp::models::IspPrefix, sys::{actor::SysActor, error::SysError, link::domain::{LinkId, LinkStatus}, util::netlink::{Netlink, NlCommand}}, util::{actor::{AsyncProcess, Payload, Process}, domain::Id}};
+use tokio::sync::Mutex;
+use crate::{net::{probe::{engine::ProbeEngine, ProbeConfig, ProbeSource}, IpPrefix, LinkId, Prefix}, sys::{actor::SysActor, error::SysError, link::domain::LinkStatus, util::netlink::{Netlink, NlCommand}}, util::actor::{AsyncProcess, Payload, Process}};
 use super::trackers::{LinkGatewayTracker, LinkStatusTracker};
 
 pub struct EnableLink {
@@ -17,7 +18,7 @@ impl AsyncProcess for EnableLink {
     type Actor = SysActor;
 
     async fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
-        Ok(actor.netlink.exec(self).await?)
+        actor.netlink.exec(self).await
     }
 }
 
@@ -45,7 +46,7 @@ impl AsyncProcess for DisableLink {
     type Actor = SysActor;
 
     async fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
-        Ok(actor.netlink.exec(self).await?)
+        actor.netlink.exec(self).await
     }
 }
 
@@ -61,47 +62,59 @@ impl NlCommand for DisableLink {
 }
 
 
-pub struct TrackIspLink {
-    // pass link: Link instead of status, id, prefix?
+/// Tracks the status of the link (reported as LinkEvents) and the gateways seen on it,
+/// tracking a link again replaces its trackers
+pub struct TrackLink {
     pub link: LinkId,
     pub status: LinkStatus,
-    pub prefix: IspPrefix
+    /// Prefix whose last address is used as the source of the connectivity probes
+    pub prefix: Prefix,
+    /// Targets and thresholds Internet reachability is probed with, usually `settings().probes`
+    pub probes: ProbeConfig
 }
 
-impl Payload for TrackIspLink {
+impl Payload for TrackLink {
     type Ok = ();
-    type Err = ();    
+    type Err = SysError;
 }
 
-impl Process for TrackIspLink {
+impl Process for TrackLink {
     type Actor = SysActor;
 
     fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
         actor.trackers.untrack(&self.link);
+        if let Ok(mut tracked) = actor.tracked.write() {
+            tracked.remove(&self.link);
+        }
 
         // Load Trackers
+        let source = ProbeSource { device: None, ipv4: self.prefix.v4().map(|prefix| prefix.last()), ipv6: self.prefix.v6().map(|prefix| prefix.last()) };
         let status_tracker = LinkStatusTracker {
-            link: self.link, status: self.status, prefix: self.prefix, netlink: actor.netlink.clone(), app: actor.app.clone()
+            link: self.link, status: self.status, engine: Arc::new(Mutex::new(ProbeEngine::new(source, self.probes))), netlink: actor.netlink.clone(), events: actor.events.clone(), tracked: actor.tracked.clone(),
+            notifications: actor.monitor.subscribe()
         };
 
         // Link Gateway Tracker
         let mut ebpf = aya::Ebpf::load(aya::include_bytes_aligned!(concat!(
             env!("OUT_DIR"),
             "/rackd"
-        ))).unwrap();
+        ))).map_err(|e| SysError::Ebpf(e.to_string()))?;
 
         if let Err(e) = aya_log::EbpfLogger::init(&mut ebpf) {
             // This can happen if you remove all log statements from your eBPF program.
             warn!("failed to initialize eBPF logger: {}", e);
         }
-        
+
         // Load EBPF Program
-        let program: &mut Xdp = ebpf.program_mut("program").unwrap().try_into().expect("Failed to cast program to XDP program");
-        program.load().expect("failed to load ebpf program");
-        program.attach_to_if_index(self.link.into(), XdpFlags::default()).expect("failed to attach the XDP program with default flags - try changing XdpFlags::default() to XdpFlags::SKB_MODE");
+        let program: &mut Xdp = ebpf.program_mut("program")
+            .ok_or_else(|| SysError::Ebpf(String::from("XDP program not found")))?
+            .try_into().map_err(|e: aya::programs::ProgramError| SysError::Ebpf(e.to_string()))?;
+        program.load().map_err(|e| SysError::Ebpf(e.to_string()))?;
+        // Try changing XdpFlags::default() to XdpFlags::SKB_MODE if the driver doesn't support XDP
+        program.attach_to_if_index(self.link.into(), XdpFlags::default()).map_err(|e| SysError::Ebpf(e.to_string()))?;
 
         let gateway_tracker = LinkGatewayTracker {
-            link: self.link, app: actor.app.clone(), ebpf: Arc::new(ebpf)
+            link: self.link, ebpf: Arc::new(ebpf), tracked: actor.tracked.clone()
         };
         actor.trackers.spawn(status_tracker);
         actor.trackers.spawn(gateway_tracker);
@@ -112,24 +125,23 @@ impl Process for TrackIspLink {
 #[cfg(test)]
 mod tests {
     use std::{net::Ipv4Addr, str::FromStr};
-    use crate::{network::isp::data::IspPrefix, sys::{link::{cmd::*, domain::*, query::*}, util::netlink::Netlink}, system::System, util::net::types::Ipv4Prefix};
-    use crate::sys::util::netlink::NlQuery;
-    use super::TrackIspLink;
-    use crate::util::net::types::IpPrefix;
+    use crate::{actors::system::Rackd, conf::settings, net::{IpPrefix, Ipv4Prefix, LinkName, Prefix}, sys::{actor::SysActor, link::{cmd::*, domain::*, query::*}, util::{monitor::NlMonitor, netlink::{Netlink, NlCommand, NlQuery}}}};
+    use super::TrackLink;
 
     #[tokio::test]
-    async fn can_track_isp_link() {
-        let system = System::mock();
-        let sys = system.sys;
+    #[ignore = "needs a dummy1 link on the host and the rackd eBPF program"]
+    async fn can_track_link() {
+        let rackd = Rackd::mock_with(SysActor::spawn(Netlink::connect().unwrap(), NlMonitor::connect().unwrap())).unwrap();
         let query = GetLinkByName { name: LinkName::from_str("dummy1").unwrap() };
-        let link = sys.send(query).await.unwrap();
-        let prefix = IspPrefix::V4(Ipv4Prefix::new(Ipv4Addr::new(127, 0, 0, 1), 24));
-        
-        let cmd = TrackIspLink { link: link.id, status: LinkStatus::Unknown, prefix };
-        assert!(sys.send(cmd).await.is_ok());
+        let link = rackd.sys.send(query).await.unwrap();
+        let prefix = Prefix::V4(Ipv4Prefix::new(Ipv4Addr::new(127, 0, 0, 1), 24));
+
+        let cmd = TrackLink { link: link.id, status: LinkStatus::Unknown, prefix, probes: settings().probes.clone() };
+        assert!(rackd.sys.send(cmd).await.is_ok());
     }
 
     #[tokio::test]
+    #[ignore = "needs a dummy1 link on the host"]
     async fn enable_link() {
         let netlink = Netlink::connect().unwrap();
         let query = GetLinkByName { name: LinkName::from_str("dummy1").unwrap() };
@@ -142,6 +154,7 @@ mod tests {
     }
 
     #[tokio::test]
+    #[ignore = "needs a dummy1 link on the host"]
     async fn disable_link() {
         let netlink = Netlink::connect().unwrap();
         let query = GetLinkByName { name: LinkName::from_str("dummy1").unwrap() };
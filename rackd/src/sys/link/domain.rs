@@ -1,6 +1,6 @@
-use std::{fmt::Display, net::{Ipv4Addr, Ipv6Addr}, str::FromStr};
+use std::{collections::HashMap, net::{IpAddr, Ipv4Addr, Ipv6Addr}, sync::{Arc, RwLock}};
 use serde::{Deserialize, Serialize};
-use crate::net::util::{tools::InternetUp, models::*};
+use crate::net::{probe::{engine::TargetStats, InternetUp}, IpPrefix, Ipv4Prefix, Ipv6Prefix, LinkId, LinkName};
 
 #[derive(Debug, Default)]
 pub struct Link {
@@ -11,17 +11,9 @@ pub struct Link {
     pub status: LinkStatus
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
-pub struct LinkName(String);
-
-impl Display for LinkName {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
-    }
-}
-
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Copy, Clone)]
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize, Copy, Clone)]
 pub enum LinkStatus {
+    #[default]
     Unknown,
     Up(LinkUp),
     Down(LinkDown)
@@ -40,21 +32,35 @@ pub enum LinkUp {
     InternetUp(InternetUp)     // L3 is working correctly
 }
 
-impl Default for LinkStatus {
-    fn default() -> Self {
-        LinkStatus::Unknown
-    }
+/// Live state of a link as seen by the host, it isn't persisted
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkTelemetry {
+    pub status: LinkStatus,
+    pub ipv4_addrs: Vec<Ipv4Addr>,
+    pub ipv6_addrs: Vec<Ipv6Addr>,
+    /// Next hops of the default routes through the link and the gateways its trackers learned
+    pub gateways: Vec<IpAddr>,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub rx_packets: u64,
+    pub tx_packets: u64,
+    pub carrier_changes: u32,
+    /// Reachability of the probe targets from the link, empty unless the link is tracked
+    pub probes: Vec<TargetStats>
 }
 
-#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Copy, Clone)]
-pub struct LinkId(u32);
-
-impl Display for LinkId {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
-    }
+/// What the trackers of a link found out, the flags of a link only tell whether it's Connected
+#[derive(Debug, Default, Clone)]
+pub struct TrackedLink {
+    pub status: Option<LinkStatus>,
+    pub ipv4_gateway: Option<Ipv4Addr>,
+    pub ipv6_gateway: Option<Ipv6Addr>,
+    pub probes: Vec<TargetStats>
 }
 
+/// Shared between the SysActor and its trackers so telemetry never waits on a tracker
+pub type TrackedLinks = Arc<RwLock<HashMap<LinkId, TrackedLink>>>;
+
 impl Link {
     pub fn ipv6_prefix(&self, prefix_len: u8) -> Option<Ipv6Prefix> {
         for addr in &self.ipv6_addrs {
@@ -83,39 +89,6 @@ impl Link {
 mod casts {
     use super::*;
 
-    impl From<u32> for LinkId {
-        fn from(value: u32) -> Self {
-            LinkId(value)
-        }
-    }
-
-    impl From<LinkId> for u32 {
-        fn from(id: LinkId) -> Self {
-            id.0
-        }
-    }
-
-    #[derive(Debug)]
-    pub enum LinkNameParseError {
-        TooLarge
-    }
-
-    impl FromStr for LinkName {
-        type Err = LinkNameParseError;
-
-        fn from_str(s: &str) -> Result<Self, Self::Err> {
-            let s = String::from(s.to_lowercase());
-            let length = s.len();
-            if length >= 1 && length <= 40 {
-                return Ok(LinkName(s));
-            }
-            // let mut rules = Rules::new();
-            // rules += StringRule::BoundedLength { min: 0, max: 40 }; 
-            // rules.eval(s, |s| NetLink(s), |value, violations| Error::NetLink(NetLinkError::Invalid { value, violations }))
-            Err(LinkNameParseError::TooLarge)
-        }
-    }
-
     mod sql {
         use rusqlite::{types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef}, Error, Result, ToSql};
         use super::*;
@@ -133,37 +106,13 @@ mod casts {
                 Ok(status)
             }
         }
-        
-        impl ToSql for LinkName {
-            fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
-                Ok(self.to_string().into())
-            }
-        }
-
-        impl FromSql for LinkName {
-            fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
-                Ok(LinkName(String::from(value.as_str()?)))
-            }
-        }
-
-        impl ToSql for LinkId {
-            fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
-                Ok(u32::from(*self).into())
-            }
-        }
-
-        impl FromSql for LinkId {
-            fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
-                let value = u32::try_from(value.as_i64()?).map_err(|e| FromSqlError::Other(Box::new(e)))?;
-                Ok(Self::from(value))
-            }
-        }
     }
 
     mod netlink {
         use futures::{TryStream, TryStreamExt};
         use netlink_packet_route::link::{LinkAttribute, LinkFlag, LinkMessage};
         use rtnetlink::Error;
+        use std::str::FromStr;
         use crate::sys::util::netlink::FromNetlinkMessage;
         use super::*;
 
@@ -218,9 +167,8 @@ mod casts {
                 link.status = LinkStatus::from(msg.header.flags);
 
                 for attribute in msg.attributes {
-                    match attribute {
-                        LinkAttribute::IfName(name) => link.name = LinkName(name),
-                        _ => { }
+                    if let LinkAttribute::IfName(name) = attribute && let Ok(name) = LinkName::from_str(&name) {
+                        link.name = name;
                     }
                 }
                 Some(link)
@@ -232,9 +180,10 @@ mod casts {
 #[cfg(test)]
 mod test {
     use std::str::FromStr;
-    use crate::sys::{link::{domain::LinkName, query::GetLinkByName}, util::netlink::Netlink};
+    use crate::{net::LinkName, sys::{link::query::GetLinkByName, util::netlink::Netlink}};
 
-    #[tokio::test] 
+    #[tokio::test]
+    #[ignore = "needs a dummy1 link on the host"]
     async fn get_link_ipv4_prefix() {
         // TBD: create interface
         let netlink = Netlink::connect().unwrap();
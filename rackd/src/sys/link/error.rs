@@ -1,2 +0,0 @@
-use super::domain::{Link, LinkId, LinkName};
-
@@ -0,0 +1,17 @@
+use std::net::IpAddr;
+use serde::{Deserialize, Serialize};
+use crate::net::LinkId;
+use super::domain::{LinkDown, LinkUp};
+
+/// Changes of the tracked links, they aren't stored nor replicated, every node tracks its own links
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LinkEvent {
+    WentUp { link: LinkId, up: LinkUp },
+    WentDown { link: LinkId, down: LinkDown },
+    AddressChanged { link: LinkId, addr: IpAddr, prefix_len: u8, change: AddressChange }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AddressChange {
+    Added, Removed
+}
@@ -1,5 +1,5 @@
 pub mod domain;
-pub mod error;
 pub mod query;
 pub mod cmd;
+pub mod events;
 pub mod trackers;
\ No newline at end of file
@@ -1,7 +1,8 @@
 use futures::TryStreamExt;
-use netlink_packet_route::address::AddressAttribute;
-use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
-use crate::{sys::{actor::SysActor, error::SysError, util::netlink::{FromNetlinkMessage, Netlink, NlQuery}}, util::actor::{AsyncProcess, Payload}};
+use netlink_packet_route::{address::AddressAttribute, link::{LinkAttribute, LinkMessage}, route::{RouteAddress, RouteAttribute, RouteMessage}};
+use rtnetlink::IpVersion;
+use std::{collections::HashMap, net::{IpAddr, Ipv4Addr, Ipv6Addr}, str::FromStr};
+use crate::{net::{LinkId, LinkName}, sys::{actor::SysActor, error::SysError, util::netlink::{FromNetlinkMessage, Netlink, NlQuery}}, util::actor::{AsyncProcess, Payload}};
 use super::domain::*;
 
 #[derive(Copy, Clone)]
@@ -33,7 +34,7 @@ impl NlQuery for GetLinkById {
             Some(link) => link,
             None => Err(SysError::NotFound)?
         };
-        (link.ipv6_addrs, link.ipv4_addrs) = netlink.run(GetLinkAddressesById { id: self.id }).await.unwrap();
+        (link.ipv6_addrs, link.ipv4_addrs) = netlink.run(GetLinkAddressesById { id: self.id }).await?;
         Ok(link) 
     }   
 }
@@ -66,7 +67,7 @@ impl NlQuery for GetLinkByName {
             Some(link) => link,
             None => Err(SysError::NotFound)?
         };
-        (link.ipv6_addrs, link.ipv4_addrs) = netlink.run(GetLinkAddressesById { id: link.id }).await.unwrap();
+        (link.ipv6_addrs, link.ipv4_addrs) = netlink.run(GetLinkAddressesById { id: link.id }).await?;
         Ok(link)
     }
 }
@@ -77,13 +78,13 @@ pub struct GetLinkAddressesById {
 
 impl NlQuery for GetLinkAddressesById {
     type Ok = (Vec<Ipv6Addr>, Vec<Ipv4Addr>);
-    type Err = ();
+    type Err = SysError;
 
     async fn run(self, netlink: &Netlink) -> Result<Self::Ok, Self::Err> {
         let mut stream = netlink.route().address().get().set_link_index_filter(self.id.into()).execute();
         let mut ipv6_addrs = Vec::new();
         let mut ipv4_addrs = Vec::new();
-        while let Ok(Some(packet)) = stream.try_next().await {
+        while let Some(packet) = stream.try_next().await? {
             for attr in packet.attributes {
                 match attr {
                     AddressAttribute::Address(IpAddr::V6(addr)) => ipv6_addrs.push(addr),
@@ -96,12 +97,118 @@ impl NlQuery for GetLinkAddressesById {
     }
 }
 
+/// Telemetry of the links with the given names, a link missing from the host is `Down(WentMissing)`.
+/// The host is dumped once whatever the number of links so it can back every query of a page
+pub struct GetLinksTelemetry {
+    pub names: Vec<LinkName>
+}
+
+impl Payload for GetLinksTelemetry {
+    type Ok = HashMap<LinkName, LinkTelemetry>;
+    type Err = SysError;
+}
+
+impl AsyncProcess for GetLinksTelemetry {
+    type Actor = SysActor;
+
+    async fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let links = actor.netlink.run(self).await?;
+        let Ok(tracked) = actor.tracked.read() else { return Ok(links.into_iter().map(|(name, (_, telemetry))| (name, telemetry)).collect()) };
+        let telemetry = links.into_iter().map(|(name, (id, mut telemetry))| {
+            if let Some(link) = id.and_then(|id| tracked.get(&id)) {
+                // Only trackers probe L3, the flags are enough to tell a link is down
+                if let (LinkStatus::Up(_), Some(status @ LinkStatus::Up(_))) = (telemetry.status, link.status) {
+                    telemetry.status = status;
+                }
+                let learned = link.ipv4_gateway.map(IpAddr::V4).into_iter().chain(link.ipv6_gateway.map(IpAddr::V6));
+                for gateway in learned {
+                    if !telemetry.gateways.contains(&gateway) {
+                        telemetry.gateways.push(gateway);
+                    }
+                }
+                telemetry.probes = link.probes.clone();
+            }
+            (name, telemetry)
+        });
+        Ok(telemetry.collect())
+    }
+}
+
+impl NlQuery for GetLinksTelemetry {
+    type Ok = HashMap<LinkName, (Option<LinkId>, LinkTelemetry)>;
+    type Err = SysError;
+
+    async fn run(self, netlink: &Netlink) -> Result<Self::Ok, Self::Err> {
+        let route = netlink.route();
+        let links: Vec<LinkMessage> = route.link().get().execute().try_collect().await?;
+        let mut by_name = HashMap::new();
+        for link in links {
+            let name = link.attributes.iter().find_map(|attr| match attr {
+                LinkAttribute::IfName(name) => LinkName::from_str(name).ok(),
+                _ => None
+            });
+            if let Some(name) = name.filter(|name| self.names.contains(name)) {
+                by_name.insert(name, link);
+            }
+        }
+        let addrs: Vec<_> = route.address().get().execute().try_collect().await?;
+        let mut routes: Vec<RouteMessage> = route.route().get(IpVersion::V4).execute().try_collect().await?;
+        routes.extend(route.route().get(IpVersion::V6).execute().try_collect::<Vec<_>>().await?);
+
+        let mut telemetry = HashMap::with_capacity(self.names.len());
+        for name in self.names {
+            let Some(link) = by_name.remove(&name) else {
+                telemetry.insert(name, (None, LinkTelemetry { status: LinkStatus::Down(LinkDown::WentMissing), ..Default::default() }));
+                continue;
+            };
+            let index = link.header.index;
+            let mut link_telemetry = LinkTelemetry { status: LinkStatus::from(link.header.flags), ..Default::default() };
+            for attr in link.attributes {
+                match attr {
+                    LinkAttribute::Stats64(stats) => {
+                        link_telemetry.rx_bytes = stats.rx_bytes;
+                        link_telemetry.tx_bytes = stats.tx_bytes;
+                        link_telemetry.rx_packets = stats.rx_packets;
+                        link_telemetry.tx_packets = stats.tx_packets;
+                    },
+                    LinkAttribute::CarrierChanges(changes) => link_telemetry.carrier_changes = changes,
+                    _ => continue
+                }
+            }
+            for addr in addrs.iter().filter(|msg| msg.header.index == index).flat_map(|msg| &msg.attributes) {
+                match addr {
+                    AddressAttribute::Address(IpAddr::V6(addr)) => link_telemetry.ipv6_addrs.push(*addr),
+                    AddressAttribute::Address(IpAddr::V4(addr)) => link_telemetry.ipv4_addrs.push(*addr),
+                    _ => continue
+                }
+            }
+            // Default routes of every table, static wans keep theirs off the main table
+            for msg in routes.iter().filter(|msg| msg.header.destination_prefix_length == 0) {
+                if !msg.attributes.contains(&RouteAttribute::Oif(index)) {
+                    continue;
+                }
+                let gateway = msg.attributes.iter().find_map(|attr| match attr {
+                    RouteAttribute::Gateway(RouteAddress::Inet(addr)) => Some(IpAddr::V4(*addr)),
+                    RouteAttribute::Gateway(RouteAddress::Inet6(addr)) => Some(IpAddr::V6(*addr)),
+                    _ => None
+                });
+                if let Some(gateway) = gateway.filter(|gateway| !link_telemetry.gateways.contains(gateway)) {
+                    link_telemetry.gateways.push(gateway);
+                }
+            }
+            telemetry.insert(name, (Some(LinkId::from(index)), link_telemetry));
+        }
+        Ok(telemetry)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::str::FromStr;
-    use crate::sys::{link::{domain::LinkName, query::GetLinkByName}, util::netlink::Netlink};
+    use crate::{net::LinkName, sys::{link::query::GetLinkByName, util::netlink::Netlink}};
 
     #[tokio::test]
+    #[ignore = "needs a dummy1 link on the host"]
     async fn get_link_by_name() {
         let netlink = Netlink::connect().unwrap();
         let query = GetLinkByName { name: LinkName::from_str("dummy1").unwrap() };
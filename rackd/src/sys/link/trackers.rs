@@ -1,61 +1,132 @@
-use std::{net::{Ipv4Addr, Ipv6Addr}, sync::Arc, time::Duration};
-use crate::{app::{actor::AppMessage, isp::{events::{IspLinkWentDown, IspLinkWentUp}, domain::IspPrefix}}, util::{actor::Handle, domain::Id, net::{tools::InternetTester, types::IpPrefix}}};
-use crate::sys::{link::domain::{LinkDown, LinkId, LinkStatus, LinkUp}, util::{netlink::Netlink, trackers::LinkTracker}};
+use std::{future::Future, net::{Ipv4Addr, Ipv6Addr}, pin::Pin, sync::Arc, time::Duration};
+use log::{debug, warn};
+use tokio::{sync::{broadcast::{self, error::RecvError}, Mutex}, time::Instant};
+use crate::net::{probe::{engine::ProbeEngine, InternetUp}, LinkId};
+use crate::sys::{link::{domain::{LinkDown, LinkStatus, LinkUp, TrackedLinks}, events::{AddressChange, LinkEvent}}, util::{monitor::NlNotification, netlink::Netlink, trackers::LinkTracker}};
 use super::query::GetLinkById;
 use aya::{maps::Array, Ebpf};
 
+/// Follows the kernel notifications of a link, cable pulls and PPPoE drops are reported as soon as they're notified
+/// while Internet connectivity is probed whenever the link comes up or its addresses or routes change.
+/// Connectivity is lost without the kernel noticing so it's also probed on every interval of the engine
 pub struct LinkStatusTracker {
     pub link: LinkId,
     pub status: LinkStatus,
-    pub prefix: IspPrefix,
+    /// Shared with the probe in flight, a round only changes it once it's done
+    pub engine: Arc<Mutex<ProbeEngine>>,
     pub netlink: Netlink,
-    pub app: Handle<AppMessage>,
+    pub events: broadcast::Sender<LinkEvent>,
+    pub tracked: TrackedLinks,
+    /// Subscribed before the link is first read so no notification is missed in between
+    pub notifications: broadcast::Receiver<NlNotification>
 }
 
 impl LinkStatusTracker {
-    async fn set_status(&mut self, status: LinkStatus) -> () {
+    async fn set_status(&mut self, status: LinkStatus) {
         if self.status != status {
             self.status = status;
-            match self.status {
-                LinkStatus::Up(up) => self.app.emit(IspLinkWentUp { id: self.link, up }).await,
-                LinkStatus::Down(down) => self.app.emit(IspLinkWentDown { id: self.link, down }).await,
-                _ => { }
+            if let Ok(mut tracked) = self.tracked.write() {
+                tracked.entry(self.link).or_default().status = Some(status);
             }
+            // No subscribers isn't an error
+            let _ = match self.status {
+                LinkStatus::Up(up) => self.events.send(LinkEvent::WentUp { link: self.link, up }),
+                LinkStatus::Down(down) => self.events.send(LinkEvent::WentDown { link: self.link, down }),
+                LinkStatus::Unknown => return
+            };
+        }
+    }
+
+    /// Statuses read from the flags of the link, Up is only ever Connected until Internet is probed
+    async fn flags_changed(&mut self, status: LinkStatus, probe: &mut Option<Probe>) {
+        match status {
+            LinkStatus::Up(_) => if !matches!(self.status, LinkStatus::Up(_)) {
+                self.set_status(LinkStatus::Up(LinkUp::Connected)).await;
+                *probe = Some(self.probe());
+            },
+            status => {
+                *probe = None;
+                // Reachability must be proven again once the link is back
+                self.engine.lock().await.reset();
+                self.set_status(status).await;
+            }
+        }
+    }
+
+    async fn read_link(&mut self, probe: &mut Option<Probe>) {
+        // The probe in flight holds the engine until its round is done
+        *probe = None;
+        let status = match self.netlink.run(GetLinkById { id: self.link }).await {
+            Ok(link) => {
+                // Probes leave through the link whatever the routes of the other links
+                self.engine.lock().await.source.device = Some(link.name);
+                link.status
+            },
+            Err(_) => LinkStatus::Down(LinkDown::WentMissing)
+        };
+        self.flags_changed(status, probe).await;
+        // Changes might have been missed, reachability is proven again
+        if matches!(self.status, LinkStatus::Up(_)) && probe.is_none() {
+            *probe = Some(self.probe());
         }
     }
+
+    /// Runs a round of the engine and publishes the stats of its targets
+    fn probe(&self) -> Probe {
+        let (link, engine, tracked) = (self.link, self.engine.clone(), self.tracked.clone());
+        Box::pin(async move {
+            let mut engine = engine.lock().await;
+            let up = engine.round().await;
+            if let Ok(mut tracked) = tracked.write() {
+                tracked.entry(link).or_default().probes = engine.stats();
+            }
+            (up, engine.pending())
+        })
+    }
 }
 
+/// Outcome of a round and whether the next one should follow right away
+type Probe = Pin<Box<dyn Future<Output = (Option<InternetUp>, bool)> + Send>>;
+
 impl LinkTracker for LinkStatusTracker {
     fn link(&self) -> LinkId { self.link }
-    async fn work(&mut self) -> () {
+    async fn work(&mut self) {
+        let mut probe = None;
+        self.read_link(&mut probe).await;
+        let every = self.engine.lock().await.interval();
+        let mut interval = tokio::time::interval_at(Instant::now() + every, every);
         loop {
-            tokio::time::sleep(Duration::from_secs(1)).await;
-            let link = match self.netlink.run(GetLinkById { id: self.link }).await {
-                Ok(link) => link,
-                Err(_) =>  {
-                    self.set_status(LinkStatus::Down(LinkDown::WentMissing)).await;
-                    continue;
-                }
-            };
-
-            if let LinkStatus::Up(up) = link.status {
-                let tester = InternetTester {
-                    ipv4_addr: self.prefix.v4().unwrap_or_default().last(),
-                    ipv6_addr: self.prefix.v6().unwrap_or_default().last()
-                };
-                match up {
-                    LinkUp::Connected => { 
-                        if let Some(up) = tester.connectivity().await {
-                            self.set_status(LinkStatus::Up(LinkUp::InternetUp(up))).await
+            tokio::select! {
+                notification = self.notifications.recv() => match notification {
+                    Ok(notification) if notification.link() != self.link => continue,
+                    Ok(NlNotification::Link { status, .. }) => self.flags_changed(status, &mut probe).await,
+                    Ok(NlNotification::Address { addr, prefix_len, removed, .. }) => {
+                        let change = if removed { AddressChange::Removed } else { AddressChange::Added };
+                        let _ = self.events.send(LinkEvent::AddressChanged { link: self.link, addr, prefix_len, change });
+                        if matches!(self.status, LinkStatus::Up(_)) {
+                            probe = Some(self.probe());
                         }
                     },
-                    LinkUp::InternetUp(up) => {
-                        // perhaps we should try again at least twice 
-                        // in case connectivity is flapping more a few ms
-                        if tester.connectivity().await.is_none() {
-                            self.set_status(LinkStatus::Up(LinkUp::Connected)).await
+                    Ok(NlNotification::Prefix { .. } | NlNotification::Route { .. }) => if matches!(self.status, LinkStatus::Up(_)) {
+                        probe = Some(self.probe());
+                    },
+                    Err(RecvError::Lagged(n)) => {
+                        warn!("[LINK_STATUS_TRACKER] Lagged behind, {} notifications were dropped, reading link {} again", n, self.link);
+                        self.read_link(&mut probe).await;
+                    },
+                    Err(RecvError::Closed) => return
+                },
+                (up, pending) = async { probe.as_mut().expect("guarded by the select precondition").await }, if probe.is_some() => {
+                    probe = None;
+                    if matches!(self.status, LinkStatus::Up(_)) {
+                        self.set_status(LinkStatus::Up(up.map_or(LinkUp::Connected, LinkUp::InternetUp))).await;
+                        if pending {
+                            probe = Some(self.probe());
                         }
                     }
+                },
+                _ = interval.tick() => if matches!(self.status, LinkStatus::Up(_)) && probe.is_none() {
+                    probe = Some(self.probe());
                 }
             }
         }
@@ -65,33 +136,66 @@ impl LinkTracker for LinkStatusTracker {
 pub struct LinkGatewayTracker {
     pub link: LinkId,
     pub ebpf: Arc<Ebpf>,
-    pub app: Handle<AppMessage>,
+    pub tracked: TrackedLinks
 }
 
 impl LinkTracker for LinkGatewayTracker {
     fn link(&self) -> LinkId { self.link }
-    async fn work(&mut self) -> () {
+    async fn work(&mut self) {
+        let maps = self.ebpf.map("IPV6_GATEWAY").and_then(|map| Array::<_, u128>::try_from(map).ok())
+            .zip(self.ebpf.map("IPV4_GATEWAY").and_then(|map| Array::<_, u32>::try_from(map).ok()));
+        let Some((gateway6_map, gateway4_map)) = maps else {
+            warn!("[LINK_GATEWAY_TRACKER] Gateway maps missing from the eBPF program of link {}", self.link);
+            return;
+        };
         loop {
             tokio::time::sleep(Duration::from_secs(2)).await;
-
-            let gateway6_map: Array::<_, u128> = Array::try_from(self.ebpf.map("IPV6_GATEWAY").unwrap()).unwrap();
-            let gateway4_map: Array::<_, u32> = Array::try_from(self.ebpf.map("IPV4_GATEWAY").unwrap()).unwrap();
-            let gateway6 = Ipv6Addr::from_bits(gateway6_map.get(&0, 0).unwrap());
-            let gateway4 = Ipv4Addr::from_bits(gateway4_map.get(&0, 0).unwrap());
-    
-            println!("IPv6 Gateway: {gateway6:?} - IPv4 Gateway: {gateway4:?}");
-            println!("ok 2 sec elapsed");
+            let gateway6 = gateway6_map.get(&0, 0).map(Ipv6Addr::from_bits);
+            let gateway4 = gateway4_map.get(&0, 0).map(Ipv4Addr::from_bits);
+            debug!("[LINK_GATEWAY_TRACKER] Link {} IPv6 Gateway: {:?} - IPv4 Gateway: {:?}", self.link, gateway6, gateway4);
+            if let Ok(mut tracked) = self.tracked.write() {
+                // Unspecified until the program sees traffic from a gateway
+                let tracked = tracked.entry(self.link).or_default();
+                tracked.ipv6_gateway = gateway6.ok().filter(|addr| !addr.is_unspecified());
+                tracked.ipv4_gateway = gateway4.ok().filter(|addr| !addr.is_unspecified());
+            }
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::time::Duration;
-    use aya::programs::{Xdp, XdpFlags};
+    use std::{str::FromStr, sync::Arc, time::Duration};
+    use tokio::sync::{broadcast, Mutex};
+    use crate::{conf::settings, net::{probe::{engine::ProbeEngine, ProbeSource}, LinkName}, sys::{link::{domain::{LinkDown, LinkStatus, TrackedLinks}, events::LinkEvent, query::GetLinkByName}, util::{monitor::NlMonitor, netlink::Netlink, trackers::LinkTrackers}}};
+    use super::LinkStatusTracker;
+
+    #[tokio::test]
+    #[ignore = "needs CAP_NET_ADMIN and the dummy module, run it in a namespace: unshare -rn cargo test trackers -- --ignored"]
+    async fn link_status_follows_kernel_notifications() {
+        let netlink = Netlink::connect().unwrap();
+        netlink.route().link().add().dummy(String::from("rkdtrack0")).execute().await.unwrap();
+        let link = netlink.run(GetLinkByName { name: LinkName::from_str("rkdtrack0").unwrap() }).await.unwrap();
+        let monitor = NlMonitor::connect().unwrap();
+        let (events, mut receiver) = broadcast::channel(16);
+        let tracker = LinkStatusTracker {
+            link: link.id, status: LinkStatus::Unknown, engine: Arc::new(Mutex::new(ProbeEngine::new(ProbeSource::default(), settings().probes.clone()))),
+            netlink: netlink.clone(), events,
+            tracked: TrackedLinks::default(), notifications: monitor.subscribe()
+        };
+        let mut trackers = LinkTrackers::new();
+        trackers.spawn(tracker);
+        let event = tokio::time::timeout(Duration::from_secs(1), receiver.recv()).await.unwrap().unwrap();
+        assert_eq!(event, LinkEvent::WentDown { link: link.id, down: LinkDown::AdminDown });
+
+        netlink.route().link().del(link.id.into()).execute().await.unwrap();
+        let event = tokio::time::timeout(Duration::from_secs(1), receiver.recv()).await.unwrap().unwrap();
+        assert_eq!(event, LinkEvent::WentDown { link: link.id, down: LinkDown::WentMissing });
+    }
 
     #[tokio::test]
     async fn test_ebpf_gateway_tracker() {
+        // use aya::programs::{Xdp, XdpFlags};
         // let mut ebpf = aya::Ebpf::load(aya::include_bytes_aligned!(concat!(
         //     env!("OUT_DIR"),
         //     "/rackd"
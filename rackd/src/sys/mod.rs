@@ -1,4 +1,6 @@
-pub mod link;
 pub mod actor;
 pub mod error;
+pub mod hydrate;
+pub mod link;
+pub mod reconciler;
 pub mod util;
\ No newline at end of file
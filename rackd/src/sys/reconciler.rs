@@ -0,0 +1,266 @@
+use std::net::IpAddr;
+use futures::TryStreamExt;
+use log::{error, warn};
+use netlink_packet_route::{address::{AddressAttribute, AddressFlag, AddressMessage}, link::{InfoData, InfoVlan, LinkAttribute, LinkFlag, LinkInfo, LinkMessage}};
+use rtnetlink::Handle;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::sync::CancellationToken;
+use crate::{actors::system::Rackd, db::raft::NodeId, net::{Ipv4Params, MacAddr}, sys::{error::SysError, util::netlink::{Netlink, NlCommand}}, util::models::EventData, wan::{model::values::WanId, query::get_host_links::{GetWanHostLinks, WanHostLink}}};
+
+/// Routing table holding the default route of a static wan is `WAN_TABLE_BASE + vlan`,
+/// each wan gets its own table so their default routes don't clash on the main table
+pub const WAN_TABLE_BASE: u32 = 10000;
+
+/// Converges the host links of the node with the committed state of the wans.
+/// Every wan is reconciled on start, when the bus drops events and whenever a trunk changes,
+/// otherwise only the wan an event belongs to is reconciled
+pub struct WanReconciler {
+    rackd: Rackd,
+    netlink: Netlink,
+    node: NodeId
+}
+
+impl WanReconciler {
+    pub fn new(rackd: Rackd, netlink: Netlink, node: NodeId) -> Self {
+        Self { rackd, netlink, node }
+    }
+
+    pub async fn run(self, cancel: CancellationToken) {
+        tokio::select! {
+            _ = cancel.cancelled() => {
+                // Log termination
+            }
+            _ = self.work() => {
+                // Event Bus closed
+            }
+        }
+    }
+
+    async fn work(&self) {
+        // Subscribe before the first pass so no event committed in between is missed
+        let mut events = self.rackd.events.subscribe();
+        self.reconcile(None).await;
+        loop {
+            match events.recv().await {
+                Ok(e) => match e.data {
+                    EventData::Wan(_) => self.reconcile(Some(WanId(e.stream_id))).await,
+                    EventData::Trunk(_) => self.reconcile(None).await,
+                    _ => continue
+                },
+                Err(RecvError::Lagged(n)) => {
+                    warn!("[RECONCILER] Lagged behind, {} events were dropped, reconciling every wan", n);
+                    self.reconcile(None).await;
+                },
+                Err(RecvError::Closed) => return
+            }
+        }
+    }
+
+    async fn reconcile(&self, id: Option<WanId>) {
+        let links = match self.rackd.query(GetWanHostLinks { node: self.node, id }).await {
+            Ok(links) => links,
+            Err(e) => {
+                error!("[RECONCILER] Failed to read wan links: {}", e);
+                return;
+            }
+        };
+        for link in links {
+            let (id, name) = (link.id, link.name.clone());
+            if let Err(e) = self.netlink.exec(ReconcileWanLink { link }).await {
+                error!("[RECONCILER] Failed to reconcile {} ({}): {}", name, id, e);
+            }
+        }
+    }
+}
+
+/// Brings the 802.1Q link of a wan to the given state, running it again on a converged link changes nothing.
+/// The link is found by its parent and VLAN and named after them (see LinkName::vlan), the wan's name can't
+/// be used since it doesn't fit in an interface name
+pub struct ReconcileWanLink {
+    pub link: WanHostLink
+}
+
+impl NlCommand for ReconcileWanLink {
+    type Ok = ();
+    type Err = SysError;
+
+    async fn exec(self, netlink: &Netlink) -> Result<Self::Ok, Self::Err> {
+        let route = netlink.route();
+        let wan = self.link;
+        let vlan = u16::from(wan.vlan);
+        // Nothing to converge until the trunk is bound to an interface present on this node
+        let Some(parent_name) = &wan.parent else { return Ok(()) };
+        let Some(parent) = link_by_name(&route, &parent_name.to_string()).await? else { return Ok(()) };
+        let existing = vlan_link(&route, parent.header.index, vlan).await?;
+        if wan.deleted {
+            if let Some(link) = existing {
+                route.link().del(link.header.index).execute().await?;
+            }
+            return Ok(());
+        }
+
+        let name = parent_name.vlan(wan.vlan).to_string();
+        let link = match existing {
+            Some(link) => link,
+            None => {
+                route.link().add().vlan(name.clone(), parent.header.index, vlan).execute().await?;
+                vlan_link(&route, parent.header.index, vlan).await?.ok_or(SysError::NotFound)?
+            }
+        };
+        let index = link.header.index;
+        let mut up = link.header.flags.contains(&LinkFlag::Up);
+        if link_name(&link) != Some(&name) {
+            // Links can only be renamed while they're down (ie: links created by hand on the VLAN)
+            route.link().set(index).down().execute().await?;
+            route.link().set(index).name(name).execute().await?;
+            up = false;
+        }
+
+        let mac = match wan.mac {
+            MacAddr::Spoofed(mac) => Some(mac.as_bytes().to_vec()),
+            MacAddr::Auto => link_address(&parent).cloned()
+        };
+        if let Some(mac) = mac.filter(|mac| link_address(&link) != Some(mac)) {
+            route.link().set(index).address(mac).execute().await?;
+        }
+        if !up {
+            route.link().set(index).up().execute().await?;
+        }
+
+        let addrs = ipv4_addresses(&route, index).await?;
+        match wan.ipv4 {
+            Ipv4Params::Static { addr, mask_len, gateway } => {
+                let len = u8::from(mask_len);
+                let mut present = false;
+                for msg in addrs {
+                    if address(&msg) == Some(IpAddr::V4(addr)) && msg.header.prefix_len == len {
+                        present = true;
+                    } else {
+                        route.address().del(msg).execute().await?;
+                    }
+                }
+                if !present {
+                    route.address().add(index, IpAddr::V4(addr), len).execute().await?;
+                }
+                route.route().add().v4().replace().output_interface(index).gateway(gateway).table_id(WAN_TABLE_BASE + vlan as u32).execute().await?;
+            },
+            Ipv4Params::DHCP => {
+                // Leases carry a lifetime, a permanent address was set while the wan was static
+                for msg in addrs.into_iter().filter(is_permanent) {
+                    route.address().del(msg).execute().await?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn link_by_name(route: &Handle, name: &str) -> Result<Option<LinkMessage>, SysError> {
+    let mut links = route.link().get().match_name(String::from(name)).execute();
+    match links.try_next().await {
+        Ok(link) => Ok(link),
+        Err(rtnetlink::Error::NetlinkError(e)) if e.raw_code() == -libc::ENODEV => Ok(None),
+        Err(e) => Err(e)?
+    }
+}
+
+/// 802.1Q link with the given VLAN on top of the parent link
+async fn vlan_link(route: &Handle, parent: u32, vlan: u16) -> Result<Option<LinkMessage>, SysError> {
+    let mut links = route.link().get().execute();
+    while let Some(link) = links.try_next().await? {
+        let on_parent = link.attributes.iter().any(|attr| matches!(attr, LinkAttribute::Link(index) if *index == parent));
+        let on_vlan = link.attributes.iter().any(|attr| match attr {
+            LinkAttribute::LinkInfo(infos) => infos.iter().any(|info| match info {
+                LinkInfo::Data(InfoData::Vlan(data)) => data.contains(&InfoVlan::Id(vlan)),
+                _ => false
+            }),
+            _ => false
+        });
+        if on_parent && on_vlan {
+            return Ok(Some(link));
+        }
+    }
+    Ok(None)
+}
+
+async fn ipv4_addresses(route: &Handle, index: u32) -> Result<Vec<AddressMessage>, SysError> {
+    let addrs = route.address().get().set_link_index_filter(index).execute().try_collect::<Vec<_>>().await?;
+    Ok(addrs.into_iter().filter(|msg| matches!(address(msg), Some(IpAddr::V4(_)))).collect())
+}
+
+fn link_name(link: &LinkMessage) -> Option<&String> {
+    link.attributes.iter().find_map(|attr| match attr {
+        LinkAttribute::IfName(name) => Some(name),
+        _ => None
+    })
+}
+
+fn link_address(link: &LinkMessage) -> Option<&Vec<u8>> {
+    link.attributes.iter().find_map(|attr| match attr {
+        LinkAttribute::Address(addr) => Some(addr),
+        _ => None
+    })
+}
+
+fn address(msg: &AddressMessage) -> Option<IpAddr> {
+    msg.attributes.iter().find_map(|attr| match attr {
+        AddressAttribute::Address(addr) => Some(*addr),
+        _ => None
+    })
+}
+
+fn is_permanent(msg: &AddressMessage) -> bool {
+    msg.attributes.iter().any(|attr| matches!(attr, AddressAttribute::Flags(flags) if flags.contains(&AddressFlag::Permanent)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::Ipv4Addr, str::FromStr};
+    use futures::TryStreamExt;
+    use macaddr::MacAddr6;
+    use crate::{net::{Ipv4Params, LinkName, MacAddr, VlanId}, sys::util::netlink::Netlink, util::models::Id, wan::{model::values::WanId, query::get_host_links::WanHostLink}};
+    use super::{ipv4_addresses, link_address, link_by_name, vlan_link, ReconcileWanLink};
+
+    #[tokio::test]
+    #[ignore = "needs CAP_NET_ADMIN and the dummy and 8021q modules, run it in a namespace: unshare -rn cargo test reconciler -- --ignored"]
+    async fn reconciling_twice_converges_to_the_same_link() {
+        let netlink = Netlink::connect().unwrap();
+        let route = netlink.route();
+        route.link().add().dummy(String::from("rkdtrunk0")).execute().await.unwrap();
+        let parent = link_by_name(&route, "rkdtrunk0").await.unwrap().unwrap();
+        route.link().set(parent.header.index).up().execute().await.unwrap();
+
+        let mac = MacAddr6::new(0x02, 0, 0, 0, 0x40, 0x01);
+        let mut link = WanHostLink {
+            id: WanId(Id::new()),
+            name: String::from("movistar"),
+            parent: Some(LinkName::from_str("rkdtrunk0").unwrap()),
+            vlan: VlanId::try_from(4001).unwrap(),
+            mac: MacAddr::Spoofed(mac),
+            ipv4: Ipv4Params::Static { addr: Ipv4Addr::new(100, 64, 0, 2), mask_len: 30u8.try_into().unwrap(), gateway: Ipv4Addr::new(100, 64, 0, 1) },
+            deleted: false
+        };
+        netlink.exec(ReconcileWanLink { link: link.clone() }).await.unwrap();
+        netlink.exec(ReconcileWanLink { link: link.clone() }).await.unwrap();
+
+        let vlan = vlan_link(&route, parent.header.index, 4001).await.unwrap().unwrap();
+        assert!(link_by_name(&route, "rkdtrunk0.4001").await.unwrap().is_some());
+        assert_eq!(link_address(&vlan), Some(&mac.as_bytes().to_vec()));
+        assert_eq!(ipv4_addresses(&route, vlan.header.index).await.unwrap().len(), 1);
+
+        // Renaming the wan leaves its link alone
+        link.name = String::from("claro");
+        link.ipv4 = Ipv4Params::DHCP;
+        netlink.exec(ReconcileWanLink { link: link.clone() }).await.unwrap();
+        let same = link_by_name(&route, "rkdtrunk0.4001").await.unwrap().unwrap();
+        assert_eq!(same.header.index, vlan.header.index);
+        assert!(ipv4_addresses(&route, vlan.header.index).await.unwrap().is_empty());
+
+        link.deleted = true;
+        netlink.exec(ReconcileWanLink { link }).await.unwrap();
+        assert!(vlan_link(&route, parent.header.index, 4001).await.unwrap().is_none());
+        let links = route.link().get().execute().try_collect::<Vec<_>>().await.unwrap();
+        assert!(links.iter().any(|link| link.header.index == parent.header.index));
+        route.link().del(parent.header.index).execute().await.unwrap();
+    }
+}
@@ -1,2 +1,3 @@
 pub mod trackers;
+pub mod monitor;
 pub mod netlink;
\ No newline at end of file
@@ -0,0 +1,122 @@
+use std::net::IpAddr;
+use futures::StreamExt;
+use log::warn;
+use netlink_packet_core::NetlinkPayload;
+use netlink_packet_route::{address::{AddressAttribute, AddressMessage}, route::RouteAttribute, RouteNetlinkMessage};
+use netlink_sys::{AsyncSocket, SocketAddr};
+use rtnetlink::constants::{RTMGRP_IPV4_IFADDR, RTMGRP_IPV4_ROUTE, RTMGRP_IPV6_IFADDR, RTMGRP_IPV6_PREFIX, RTMGRP_IPV6_ROUTE, RTMGRP_LINK};
+use tokio::sync::broadcast;
+use crate::{net::LinkId, sys::link::domain::{LinkDown, LinkStatus}};
+
+const CAPACITY: usize = 1024;
+
+/// rtnetlink multicast groups (RTNLGRP_LINK, IPV4_IFADDR, IPV6_IFADDR, IPV6_PREFIX, IPV4_ROUTE and IPV6_ROUTE)
+const GROUPS: u32 = RTMGRP_LINK | RTMGRP_IPV4_IFADDR | RTMGRP_IPV6_IFADDR | RTMGRP_IPV6_PREFIX | RTMGRP_IPV4_ROUTE | RTMGRP_IPV6_ROUTE;
+
+/// Change of a link notified by the kernel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NlNotification {
+    /// The flags of the link changed, a deleted link `WentMissing`
+    Link { link: LinkId, status: LinkStatus },
+    Address { link: LinkId, addr: IpAddr, prefix_len: u8, removed: bool },
+    /// A router advertised a prefix on the link
+    Prefix { link: LinkId },
+    /// A route through the link was added or removed
+    Route { link: LinkId }
+}
+
+impl NlNotification {
+    pub fn link(&self) -> LinkId {
+        match self {
+            Self::Link { link, .. } | Self::Address { link, .. } | Self::Prefix { link } | Self::Route { link } => *link
+        }
+    }
+}
+
+/// Listens to the rtnetlink multicast groups and fans the notifications out to every subscriber,
+/// a subscriber that lags behind should read the links again instead of waiting for the next notification
+#[derive(Clone)]
+pub struct NlMonitor {
+    notifications: broadcast::Sender<NlNotification>
+}
+
+impl NlMonitor {
+    pub fn connect() -> std::io::Result<NlMonitor> {
+        let (mut connection, _, mut messages) = rtnetlink::new_connection()?;
+        connection.socket_mut().socket_mut().bind(&SocketAddr::new(0, GROUPS))?;
+        tokio::spawn(connection);
+        let (notifications, _) = broadcast::channel(CAPACITY);
+        let sender = notifications.clone();
+        tokio::spawn(async move {
+            while let Some((message, _)) = messages.next().await {
+                if let NetlinkPayload::InnerMessage(message) = message.payload && let Ok(notification) = NlNotification::try_from(message) {
+                    // No subscribers isn't an error
+                    let _ = sender.send(notification);
+                }
+            }
+            warn!("[NL_MONITOR] Netlink connection closed, links won't be notified anymore");
+        });
+        Ok(Self { notifications })
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<NlNotification> {
+        self.notifications.subscribe()
+    }
+}
+
+impl TryFrom<RouteNetlinkMessage> for NlNotification {
+    type Error = ();
+
+    fn try_from(message: RouteNetlinkMessage) -> Result<Self, Self::Error> {
+        match message {
+            RouteNetlinkMessage::NewLink(msg) => Ok(Self::Link { link: LinkId::from(msg.header.index), status: LinkStatus::from(msg.header.flags) }),
+            RouteNetlinkMessage::DelLink(msg) => Ok(Self::Link { link: LinkId::from(msg.header.index), status: LinkStatus::Down(LinkDown::WentMissing) }),
+            RouteNetlinkMessage::NewAddress(msg) => address(msg, false),
+            RouteNetlinkMessage::DelAddress(msg) => address(msg, true),
+            RouteNetlinkMessage::NewPrefix(msg) => u32::try_from(msg.header.ifindex).map(|index| Self::Prefix { link: LinkId::from(index) }).map_err(|_| ()),
+            RouteNetlinkMessage::NewRoute(msg) | RouteNetlinkMessage::DelRoute(msg) => msg.attributes.iter().find_map(|attr| match attr {
+                RouteAttribute::Oif(index) => Some(Self::Route { link: LinkId::from(*index) }),
+                _ => None
+            }).ok_or(()),
+            _ => Err(())
+        }
+    }
+}
+
+fn address(msg: AddressMessage, removed: bool) -> Result<NlNotification, ()> {
+    // Peer to peer links (ie: PPPoE) carry the local address on IFA_LOCAL and the peer on IFA_ADDRESS
+    let addr = msg.attributes.iter().find_map(|attr| match attr {
+        AddressAttribute::Local(addr) => Some(*addr),
+        _ => None
+    }).or_else(|| msg.attributes.iter().find_map(|attr| match attr {
+        AddressAttribute::Address(addr) => Some(*addr),
+        _ => None
+    }));
+    addr.map(|addr| NlNotification::Address { link: LinkId::from(msg.header.index), addr, prefix_len: msg.header.prefix_len, removed }).ok_or(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+    use netlink_packet_route::{address::{AddressAttribute, AddressMessage}, link::{LinkFlag, LinkMessage}, RouteNetlinkMessage};
+    use crate::{net::LinkId, sys::link::domain::{LinkDown, LinkStatus, LinkUp}};
+    use super::NlNotification;
+
+    #[test]
+    fn kernel_messages_become_notifications() {
+        let mut link = LinkMessage::default();
+        link.header.index = 7;
+        link.header.flags = vec![LinkFlag::Up, LinkFlag::LowerUp];
+        let notification = NlNotification::try_from(RouteNetlinkMessage::NewLink(link.clone())).unwrap();
+        assert_eq!(notification, NlNotification::Link { link: LinkId::from(7), status: LinkStatus::Up(LinkUp::Connected) });
+        let notification = NlNotification::try_from(RouteNetlinkMessage::DelLink(link)).unwrap();
+        assert_eq!(notification, NlNotification::Link { link: LinkId::from(7), status: LinkStatus::Down(LinkDown::WentMissing) });
+
+        let mut addr = AddressMessage::default();
+        addr.header.index = 7;
+        addr.header.prefix_len = 32;
+        addr.attributes = vec![AddressAttribute::Address(IpAddr::V4(Ipv4Addr::new(100, 64, 0, 1))), AddressAttribute::Local(IpAddr::V4(Ipv4Addr::new(100, 64, 0, 2)))];
+        let notification = NlNotification::try_from(RouteNetlinkMessage::DelAddress(addr)).unwrap();
+        assert_eq!(notification, NlNotification::Address { link: LinkId::from(7), addr: IpAddr::V4(Ipv4Addr::new(100, 64, 0, 2)), prefix_len: 32, removed: true });
+    }
+}
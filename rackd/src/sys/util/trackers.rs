@@ -1,17 +1,18 @@
 use std::{collections::HashMap, future::Future};
 use tokio_util::sync::CancellationToken;
-use crate::sys::link::domain::LinkId;
+use crate::net::LinkId;
 
 pub trait LinkTracker where Self: Send + 'static {
     fn link(&self) -> LinkId;
     fn work(&mut self) -> impl Future<Output = ()> + Send;
 }
 
+#[derive(Default)]
 pub struct LinkTrackers(HashMap<LinkId, Vec<TrackerHandle>>);
 
 impl LinkTrackers {
     pub fn new() -> Self {
-        Self(HashMap::new())
+        Self::default()
     }
 
     pub fn spawn<T>(&mut self, tracker: T) where T: LinkTracker {
@@ -37,6 +38,15 @@ impl LinkTrackers {
         self.0.remove(link);
     }
 
+    pub fn untrack_all(&mut self) {
+        for trackers in self.0.values() {
+            for tracker in trackers {
+                tracker.cancel();
+            }
+        }
+        self.0.clear();
+    }
+
     async fn run<T>(mut tracker: T, cancellation_token: CancellationToken) where T: LinkTracker {
         tokio::select! {
             _ = cancellation_token.cancelled() => {
@@ -49,6 +59,13 @@ impl LinkTrackers {
     }
 }
 
+/// Trackers don't outlive their owner, ie: a SysActor restarted by its supervisor starts with no trackers
+impl Drop for LinkTrackers {
+    fn drop(&mut self) {
+        self.untrack_all();
+    }
+}
+
 #[derive(Clone)]
 pub struct TrackerHandle {
     token: CancellationToken
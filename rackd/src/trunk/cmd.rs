@@ -1,7 +1,14 @@
+use serde::{Deserialize, Serialize};
 use crate::util::actor::Msg;
 pub mod create;
+pub mod rename;
+pub mod delete;
+pub mod bind;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub enum TrunkCmd {
-    Create(Msg<create::CreateTrunk>)
-}
\ No newline at end of file
+    Create(Msg<create::CreateTrunk>),
+    Rename(Msg<rename::RenameTrunk>),
+    Delete(Msg<delete::DeleteTrunk>),
+    BindInterface(Msg<bind::BindTrunkInterface>)
+}
@@ -0,0 +1,186 @@
+use field_types::FieldName;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::{EntityStore, VersionConflict}, raft::{ClusterError, NodeId}, query::traits::QueryRunner, Tx}, net::LinkName, trunk::{model::{Trunk, TrunkEvent, TrunkId}, query::GetTrunkByLink, views::TrunkView}, util::{actor::{Payload, Process}, models::Entity}};
+
+/// Picks the host interface (link) backing the trunk on a node of the rack,
+/// binding it again replaces the previous link
+#[derive(Debug, Serialize, Deserialize, ToSchema, FieldName)]
+pub struct BindTrunkInterface {
+    pub id: TrunkId,
+    #[schema(value_type = u64)]
+    pub node: NodeId,
+    pub link: LinkName
+}
+
+#[derive(Debug, Error)]
+pub enum BindTrunkInterfaceError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Conflict(#[from] VersionConflict),
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
+    #[error("Trunk with ID not found")]
+    TrunkNotFound,
+    #[error("Link is already bound to trunk {0} on this node")]
+    LinkInUse(String)
+}
+
+impl Payload for BindTrunkInterface {
+    type Ok = ();
+    type Err = BindTrunkInterfaceError;
+}
+
+impl BindTrunkInterface {
+    fn exec(&self, trunk: Option<Trunk>, link_twin: Option<TrunkView>) -> Result<Option<Trunk>, BindTrunkInterfaceError> {
+        let mut trunk = trunk.filter(|trunk| !trunk.deleted).ok_or(BindTrunkInterfaceError::TrunkNotFound)?;
+        match link_twin {
+            Some(twin) if twin.id.0 == trunk.id.0 => return Ok(None),
+            Some(twin) => Err(BindTrunkInterfaceError::LinkInUse(twin.name.to_string()))?,
+            None => {}
+        }
+        trunk.process(TrunkEvent::InterfaceBound {
+            node: self.node,
+            from: trunk.links.get(&self.node).cloned(),
+            to: self.link.clone()
+        });
+        Ok(Some(trunk))
+    }
+}
+
+impl Process for BindTrunkInterface {
+    type Actor = RackdCmdActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let trunk = tx.load(self.id)?;
+        let link_twin = tx.run(GetTrunkByLink { node: self.node, link: self.link.clone() })?;
+        self.exec(trunk, link_twin).map(|trunk| {
+            if let Some(mut trunk) = trunk {
+                tx.save(&mut trunk)?;
+            }
+            tx.commit()?;
+            Ok(())
+        })?
+    }
+}
+
+pub mod casts {
+    use crate::{actors::cmd::RackdCmd, db::cmd::traits::EntityStoreError, trunk::cmd::TrunkCmd, util::actor::Msg};
+    use super::{BindTrunkInterface, BindTrunkInterfaceError};
+
+    impl From<EntityStoreError> for BindTrunkInterfaceError {
+        fn from(error: EntityStoreError) -> Self {
+            match error {
+                EntityStoreError::Db(e) => Self::Db(e),
+                EntityStoreError::Conflict(e) => Self::Conflict(e)
+            }
+        }
+    }
+
+    impl From<Msg<BindTrunkInterface>> for RackdCmd {
+        fn from(cmd: Msg<BindTrunkInterface>) -> Self {
+            Self::Trunk(TrunkCmd::BindInterface(cmd))
+        }
+    }
+}
+
+pub mod api {
+    use std::collections::HashMap;
+    use serde_json::Value;
+    use axum::{extract::{OriginalUri, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, db::raft::NodeId, net::LinkName, trunk::model::TrunkId, util::api::{Error, Json, Response, TryFromJson}};
+    use super::{BindTrunkInterface, BindTrunkInterfaceError, BindTrunkInterfaceFieldName};
+
+    #[utoipa::path(put, path = "/trunk/bind", tag = "trunk",
+        request_body = BindTrunkInterface,
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn bind(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Json(cmd): Json<BindTrunkInterface>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.exec(cmd).await
+            .map(|_| Response::ok((), path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    fn node_id(value: Value) -> Result<NodeId, Error> {
+        match value {
+            Value::Number(ref n) => n.as_u64().ok_or(Error::new("NODE_ID_ERROR", format!("Value is not a valid Node ID [{}]", value))),
+            Value::Null => Err(Error::new("NODE_ID_ERROR", "No value provided")),
+            _ => Err(Error::new("NODE_ID_ERROR", format!("Value is not a number [{}]", value)))
+        }
+    }
+
+    impl TryFromJson for BindTrunkInterface {
+        fn try_from(mut map: HashMap<String, Value>) -> Result<Self, Vec<Error>> {
+            Self::check_keys(&map, BindTrunkInterface::as_field_name_array().map(|f| f.name()))?;
+            let id = map.remove(BindTrunkInterfaceFieldName::Id.name()).unwrap_or_default();
+            let node = map.remove(BindTrunkInterfaceFieldName::Node.name()).unwrap_or_default();
+            let link = map.remove(BindTrunkInterfaceFieldName::Link.name()).unwrap_or_default();
+
+            match (TrunkId::try_from(id), node_id(node), LinkName::try_from(link)) {
+                (Ok(id), Ok(node), Ok(link)) => Ok(Self { id, node, link }),
+                (r1, r2, r3) => {
+                    let e1 = r1.map_err(Error::from).err();
+                    let e2 = r2.err();
+                    let e3 = r3.map_err(Error::from).err();
+
+                    let errors: Vec<Error> = [e1, e2, e3].into_iter().flatten().collect();
+                    Err(errors)
+                }
+            }
+        }
+    }
+
+    impl From<BindTrunkInterfaceError> for Error {
+        fn from(error: BindTrunkInterfaceError) -> Self {
+            let msg = error.to_string();
+            match error {
+                BindTrunkInterfaceError::Db(_) => Error::new("BIND_TRUNK_INTERFACE_DB_ERROR", msg),
+                BindTrunkInterfaceError::Conflict(_) => Error::new("BIND_TRUNK_INTERFACE_CONFLICT", msg),
+                BindTrunkInterfaceError::Cluster(e) => Error::from(e),
+                BindTrunkInterfaceError::TrunkNotFound => Error::new("BIND_TRUNK_INTERFACE_TRUNK_NOT_FOUND", msg),
+                BindTrunkInterfaceError::LinkInUse(_) => Error::new("BIND_TRUNK_INTERFACE_LINK_IN_USE", msg)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::{actors::system::Rackd, event::{model::EventFilter, query::get_events::GetEvents}, net::LinkName, trunk::{cmd::{bind::{BindTrunkInterface, BindTrunkInterfaceError}, create::CreateTrunk}, model::{TrunkEvent, TrunkName}}, util::models::EventData};
+    use crate::util::test_support::init_rack;
+
+    #[tokio::test]
+    async fn cant_bind_a_link_used_by_another_trunk_on_the_same_node() {
+        let rackd = Rackd::mock().unwrap();
+        let trunk1 = init_rack(&rackd).await;
+        let cmd = CreateTrunk {
+            name: TrunkName::from_str("trunk2").unwrap()
+        };
+        let trunk2 = rackd.exec(cmd).await.unwrap();
+        rackd.exec(BindTrunkInterface { id: trunk1, node: 1, link: LinkName::from_str("eth0").unwrap() }).await.unwrap();
+        rackd.exec(BindTrunkInterface { id: trunk2, node: 2, link: LinkName::from_str("eth0").unwrap() }).await.unwrap();
+        let cmd = BindTrunkInterface { id: trunk2, node: 1, link: LinkName::from_str("eth0").unwrap() };
+        assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, BindTrunkInterfaceError::LinkInUse(name) if name == "trunk1")));
+    }
+
+    #[tokio::test]
+    async fn rebinding_replaces_the_link_of_the_node() {
+        let rackd = Rackd::mock().unwrap();
+        let trunk_id = init_rack(&rackd).await;
+        rackd.exec(BindTrunkInterface { id: trunk_id, node: 1, link: LinkName::from_str("eth0").unwrap() }).await.unwrap();
+        rackd.exec(BindTrunkInterface { id: trunk_id, node: 1, link: LinkName::from_str("eth0").unwrap() }).await.unwrap();
+        rackd.exec(BindTrunkInterface { id: trunk_id, node: 1, link: LinkName::from_str("eth1").unwrap() }).await.unwrap();
+
+        let filter = EventFilter { stream_id: Some(trunk_id.0), ..Default::default() };
+        let events = rackd.query(GetEvents { after: 0, filter, limit: 100 }).await.unwrap();
+        assert_eq!(events.len(), 3);
+        assert!(matches!(&events[2].data, EventData::Trunk(TrunkEvent::InterfaceBound { node: 1, from: Some(from), to }) if from.to_string() == "eth0" && to.to_string() == "eth1"));
+    }
+}
@@ -1,10 +1,10 @@
 use field_types::FieldName;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use utoipa::ToSchema;
-use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::EntityStore, query::traits::QueryRunner, Tx}, rack::Rack, trunk::{model::{Trunk, TrunkEvent, TrunkId, TrunkName}, query::{GetTrunkById, GetTrunkByName}, views::TrunkView}, util::{actor::{Payload, Process}, models::Entity, traits::OptionExt}};
+use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::{EntityStore, VersionConflict}, raft::ClusterError, query::traits::QueryRunner, Tx}, rack::{query::get_rack::LoadRack, Rack}, trunk::{model::{Trunk, TrunkEvent, TrunkId, TrunkName}, query::{GetTrunkById, GetTrunkByName}, views::TrunkView}, util::{actor::{Payload, Process}, models::Entity, traits::OptionExt}};
 
-#[derive(Debug, Deserialize, ToSchema, FieldName)]
+#[derive(Debug, Serialize, Deserialize, ToSchema, FieldName)]
 pub struct CreateTrunk {
     pub name: TrunkName
 }
@@ -13,8 +13,12 @@ pub struct CreateTrunk {
 pub enum CreateTrunkError {
     #[error("Db Error")]
     Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Conflict(#[from] VersionConflict),
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
     #[error("Rack hasn't been initialized")]
-    RackUninitialized,
+    RackNotFound,
     #[error("Wan Name already in use")]
     NameAlreadyInUse
 }
@@ -26,7 +30,7 @@ impl Payload for CreateTrunk {
 
 impl CreateTrunk {
     fn exec(&self, rack: Option<Rack>, name_twin_trunk: Option<TrunkView>) -> Result<Trunk, CreateTrunkError> {
-        let rack = rack.ok_or(CreateTrunkError::RackUninitialized)?;
+        let rack = rack.ok_or(CreateTrunkError::RackNotFound)?;
         name_twin_trunk.err_or(CreateTrunkError::NameAlreadyInUse)?;
         let mut trunk = Trunk::default();
         trunk.process(TrunkEvent::Created {
@@ -42,20 +46,29 @@ impl Process for CreateTrunk {
     type Actor = RackdCmdActor;
     fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
         let tx = actor.conn.tx()?;
-        // get rack from KeyValueStore
-        let rack = Some(Rack::default()); 
+        let rack = tx.run(LoadRack)?;
         let name_twin = tx.run(GetTrunkByName { name: self.name.clone() })?;
         self.exec(rack, name_twin).map(|mut trunk| {
             tx.save(&mut trunk)?;
+            tx.commit()?;
             Ok(trunk.id)
         })?
     }
 }
 
 pub mod casts {
-    use crate::{actors::cmd::RackdCmd, trunk::cmd::TrunkCmd, util::actor::Msg};
+    use crate::{actors::cmd::RackdCmd, db::cmd::traits::EntityStoreError, trunk::cmd::TrunkCmd, util::actor::Msg};
     use super::*;
 
+    impl From<EntityStoreError> for CreateTrunkError {
+        fn from(error: EntityStoreError) -> Self {
+            match error {
+                EntityStoreError::Db(e) => Self::Db(e),
+                EntityStoreError::Conflict(e) => Self::Conflict(e)
+            }
+        }
+    }
+
     impl From<Msg<CreateTrunk>> for RackdCmd {
         fn from(cmd: Msg<CreateTrunk>) -> Self {
             Self::Trunk(TrunkCmd::Create(cmd))
@@ -98,7 +111,9 @@ pub mod api {
             let msg = error.to_string();
             match error {
                 CreateTrunkError::Db(_) => Self::new("CREATE_TRUNK_DB_ERROR", msg),
-                CreateTrunkError::RackUninitialized => Self::new("CREATE_TRUNK_RACK_NOT_FOUND", msg),
+                CreateTrunkError::Conflict(_) => Self::new("CREATE_TRUNK_CONFLICT", msg),
+                CreateTrunkError::Cluster(e) => Self::from(e),
+                CreateTrunkError::RackNotFound => Self::new("CREATE_TRUNK_RACK_NOT_FOUND", msg),
                 CreateTrunkError::NameAlreadyInUse => Self::new("CREATE_TRUNK_NAME_ALREADY_IN_USE", msg)
             }
         }
@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::{EntityStore, VersionConflict}, raft::ClusterError, query::traits::QueryRunner, Tx}, net::{query::GetNetworksByTrunk, views::NetworkView}, trunk::model::{Trunk, TrunkEvent, TrunkId}, util::{actor::{Payload, Process}, models::Entity, traits::SoftDeleteExt}};
+
+/// Deletes a trunk no network rides, deleted networks included since they can still be restored onto it
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DeleteTrunk {
+    pub id: TrunkId
+}
+
+#[derive(Debug, Error)]
+pub enum DeleteTrunkError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Conflict(#[from] VersionConflict),
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
+    #[error("Trunk with ID not found")]
+    TrunkNotFound,
+    #[error("Trunk is in use by {0} network(s)")]
+    TrunkInUse(usize)
+}
+
+impl Payload for DeleteTrunk {
+    type Ok = ();
+    type Err = DeleteTrunkError;
+}
+
+impl DeleteTrunk {
+    fn exec(&self, trunk: Option<Trunk>, networks: Vec<NetworkView>) -> Result<Trunk, DeleteTrunkError> {
+        let mut trunk = trunk.live_or(DeleteTrunkError::TrunkNotFound)?;
+        if !networks.is_empty() {
+            Err(DeleteTrunkError::TrunkInUse(networks.len()))?
+        }
+        trunk.process(TrunkEvent::Deleted);
+        Ok(trunk)
+    }
+}
+
+impl Process for DeleteTrunk {
+    type Actor = RackdCmdActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let trunk = tx.load(self.id)?;
+        let networks = tx.run(GetNetworksByTrunk { trunk: self.id, include_deleted: true })?;
+        self.exec(trunk, networks).map(|mut trunk| {
+            tx.save(&mut trunk)?;
+            tx.commit()?;
+            Ok(())
+        })?
+    }
+}
+
+pub mod casts {
+    use crate::{actors::cmd::RackdCmd, db::cmd::traits::EntityStoreError, trunk::cmd::TrunkCmd, util::actor::Msg};
+    use super::{DeleteTrunk, DeleteTrunkError};
+
+    impl From<EntityStoreError> for DeleteTrunkError {
+        fn from(error: EntityStoreError) -> Self {
+            match error {
+                EntityStoreError::Db(e) => Self::Db(e),
+                EntityStoreError::Conflict(e) => Self::Conflict(e)
+            }
+        }
+    }
+
+    impl From<Msg<DeleteTrunk>> for RackdCmd {
+        fn from(cmd: Msg<DeleteTrunk>) -> Self {
+            Self::Trunk(TrunkCmd::Delete(cmd))
+        }
+    }
+}
+
+pub mod api {
+    use axum::{extract::{OriginalUri, Path, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, trunk::model::TrunkId, util::api::{Error, Response}};
+    use super::{DeleteTrunk, DeleteTrunkError};
+
+    #[utoipa::path(delete, path = "/trunk/{trunk_id}", tag = "trunk",
+        params(("trunk_id" = TrunkId, Path, description = "Trunk UUID")),
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn delete(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Path(trunk_id): Path<TrunkId>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.exec(DeleteTrunk { id: trunk_id }).await
+            .map(|_| Response::ok((), path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl From<DeleteTrunkError> for Error {
+        fn from(error: DeleteTrunkError) -> Self {
+            let msg = error.to_string();
+            match error {
+                DeleteTrunkError::Db(_) => Error::new("DELETE_TRUNK_DB_ERROR", msg),
+                DeleteTrunkError::Conflict(_) => Error::new("DELETE_TRUNK_CONFLICT", msg),
+                DeleteTrunkError::Cluster(e) => Error::from(e),
+                DeleteTrunkError::TrunkNotFound => Error::new("DELETE_TRUNK_NOT_FOUND", msg),
+                DeleteTrunkError::TrunkInUse(_) => Error::new("DELETE_TRUNK_IN_USE", msg)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::{actors::system::Rackd, lan::cmd::{create::CreateLan, delete::DeleteLan}, net::{NetName, Prefix, VlanId}, trunk::{cmd::{create::CreateTrunk, delete::{DeleteTrunk, DeleteTrunkError}}, model::TrunkName}, wan::{cmd::{delete::DeleteWan, restore::RestoreWan}, model::values::WanMode, query::get_by_key::GetWanById}};
+    use crate::util::test_support::{create_wan, init_rack};
+
+    #[tokio::test]
+    async fn cant_delete_while_networks_ride_the_trunk() {
+        let rackd = Rackd::mock().unwrap();
+        let trunk_id = init_rack(&rackd).await;
+        let cmd = CreateLan {
+            trunk: trunk_id,
+            vlan: VlanId::try_from(100).unwrap(),
+            name: NetName::from_str("lab").unwrap(),
+            prefix: Prefix::from_str("fd00:0:0:100::/64").unwrap()
+        };
+        let lan_id = rackd.exec(cmd).await.unwrap();
+        assert!(rackd.exec(DeleteTrunk { id: trunk_id }).await.is_err_and(|e| matches!(e, DeleteTrunkError::TrunkInUse(1))));
+
+        // Deleted networks still hold on to the trunk
+        rackd.exec(DeleteLan { id: lan_id }).await.unwrap();
+        assert!(rackd.exec(DeleteTrunk { id: trunk_id }).await.is_err_and(|e| matches!(e, DeleteTrunkError::TrunkInUse(1))));
+    }
+
+    #[tokio::test]
+    async fn deleted_wans_can_be_restored_onto_their_trunk() {
+        let (rackd, trunk_id, id) = create_wan(WanMode::IPoE).await;
+        rackd.exec(DeleteWan { id }).await.unwrap();
+        assert!(rackd.exec(DeleteTrunk { id: trunk_id }).await.is_err_and(|e| matches!(e, DeleteTrunkError::TrunkInUse(1))));
+        rackd.exec(RestoreWan { id }).await.unwrap();
+        assert!(!rackd.query(GetWanById { id }).await.unwrap().deleted);
+    }
+
+    #[tokio::test]
+    async fn can_delete_unused_trunk() {
+        let rackd = Rackd::mock().unwrap();
+        let trunk_id = init_rack(&rackd).await;
+        rackd.exec(DeleteTrunk { id: trunk_id }).await.unwrap();
+        assert!(rackd.exec(DeleteTrunk { id: trunk_id }).await.is_err_and(|e| matches!(e, DeleteTrunkError::TrunkNotFound)));
+    }
+
+    #[tokio::test]
+    async fn name_of_a_deleted_trunk_can_be_reused() {
+        let rackd = Rackd::mock().unwrap();
+        let trunk_id = init_rack(&rackd).await;
+        rackd.exec(DeleteTrunk { id: trunk_id }).await.unwrap();
+        let cmd = CreateTrunk {
+            name: TrunkName::from_str("trunk1").unwrap()
+        };
+        assert!(rackd.exec(cmd).await.is_ok());
+    }
+}
@@ -0,0 +1,179 @@
+use field_types::FieldName;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::{EntityStore, VersionConflict}, raft::ClusterError, query::traits::QueryRunner, Tx}, trunk::{model::{Trunk, TrunkEvent, TrunkId, TrunkName}, query::GetTrunkByName, views::TrunkView}, util::{actor::{Payload, Process}, models::Entity, traits::OptionExt}};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, FieldName)]
+pub struct RenameTrunk {
+    pub id: TrunkId,
+    pub name: TrunkName
+}
+
+#[derive(Debug, Error)]
+pub enum RenameTrunkError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Conflict(#[from] VersionConflict),
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
+    #[error("Trunk with ID not found")]
+    TrunkNotFound,
+    #[error("Trunk Name already in use")]
+    NameAlreadyInUse
+}
+
+impl Payload for RenameTrunk {
+    type Ok = ();
+    type Err = RenameTrunkError;
+}
+
+impl RenameTrunk {
+    fn exec(&self, trunk: Option<Trunk>, name_twin: Option<TrunkView>) -> Result<Trunk, RenameTrunkError> {
+        let mut trunk = trunk.filter(|trunk| !trunk.deleted).ok_or(RenameTrunkError::TrunkNotFound)?;
+        name_twin.err_or(RenameTrunkError::NameAlreadyInUse)?;
+        trunk.process(TrunkEvent::Renamed { from: trunk.name.clone(), to: self.name.clone() });
+        Ok(trunk)
+    }
+}
+
+impl Process for RenameTrunk {
+    type Actor = RackdCmdActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let trunk = tx.load(self.id)?;
+        let name_twin = tx.run(GetTrunkByName { name: self.name.clone() })?;
+        self.exec(trunk, name_twin).map(|mut trunk| {
+            tx.save(&mut trunk)?;
+            tx.commit()?;
+            Ok(())
+        })?
+    }
+}
+
+pub mod casts {
+    use crate::{actors::cmd::RackdCmd, db::cmd::traits::EntityStoreError, trunk::cmd::TrunkCmd, util::actor::Msg};
+    use super::{RenameTrunk, RenameTrunkError};
+
+    impl From<EntityStoreError> for RenameTrunkError {
+        fn from(error: EntityStoreError) -> Self {
+            match error {
+                EntityStoreError::Db(e) => Self::Db(e),
+                EntityStoreError::Conflict(e) => Self::Conflict(e)
+            }
+        }
+    }
+
+    impl From<Msg<RenameTrunk>> for RackdCmd {
+        fn from(cmd: Msg<RenameTrunk>) -> Self {
+            Self::Trunk(TrunkCmd::Rename(cmd))
+        }
+    }
+}
+
+pub mod api {
+    use std::collections::HashMap;
+    use serde_json::Value;
+    use axum::{extract::{OriginalUri, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, trunk::model::{TrunkId, TrunkName}, util::api::{Error, Json, Response, TryFromJson}};
+    use super::{RenameTrunk, RenameTrunkError, RenameTrunkFieldName};
+
+    #[utoipa::path(patch, path = "/trunk/rename", tag = "trunk",
+        request_body = RenameTrunk,
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn rename(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Json(cmd): Json<RenameTrunk>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.exec(cmd).await
+            .map(|_| Response::ok((), path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl TryFromJson for RenameTrunk {
+        fn try_from(mut map: HashMap<String, Value>) -> Result<Self, Vec<Error>> {
+            Self::check_keys(&map, RenameTrunk::as_field_name_array().map(|f| f.name()))?;
+            let id = map.remove(RenameTrunkFieldName::Id.name()).unwrap_or_default();
+            let name = map.remove(RenameTrunkFieldName::Name.name()).unwrap_or_default();
+
+            match (TrunkId::try_from(id), TrunkName::try_from(name)) {
+                (Ok(id), Ok(name)) => Ok(Self { id, name }),
+                (r1, r2) => {
+                    let e1 = r1.map_err(Error::from).err();
+                    let e2 = r2.map_err(Error::from).err();
+
+                    let errors: Vec<Error> = [e1, e2].into_iter().flatten().collect();
+                    Err(errors)
+                }
+            }
+        }
+    }
+
+    impl From<RenameTrunkError> for Error {
+        fn from(error: RenameTrunkError) -> Self {
+            let msg = error.to_string();
+            match error {
+                RenameTrunkError::Db(_) => Error::new("RENAME_TRUNK_DB_ERROR", msg),
+                RenameTrunkError::Conflict(_) => Error::new("RENAME_TRUNK_CONFLICT", msg),
+                RenameTrunkError::Cluster(e) => Error::from(e),
+                RenameTrunkError::TrunkNotFound => Error::new("RENAME_TRUNK_NOT_FOUND", msg),
+                RenameTrunkError::NameAlreadyInUse => Error::new("RENAME_TRUNK_NAME_ALREADY_IN_USE", msg)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::{actors::system::Rackd, lan::{cmd::create::CreateLan, query::get_by_key::GetLanByName}, net::{NetName, Prefix, VlanId}, trunk::{cmd::{create::CreateTrunk, rename::{RenameTrunk, RenameTrunkError}}, model::TrunkName}, wan::{cmd::create::CreateWan, model::values::WanMode, query::get_by_key::GetWanByName}};
+    use crate::util::test_support::init_rack;
+
+    #[tokio::test]
+    async fn cant_rename_if_new_name_is_already_in_use() {
+        let rackd = Rackd::mock().unwrap();
+        let trunk_id = init_rack(&rackd).await;
+        let cmd = CreateTrunk {
+            name: TrunkName::from_str("trunk2").unwrap()
+        };
+        rackd.exec(cmd).await.unwrap();
+        let cmd = RenameTrunk {
+            id: trunk_id,
+            name: TrunkName::from_str("trunk2").unwrap()
+        };
+        assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, RenameTrunkError::NameAlreadyInUse)));
+    }
+
+    #[tokio::test]
+    async fn rename_is_reflected_on_networks_riding_the_trunk() {
+        let rackd = Rackd::mock().unwrap();
+        let trunk_id = init_rack(&rackd).await;
+        let cmd = CreateLan {
+            trunk: trunk_id,
+            vlan: VlanId::try_from(100).unwrap(),
+            name: NetName::from_str("lab").unwrap(),
+            prefix: Prefix::from_str("fd00:0:0:100::/64").unwrap()
+        };
+        rackd.exec(cmd).await.unwrap();
+        let cmd = CreateWan {
+            trunk: trunk_id,
+            vlan: VlanId::try_from(1005).unwrap(),
+            name: NetName::from_str("att").unwrap(),
+            mode: WanMode::PPPoE
+        };
+        rackd.exec(cmd).await.unwrap();
+        let cmd = RenameTrunk {
+            id: trunk_id,
+            name: TrunkName::from_str("uplink").unwrap()
+        };
+        rackd.exec(cmd).await.unwrap();
+
+        let lan = rackd.query(GetLanByName { name: NetName::from_str("lab").unwrap() }).await.unwrap();
+        assert_eq!(lan.trunk.name, TrunkName::from_str("uplink").unwrap());
+        let wan = rackd.query(GetWanByName { name: NetName::from_str("att").unwrap() }).await.unwrap();
+        assert_eq!(wan.trunk.name, TrunkName::from_str("uplink").unwrap());
+    }
+}
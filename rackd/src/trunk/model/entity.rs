@@ -1,18 +1,24 @@
+use std::collections::BTreeMap;
 use serde::{Deserialize, Serialize};
-use crate::{rack::Rack, util::models::{Entity, Id, Metadata}};
+use crate::{db::raft::NodeId, net::LinkName, rack::Rack, util::models::{Entity, Id, Metadata, SoftDelete}};
 use super::{TrunkId, TrunkName};
 
+/// `links` holds the host interface backing the trunk on each node of the rack
 #[derive(Default, Debug, Serialize, Deserialize, Clone)]
 pub struct Trunk {
     pub meta: Metadata,
     pub id: TrunkId,
     pub name: TrunkName,
+    pub links: BTreeMap<NodeId, LinkName>,
+    pub deleted: bool
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum TrunkEvent {
     Created { rack: Rack, id: TrunkId, name: TrunkName },
-    Renamed { from: TrunkName, to: TrunkName }
+    Renamed { from: TrunkName, to: TrunkName },
+    InterfaceBound { node: NodeId, from: Option<LinkName>, to: LinkName },
+    Deleted
 }
 
 impl Entity for Trunk {
@@ -34,7 +40,19 @@ impl Entity for Trunk {
             },
             TrunkEvent::Renamed { to, .. } => {
                 self.name = to.clone();
+            },
+            TrunkEvent::InterfaceBound { node, to, .. } => {
+                self.links.insert(*node, to.clone());
+            },
+            TrunkEvent::Deleted => {
+                self.deleted = true;
             }
         }
     }
-}
\ No newline at end of file
+}
+
+impl SoftDelete for Trunk {
+    fn deleted(&self) -> bool {
+        self.deleted
+    }
+}
@@ -12,6 +12,12 @@ impl TrunkId {
     }
 }
 
+impl Display for TrunkId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "trunk with id: {}", self.0)
+    }
+}
+
 #[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone, ToSchema)]
 pub struct TrunkName(String);
 
@@ -1,2 +1,13 @@
+use crate::util::actor::Msg;
+pub mod get_all;
 pub mod get_by_key;
-pub use get_by_key::*;
\ No newline at end of file
+pub mod get_networks;
+pub use get_by_key::*;
+
+#[derive(Debug)]
+pub enum TrunkQuery {
+    GetAllTrunks(Msg<get_all::GetAllTrunks>),
+    GetTrunkById(Msg<get_by_key::GetTrunkById>),
+    GetTrunkByName(Msg<get_by_key::GetTrunkByName>),
+    GetTrunkNetworks(Msg<get_networks::GetTrunkNetworks>)
+}
@@ -0,0 +1,75 @@
+use std::marker::PhantomData;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use crate::{actors::query::RackdQueryActor, db::{query::traits::{DbQuery, GetAll}, Tx}, trunk::views::TrunkView, util::actor::{Payload, Process}};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetAllTrunks;
+
+#[derive(Debug, Error)]
+pub enum GetAllTrunksError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error)
+}
+
+impl Payload for GetAllTrunks {
+    type Ok = Vec<TrunkView>;
+    type Err = GetAllTrunksError;
+}
+
+impl DbQuery for GetAllTrunks {
+    type Ok = Vec<TrunkView>;
+
+    fn run(&self, tx: &rusqlite::Transaction) -> Result<Self::Ok, rusqlite::Error> {
+        let mut trunks = GetAll { view: PhantomData::<TrunkView> }.run(tx)?;
+        trunks.sort_by_key(|trunk| trunk.name.to_string());
+        Ok(trunks)
+    }
+}
+
+impl Process for GetAllTrunks {
+    type Actor = RackdQueryActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        Ok(self.run(&tx)?)
+    }
+}
+
+pub mod casts {
+    use crate::{actors::query::RackdQuery, trunk::query::TrunkQuery, util::actor::Msg};
+    use super::GetAllTrunks;
+
+    impl From<Msg<GetAllTrunks>> for RackdQuery {
+        fn from(query: Msg<GetAllTrunks>) -> Self {
+            Self::Trunk(TrunkQuery::GetAllTrunks(query))
+        }
+    }
+}
+
+pub mod api {
+    use axum::{extract::{OriginalUri, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, trunk::views::TrunkView, util::api::{Error, Response}};
+    use super::{GetAllTrunks, GetAllTrunksError};
+
+    #[utoipa::path(get, path = "/trunk", tag = "trunk",
+        responses((status = OK, body = Response<Vec<TrunkView>>))
+    )]
+    #[axum::debug_handler]
+    pub async fn get_all_trunks(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.query(GetAllTrunks).await
+            .map(|trunks| Response::ok(trunks, path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl From<GetAllTrunksError> for Error {
+        fn from(error: GetAllTrunksError) -> Self {
+            let msg = error.to_string();
+            match error {
+                GetAllTrunksError::Db(_) => Error::new("GET_ALL_TRUNKS_DB_ERROR", msg)
+            }
+        }
+    }
+}
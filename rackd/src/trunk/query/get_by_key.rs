@@ -1,7 +1,9 @@
 use std::marker::PhantomData;
-use rusqlite::Transaction;
-use crate::{actors::cmd::RackdCmdActor, db::{query::traits::{DbQuery, GetByKey}, Tx}, trunk::{model::{TrunkId, TrunkName}, views::TrunkView}, util::actor::{Payload, Process}};
+use rusqlite::{named_params, Transaction};
+use serde::{Deserialize, Serialize};
+use crate::{actors::query::RackdQueryActor, db::{query::traits::{DbQuery, DbView, GetByKey}, raft::NodeId, Tx}, net::LinkName, trunk::{model::{TrunkId, TrunkName}, views::TrunkView}, util::{actor::{Payload, Process}, query::GetByKeyError}};
 
+#[derive(Debug, Serialize, Deserialize)]
 pub struct GetTrunkById {
     pub id: TrunkId
 }
@@ -20,19 +22,21 @@ impl DbQuery for GetTrunkById {
 }
 
 impl Payload for GetTrunkById {
-    type Ok = Option<TrunkView>;
-    type Err = rusqlite::Error;
+    type Ok = TrunkView;
+    type Err = GetByKeyError<TrunkId>;
 }
 
 impl Process for GetTrunkById {
-    type Actor = RackdCmdActor;
+    type Actor = RackdQueryActor;
 
     fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
         let tx = actor.conn.tx()?;
-        self.run(&tx)
+        let trunk = self.run(&tx)?;
+        trunk.ok_or(GetByKeyError::NotFound(self.id))
     }
 }
 
+#[derive(Debug, Serialize, Deserialize)]
 pub struct GetTrunkByName {
     pub name: TrunkName
 }
@@ -51,15 +55,99 @@ impl DbQuery for GetTrunkByName {
 }
 
 impl Payload for GetTrunkByName {
-    type Ok = Option<TrunkView>;
-    type Err = rusqlite::Error;
+    type Ok = TrunkView;
+    type Err = GetByKeyError<TrunkName>;
 }
 
 impl Process for GetTrunkByName {
-    type Actor = RackdCmdActor;
+    type Actor = RackdQueryActor;
 
     fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
         let tx = actor.conn.tx()?;
-        self.run(&tx)
+        let trunk = self.run(&tx)?;
+        trunk.ok_or(GetByKeyError::NotFound(self.name))
     }
-}
\ No newline at end of file
+}
+
+/// Trunk backed by the given link on a node
+pub struct GetTrunkByLink {
+    pub node: NodeId,
+    pub link: LinkName
+}
+
+impl DbQuery for GetTrunkByLink {
+    type Ok = Option<TrunkView>;
+
+    fn run(&self, tx: &Transaction) -> Result<Self::Ok, rusqlite::Error> {
+        let sql = format!("{} WHERE json_extract(links, '$.\"' || :node || '\"') = :link AND deleted = :deleted", TrunkView::sql_select());
+        match tx.query_row(&sql, named_params! { ":node": self.node, ":link": self.link, ":deleted": false }, <TrunkView as DbView>::try_from) {
+            Ok(trunk) => Ok(Some(trunk)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e)
+        }
+    }
+}
+
+pub mod casts {
+    use crate::{actors::query::RackdQuery, trunk::query::TrunkQuery, util::actor::Msg};
+    use super::{GetTrunkById, GetTrunkByName};
+
+    impl From<Msg<GetTrunkById>> for RackdQuery {
+        fn from(query: Msg<GetTrunkById>) -> Self {
+            Self::Trunk(TrunkQuery::GetTrunkById(query))
+        }
+    }
+
+    impl From<Msg<GetTrunkByName>> for RackdQuery {
+        fn from(query: Msg<GetTrunkByName>) -> Self {
+            Self::Trunk(TrunkQuery::GetTrunkByName(query))
+        }
+    }
+}
+
+pub mod api {
+    use axum::{extract::{OriginalUri, Path, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, trunk::{model::TrunkId, views::TrunkView}, util::api::Response};
+    use super::GetTrunkById;
+
+    #[utoipa::path(get, path = "/trunk/{trunk_id}", tag = "trunk",
+        params(("trunk_id" = TrunkId, Path, description = "Trunk UUID")),
+        responses((status = OK, body = Response<TrunkView>))
+    )]
+    #[axum::debug_handler]
+    pub async fn get_trunk_by_id(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Path(trunk_id): Path<TrunkId>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.query(GetTrunkById { id: trunk_id }).await
+            .map(|trunk| Response::ok(trunk, path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::{actors::system::Rackd, net::LinkName, trunk::{cmd::{bind::BindTrunkInterface, delete::DeleteTrunk}, model::{TrunkId, TrunkName}, query::{GetTrunkById, GetTrunkByName}}, util::query::GetByKeyError};
+    use crate::util::test_support::init_rack;
+
+    #[tokio::test]
+    async fn cant_get_trunk_if_it_doesnt_exist() {
+        let rackd = Rackd::mock().unwrap();
+        assert!(rackd.query(GetTrunkById { id: TrunkId::new() }).await.is_err_and(|e| matches!(e, GetByKeyError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn can_get_trunk_with_its_links() {
+        let rackd = Rackd::mock().unwrap();
+        let trunk_id = init_rack(&rackd).await;
+        rackd.exec(BindTrunkInterface { id: trunk_id, node: 1, link: LinkName::from_str("eth0").unwrap() }).await.unwrap();
+        rackd.exec(BindTrunkInterface { id: trunk_id, node: 2, link: LinkName::from_str("enp1s0").unwrap() }).await.unwrap();
+
+        let trunk = rackd.query(GetTrunkByName { name: TrunkName::from_str("trunk1").unwrap() }).await.unwrap();
+        assert_eq!(trunk.id.0, trunk_id.0);
+        assert_eq!(trunk.links.get(&2), Some(&LinkName::from_str("enp1s0").unwrap()));
+
+        rackd.exec(DeleteTrunk { id: trunk_id }).await.unwrap();
+        assert!(rackd.query(GetTrunkById { id: trunk_id }).await.is_err_and(|e| matches!(e, GetByKeyError::NotFound(_))));
+    }
+}
@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+use crate::{actors::query::RackdQueryActor, db::{query::traits::QueryRunner, Tx}, net::{query::GetNetworksByTrunk, views::NetworkView, VlanId}, trunk::{model::TrunkId, query::GetTrunkById}, util::{actor::{Payload, Process}, query::GetByKeyError}};
+
+/// Lans and Wans riding a trunk, optionally only the ones on a VLAN
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetTrunkNetworks {
+    pub id: TrunkId,
+    pub vlan: Option<VlanId>
+}
+
+impl Payload for GetTrunkNetworks {
+    type Ok = Vec<NetworkView>;
+    type Err = GetByKeyError<TrunkId>;
+}
+
+impl Process for GetTrunkNetworks {
+    type Actor = RackdQueryActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        if tx.run(GetTrunkById { id: self.id })?.is_none() {
+            Err(GetByKeyError::NotFound(self.id))?
+        }
+        let networks = tx.run(GetNetworksByTrunk { trunk: self.id, include_deleted: false })?;
+        Ok(match self.vlan {
+            Some(vlan) => networks.into_iter().filter(|network| network.vlan == vlan).collect(),
+            None => networks
+        })
+    }
+}
+
+pub mod casts {
+    use crate::{actors::query::RackdQuery, trunk::query::TrunkQuery, util::actor::Msg};
+    use super::GetTrunkNetworks;
+
+    impl From<Msg<GetTrunkNetworks>> for RackdQuery {
+        fn from(query: Msg<GetTrunkNetworks>) -> Self {
+            Self::Trunk(TrunkQuery::GetTrunkNetworks(query))
+        }
+    }
+}
+
+pub mod api {
+    use axum::{extract::{OriginalUri, Path, Query, State}, response::IntoResponse};
+    use serde::Deserialize;
+    use utoipa::IntoParams;
+    use crate::{actors::system::Rackd, net::{views::NetworkView, VlanId}, trunk::model::TrunkId, util::api::Response};
+    use super::GetTrunkNetworks;
+
+    #[derive(Debug, Deserialize, IntoParams)]
+    #[into_params(parameter_in = Query)]
+    pub struct VlanParams {
+        /// Only networks on this VLAN
+        #[param(value_type = Option<u16>)]
+        pub vlan: Option<VlanId>
+    }
+
+    #[utoipa::path(get, path = "/trunk/{trunk_id}/networks", tag = "trunk",
+        params(("trunk_id" = TrunkId, Path, description = "Trunk UUID"), VlanParams),
+        responses((status = OK, body = Response<Vec<NetworkView>>))
+    )]
+    #[axum::debug_handler]
+    pub async fn get_trunk_networks(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Path(trunk_id): Path<TrunkId>, Query(params): Query<VlanParams>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.query(GetTrunkNetworks { id: trunk_id, vlan: params.vlan }).await
+            .map(|networks| Response::ok(networks, path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::{actors::system::Rackd, lan::cmd::create::CreateLan, net::{NetName, Prefix, VlanId}, trunk::{cmd::create::CreateTrunk, model::TrunkName, query::{get_all::GetAllTrunks, get_networks::GetTrunkNetworks}}, wan::{cmd::create::CreateWan, model::values::WanMode}};
+    use crate::util::test_support::init_rack;
+
+    #[tokio::test]
+    async fn can_list_networks_riding_a_trunk() {
+        let rackd = Rackd::mock().unwrap();
+        let trunk_id = init_rack(&rackd).await;
+        let cmd = CreateLan {
+            trunk: trunk_id,
+            vlan: VlanId::try_from(100).unwrap(),
+            name: NetName::from_str("lab").unwrap(),
+            prefix: Prefix::from_str("fd00:0:0:100::/64").unwrap()
+        };
+        rackd.exec(cmd).await.unwrap();
+        let cmd = CreateWan {
+            trunk: trunk_id,
+            vlan: VlanId::try_from(1005).unwrap(),
+            name: NetName::from_str("att").unwrap(),
+            mode: WanMode::PPPoE
+        };
+        rackd.exec(cmd).await.unwrap();
+
+        let networks = rackd.query(GetTrunkNetworks { id: trunk_id, vlan: None }).await.unwrap();
+        assert_eq!(networks.len(), 2);
+        let networks = rackd.query(GetTrunkNetworks { id: trunk_id, vlan: Some(VlanId::try_from(1005).unwrap()) }).await.unwrap();
+        assert_eq!(networks.len(), 1);
+        assert_eq!(networks[0].name, NetName::from_str("att").unwrap());
+    }
+
+    #[tokio::test]
+    async fn can_list_trunks() {
+        let rackd = Rackd::mock().unwrap();
+        init_rack(&rackd).await;
+        let cmd = CreateTrunk {
+            name: TrunkName::from_str("trunk0").unwrap()
+        };
+        rackd.exec(cmd).await.unwrap();
+        let trunks = rackd.query(GetAllTrunks).await.unwrap();
+        assert_eq!(trunks.iter().map(|t| t.name.to_string()).collect::<Vec<_>>(), vec!["trunk0", "trunk1"]);
+    }
+}
@@ -1,16 +1,19 @@
-use log::error;
-use rusqlite::{named_params, params, Row, Transaction};
+use std::collections::BTreeMap;
+use rusqlite::{named_params, params, types::Type, Row, Transaction};
 use serde::{Deserialize, Serialize};
-use crate::{db::query::traits::DbView, util::models::{Event, EventData}};
+use utoipa::ToSchema;
+use crate::{db::{query::traits::DbView, raft::NodeId}, net::LinkName, util::models::{Event, EventData}};
 use super::model::{TrunkEvent, TrunkId, TrunkName};
 
-#[derive(Debug, Serialize, Deserialize)]
+/// `links` maps every node of the rack to the host interface backing the trunk
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
 pub struct TrunkView {
     pub id: TrunkId,
-    pub name: TrunkName
+    pub name: TrunkName,
+    pub links: BTreeMap<NodeId, LinkName>
 }
 
-#[derive(Default, Debug, Serialize, Deserialize)]
+#[derive(Default, Debug, Serialize, Deserialize, ToSchema)]
 pub struct TrunkIdView {
     pub id: TrunkId,
     pub name: TrunkName
@@ -21,31 +24,53 @@ impl DbView for TrunkView {
         "trunk_view"
     }
 
-    fn update(tx: &Transaction, e: &Event) {
+    fn update(tx: &Transaction, e: &Event) -> Result<(), rusqlite::Error> {
         match &e.data {
             EventData::Trunk(data) => match data {
-                TrunkEvent::Created { rack, id, name } => {
-                    let sql = format!("INSERT INTO {} (id, name) VALUES (?1, ?2)", Self::name());
-                    tx.execute(&sql, params![e.stream_id, name]).map_err(|e| error!("{e}")).unwrap();
+                TrunkEvent::Created { name, .. } => {
+                    let sql = format!("INSERT INTO {} (id, name) VALUES (?1, ?2)", <Self as DbView>::name());
+                    tx.execute(&sql, params![e.stream_id, name])?;
                 },
                 TrunkEvent::Renamed { to, .. } => {
-                    let sql = format!("UPDATE {} SET name = :name WHERE id = :id", Self::name());
-                    tx.execute(&sql, named_params! { ":id": e.stream_id, ":name": to }).map_err(|e| error!("{e}")).unwrap();
+                    let sql = format!("UPDATE {} SET name = :name WHERE id = :id", <Self as DbView>::name());
+                    tx.execute(&sql, named_params! { ":id": e.stream_id, ":name": to })?;
+                },
+                TrunkEvent::InterfaceBound { node, to, .. } => {
+                    let sql = format!("UPDATE {} SET links = json_set(links, '$.\"' || :node || '\"', :link) WHERE id = :id", <Self as DbView>::name());
+                    tx.execute(&sql, named_params! { ":id": e.stream_id, ":node": node, ":link": to })?;
+                },
+                TrunkEvent::Deleted => {
+                    let sql = format!("UPDATE {} SET deleted = 1 WHERE id = :id", <Self as DbView>::name());
+                    tx.execute(&sql, named_params! { ":id": e.stream_id })?;
                 }
             },
             _ => {}
         }
+        Ok(())
+    }
+
+    fn sql_create() -> &'static str {
+        r#"
+        CREATE TABLE IF NOT EXISTS trunk_view (
+            id              TEXT        PRIMARY KEY,
+            name            TEXT        NOT NULL,
+            links           TEXT        NOT NULL DEFAULT '{}',
+            deleted         INTEGER     NOT NULL DEFAULT 0
+        )
+        "#
     }
 
     fn select_fields() -> &'static str {
-        "id, name"
+        "id, name, links"
     }
 
     
     fn try_from(row: &Row) -> Result<Self, rusqlite::Error> {
+        let links: String = row.get(2)?;
         Ok(Self {
             id: TrunkId(row.get(0)?),
-            name: row.get(1)?
+            name: row.get(1)?,
+            links: serde_json::from_str(&links).map_err(|e| rusqlite::Error::FromSqlConversionFailure(2, Type::Text, Box::new(e)))?
         })
     }
 }
\ No newline at end of file
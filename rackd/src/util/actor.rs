@@ -1,7 +1,40 @@
+use std::cell::Cell;
 use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use futures::FutureExt;
+use log::error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use tokio::sync::oneshot;
 use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
+use crate::util::models::Id;
+
+tokio::task_local! {
+    // Set by the caller (e.g. the API request id) for every message it sends
+    static TASK_ORIGIN: Id;
+}
+
+thread_local! {
+    // Origin of the message a synchronous Actor is processing
+    static ACTOR_ORIGIN: Cell<Option<Id>> = const { Cell::new(None) };
+}
+
+/// Id of the request or actor that originated the work currently being done
+pub fn origin() -> Option<Id> {
+    TASK_ORIGIN.try_with(|id| *id).ok().or_else(|| ACTOR_ORIGIN.get())
+}
+
+/// Runs the future with the given origin, every message sent through a Handle inherits it
+pub async fn with_origin<F>(origin: Id, f: F) -> F::Output where F: Future {
+    TASK_ORIGIN.scope(origin, f).await
+}
+
+/// Message along with the origin of the sender
+#[derive(Debug)]
+pub struct Envelope<M> {
+    pub origin: Option<Id>,
+    pub message: M
+}
 
 // pub fn spawn<A: Actor + Send + 'static>(actor: A) -> Handle<A::Message> {
 //     let (tx, rx) = mpsc::channel::<A::Message>(10);
@@ -16,17 +49,23 @@ pub trait Actor {
     
     fn receive(&mut self, message: Self::Message);
 
+    /// Processes the message on behalf of its origin
+    fn deliver(&mut self, envelope: Envelope<Self::Message>) {
+        ACTOR_ORIGIN.set(envelope.origin);
+        self.receive(envelope.message);
+    }
+
     fn spawn<T: Actor + Send + 'static>(actor: T) -> Handle<T::Message> {
-        let (tx, rx) = mpsc::channel::<T::Message>(10);
+        let (tx, rx) = mpsc::channel::<Envelope<T::Message>>(10);
         let handle = Handle { sender: tx };
         tokio::task::spawn_blocking(T::run(actor, rx));
         handle
     }
 
-    fn run<T: Actor + Send + 'static>(mut actor: T, mut receiver: mpsc::Receiver<T::Message>) -> impl FnOnce() -> () + Send + 'static {
+    fn run<T: Actor + Send + 'static>(mut actor: T, mut receiver: mpsc::Receiver<Envelope<T::Message>>) -> impl FnOnce() + Send + 'static {
         move || { 
-            while let Some(message) = receiver.blocking_recv() {
-                actor.receive(message);
+            while let Some(envelope) = receiver.blocking_recv() {
+                actor.deliver(envelope);
             } 
         }
     }
@@ -43,9 +82,9 @@ pub trait Actor {
 /// Actor that asyncronously receives and processes messages
 pub trait AsyncActor {
     type Message: Send + 'static;
-    fn receive(&mut self, message: Self::Message) -> impl Future<Output=()>;
+    fn receive(&mut self, message: Self::Message) -> impl Future<Output=()> + Send;
 
-    async fn run<M, T>(actor: T, receiver: mpsc::Receiver<M>, cancel: CancellationToken) where T: AsyncActor<Message=M>, M: Send + 'static {
+    async fn run<M, T>(actor: T, receiver: mpsc::Receiver<Envelope<M>>, cancel: CancellationToken) where T: AsyncActor<Message=M>, M: Send + 'static {
         tokio::select! {
             _ = cancel.cancelled() => {
                 // Log termination
@@ -55,12 +94,45 @@ pub trait AsyncActor {
             }
         }
     
-        async fn work<M, T>(mut actor: T, mut receiver: mpsc::Receiver<M>) where T: AsyncActor<Message=M>, M: Send + 'static {
-            while let Some(message) = receiver.recv().await {
-                actor.receive(message).await;
+        async fn work<M, T>(mut actor: T, mut receiver: mpsc::Receiver<Envelope<M>>) where T: AsyncActor<Message=M>, M: Send + 'static {
+            while let Some(envelope) = receiver.recv().await {
+                match envelope.origin {
+                    Some(origin) => with_origin(origin, actor.receive(envelope.message)).await,
+                    None => actor.receive(envelope.message).await
+                }
             }    
         }
     }
+
+    /// Runs the actor built by `init` and replaces it with a new one whenever it panics while processing a message,
+    /// the message that caused the panic is dropped (its sender is never responded to)
+    fn supervise<M, T, F>(init: F, receiver: mpsc::Receiver<Envelope<M>>, cancel: CancellationToken) -> impl Future<Output=()> + Send
+        where T: AsyncActor<Message=M> + Send, F: Fn() -> T + Send, M: Send + 'static {
+        async fn work<M, T, F>(init: F, mut receiver: mpsc::Receiver<Envelope<M>>) where T: AsyncActor<Message=M>, F: Fn() -> T, M: Send + 'static {
+            let mut actor = init();
+            while let Some(envelope) = receiver.recv().await {
+                let result = match envelope.origin {
+                    Some(origin) => AssertUnwindSafe(with_origin(origin, actor.receive(envelope.message))).catch_unwind().await,
+                    None => AssertUnwindSafe(actor.receive(envelope.message)).catch_unwind().await
+                };
+                if result.is_err() {
+                    error!("[SUPERVISOR] Actor panicked while processing a message, restarting it");
+                    actor = init();
+                }
+            }
+        }
+
+        async move {
+            tokio::select! {
+                _ = cancel.cancelled() => {
+                    // Log termination
+                }
+                _ = work(init, receiver) => {
+                    // Work Terminated
+                }
+            }
+        }
+    }
 }
 
 pub trait Payload {
@@ -75,6 +147,21 @@ pub struct Msg<P> where P: Payload {
     pub respond_to: oneshot::Sender<Result<P::Ok, P::Err>>
 }
 
+/// Only the payload is serialized, a deserialized Msg has nobody to respond to
+impl<P> Serialize for Msg<P> where P: Payload + Serialize {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        self.payload.serialize(serializer)
+    }
+}
+
+impl<'de, P> Deserialize<'de> for Msg<P> where P: Payload + Deserialize<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        let payload = P::deserialize(deserializer)?;
+        let (respond_to, _) = oneshot::channel();
+        Ok(Self { payload, respond_to })
+    }
+}
+
 pub trait Process: Payload {
     type Actor;
     fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err>;
@@ -86,7 +173,7 @@ pub trait AsyncProcess: Payload {
 }
 
 pub struct Handle<M> {
-    pub sender: mpsc::Sender<M>
+    pub sender: mpsc::Sender<Envelope<M>>
 }
 
 impl<M> Handle<M>  {
@@ -94,7 +181,7 @@ impl<M> Handle<M>  {
     pub async fn send<P>(&self, payload: P) -> Result<P::Ok, P::Err> where P: Payload, M: From<Msg<P>>  {
         let (sender, receiver) = oneshot::channel::<Result<P::Ok, P::Err>>();
         let msg  = Msg { payload, respond_to: sender };
-        let message: M = msg.into();
+        let message = Envelope { origin: origin(), message: M::from(msg) };
         let _ = self.sender.send(message).await;
         receiver.await.expect("Actor has been killed - send()")
     }
@@ -103,7 +190,7 @@ impl<M> Handle<M>  {
     pub fn blocking_send<P>(&self, payload: P) -> Result<P::Ok, P::Err> where P: Payload, M: From<Msg<P>>  {
         let (sender, receiver) = oneshot::channel::<Result<P::Ok, P::Err>>();
         let msg  = Msg { payload, respond_to: sender };
-        let message: M = msg.into();
+        let message = Envelope { origin: origin(), message: M::from(msg) };
         let _ = self.sender.blocking_send(message);
         receiver.blocking_recv().expect("Actor has been killed - blocking_send()")
     }
@@ -112,14 +199,14 @@ impl<M> Handle<M>  {
     pub async fn emit<P>(&self, payload: P) -> () where P: Payload, M: From<Msg<P>> {
         let (sender, receiver) = oneshot::channel::<Result<P::Ok, P::Err>>();
         let msg  = Msg { payload, respond_to: sender };
-        let message: M = msg.into();
+        let message = Envelope { origin: origin(), message: M::from(msg) };
         let _ = self.sender.send(message).await;
     } 
 
     pub fn blocking_emit<P>(&self, payload: P) -> () where P: Payload, M: From<Msg<P>> {
         let (sender, receiver) = oneshot::channel::<Result<P::Ok, P::Err>>();
         let msg  = Msg { payload, respond_to: sender };
-        let message: M = msg.into();
+        let message = Envelope { origin: origin(), message: M::from(msg) };
         let _ = self.sender.blocking_send(message);
     } 
 }
@@ -198,4 +285,42 @@ impl<M> Clone for Handle<M> {
 //     fn clone(&self) -> Self {
 //         Self { sender: self.sender.clone(), token: self.token.clone() }
 //     }
-// } 
\ No newline at end of file
+// } 
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc;
+    use tokio_util::sync::CancellationToken;
+    use super::{AsyncActor, Handle, Msg, Payload};
+
+    struct Counter(u32);
+
+    struct Increment { panic: bool }
+
+    impl Payload for Increment {
+        type Ok = u32;
+        type Err = ();
+    }
+
+    impl AsyncActor for Counter {
+        type Message = Msg<Increment>;
+
+        async fn receive(&mut self, msg: Self::Message) {
+            if msg.payload.panic {
+                panic!("Increment failed");
+            }
+            self.0 += 1;
+            let _ = msg.respond_to.send(Ok(self.0));
+        }
+    }
+
+    #[tokio::test]
+    async fn supervised_actors_are_restarted_after_a_panic() {
+        let (sender, receiver) = mpsc::channel(10);
+        tokio::spawn(Counter::supervise(|| Counter(0), receiver, CancellationToken::new()));
+        let handle = Handle { sender };
+        assert_eq!(handle.send(Increment { panic: false }).await, Ok(1));
+        handle.emit(Increment { panic: true }).await;
+        // The restarted actor starts from scratch
+        assert_eq!(handle.send(Increment { panic: false }).await, Ok(1));
+    }
+}
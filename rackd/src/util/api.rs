@@ -1,9 +1,10 @@
-use std::{collections::{HashMap, HashSet}, default, fmt::Display, marker::PhantomData, time::SystemTime};
+use std::{collections::{HashMap, HashSet}, default, fmt::Display, marker::PhantomData, str::FromStr, time::SystemTime};
 use axum::{body::Bytes, extract::{FromRequest, FromRequestParts, OriginalUri}, http::{header, StatusCode}};
 use serde::Serialize;
 use serde_json::Value;
 use thiserror::Error;
 use utoipa::ToSchema;
+use crate::util::{actor, models::Id};
 
 #[derive(Debug, Default, Serialize, ToSchema)]
 pub struct Error {
@@ -236,6 +237,22 @@ impl<T, S> FromRequest<S> for Json<T> where S: Send + Sync, T: TryFromJson {
 
 
 
+pub const REQUEST_ID: &str = "x-request-id";
+
+/// Middleware tagging every request with an id (taken from the X-Request-Id header when it's a valid UUID),
+/// events stored while handling the request record it as their origin
+pub async fn request_id(req: axum::extract::Request, next: axum::middleware::Next) -> axum::response::Response {
+    let id = req.headers().get(REQUEST_ID)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| Id::from_str(v).ok())
+        .unwrap_or_else(Id::new);
+    let mut response = actor::with_origin(id, next.run(req)).await;
+    if let Ok(value) = header::HeaderValue::from_str(&id.to_string()) {
+        response.headers_mut().insert(REQUEST_ID, value);
+    }
+    response
+}
+
 pub fn is_json_content_type(content_type: &axum::http::HeaderValue) -> bool { 
     let content_type = if let Ok(content_type) = content_type.to_str() {
         content_type
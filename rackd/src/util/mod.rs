@@ -2,4 +2,7 @@ pub mod actor;
 pub mod models;
 pub mod traits;
 pub mod api;
-pub mod query;
\ No newline at end of file
+pub mod query;
+pub mod secret;
+#[cfg(test)]
+pub mod test_support;
\ No newline at end of file
@@ -1,8 +1,9 @@
-use std::fmt::Display;
+use std::{cell::Cell, fmt::Display};
+use chrono::{DateTime, Utc};
 use utoipa::ToSchema;
 use uuid::Uuid;
 use serde::{Deserialize, Serialize};
-use crate::{trunk::model::TrunkEvent, wan::model::entity::WanEvent};
+use crate::{lan::model::entity::LanEvent, org::model::OrgEvent, rack::RackEvent, trunk::model::TrunkEvent, wan::model::entity::WanEvent};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Metadata {
@@ -35,22 +36,34 @@ pub trait Entity where Self::E: Into<EventData> {
     }    
 }
 
+/// Entities whose Deleted event only marks them as deleted, they're kept so they can be restored
+pub trait SoftDelete: Entity {
+    fn deleted(&self) -> bool;
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Event {
     pub id: Id,
-    // pub seq: u32,
+    /// Position in the event log, assigned when the event is stored
+    pub seq: u64,
     pub stream_id: Id,
     pub data: EventData,
-    pub version: u32
+    pub version: u32,
+    pub timestamp: DateTime<Utc>,
+    /// Request or actor that caused the event
+    pub origin: Option<Id>
 }
 
 impl Event {
     pub fn single(stream_id: Id, inner_event: EventData, current_version: u32) -> Self {
         Self {
             id: Id::new(),
+            seq: 0,
             stream_id,
             data: inner_event,
-            version: current_version + 1
+            version: current_version + 1,
+            timestamp: now(),
+            origin: crate::util::actor::origin()
         }
     }
 
@@ -69,15 +82,88 @@ impl Event {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum EventData {
     Wan(WanEvent),
-    Trunk(TrunkEvent)
+    Lan(LanEvent),
+    Trunk(TrunkEvent),
+    Org(OrgEvent),
+    Rack(RackEvent)
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy, ToSchema)]
+pub enum EventKind {
+    Wan,
+    Lan,
+    Trunk,
+    Org,
+    Rack
+}
+
+impl EventData {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            Self::Wan(_) => EventKind::Wan,
+            Self::Lan(_) => EventKind::Lan,
+            Self::Trunk(_) => EventKind::Trunk,
+            Self::Org(_) => EventKind::Org,
+            Self::Rack(_) => EventKind::Rack
+        }
+    }
+}
+
+impl Display for EventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, ToSchema)]
 pub struct Id(Uuid);
 
+thread_local! {
+    // (seed, ids generated so far, timestamp) while running a seeded closure
+    static SEED: Cell<Option<(u128, u64, DateTime<Utc>)>> = const { Cell::new(None) };
+}
+
+/// Runs the closure deriving every new Id and timestamp from the seed,
+/// replicas applying the same command this way produce the exact same events
+pub fn seeded<T>(seed: Id, timestamp: DateTime<Utc>, f: impl FnOnce() -> T) -> T {
+    let previous = SEED.replace(Some((seed.0.as_u128(), 0, timestamp)));
+    let result = f();
+    SEED.set(previous);
+    result
+}
+
+/// Current time, or the seeded timestamp within `seeded()`
+pub fn now() -> DateTime<Utc> {
+    SEED.get().map(|(_, _, timestamp)| timestamp).unwrap_or_else(Utc::now)
+}
+
 impl Id {
     pub fn new() -> Self {
-        Self(Uuid::new_v4())
+        match SEED.get() {
+            Some((seed, n, timestamp)) => {
+                SEED.set(Some((seed, n + 1, timestamp)));
+                Self(Self::derive(seed, n))
+            },
+            None => Self(Uuid::new_v4())
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        self.0.as_bytes()
+    }
+
+    fn derive(seed: u128, n: u64) -> Uuid {
+        // splitmix64
+        fn mix(mut x: u64) -> u64 {
+            x = x.wrapping_add(0x9E3779B97F4A7C15);
+            x = (x ^ (x >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+            x = (x ^ (x >> 27)).wrapping_mul(0x94D049BB133111EB);
+            x ^ (x >> 31)
+        }
+        let hi = mix((seed >> 64) as u64 ^ n);
+        let lo = mix(seed as u64 ^ hi);
+        let bytes = ((hi as u128) << 64 | lo as u128).to_be_bytes();
+        uuid::Builder::from_random_bytes(bytes).into_uuid()
     }
 }
 
@@ -133,6 +219,10 @@ pub mod casts {
         }
     }  
 
+    #[derive(Debug, Error)]
+    #[error("Event doesn't belong to this stream: {:?}", .0)]
+    pub struct UnexpectedEvent(pub super::EventData);
+
     #[derive(Debug)]
     pub struct InvalidChars { 
         pub value: String, 
@@ -211,8 +301,9 @@ pub mod sqlite {
     use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef};
     use rusqlite::{Error, ToSql};
     use rusqlite::Result;
+    use chrono::{DateTime, Utc};
     use uuid::Uuid;
-    use crate::util::models::EventData;
+    use crate::util::models::{Event, EventData};
     use super::Id;
 
     impl ToSql for Id {
@@ -241,5 +332,38 @@ pub mod sqlite {
             Ok(value)
         }
     }
+
+    impl TryFrom<&rusqlite::Row<'_>> for Event {
+        type Error = Error;
+
+        /// Expects the columns in `Event::select_fields()` order
+        fn try_from(row: &rusqlite::Row<'_>) -> Result<Self> {
+            Ok(Self {
+                seq: row.get(0)?,
+                id: row.get(1)?,
+                stream_id: row.get(2)?,
+                version: row.get(3)?,
+                data: row.get(4)?,
+                timestamp: timestamp_from_sql(row.get(5)?)?,
+                origin: row.get(6)?
+            })
+        }
+    }
+
+    impl Event {
+        pub fn select_fields() -> &'static str {
+            "seq, id, stream_id, version, data, timestamp, origin"
+        }
+    }
+
+    /// Timestamps are stored as microseconds since the unix epoch
+    pub fn timestamp_to_sql(timestamp: &DateTime<Utc>) -> i64 {
+        timestamp.timestamp_micros()
+    }
+
+    pub fn timestamp_from_sql(micros: i64) -> Result<DateTime<Utc>> {
+        DateTime::from_timestamp_micros(micros)
+            .ok_or(Error::IntegralValueOutOfRange(5, micros))
+    }
 }
 
@@ -0,0 +1,126 @@
+use std::{fmt::Debug, fs, sync::OnceLock};
+use chacha20poly1305::{aead::{Aead, AeadCore, KeyInit, OsRng}, ChaCha20Poly1305, Key, Nonce};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+const NONCE_LEN: usize = 12;
+
+static KEY: OnceLock<Key> = OnceLock::new();
+
+#[derive(Debug, Error)]
+pub enum SecretKeyError {
+    #[error("Failed to read secret from {}: {}", .0, .1)]
+    Read(String, std::io::Error),
+    #[error("Secrets key in {} isn't 32 hex encoded bytes", .0)]
+    Invalid(String),
+    #[error("Cluster secret in {} is empty", .0)]
+    Empty(String)
+}
+
+/// Loads the key every secret of the cluster is sealed with, all the nodes must share the same key file
+/// (32 bytes, hex encoded) since sealed values are replicated as they are. Called once at startup
+pub fn load(path: &str) -> Result<(), SecretKeyError> {
+    let hex = fs::read_to_string(path).map_err(|e| SecretKeyError::Read(path.to_string(), e))?;
+    let bytes = from_hex(hex.trim()).and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| SecretKeyError::Invalid(path.to_string()))?;
+    let _ = KEY.set(Key::from(bytes));
+    Ok(())
+}
+
+/// Loads a random key unless one has already been loaded, secrets sealed with it can only be opened
+/// by this process. Meant for in-memory nodes (see Rackd::mock)
+pub fn load_random() {
+    KEY.get_or_init(|| ChaCha20Poly1305::generate_key(&mut OsRng));
+}
+
+fn key() -> Result<&'static Key, SealedError> {
+    KEY.get().ok_or(SealedError::KeyNotLoaded)
+}
+
+/// Secret encrypted with ChaCha20-Poly1305 (nonce || ciphertext), the plaintext never ends up in
+/// commands, events, snapshots or logs, it's only available through `open()`
+#[derive(Clone, PartialEq, Eq)]
+pub struct Sealed(Vec<u8>);
+
+#[derive(Debug, Error)]
+pub enum SealedError {
+    #[error("Secret can't be opened with the current key")]
+    InvalidKey,
+    #[error("Secret isn't valid UTF-8")]
+    InvalidUtf8,
+    #[error("Secrets key hasn't been loaded")]
+    KeyNotLoaded,
+    #[error("Secret is too long to be sealed")]
+    TooLong
+}
+
+impl Sealed {
+    pub fn seal(plaintext: &str) -> Result<Self, SealedError> {
+        let cipher = ChaCha20Poly1305::new(key()?);
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        // Encryption only fails for plaintexts longer than ~256GiB
+        let ciphertext = cipher.encrypt(&nonce, plaintext.as_bytes()).map_err(|_| SealedError::TooLong)?;
+        Ok(Self([nonce.as_slice(), &ciphertext].concat()))
+    }
+
+    pub fn open(&self) -> Result<String, SealedError> {
+        if self.0.len() < NONCE_LEN {
+            Err(SealedError::InvalidKey)?
+        }
+        let (nonce, ciphertext) = self.0.split_at(NONCE_LEN);
+        let cipher = ChaCha20Poly1305::new(key()?);
+        let plaintext = cipher.decrypt(Nonce::from_slice(nonce), ciphertext).map_err(|_| SealedError::InvalidKey)?;
+        String::from_utf8(plaintext).map_err(|_| SealedError::InvalidUtf8)
+    }
+}
+
+impl Debug for Sealed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Sealed(<redacted>)")
+    }
+}
+
+impl Serialize for Sealed {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        serializer.serialize_str(&to_hex(&self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for Sealed {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        let s = String::deserialize(deserializer)?;
+        from_hex(&s).map(Self).ok_or_else(|| de::Error::custom("sealed secret isn't hex encoded"))
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len()).step_by(2).map(|i| s.get(i..i + 2).and_then(|b| u8::from_str_radix(b, 16).ok())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load_random, Sealed};
+
+    #[test]
+    fn sealed_secrets_round_trip_and_stay_redacted() {
+        load_random();
+        let sealed = Sealed::seal("hunter2").unwrap();
+        assert_ne!(sealed, Sealed::seal("hunter2").unwrap());
+        assert_eq!(sealed.open().unwrap(), "hunter2");
+        assert_eq!(format!("{:?}", sealed), "Sealed(<redacted>)");
+
+        let json = serde_json::to_string(&sealed).unwrap();
+        assert!(!json.contains("hunter2"));
+        let mut tampered: Sealed = serde_json::from_str(&json).unwrap();
+        assert_eq!(tampered.open().unwrap(), "hunter2");
+        tampered.0[20] ^= 1;
+        assert!(tampered.open().is_err());
+    }
+}
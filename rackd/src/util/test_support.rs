@@ -0,0 +1,31 @@
+use std::str::FromStr;
+use crate::{actors::system::Rackd, net::{Ipv6Prefix, NetName, VlanId}, org::model::Asn, rack::{cmd::init::InitRack, RackName}, trunk::model::{TrunkId, TrunkName}, wan::{cmd::create::CreateWan, model::values::{WanId, WanMode}}};
+
+/// Rack the tests of the other modules run on, create commands require an initialized rack
+pub fn lim15109() -> InitRack {
+    InitRack {
+        asn: Asn::try_from(4200000001).unwrap(),
+        name: RackName::from_str("lim15109").unwrap(),
+        prefix: Ipv6Prefix::from_str("2a0f:85c1:83f:100::/56").unwrap(),
+        trunk: TrunkName::from_str("trunk1").unwrap()
+    }
+}
+
+/// Initializes the rack and returns the id of its trunk
+pub async fn init_rack(rackd: &Rackd) -> TrunkId {
+    rackd.exec(lim15109()).await.unwrap().trunk
+}
+
+/// In-memory node whose rack has a single wan, movistar on VLAN 4001 of the rack's trunk
+pub async fn create_wan(mode: WanMode) -> (Rackd, TrunkId, WanId) {
+    let rackd = Rackd::mock().unwrap();
+    let trunk = init_rack(&rackd).await;
+    let cmd = CreateWan {
+        trunk,
+        vlan: VlanId::try_from(4001).unwrap(),
+        name: NetName::from_str("movistar").unwrap(),
+        mode
+    };
+    let wan = rackd.exec(cmd).await.unwrap();
+    (rackd, trunk, wan)
+}
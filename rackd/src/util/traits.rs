@@ -1,3 +1,5 @@
+use super::models::SoftDelete;
+
 pub trait OptionExt<T> {
     fn err_or<E>(self, err: E) -> Result<(), E>;
 }
@@ -11,6 +13,27 @@ impl<T> OptionExt<T> for Option<T> {
     }
 }
 
+pub trait SoftDeleteExt<T> {
+    /// The entity, `not_found` if it doesn't exist or has been deleted
+    fn live_or<E>(self, not_found: E) -> Result<T, E>;
+    /// The deleted entity, `not_found` if it doesn't exist and `not_deleted` if it hasn't been deleted
+    fn deleted_or<E>(self, not_found: E, not_deleted: E) -> Result<T, E>;
+}
+
+impl<T> SoftDeleteExt<T> for Option<T> where T: SoftDelete {
+    fn live_or<E>(self, not_found: E) -> Result<T, E> {
+        self.filter(|entity| !entity.deleted()).ok_or(not_found)
+    }
+
+    fn deleted_or<E>(self, not_found: E, not_deleted: E) -> Result<T, E> {
+        match self {
+            Some(entity) if entity.deleted() => Ok(entity),
+            Some(_) => Err(not_deleted),
+            None => Err(not_found)
+        }
+    }
+}
+
 // pub trait Wrap {
 //     type Wrapped<T>;
 // }
@@ -1,15 +1,30 @@
+use serde::{Deserialize, Serialize};
 use crate::util::actor::Msg;
 pub mod create;
 pub mod rename;
 pub mod set_mac;
 pub mod set_ipv6;
 pub mod set_ipv4;
+pub mod set_dhcp6_duid;
+pub mod set_dhcp6_iana;
+pub mod set_dhcp6_iapd;
+pub mod set_pppoe;
+pub mod set_mode;
+pub mod delete;
+pub mod restore;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub enum WanCmd {
     Create(Msg<create::CreateWan>),
     Rename(Msg<rename::RenameWan>),
     SetMacAddr(Msg<set_mac::SetMacAddr>),
     SetIpv4Params(Msg<set_ipv4::SetIpv4Params>),
-    // SetIpv6(Msg<set_ipv6::SetIpv6>)
+    SetIpv6(Msg<set_ipv6::SetIpv6>),
+    SetDhcp6Duid(Msg<set_dhcp6_duid::SetDhcp6Duid>),
+    SetDhcp6Iana(Msg<set_dhcp6_iana::SetDhcp6Iana>),
+    SetDhcp6Iapd(Msg<set_dhcp6_iapd::SetDhcp6Iapd>),
+    SetPppoeCredentials(Msg<set_pppoe::SetPppoeCredentials>),
+    SetMode(Msg<set_mode::SetWanMode>),
+    Delete(Msg<delete::DeleteWan>),
+    Restore(Msg<restore::RestoreWan>)
 }
\ No newline at end of file
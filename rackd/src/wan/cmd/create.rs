@@ -1,10 +1,10 @@
 use field_types::FieldName;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use utoipa::ToSchema;
-use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::EntityStore, query::traits::QueryRunner, Tx}, net::{query::{GetNetworkByName, GetNetworkByTrunkVlan}, views::NetworkView, NetName, VlanId}, rack::Rack, trunk::model::{Trunk, TrunkId}, util::{actor::{Payload, Process}, models::Entity, traits::OptionExt}, wan::model::{entity::{Wan, WanEvent}, values::{WanId, WanMode}}};
+use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::{EntityStore, VersionConflict}, raft::ClusterError, query::traits::QueryRunner, Tx}, net::{query::{GetNetworkByName, GetNetworkByTrunkVlan}, views::NetworkView, NetName, VlanId}, rack::{query::get_rack::LoadRack, Rack}, trunk::model::{Trunk, TrunkId}, util::{actor::{Payload, Process}, models::Entity, traits::OptionExt}, wan::model::{entity::{Wan, WanEvent}, values::{WanId, WanMode}}};
 
-#[derive(Debug, Deserialize, ToSchema, FieldName)]
+#[derive(Debug, Serialize, Deserialize, ToSchema, FieldName)]
 pub struct CreateWan {
     pub trunk: TrunkId,
     pub vlan: VlanId,
@@ -16,6 +16,10 @@ pub struct CreateWan {
 pub enum CreateWanError {
     #[error("Db Error")]
     Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Conflict(#[from] VersionConflict),
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
     #[error("Rack hasn't been initialized")]
     RackNotFound,
     #[error("Trunk with ID not found")]
@@ -55,22 +59,31 @@ impl Process for CreateWan {
 
     fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
         let tx = actor.conn.tx()?;
-        // get rack from KeyValueStore
-        let rack = Some(Rack::default()); 
+        let rack = tx.run(LoadRack)?;
         let trunk = tx.load(self.trunk)?;
         let name_twin = tx.run(GetNetworkByName { name: self.name.clone() })?;
         let trunk_vlan_twin = tx.run(GetNetworkByTrunkVlan { trunk: self.trunk, vlan: self.vlan })?;
         self.exec(rack, trunk, name_twin, trunk_vlan_twin).map(|mut wan| {
             tx.save(&mut wan)?;
+            tx.commit()?;
             Ok(wan.id)
         })?
     }
 }
 
 pub mod casts {
-    use crate::{actors::cmd::RackdCmd, util::actor::Msg, wan::cmd::WanCmd};
+    use crate::{actors::cmd::RackdCmd, db::cmd::traits::EntityStoreError, util::actor::Msg, wan::cmd::WanCmd};
     use super::*;
 
+    impl From<EntityStoreError> for CreateWanError {
+        fn from(error: EntityStoreError) -> Self {
+            match error {
+                EntityStoreError::Db(e) => Self::Db(e),
+                EntityStoreError::Conflict(e) => Self::Conflict(e)
+            }
+        }
+    }
+
     // impl From<rusqlite::Error> for CreateWanError {
     //     fn from(error: rusqlite::Error) -> Self {
     //         Self::Db(format!("{error:?}"))
@@ -88,7 +101,7 @@ pub mod api {
     use std::collections::HashMap;
     use serde_json::Value;
     use axum::{extract::{OriginalUri, State}, response::IntoResponse};
-    use crate::{actors::system::Rackd, net::{NetName, VlanId}, trunk::model::TrunkId, util::api::{Error, Json, JsonKeyError, Response, TryFromJson}, wan::model::values::WanMode};
+    use crate::{actors::system::Rackd, net::{NetName, VlanId}, trunk::model::TrunkId, util::api::{Error, Json, Response, TryFromJson}, wan::model::values::WanMode};
     use super::{CreateWan, CreateWanError, CreateWanFieldName};
 
     #[utoipa::path(post, path = "/wan/create", tag = "wan",
@@ -115,12 +128,12 @@ pub mod api {
             match (TrunkId::try_from(trunk), VlanId::try_from(vlan), NetName::try_from(name), WanMode::try_from(mode)) {
                 (Ok(trunk), Ok(vlan), Ok(name), Ok(mode)) => Ok(Self { trunk, vlan, name, mode }),
                 (r1, r2, r3, r4) => {
-                    let e1 = r1.map_err(|e| Error::from(e)).err();
-                    let e2 = r2.map_err(|e| Error::from(e)).err();
-                    let e3 = r3.map_err(|e| Error::from(e)).err();
-                    let e4 = r4.map_err(|e| Error::from(e)).err();
+                    let e1 = r1.map_err(Error::from).err();
+                    let e2 = r2.map_err(Error::from).err();
+                    let e3 = r3.map_err(Error::from).err();
+                    let e4 = r4.map_err(Error::from).err();
 
-                    let errors: Vec<Error> = [e1, e2, e3, e4].into_iter().filter_map(|e| e).collect();
+                    let errors: Vec<Error> = [e1, e2, e3, e4].into_iter().flatten().collect();
                     Err(errors)
                 }
             }
@@ -132,6 +145,8 @@ pub mod api {
             let msg = error.to_string();
             match error {
                 CreateWanError::Db(_) => Error::new("CREATE_WAN_DB_ERROR", msg),
+                CreateWanError::Conflict(_) => Error::new("CREATE_WAN_CONFLICT", msg),
+                CreateWanError::Cluster(e) => Error::from(e),
                 CreateWanError::RackNotFound => Error::new("CREATE_WAN_RACK_NOT_FOUND", msg),
                 CreateWanError::TrunkNotFound => Error::new("CREATE_WAN_TRUNK_NOT_FOUND", msg),
                 CreateWanError::NameAlreadyInUse => Error::new("CREATE_WAN_NAME_ALREADY_IN_USE", msg),
@@ -144,19 +159,28 @@ pub mod api {
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
-    use crate::{actors::system::Rackd, net::{NetName, VlanId}, trunk::{cmd::create::CreateTrunk, model::{TrunkId, TrunkName}}, wan::{cmd::create::{CreateWan, CreateWanError}, model::values::WanMode, *}};
+    use crate::{actors::system::Rackd, net::{NetName, VlanId}, trunk::model::TrunkId, wan::{cmd::create::{CreateWan, CreateWanError}, model::values::WanMode}};
+    use crate::util::test_support::init_rack;
 
     #[tokio::test]
     async fn cant_create_if_rack_doesnt_exist() {
-        todo!()
+        let rackd = Rackd::mock().unwrap();
+        let cmd = CreateWan {
+            trunk: TrunkId::new(),
+            name: NetName::from_str("att").unwrap(),
+            vlan: VlanId::try_from(1005).unwrap(),
+            mode: WanMode::PPPoE
+        };
+        assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, CreateWanError::RackNotFound)));
     }
 
     #[tokio::test]
     async fn cant_create_if_trunk_doesnt_exist() {
         let rackd = Rackd::mock().unwrap();
+        init_rack(&rackd).await;
         let cmd = CreateWan {
             trunk: TrunkId::new(),
-            name: NetName::from_str("at&t").unwrap(),
+            name: NetName::from_str("att").unwrap(),
             vlan: VlanId::try_from(1005).unwrap(),
             mode: WanMode::PPPoE
         };
@@ -166,14 +190,11 @@ mod tests {
     #[tokio::test]
     async fn cant_create_if_trunk_vlan_is_in_use() {
         let rackd = Rackd::mock().unwrap();
-        let cmd = CreateTrunk {
-            name: TrunkName::from_str("trunk1").unwrap()
-        };
-        let trunk_id = rackd.exec(cmd).await.unwrap();
+        let trunk_id = init_rack(&rackd).await;
         let cmd = CreateWan {
             trunk: trunk_id,
             vlan: VlanId::try_from(1005).unwrap(),
-            name: NetName::from_str("at&t").unwrap(),
+            name: NetName::from_str("att").unwrap(),
             mode: WanMode::IPoE
         };
         let _ = rackd.exec(cmd).await.unwrap();
@@ -192,10 +213,7 @@ mod tests {
     #[tokio::test]
     async fn cant_create_if_name_is_in_use() {
         let rackd = Rackd::mock().unwrap();
-        let cmd = CreateTrunk {
-            name: TrunkName::from_str("trunk1").unwrap()
-        };
-        let trunk_id = rackd.exec(cmd).await.unwrap();
+        let trunk_id = init_rack(&rackd).await;
         let cmd = CreateWan { 
             trunk: trunk_id,
             vlan: VlanId::try_from(4002).unwrap(),
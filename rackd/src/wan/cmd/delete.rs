@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::{EntityStore, VersionConflict}, raft::ClusterError, Tx}, wan::model::{entity::{Wan, WanEvent}, values::WanId}, util::{actor::{Payload, Process}, models::Entity, traits::SoftDeleteExt}};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DeleteWan {
+    pub id: WanId
+}
+
+#[derive(Debug, Error)]
+pub enum DeleteWanError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Conflict(#[from] VersionConflict),
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
+    #[error("Wan with id x can't be found")]
+    WanNotFound
+}
+
+impl Payload for DeleteWan {
+    type Ok = ();
+    type Err = DeleteWanError;
+}
+
+impl DeleteWan {
+    fn exec(&self, wan: Option<Wan>) -> Result<Wan, DeleteWanError> {
+        let mut wan = wan.live_or(DeleteWanError::WanNotFound)?;
+        wan.process(WanEvent::Deleted);
+        Ok(wan)
+    }
+}
+
+impl Process for DeleteWan {
+    type Actor = RackdCmdActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let wan = tx.load(self.id)?;
+        self.exec(wan).map(|mut wan| {
+            tx.save(&mut wan)?;
+            tx.commit()?;
+            Ok(())
+        })?
+    }
+}
+
+pub mod casts {
+    use crate::{actors::cmd::RackdCmd, db::cmd::traits::EntityStoreError, wan::cmd::WanCmd, util::actor::Msg};
+    use super::{DeleteWan, DeleteWanError};
+
+    impl From<EntityStoreError> for DeleteWanError {
+        fn from(error: EntityStoreError) -> Self {
+            match error {
+                EntityStoreError::Db(e) => Self::Db(e),
+                EntityStoreError::Conflict(e) => Self::Conflict(e)
+            }
+        }
+    }
+
+    impl From<Msg<DeleteWan>> for RackdCmd {
+        fn from(cmd: Msg<DeleteWan>) -> Self {
+            Self::Wan(WanCmd::Delete(cmd))
+        }
+    }
+}
+
+pub mod api {
+    use axum::{extract::{OriginalUri, Path, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, wan::model::values::WanId, util::api::{Error, Response}};
+    use super::{DeleteWan, DeleteWanError};
+
+    #[utoipa::path(delete, path = "/wan/{wan_id}", tag = "wan",
+        params(("wan_id" = WanId, Path, description = "Wan UUID")),
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn delete(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Path(wan_id): Path<WanId>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.exec(DeleteWan { id: wan_id }).await
+            .map(|_| Response::ok((), path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl From<DeleteWanError> for Error {
+        fn from(error: DeleteWanError) -> Self {
+            let msg = error.to_string();
+            match error {
+                DeleteWanError::Db(_) => Error::new("DELETE_WAN_DB_ERROR", msg),
+                DeleteWanError::Conflict(_) => Error::new("DELETE_WAN_CONFLICT", msg),
+                DeleteWanError::Cluster(e) => Error::from(e),
+                DeleteWanError::WanNotFound => Error::new("DELETE_WAN_NOT_FOUND", msg)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::{actors::system::Rackd, net::{NetName, VlanId}, wan::{cmd::{create::CreateWan, delete::{DeleteWan, DeleteWanError}}, model::values::{WanId, WanMode}, query::{get_all::GetAllWans, get_by_key::GetWanById}}};
+    use crate::util::test_support::create_wan;
+
+    #[tokio::test]
+    async fn cant_delete_if_wan_doesnt_exist() {
+        let rackd = Rackd::mock().unwrap();
+        let cmd = DeleteWan {
+            id: WanId::new()
+        };
+        assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, DeleteWanError::WanNotFound)));
+    }
+
+    #[tokio::test]
+    async fn name_and_trunk_vlan_of_a_deleted_wan_can_be_reused() {
+        let (rackd, trunk, id) = create_wan(WanMode::IPoE).await;
+        rackd.exec(DeleteWan { id }).await.unwrap();
+        assert!(rackd.query(GetWanById { id }).await.is_err());
+        assert!(rackd.exec(DeleteWan { id }).await.is_err_and(|e| matches!(e, DeleteWanError::WanNotFound)));
+
+        let cmd = CreateWan {
+            trunk,
+            vlan: VlanId::try_from(4001).unwrap(),
+            name: NetName::from_str("movistar").unwrap(),
+            mode: WanMode::IPoE
+        };
+        rackd.exec(cmd).await.unwrap();
+        assert_eq!(rackd.query(GetAllWans::default()).await.unwrap().total, 1);
+        let page = rackd.query(GetAllWans { include_deleted: true, ..Default::default() }).await.unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(page.items.iter().filter(|wan| wan.deleted).count(), 1);
+    }
+}
@@ -1,8 +1,10 @@
+use field_types::FieldName;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::EntityStore, query::traits::QueryRunner, Tx}, net::{query::GetNetworkByName, views::NetworkView, NetName}, util::{actor::{Payload, Process}, models::Entity, traits::OptionExt}, wan::model::{entity::{Wan, WanEvent}, values::WanId}};
+use utoipa::ToSchema;
+use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::{EntityStore, VersionConflict}, raft::ClusterError, query::traits::QueryRunner, Tx}, net::{query::GetNetworkByName, views::NetworkView, NetName}, util::{actor::{Payload, Process}, models::Entity, traits::OptionExt}, wan::model::{entity::{Wan, WanEvent}, values::WanId}};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema, FieldName)]
 pub struct RenameWan {
     pub id: WanId,
     pub name: NetName
@@ -12,6 +14,10 @@ pub struct RenameWan {
 pub enum RenameWanError {
     #[error("Db Error")]
     Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Conflict(#[from] VersionConflict),
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
     #[error("Rack not found")]
     RackNotFound,
     #[error("Wan with id x Not Found")]
@@ -27,7 +33,7 @@ impl Payload for RenameWan {
 
 impl RenameWan {
     fn exec(&self, wan: Option<Wan>, name_twin: Option<NetworkView>) -> Result<Wan, RenameWanError> {
-        let mut wan = wan.ok_or(RenameWanError::WanNotFound)?;
+        let mut wan = wan.filter(|wan| !wan.deleted).ok_or(RenameWanError::WanNotFound)?;
         name_twin.err_or(RenameWanError::NameAlreadyInUse)?;
         wan.process(WanEvent::Renamed { from: wan.name.clone(), to: self.name.clone() });
         Ok(wan)
@@ -43,14 +49,24 @@ impl Process for RenameWan {
         let name_twin = tx.run(GetNetworkByName { name: self.name.clone() })?;
         self.exec(wan, name_twin).map(|mut wan| {
             tx.save(&mut wan)?;
+            tx.commit()?;
             Ok(())
         })?
     }
 }
 
 pub mod casts {
-    use crate::{actors::cmd::RackdCmd, util::actor::Msg, wan::cmd::WanCmd};
-    use super::RenameWan;
+    use crate::{actors::cmd::RackdCmd, db::cmd::traits::EntityStoreError, util::actor::Msg, wan::cmd::WanCmd};
+    use super::{RenameWan, RenameWanError};
+
+    impl From<EntityStoreError> for RenameWanError {
+        fn from(error: EntityStoreError) -> Self {
+            match error {
+                EntityStoreError::Db(e) => Self::Db(e),
+                EntityStoreError::Conflict(e) => Self::Conflict(e)
+            }
+        }
+    }
 
     impl From<Msg<RenameWan>> for RackdCmd {
         fn from(cmd: Msg<RenameWan>) -> Self {
@@ -59,17 +75,72 @@ pub mod casts {
     }
 }
 
+pub mod api {
+    use std::collections::HashMap;
+    use serde_json::Value;
+    use axum::{extract::{OriginalUri, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, net::NetName, util::api::{Error, Json, Response, TryFromJson}, wan::model::values::WanId};
+    use super::{RenameWan, RenameWanError, RenameWanFieldName};
+
+    #[utoipa::path(patch, path = "/wan/rename", tag = "wan",
+        request_body = RenameWan,
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn rename(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Json(cmd): Json<RenameWan>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.exec(cmd).await
+            .map(|_| Response::ok((), path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl TryFromJson for RenameWan {
+        fn try_from(mut map: HashMap<String, Value>) -> Result<Self, Vec<Error>> {
+            Self::check_keys(&map, RenameWan::as_field_name_array().map(|f| f.name()))?;
+            let id = map.remove(RenameWanFieldName::Id.name()).unwrap_or_default();
+            let name = map.remove(RenameWanFieldName::Name.name()).unwrap_or_default();
+
+            match (WanId::try_from(id), NetName::try_from(name)) {
+                (Ok(id), Ok(name)) => Ok(Self { id, name }),
+                (r1, r2) => {
+                    let e1 = r1.map_err(Error::from).err();
+                    let e2 = r2.map_err(Error::from).err();
+
+                    let errors: Vec<Error> = [e1, e2].into_iter().flatten().collect();
+                    Err(errors)
+                }
+            }
+        }
+    }
+
+    impl From<RenameWanError> for Error {
+        fn from(error: RenameWanError) -> Self {
+            let msg = error.to_string();
+            match error {
+                RenameWanError::Db(_) => Error::new("RENAME_WAN_DB_ERROR", msg),
+                RenameWanError::Conflict(_) => Error::new("RENAME_WAN_CONFLICT", msg),
+                RenameWanError::Cluster(e) => Error::from(e),
+                RenameWanError::RackNotFound => Error::new("RENAME_WAN_RACK_NOT_FOUND", msg),
+                RenameWanError::WanNotFound => Error::new("RENAME_WAN_NOT_FOUND", msg),
+                RenameWanError::NameAlreadyInUse => Error::new("RENAME_WAN_NAME_ALREADY_IN_USE", msg)
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
-    use crate::{actors::system::Rackd, net::{NetName, VlanId}, trunk::{cmd::create::CreateTrunk, model::TrunkName}, wan::{cmd::{create::CreateWan, rename::{RenameWan, RenameWanError}}, model::values::{WanId, WanMode}}};
+    use crate::{actors::system::Rackd, net::{NetName, VlanId}, wan::{cmd::{create::CreateWan, rename::{RenameWan, RenameWanError}}, model::values::{WanId, WanMode}}};
+    use crate::util::test_support::init_rack;
 
     #[tokio::test]
     async fn cant_rename_if_wan_doesnt_exists() {
         let rackd = Rackd::mock().unwrap();
         let cmd = RenameWan {
             id: WanId::new(),
-            name: NetName::from_str("at&t").unwrap()
+            name: NetName::from_str("att").unwrap()
         };
         assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, RenameWanError::WanNotFound)));
     }
@@ -77,14 +148,11 @@ mod tests {
     #[tokio::test]
     async fn cant_rename_if_new_name_is_already_in_use() {
         let rackd = Rackd::mock().unwrap();
-        let cmd = CreateTrunk {
-            name: TrunkName::from_str("trunk1").unwrap()
-        };
-        let trunk_id = rackd.exec(cmd).await.unwrap();
+        let trunk_id = init_rack(&rackd).await;
         let cmd = CreateWan {
             trunk: trunk_id,
             vlan: VlanId::try_from(4000).unwrap(),
-            name: NetName::from_str("at&t").unwrap(),
+            name: NetName::from_str("att").unwrap(),
             mode: WanMode::IPoE
         };
         let att_id = rackd.exec(cmd).await.unwrap();
@@ -99,14 +167,14 @@ mod tests {
         // Trying to rename with the exact same name
         let cmd = RenameWan {
             id: att_id,
-            name: NetName::from_str("at&t").unwrap()
+            name: NetName::from_str("att").unwrap()
         };
         assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, RenameWanError::NameAlreadyInUse)));
         
         // Trying to rename with the same name but different case
         let cmd = RenameWan {
             id: att_id,
-            name: NetName::from_str("AT&T").unwrap()
+            name: NetName::from_str("ATT").unwrap()
         };
         assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, RenameWanError::NameAlreadyInUse)));
         
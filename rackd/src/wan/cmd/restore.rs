@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::{EntityStore, VersionConflict}, raft::ClusterError, query::traits::QueryRunner, Tx}, net::query::{GetRestoreTwins, RestoreTwins}, util::{actor::{Payload, Process}, models::Entity, traits::SoftDeleteExt}, wan::model::{entity::{Wan, WanEvent}, values::WanId}};
+
+/// Brings back a deleted wan, as long as its name and trunk/VLAN haven't been reused since
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RestoreWan {
+    pub id: WanId
+}
+
+#[derive(Debug, Error)]
+pub enum RestoreWanError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Conflict(#[from] VersionConflict),
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
+    #[error("Wan with id x can't be found")]
+    WanNotFound,
+    #[error("Wan isn't deleted")]
+    NotDeleted,
+    #[error("Trunk of the wan has been deleted")]
+    TrunkNotFound,
+    #[error("Wan Name already in use")]
+    NameAlreadyInUse,
+    #[error("VLAN/Trunk already in use")]
+    TrunkVlanAlreadyInUse
+}
+
+impl Payload for RestoreWan {
+    type Ok = ();
+    type Err = RestoreWanError;
+}
+
+impl RestoreWan {
+    fn exec(&self, wan: Option<Wan>, twins: Option<RestoreTwins>) -> Result<Wan, RestoreWanError> {
+        let mut wan = wan.deleted_or(RestoreWanError::WanNotFound, RestoreWanError::NotDeleted)?;
+        if let Some(twins) = twins {
+            twins.check()?;
+        }
+        wan.process(WanEvent::Restored);
+        Ok(wan)
+    }
+}
+
+impl Process for RestoreWan {
+    type Actor = RackdCmdActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let wan: Option<Wan> = tx.load(self.id)?;
+        let twins = match &wan {
+            Some(wan) => Some(tx.run(GetRestoreTwins { trunk: wan.trunk, vlan: wan.vlan, name: wan.name.clone() })?),
+            None => None
+        };
+        self.exec(wan, twins).map(|mut wan| {
+            tx.save(&mut wan)?;
+            tx.commit()?;
+            Ok(())
+        })?
+    }
+}
+
+pub mod casts {
+    use crate::{actors::cmd::RackdCmd, db::cmd::traits::EntityStoreError, net::query::RestoreConflict, util::actor::Msg, wan::cmd::WanCmd};
+    use super::{RestoreWan, RestoreWanError};
+
+    impl From<RestoreConflict> for RestoreWanError {
+        fn from(conflict: RestoreConflict) -> Self {
+            match conflict {
+                RestoreConflict::TrunkNotFound => Self::TrunkNotFound,
+                RestoreConflict::NameAlreadyInUse => Self::NameAlreadyInUse,
+                RestoreConflict::TrunkVlanAlreadyInUse => Self::TrunkVlanAlreadyInUse
+            }
+        }
+    }
+
+    impl From<EntityStoreError> for RestoreWanError {
+        fn from(error: EntityStoreError) -> Self {
+            match error {
+                EntityStoreError::Db(e) => Self::Db(e),
+                EntityStoreError::Conflict(e) => Self::Conflict(e)
+            }
+        }
+    }
+
+    impl From<Msg<RestoreWan>> for RackdCmd {
+        fn from(cmd: Msg<RestoreWan>) -> Self {
+            Self::Wan(WanCmd::Restore(cmd))
+        }
+    }
+}
+
+pub mod api {
+    use axum::{extract::{OriginalUri, Path, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, util::api::{Error, Response}, wan::model::values::WanId};
+    use super::{RestoreWan, RestoreWanError};
+
+    #[utoipa::path(post, path = "/wan/{wan_id}/restore", tag = "wan",
+        params(("wan_id" = WanId, Path, description = "Wan UUID")),
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn restore(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Path(wan_id): Path<WanId>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.exec(RestoreWan { id: wan_id }).await
+            .map(|_| Response::ok((), path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl From<RestoreWanError> for Error {
+        fn from(error: RestoreWanError) -> Self {
+            let msg = error.to_string();
+            match error {
+                RestoreWanError::Db(_) => Error::new("RESTORE_WAN_DB_ERROR", msg),
+                RestoreWanError::Conflict(_) => Error::new("RESTORE_WAN_CONFLICT", msg),
+                RestoreWanError::Cluster(e) => Error::from(e),
+                RestoreWanError::WanNotFound => Error::new("RESTORE_WAN_NOT_FOUND", msg),
+                RestoreWanError::NotDeleted => Error::new("RESTORE_WAN_NOT_DELETED", msg),
+                RestoreWanError::TrunkNotFound => Error::new("RESTORE_WAN_TRUNK_NOT_FOUND", msg),
+                RestoreWanError::NameAlreadyInUse => Error::new("RESTORE_WAN_NAME_ALREADY_IN_USE", msg),
+                RestoreWanError::TrunkVlanAlreadyInUse => Error::new("RESTORE_WAN_TRUNK_VLAN_ALREADY_IN_USE", msg)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::{net::{NetName, VlanId}, wan::{cmd::{create::CreateWan, delete::DeleteWan, restore::{RestoreWan, RestoreWanError}}, model::values::WanMode, query::get_by_key::GetWanById}};
+    use crate::util::test_support::create_wan;
+
+    #[tokio::test]
+    async fn restore_only_if_name_and_trunk_vlan_are_free() {
+        let (rackd, trunk, id) = create_wan(WanMode::IPoE).await;
+        assert!(rackd.exec(RestoreWan { id }).await.is_err_and(|e| matches!(e, RestoreWanError::NotDeleted)));
+        rackd.exec(DeleteWan { id }).await.unwrap();
+
+        let cmd = CreateWan {
+            trunk,
+            vlan: VlanId::try_from(4002).unwrap(),
+            name: NetName::from_str("movistar").unwrap(),
+            mode: WanMode::IPoE
+        };
+        let twin = rackd.exec(cmd).await.unwrap();
+        assert!(rackd.exec(RestoreWan { id }).await.is_err_and(|e| matches!(e, RestoreWanError::NameAlreadyInUse)));
+        rackd.exec(DeleteWan { id: twin }).await.unwrap();
+
+        rackd.exec(RestoreWan { id }).await.unwrap();
+        assert_eq!(rackd.query(GetWanById { id }).await.unwrap().name.to_string(), "movistar");
+    }
+}
@@ -0,0 +1,148 @@
+use field_types::FieldName;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::{EntityStore, VersionConflict}, raft::ClusterError, Tx}, util::{actor::{Payload, Process}, models::Entity}, wan::model::{entity::{Wan, WanEvent}, values::{Dhcp6Duid, WanId}}};
+
+/// DUID the wan's DHCPv6 client identifies itself with
+#[derive(Debug, Serialize, Deserialize, ToSchema, FieldName)]
+pub struct SetDhcp6Duid {
+    pub id: WanId,
+    /// "auto_en", "auto_ll", "auto_llt" or {"type": "en" | "ll" | "llt" | "raw", ...}
+    #[schema(value_type = Object)]
+    pub duid: Dhcp6Duid
+}
+
+#[derive(Debug, Error)]
+pub enum SetDhcp6DuidError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Conflict(#[from] VersionConflict),
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
+    #[error("Wan with id x can't be found")]
+    WanNotFound,
+    #[error("Wan DUID already set")]
+    AlreadySet,
+    #[error("Invalid DUID: {0}")]
+    InvalidDuid(&'static str)
+}
+
+impl Payload for SetDhcp6Duid {
+    type Ok = ();
+    type Err = SetDhcp6DuidError;
+}
+
+impl SetDhcp6Duid {
+    fn exec(&self, wan: Option<Wan>) -> Result<Wan, SetDhcp6DuidError> {
+        let mut wan = wan.filter(|wan| !wan.deleted).ok_or(SetDhcp6DuidError::WanNotFound)?;
+        let event = match self.duid {
+            Dhcp6Duid::AutoEN => WanEvent::Dhcp6DuidSwitchedToAutoEN,
+            Dhcp6Duid::AutoLL => WanEvent::Dhcp6DuidSwitchedToAutoLL,
+            Dhcp6Duid::AutoLLT => WanEvent::Dhcp6DuidSwitchedToAutoLLT,
+            Dhcp6Duid::EN(en) if en.pen == 0 => Err(SetDhcp6DuidError::InvalidDuid("enterprise number can't be 0"))?,
+            Dhcp6Duid::EN(en) => WanEvent::Dhcp6DuidSwitchedToEN { pen: en.pen, id: en.id },
+            Dhcp6Duid::LL(ll) if ll.hw_type == 0 => Err(SetDhcp6DuidError::InvalidDuid("hardware type can't be 0"))?,
+            Dhcp6Duid::LL(ll) => WanEvent::Dhcp6DuidSwitchedToLL { hw_type: ll.hw_type, mac: ll.mac },
+            Dhcp6Duid::LLT(llt) if llt.hw_type == 0 => Err(SetDhcp6DuidError::InvalidDuid("hardware type can't be 0"))?,
+            Dhcp6Duid::LLT(llt) => WanEvent::Dhcp6DuidSwitchedToLLT { hw_type: llt.hw_type, mac: llt.mac, time: llt.time },
+            // The first 2 bytes are the DUID type, RFC 8415 defines 1 to 4
+            Dhcp6Duid::Raw(value) if !(1..=4).contains(&(value >> 112)) => Err(SetDhcp6DuidError::InvalidDuid("raw value doesn't start with a known DUID type"))?,
+            Dhcp6Duid::Raw(value) => WanEvent::Dhcp6DuidSwitchedToRaw { value }
+        };
+        if wan.dhcp6.duid == self.duid {
+            Err(SetDhcp6DuidError::AlreadySet)?
+        }
+        wan.process(event);
+        Ok(wan)
+    }
+}
+
+impl Process for SetDhcp6Duid {
+    type Actor = RackdCmdActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let wan = tx.load(self.id)?;
+        self.exec(wan).map(|mut wan| {
+            tx.save(&mut wan)?;
+            tx.commit()?;
+            Ok(())
+        })?
+    }
+}
+
+pub mod casts {
+    use crate::{actors::cmd::RackdCmd, db::cmd::traits::EntityStoreError, util::actor::Msg, wan::cmd::WanCmd};
+    use super::{SetDhcp6Duid, SetDhcp6DuidError};
+
+    impl From<EntityStoreError> for SetDhcp6DuidError {
+        fn from(error: EntityStoreError) -> Self {
+            match error {
+                EntityStoreError::Db(e) => Self::Db(e),
+                EntityStoreError::Conflict(e) => Self::Conflict(e)
+            }
+        }
+    }
+
+    impl From<Msg<SetDhcp6Duid>> for RackdCmd {
+        fn from(cmd: Msg<SetDhcp6Duid>) -> Self {
+            Self::Wan(WanCmd::SetDhcp6Duid(cmd))
+        }
+    }
+}
+
+pub mod api {
+    use std::collections::HashMap;
+    use serde_json::Value;
+    use axum::{extract::{OriginalUri, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, util::api::{Error, Json, Response, TryFromJson}, wan::model::values::{Dhcp6Duid, WanId}};
+    use super::{SetDhcp6Duid, SetDhcp6DuidError, SetDhcp6DuidFieldName};
+
+    #[utoipa::path(patch, path = "/wan/dhcp6/duid", tag = "wan",
+        request_body = SetDhcp6Duid,
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn set_duid(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Json(cmd): Json<SetDhcp6Duid>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.exec(cmd).await
+            .map(|_| Response::ok((), path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl TryFromJson for SetDhcp6Duid {
+        fn try_from(mut map: HashMap<String, Value>) -> Result<Self, Vec<Error>> {
+            Self::check_keys(&map, SetDhcp6Duid::as_field_name_array().map(|f| f.name()))?;
+            let id = map.remove(SetDhcp6DuidFieldName::Id.name()).unwrap_or_default();
+            let duid = map.remove(SetDhcp6DuidFieldName::Duid.name()).unwrap_or_default();
+
+            match (WanId::try_from(id), Dhcp6Duid::try_from(duid)) {
+                (Ok(id), Ok(duid)) => Ok(Self { id, duid }),
+                (r1, r2) => {
+                    let e1 = r1.map_err(Error::from).err();
+                    let e2 = r2.map_err(Error::from).err();
+
+                    let errors: Vec<Error> = [e1, e2].into_iter().flatten().collect();
+                    Err(errors)
+                }
+            }
+        }
+    }
+
+    impl From<SetDhcp6DuidError> for Error {
+        fn from(error: SetDhcp6DuidError) -> Self {
+            let msg = error.to_string();
+            match error {
+                SetDhcp6DuidError::Db(_) => Error::new("SET_DHCP6_DUID_DB_ERROR", msg),
+                SetDhcp6DuidError::Conflict(_) => Error::new("SET_DHCP6_DUID_CONFLICT", msg),
+                SetDhcp6DuidError::Cluster(e) => Error::from(e),
+                SetDhcp6DuidError::WanNotFound => Error::new("SET_DHCP6_DUID_WAN_NOT_FOUND", msg),
+                SetDhcp6DuidError::AlreadySet => Error::new("SET_DHCP6_DUID_ALREADY_SET", msg),
+                SetDhcp6DuidError::InvalidDuid(_) => Error::new("SET_DHCP6_DUID_INVALID", msg)
+            }
+        }
+    }
+}
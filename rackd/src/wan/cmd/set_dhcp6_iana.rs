@@ -0,0 +1,138 @@
+use field_types::FieldName;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::{EntityStore, VersionConflict}, raft::ClusterError, Tx}, util::{actor::{Payload, Process}, models::Entity}, wan::model::{entity::{Wan, WanEvent}, values::{Dhcp6Iana, WanId}}};
+
+/// Identity Association the wan's DHCPv6 client requests its own address with
+#[derive(Debug, Serialize, Deserialize, ToSchema, FieldName)]
+pub struct SetDhcp6Iana {
+    pub id: WanId,
+    /// {"iaid": 1, "valid_lt": 2000, "preferred_lt": 1500}
+    #[schema(value_type = Object)]
+    pub iana: Dhcp6Iana
+}
+
+#[derive(Debug, Error)]
+pub enum SetDhcp6IanaError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Conflict(#[from] VersionConflict),
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
+    #[error("Wan with id x can't be found")]
+    WanNotFound,
+    #[error("Wan IA_NA already set")]
+    AlreadySet,
+    #[error("Preferred lifetime {0} is longer than the valid lifetime {1}")]
+    InvalidLifetimes(u32, u32)
+}
+
+impl Payload for SetDhcp6Iana {
+    type Ok = ();
+    type Err = SetDhcp6IanaError;
+}
+
+impl SetDhcp6Iana {
+    fn exec(&self, wan: Option<Wan>) -> Result<Wan, SetDhcp6IanaError> {
+        let mut wan = wan.filter(|wan| !wan.deleted).ok_or(SetDhcp6IanaError::WanNotFound)?;
+        // Servers discard an IA with preferred > valid (RFC 8415 section 21.6)
+        if self.iana.preferred_lt > self.iana.valid_lt {
+            Err(SetDhcp6IanaError::InvalidLifetimes(self.iana.preferred_lt, self.iana.valid_lt))?
+        }
+        if wan.dhcp6.iana == self.iana {
+            Err(SetDhcp6IanaError::AlreadySet)?
+        }
+        wan.process(WanEvent::Dhcp6IanaSet { from: wan.dhcp6.iana, to: self.iana });
+        Ok(wan)
+    }
+}
+
+impl Process for SetDhcp6Iana {
+    type Actor = RackdCmdActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let wan = tx.load(self.id)?;
+        self.exec(wan).map(|mut wan| {
+            tx.save(&mut wan)?;
+            tx.commit()?;
+            Ok(())
+        })?
+    }
+}
+
+pub mod casts {
+    use crate::{actors::cmd::RackdCmd, db::cmd::traits::EntityStoreError, util::actor::Msg, wan::cmd::WanCmd};
+    use super::{SetDhcp6Iana, SetDhcp6IanaError};
+
+    impl From<EntityStoreError> for SetDhcp6IanaError {
+        fn from(error: EntityStoreError) -> Self {
+            match error {
+                EntityStoreError::Db(e) => Self::Db(e),
+                EntityStoreError::Conflict(e) => Self::Conflict(e)
+            }
+        }
+    }
+
+    impl From<Msg<SetDhcp6Iana>> for RackdCmd {
+        fn from(cmd: Msg<SetDhcp6Iana>) -> Self {
+            Self::Wan(WanCmd::SetDhcp6Iana(cmd))
+        }
+    }
+}
+
+pub mod api {
+    use std::collections::HashMap;
+    use serde_json::Value;
+    use axum::{extract::{OriginalUri, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, util::api::{Error, Json, Response, TryFromJson}, wan::model::values::{Dhcp6Iana, WanId}};
+    use super::{SetDhcp6Iana, SetDhcp6IanaError, SetDhcp6IanaFieldName};
+
+    #[utoipa::path(patch, path = "/wan/dhcp6/iana", tag = "wan",
+        request_body = SetDhcp6Iana,
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn set_iana(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Json(cmd): Json<SetDhcp6Iana>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.exec(cmd).await
+            .map(|_| Response::ok((), path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl TryFromJson for SetDhcp6Iana {
+        fn try_from(mut map: HashMap<String, Value>) -> Result<Self, Vec<Error>> {
+            Self::check_keys(&map, SetDhcp6Iana::as_field_name_array().map(|f| f.name()))?;
+            let id = map.remove(SetDhcp6IanaFieldName::Id.name()).unwrap_or_default();
+            let iana = map.remove(SetDhcp6IanaFieldName::Iana.name()).unwrap_or_default();
+
+            match (WanId::try_from(id), Dhcp6Iana::try_from(iana)) {
+                (Ok(id), Ok(iana)) => Ok(Self { id, iana }),
+                (r1, r2) => {
+                    let e1 = r1.map_err(Error::from).err();
+                    let e2 = r2.map_err(Error::from).err();
+
+                    let errors: Vec<Error> = [e1, e2].into_iter().flatten().collect();
+                    Err(errors)
+                }
+            }
+        }
+    }
+
+    impl From<SetDhcp6IanaError> for Error {
+        fn from(error: SetDhcp6IanaError) -> Self {
+            let msg = error.to_string();
+            match error {
+                SetDhcp6IanaError::Db(_) => Error::new("SET_DHCP6_IANA_DB_ERROR", msg),
+                SetDhcp6IanaError::Conflict(_) => Error::new("SET_DHCP6_IANA_CONFLICT", msg),
+                SetDhcp6IanaError::Cluster(e) => Error::from(e),
+                SetDhcp6IanaError::WanNotFound => Error::new("SET_DHCP6_IANA_WAN_NOT_FOUND", msg),
+                SetDhcp6IanaError::AlreadySet => Error::new("SET_DHCP6_IANA_ALREADY_SET", msg),
+                SetDhcp6IanaError::InvalidLifetimes(..) => Error::new("SET_DHCP6_IANA_INVALID_LIFETIMES", msg)
+            }
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use field_types::FieldName;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::{EntityStore, VersionConflict}, raft::ClusterError, Tx}, util::{actor::{Payload, Process}, models::Entity}, wan::model::{entity::{Wan, WanEvent}, values::{Dhcp6Iapd, WanId}}};
+
+/// Longest prefix worth hinting, anything past a /64 can't be split into LANs
+pub const MAX_HINT_LEN: u8 = 64;
+
+/// Identity Association the wan's DHCPv6 client requests a delegated prefix with
+#[derive(Debug, Serialize, Deserialize, ToSchema, FieldName)]
+pub struct SetDhcp6Iapd {
+    pub id: WanId,
+    /// {"iaid": 1, "prefix_hint": "::/56", "valid_lt": 0, "preferred_lt": 0}
+    #[schema(value_type = Object)]
+    pub iapd: Dhcp6Iapd
+}
+
+#[derive(Debug, Error)]
+pub enum SetDhcp6IapdError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Conflict(#[from] VersionConflict),
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
+    #[error("Wan with id x can't be found")]
+    WanNotFound,
+    #[error("Wan IA_PD already set")]
+    AlreadySet,
+    #[error("Preferred lifetime {0} is longer than the valid lifetime {1}")]
+    InvalidLifetimes(u32, u32),
+    #[error("Prefix hint length {0} is too long (expected <= {max})", max = MAX_HINT_LEN)]
+    InvalidPrefixHint(u8)
+}
+
+impl Payload for SetDhcp6Iapd {
+    type Ok = ();
+    type Err = SetDhcp6IapdError;
+}
+
+impl SetDhcp6Iapd {
+    fn exec(&self, wan: Option<Wan>) -> Result<Wan, SetDhcp6IapdError> {
+        let mut wan = wan.filter(|wan| !wan.deleted).ok_or(SetDhcp6IapdError::WanNotFound)?;
+        // Servers discard an IA with preferred > valid (RFC 8415 section 21.22)
+        if self.iapd.preferred_lt > self.iapd.valid_lt {
+            Err(SetDhcp6IapdError::InvalidLifetimes(self.iapd.preferred_lt, self.iapd.valid_lt))?
+        }
+        if self.iapd.prefix_hint.len > MAX_HINT_LEN {
+            Err(SetDhcp6IapdError::InvalidPrefixHint(self.iapd.prefix_hint.len))?
+        }
+        if wan.dhcp6.iapd == self.iapd {
+            Err(SetDhcp6IapdError::AlreadySet)?
+        }
+        wan.process(WanEvent::Dhcp6IapdSet { from: wan.dhcp6.iapd, to: self.iapd });
+        Ok(wan)
+    }
+}
+
+impl Process for SetDhcp6Iapd {
+    type Actor = RackdCmdActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let wan = tx.load(self.id)?;
+        self.exec(wan).map(|mut wan| {
+            tx.save(&mut wan)?;
+            tx.commit()?;
+            Ok(())
+        })?
+    }
+}
+
+pub mod casts {
+    use crate::{actors::cmd::RackdCmd, db::cmd::traits::EntityStoreError, util::actor::Msg, wan::cmd::WanCmd};
+    use super::{SetDhcp6Iapd, SetDhcp6IapdError};
+
+    impl From<EntityStoreError> for SetDhcp6IapdError {
+        fn from(error: EntityStoreError) -> Self {
+            match error {
+                EntityStoreError::Db(e) => Self::Db(e),
+                EntityStoreError::Conflict(e) => Self::Conflict(e)
+            }
+        }
+    }
+
+    impl From<Msg<SetDhcp6Iapd>> for RackdCmd {
+        fn from(cmd: Msg<SetDhcp6Iapd>) -> Self {
+            Self::Wan(WanCmd::SetDhcp6Iapd(cmd))
+        }
+    }
+}
+
+pub mod api {
+    use std::collections::HashMap;
+    use serde_json::Value;
+    use axum::{extract::{OriginalUri, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, util::api::{Error, Json, Response, TryFromJson}, wan::model::values::{Dhcp6Iapd, WanId}};
+    use super::{SetDhcp6Iapd, SetDhcp6IapdError, SetDhcp6IapdFieldName};
+
+    #[utoipa::path(patch, path = "/wan/dhcp6/iapd", tag = "wan",
+        request_body = SetDhcp6Iapd,
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn set_iapd(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Json(cmd): Json<SetDhcp6Iapd>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.exec(cmd).await
+            .map(|_| Response::ok((), path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl TryFromJson for SetDhcp6Iapd {
+        fn try_from(mut map: HashMap<String, Value>) -> Result<Self, Vec<Error>> {
+            Self::check_keys(&map, SetDhcp6Iapd::as_field_name_array().map(|f| f.name()))?;
+            let id = map.remove(SetDhcp6IapdFieldName::Id.name()).unwrap_or_default();
+            let iapd = map.remove(SetDhcp6IapdFieldName::Iapd.name()).unwrap_or_default();
+
+            match (WanId::try_from(id), Dhcp6Iapd::try_from(iapd)) {
+                (Ok(id), Ok(iapd)) => Ok(Self { id, iapd }),
+                (r1, r2) => {
+                    let e1 = r1.map_err(Error::from).err();
+                    let e2 = r2.map_err(Error::from).err();
+
+                    let errors: Vec<Error> = [e1, e2].into_iter().flatten().collect();
+                    Err(errors)
+                }
+            }
+        }
+    }
+
+    impl From<SetDhcp6IapdError> for Error {
+        fn from(error: SetDhcp6IapdError) -> Self {
+            let msg = error.to_string();
+            match error {
+                SetDhcp6IapdError::Db(_) => Error::new("SET_DHCP6_IAPD_DB_ERROR", msg),
+                SetDhcp6IapdError::Conflict(_) => Error::new("SET_DHCP6_IAPD_CONFLICT", msg),
+                SetDhcp6IapdError::Cluster(e) => Error::from(e),
+                SetDhcp6IapdError::WanNotFound => Error::new("SET_DHCP6_IAPD_WAN_NOT_FOUND", msg),
+                SetDhcp6IapdError::AlreadySet => Error::new("SET_DHCP6_IAPD_ALREADY_SET", msg),
+                SetDhcp6IapdError::InvalidLifetimes(..) => Error::new("SET_DHCP6_IAPD_INVALID_LIFETIMES", msg),
+                SetDhcp6IapdError::InvalidPrefixHint(_) => Error::new("SET_DHCP6_IAPD_INVALID_PREFIX_HINT", msg)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::{net::Ipv6Prefix, wan::{cmd::{set_dhcp6_iapd::{SetDhcp6Iapd, SetDhcp6IapdError}}, model::values::{Dhcp6Iapd, WanMode}, query::get_by_key::GetWanById}};
+    use crate::util::test_support::create_wan;
+
+    #[tokio::test]
+    async fn can_set_iapd_with_a_valid_hint() {
+        let (rackd, _, id) = create_wan(WanMode::IPoE).await;
+
+        let iapd = Dhcp6Iapd { iaid: 1, prefix_hint: Ipv6Prefix::from_str("::/72").unwrap(), valid_lt: 0, preferred_lt: 0 };
+        assert!(rackd.exec(SetDhcp6Iapd { id, iapd }).await.is_err_and(|e| matches!(e, SetDhcp6IapdError::InvalidPrefixHint(72))));
+        let iapd = Dhcp6Iapd { iaid: 1, prefix_hint: Ipv6Prefix::from_str("::/56").unwrap(), valid_lt: 3600, preferred_lt: 7200 };
+        assert!(rackd.exec(SetDhcp6Iapd { id, iapd }).await.is_err_and(|e| matches!(e, SetDhcp6IapdError::InvalidLifetimes(7200, 3600))));
+
+        let iapd = Dhcp6Iapd { iaid: 1, prefix_hint: Ipv6Prefix::from_str("::/56").unwrap(), valid_lt: 7200, preferred_lt: 3600 };
+        rackd.exec(SetDhcp6Iapd { id, iapd }).await.unwrap();
+        assert_eq!(rackd.query(GetWanById { id }).await.unwrap().dhcp6.iapd, iapd);
+    }
+}
@@ -1,10 +1,14 @@
+use field_types::FieldName;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::EntityStore, Tx}, net::Ipv4Params, util::{actor::{Payload, Process}, models::Entity}, wan::model::{entity::{Wan, WanEvent}, values::WanId}};
+use utoipa::ToSchema;
+use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::{EntityStore, VersionConflict}, raft::ClusterError, Tx}, net::Ipv4Params, util::{actor::{Payload, Process}, models::Entity}, wan::model::{entity::{Wan, WanEvent}, values::WanId}};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema, FieldName)]
 pub struct SetIpv4Params {
     pub id: WanId,
+    /// "dhcp" or {"addr": "192.0.2.2", "mask_len": 24, "gateway": "192.0.2.1"}
+    #[schema(value_type = Object)]
     pub ip: Ipv4Params
 }
 
@@ -12,6 +16,10 @@ pub struct SetIpv4Params {
 pub enum SetIpv4ParamsError {
     #[error("Db Error")]
     Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Conflict(#[from] VersionConflict),
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
     #[error("Rack not found")]
     RackNotFound,
     #[error("Wan with id x can't be found")]
@@ -27,7 +35,7 @@ impl Payload for SetIpv4Params {
 
 impl SetIpv4Params {
     fn exec(&self, wan: Option<Wan>) -> Result<Wan, SetIpv4ParamsError> {
-        let mut wan = wan.ok_or(SetIpv4ParamsError::WanNotFound)?;
+        let mut wan = wan.filter(|wan| !wan.deleted).ok_or(SetIpv4ParamsError::WanNotFound)?;
         if wan.ipv4 == self.ip {
             Err(SetIpv4ParamsError::AlreadySet)?
         } else {
@@ -45,14 +53,24 @@ impl Process for SetIpv4Params {
         let wan = tx.load(self.id)?;
         self.exec(wan).map(|mut wan| {
             tx.save(&mut wan)?;
+            tx.commit()?;
             Ok(())
         })?
     }
 }
 
 pub mod casts {
-    use crate::{actors::cmd::RackdCmd, util::actor::Msg, wan::cmd::WanCmd};
-    use super::SetIpv4Params;
+    use crate::{actors::cmd::RackdCmd, db::cmd::traits::EntityStoreError, util::actor::Msg, wan::cmd::WanCmd};
+    use super::{SetIpv4Params, SetIpv4ParamsError};
+
+    impl From<EntityStoreError> for SetIpv4ParamsError {
+        fn from(error: EntityStoreError) -> Self {
+            match error {
+                EntityStoreError::Db(e) => Self::Db(e),
+                EntityStoreError::Conflict(e) => Self::Conflict(e)
+            }
+        }
+    }
 
     impl From<Msg<SetIpv4Params>> for RackdCmd {
         fn from(cmd: Msg<SetIpv4Params>) -> Self {
@@ -61,10 +79,66 @@ pub mod casts {
     }
 }
 
+pub mod api {
+    use std::collections::HashMap;
+    use serde_json::Value;
+    use axum::{extract::{OriginalUri, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, net::Ipv4Params, util::api::{Error, Json, Response, TryFromJson}, wan::model::values::WanId};
+    use super::{SetIpv4Params, SetIpv4ParamsError, SetIpv4ParamsFieldName};
+
+    #[utoipa::path(patch, path = "/wan/ipv4", tag = "wan",
+        request_body = SetIpv4Params,
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn set_ipv4(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Json(cmd): Json<SetIpv4Params>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.exec(cmd).await
+            .map(|_| Response::ok((), path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl TryFromJson for SetIpv4Params {
+        fn try_from(mut map: HashMap<String, Value>) -> Result<Self, Vec<Error>> {
+            Self::check_keys(&map, SetIpv4Params::as_field_name_array().map(|f| f.name()))?;
+            let id = map.remove(SetIpv4ParamsFieldName::Id.name()).unwrap_or_default();
+            let ip = map.remove(SetIpv4ParamsFieldName::Ip.name()).unwrap_or_default();
+
+            match (WanId::try_from(id), Ipv4Params::try_from(ip)) {
+                (Ok(id), Ok(ip)) => Ok(Self { id, ip }),
+                (r1, r2) => {
+                    let e1 = r1.map_err(Error::from).err();
+                    let e2 = r2.map_err(Error::from).err();
+
+                    let errors: Vec<Error> = [e1, e2].into_iter().flatten().collect();
+                    Err(errors)
+                }
+            }
+        }
+    }
+
+    impl From<SetIpv4ParamsError> for Error {
+        fn from(error: SetIpv4ParamsError) -> Self {
+            let msg = error.to_string();
+            match error {
+                SetIpv4ParamsError::Db(_) => Error::new("SET_IPV4_PARAMS_DB_ERROR", msg),
+                SetIpv4ParamsError::Conflict(_) => Error::new("SET_IPV4_PARAMS_CONFLICT", msg),
+                SetIpv4ParamsError::Cluster(e) => Error::from(e),
+                SetIpv4ParamsError::RackNotFound => Error::new("SET_IPV4_PARAMS_RACK_NOT_FOUND", msg),
+                SetIpv4ParamsError::WanNotFound => Error::new("SET_IPV4_PARAMS_WAN_NOT_FOUND", msg),
+                SetIpv4ParamsError::AlreadySet => Error::new("SET_IPV4_PARAMS_ALREADY_SET", msg)
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
-    use crate::{actors::system::Rackd, net::{Ipv4Params, NetName, VlanId}, trunk::{cmd::create::CreateTrunk, model::TrunkName}, wan::{cmd::{create::CreateWan, set_ipv4::{SetIpv4Params, SetIpv4ParamsError}}, model::values::{WanId, WanMode}}};
+    use std::net::Ipv4Addr;
+    use crate::{actors::system::Rackd, net::{Ipv4Params, Ipv4PrefixLen, NetName, VlanId}, wan::{cmd::{create::CreateWan, set_ipv4::{SetIpv4Params, SetIpv4ParamsError}}, model::values::{WanId, WanMode}, query::get_by_key::GetWanById}};
+    use crate::util::test_support::init_rack;
 
     #[tokio::test]
     async fn cant_set_ipv4_if_wan_doesnt_exist() {
@@ -79,14 +153,11 @@ mod tests {
     #[tokio::test]
     async fn cant_set_ipv4_if_already_set() {
         let rackd = Rackd::mock().unwrap();
-        let cmd = CreateTrunk {
-            name: TrunkName::from_str("trunk1").unwrap()
-        };
-        let trunk_id = rackd.exec(cmd).await.unwrap();
+        let trunk_id = init_rack(&rackd).await;
         let cmd = CreateWan {
             trunk: trunk_id,
             vlan: VlanId::try_from(4000).unwrap(),
-            name: NetName::from_str("at&t").unwrap(),
+            name: NetName::from_str("att").unwrap(),
             mode: WanMode::IPoE
         };
         let wan_id = rackd.exec(cmd).await.unwrap();
@@ -98,6 +169,27 @@ mod tests {
         assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, SetIpv4ParamsError::AlreadySet)));
     }
 
+    #[tokio::test]
+    async fn static_ipv4_is_in_the_wan_view() {
+        let rackd = Rackd::mock().unwrap();
+        let trunk_id = init_rack(&rackd).await;
+        let cmd = CreateWan {
+            trunk: trunk_id,
+            vlan: VlanId::try_from(4000).unwrap(),
+            name: NetName::from_str("att").unwrap(),
+            mode: WanMode::IPoE
+        };
+        let wan_id = rackd.exec(cmd).await.unwrap();
+        let ip = Ipv4Params::Static {
+            addr: Ipv4Addr::new(192, 0, 2, 2),
+            mask_len: Ipv4PrefixLen::try_from(24).unwrap(),
+            gateway: Ipv4Addr::new(192, 0, 2, 1)
+        };
+        rackd.exec(SetIpv4Params { id: wan_id, ip }).await.unwrap();
+        let wan = rackd.query(GetWanById { id: wan_id }).await.unwrap();
+        assert_eq!(wan.ipv4, ip);
+    }
+
     // #[tokio::test]
 // async fn can_spoof_and_unspoof_mac() {
 //     let api = new_api();
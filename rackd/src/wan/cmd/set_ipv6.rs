@@ -1,70 +1,214 @@
-// use serde::{Deserialize, Serialize};
-// use thiserror::Error;
-// use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::EntityStore, Tx}, wan::model::{values::{WanId, WanIpv6}, Wan, WanEvent}, util::{actor::{Payload, Process}, models::Entity}};
-
-// #[derive(Debug, Serialize, Deserialize)]
-// pub struct SetIpv6 {
-//     pub id: WanId,
-//     pub ip: WanIpv6
-// }
-
-// #[derive(Debug, Error)]
-// pub enum SetIpv6Error {
-//     #[error("Db Error")]
-//     Db(#[from] rusqlite::Error),
-//     #[error("Wan with id x can't be found")]
-//     WanNotFound,
-//     #[error("Wan IPv6 settings not available for PPPoE Connections")]
-//     ConnectionIsPPPoE,
-//     #[error("Wan IPv6 Host already set")]
-//     AlreadySet,
-
-//     // #[error("Prefix Length needs to be greated than 64")]
-//     // InvalidPrefixLength(Ipv6PrefixLen),
-//     // #[error("IPv6 Address {0} isn't a valid GUA Address")]
-//     // InvalidIpv6Address(Ipv6Addr),
-//     // #[error("Gateway {0} isn't a valid LL Address")]
-//     // InvalidIpv6Gateway(Ipv6Addr)
-// }
-
-// impl Payload for SetIpv6 {
-//     type Ok = ();
-//     type Err = SetIpv6Error;
-// }
-
-// impl SetIpv6 {
-//     fn exec(&self, wan: Option<Wan>) -> Result<Wan, SetIpv6Error> {
-//         let mut wan = wan.ok_or(SetIpv6Error::WanNotFound)?;
-
-//         if wan.ip.ipv6 == self.ip {
-//             Err(SetIpv6Error::AlreadySet)?
-//         } else {
-//             wan.process(WanEvent::Ipv6Set { from: wan.ip.ipv6.clone(), to: self.ip.clone() });
-//             Ok(wan)
-//         }
-
-//         // TBD: Create struct: WanIpv6Host(Ipv6Host) so that WanIpv6Host::new(Ipv6Host)? returns an 
-//         // error that is mapped to SetIpv6::InvalidHost
-//         // Perhaps this should just be warnings displayed on the client side
-//         // if self.host.addr.prefix_len.value() < 64 {
-//         //     Err(SetIpv6ToStaticError::InvalidPrefixLength(self.host.addr.prefix_len))?;
-//         // } else if !Ipv6Addr::is_global(&self.host.addr.addr) {
-//         //     Err(SetIpv6ToStaticError::InvalidIpv6Address(self.host.addr.addr))?;
-//         // } else if !Ipv6Addr::is_unicast_link_local(&self.host.gateway) {
-//         //     Err(SetIpv6ToStaticError::InvalidIpv6Gateway(self.host.gateway))?;
-//         // }
-//     }
-// }
-
-// impl Process for SetIpv6 {
-//     type Actor = RackdCmdActor;
-
-//     fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
-//         let tx = actor.conn.tx()?;
-//         let wan = tx.load::<Wan>(self.id)?;
-//         self.exec(wan).map(|mut wan| {
-//             tx.save(&mut wan)?;
-//             Ok(())
-//         })?
-//     }
-// }
\ No newline at end of file
+use std::net::Ipv6Addr;
+use field_types::FieldName;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::{EntityStore, VersionConflict}, raft::ClusterError, Tx}, util::{actor::{Payload, Process}, models::Entity}, wan::model::{entity::{Wan, WanEvent}, values::{Ipv6Host, WanId, WanIpv6, WanMode}}};
+
+/// Longest prefix a static address can be on, SLAAC and most ISPs hand out /64s
+pub const MIN_PREFIX_LEN: u8 = 64;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, FieldName)]
+pub struct SetIpv6 {
+    pub id: WanId,
+    /// "auto" or {"addr": "2001:db8::2", "prefix_len": 64, "gateway": "fe80::1"}
+    #[schema(value_type = Object)]
+    pub ip: WanIpv6
+}
+
+#[derive(Debug, Error)]
+pub enum SetIpv6Error {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Conflict(#[from] VersionConflict),
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
+    #[error("Wan with id x can't be found")]
+    WanNotFound,
+    #[error("Wan IPv6 settings not available for PPPoE Connections")]
+    ConnectionIsPPPoE,
+    #[error("Wan IPv6 already set")]
+    AlreadySet,
+    #[error("Prefix Length {0} is too short (expected >= {min})", min = MIN_PREFIX_LEN)]
+    InvalidPrefixLength(u8),
+    #[error("IPv6 Address {0} isn't a valid GUA Address")]
+    InvalidIpv6Address(Ipv6Addr),
+    #[error("Gateway {0} isn't a valid LL Address")]
+    InvalidIpv6Gateway(Ipv6Addr)
+}
+
+impl Payload for SetIpv6 {
+    type Ok = ();
+    type Err = SetIpv6Error;
+}
+
+impl SetIpv6 {
+    fn exec(&self, wan: Option<Wan>) -> Result<Wan, SetIpv6Error> {
+        let mut wan = wan.filter(|wan| !wan.deleted).ok_or(SetIpv6Error::WanNotFound)?;
+        if let WanIpv6::Static(host) = &self.ip {
+            // PPPoE gets its addresses over the session (IPv6CP + RA/DHCPv6), there's nothing to pin
+            if wan.mode == WanMode::PPPoE {
+                Err(SetIpv6Error::ConnectionIsPPPoE)?
+            }
+            Self::validate(host)?;
+        }
+        if wan.ipv6 == self.ip {
+            Err(SetIpv6Error::AlreadySet)?
+        }
+        wan.process(WanEvent::Ipv6Set { from: wan.ipv6.clone(), to: self.ip.clone() });
+        Ok(wan)
+    }
+
+    fn validate(host: &Ipv6Host) -> Result<(), SetIpv6Error> {
+        let prefix_len = u8::from(host.addr.prefix_len);
+        // Global Unicast is 2000::/3 (RFC 4291)
+        let is_global_unicast = host.addr.addr.segments()[0] & 0xe000 == 0x2000;
+        if prefix_len < MIN_PREFIX_LEN {
+            Err(SetIpv6Error::InvalidPrefixLength(prefix_len))
+        } else if !is_global_unicast {
+            Err(SetIpv6Error::InvalidIpv6Address(host.addr.addr))
+        } else if !host.gateway.is_unicast_link_local() {
+            Err(SetIpv6Error::InvalidIpv6Gateway(host.gateway))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Process for SetIpv6 {
+    type Actor = RackdCmdActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let wan = tx.load(self.id)?;
+        self.exec(wan).map(|mut wan| {
+            tx.save(&mut wan)?;
+            tx.commit()?;
+            Ok(())
+        })?
+    }
+}
+
+pub mod casts {
+    use crate::{actors::cmd::RackdCmd, db::cmd::traits::EntityStoreError, util::actor::Msg, wan::cmd::WanCmd};
+    use super::{SetIpv6, SetIpv6Error};
+
+    impl From<EntityStoreError> for SetIpv6Error {
+        fn from(error: EntityStoreError) -> Self {
+            match error {
+                EntityStoreError::Db(e) => Self::Db(e),
+                EntityStoreError::Conflict(e) => Self::Conflict(e)
+            }
+        }
+    }
+
+    impl From<Msg<SetIpv6>> for RackdCmd {
+        fn from(cmd: Msg<SetIpv6>) -> Self {
+            Self::Wan(WanCmd::SetIpv6(cmd))
+        }
+    }
+}
+
+pub mod api {
+    use std::collections::HashMap;
+    use serde_json::Value;
+    use axum::{extract::{OriginalUri, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, util::api::{Error, Json, Response, TryFromJson}, wan::model::values::{WanId, WanIpv6}};
+    use super::{SetIpv6, SetIpv6Error, SetIpv6FieldName};
+
+    #[utoipa::path(patch, path = "/wan/ipv6", tag = "wan",
+        request_body = SetIpv6,
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn set_ipv6(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Json(cmd): Json<SetIpv6>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.exec(cmd).await
+            .map(|_| Response::ok((), path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl TryFromJson for SetIpv6 {
+        fn try_from(mut map: HashMap<String, Value>) -> Result<Self, Vec<Error>> {
+            Self::check_keys(&map, SetIpv6::as_field_name_array().map(|f| f.name()))?;
+            let id = map.remove(SetIpv6FieldName::Id.name()).unwrap_or_default();
+            let ip = map.remove(SetIpv6FieldName::Ip.name()).unwrap_or_default();
+
+            match (WanId::try_from(id), WanIpv6::try_from(ip)) {
+                (Ok(id), Ok(ip)) => Ok(Self { id, ip }),
+                (r1, r2) => {
+                    let e1 = r1.map_err(Error::from).err();
+                    let e2 = r2.map_err(Error::from).err();
+
+                    let errors: Vec<Error> = [e1, e2].into_iter().flatten().collect();
+                    Err(errors)
+                }
+            }
+        }
+    }
+
+    impl From<SetIpv6Error> for Error {
+        fn from(error: SetIpv6Error) -> Self {
+            let msg = error.to_string();
+            match error {
+                SetIpv6Error::Db(_) => Error::new("SET_IPV6_DB_ERROR", msg),
+                SetIpv6Error::Conflict(_) => Error::new("SET_IPV6_CONFLICT", msg),
+                SetIpv6Error::Cluster(e) => Error::from(e),
+                SetIpv6Error::WanNotFound => Error::new("SET_IPV6_WAN_NOT_FOUND", msg),
+                SetIpv6Error::ConnectionIsPPPoE => Error::new("SET_IPV6_CONNECTION_IS_PPPOE", msg),
+                SetIpv6Error::AlreadySet => Error::new("SET_IPV6_ALREADY_SET", msg),
+                SetIpv6Error::InvalidPrefixLength(_) => Error::new("SET_IPV6_INVALID_PREFIX_LENGTH", msg),
+                SetIpv6Error::InvalidIpv6Address(_) => Error::new("SET_IPV6_INVALID_ADDRESS", msg),
+                SetIpv6Error::InvalidIpv6Gateway(_) => Error::new("SET_IPV6_INVALID_GATEWAY", msg)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::Ipv6Addr, str::FromStr};
+    use crate::{net::Ipv6HostAddr, wan::{cmd::set_ipv6::{SetIpv6, SetIpv6Error}, model::values::{Ipv6Host, WanIpv6, WanMode}, query::get_by_key::GetWanById}};
+    use crate::util::test_support::create_wan;
+
+    fn host(addr: &str, prefix_len: u8, gateway: &str) -> WanIpv6 {
+        WanIpv6::Static(Ipv6Host {
+            addr: Ipv6HostAddr {
+                addr: Ipv6Addr::from_str(addr).unwrap(),
+                prefix_len: prefix_len.try_into().unwrap()
+            },
+            gateway: Ipv6Addr::from_str(gateway).unwrap()
+        })
+    }
+
+    #[tokio::test]
+    async fn cant_set_invalid_static_host() {
+        let (rackd, _, id) = create_wan(WanMode::IPoE).await;
+        let cmd = SetIpv6 { id, ip: host("2800:200:44:8814::2", 56, "fe80::1") };
+        assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, SetIpv6Error::InvalidPrefixLength(56))));
+        let cmd = SetIpv6 { id, ip: host("fd00::2", 64, "fe80::1") };
+        assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, SetIpv6Error::InvalidIpv6Address(_))));
+        let cmd = SetIpv6 { id, ip: host("2800:200:44:8814::2", 64, "2800:200:44:8814::1") };
+        assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, SetIpv6Error::InvalidIpv6Gateway(_))));
+    }
+
+    #[tokio::test]
+    async fn cant_set_static_host_on_pppoe() {
+        let (rackd, _, id) = create_wan(WanMode::PPPoE).await;
+        let cmd = SetIpv6 { id, ip: host("2800:200:44:8814::2", 64, "fe80::1") };
+        assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, SetIpv6Error::ConnectionIsPPPoE)));
+    }
+
+    #[tokio::test]
+    async fn can_switch_between_ipv6_modes() {
+        let (rackd, _, id) = create_wan(WanMode::IPoE).await;
+        let ip = host("2800:200:44:8814:216:3eff:fe17:bb6f", 64, "fe80::216:3eff:fe17:bb6f");
+        rackd.exec(SetIpv6 { id, ip: ip.clone() }).await.unwrap();
+        assert_eq!(rackd.query(GetWanById { id }).await.unwrap().ipv6, ip);
+        rackd.exec(SetIpv6 { id, ip: WanIpv6::Auto }).await.unwrap();
+        assert_eq!(rackd.query(GetWanById { id }).await.unwrap().ipv6, WanIpv6::Auto);
+        assert!(rackd.exec(SetIpv6 { id, ip: WanIpv6::Auto }).await.is_err_and(|e| matches!(e, SetIpv6Error::AlreadySet)));
+    }
+}
@@ -1,17 +1,25 @@
+use field_types::FieldName;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::EntityStore, Tx}, net::MacAddr, util::{actor::{Payload, Process}, models::Entity}, wan::model::{entity::{Wan, WanEvent}, values::WanId}};
+use utoipa::ToSchema;
+use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::{EntityStore, VersionConflict}, raft::ClusterError, Tx}, net::MacAddr, util::{actor::{Payload, Process}, models::Entity}, wan::model::{entity::{Wan, WanEvent}, values::WanId}};
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Spoofs the MAC of the wan's link, or goes back to the link's own MAC with {"mode": "auto"} ("auto" and the MAC alone are accepted too)
+#[derive(Debug, Serialize, Deserialize, ToSchema, FieldName)]
 pub struct SetMacAddr {
-    id: WanId,
-    mac: MacAddr
+    pub id: WanId,
+    #[schema(value_type = Object, example = json!({"mode": "spoofed", "mac": "76:dc:3a:78:af:d0"}))]
+    pub mac: MacAddr
 }
 
 #[derive(Debug, Error)]
 pub enum SetMacAddrError {
     #[error("Db Error")]
     Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Conflict(#[from] VersionConflict),
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
     #[error("Wan's MAC is already set")]
     AlreadySet,
     #[error("Wan with id x can't be found")]
@@ -25,11 +33,11 @@ impl Payload for SetMacAddr {
 
 impl SetMacAddr {
     fn exec(&self, wan: Option<Wan>) -> Result<Wan, SetMacAddrError> {
-        let mut wan = wan.ok_or(SetMacAddrError::WanNotFound)?;
+        let mut wan = wan.filter(|wan| !wan.deleted).ok_or(SetMacAddrError::WanNotFound)?;
         if wan.mac == self.mac {
             Err(SetMacAddrError::AlreadySet)?
         } else {
-            wan.process(WanEvent::MacAddrSet { from: wan.mac, to: MacAddr::Auto });
+            wan.process(WanEvent::MacAddrSet { from: wan.mac, to: self.mac });
             Ok(wan)
         }
     }
@@ -43,14 +51,24 @@ impl Process for SetMacAddr {
         let wan = tx.load(self.id)?;
         self.exec(wan).map(|mut wan| {
             tx.save(&mut wan)?;
+            tx.commit()?;
             Ok(())
         })?
     }
 }
 
 pub mod casts {
-    use crate::{actors::cmd::RackdCmd, util::actor::Msg, wan::cmd::WanCmd};
-    use super::SetMacAddr;
+    use crate::{actors::cmd::RackdCmd, db::cmd::traits::EntityStoreError, util::actor::Msg, wan::cmd::WanCmd};
+    use super::{SetMacAddr, SetMacAddrError};
+
+    impl From<EntityStoreError> for SetMacAddrError {
+        fn from(error: EntityStoreError) -> Self {
+            match error {
+                EntityStoreError::Db(e) => Self::Db(e),
+                EntityStoreError::Conflict(e) => Self::Conflict(e)
+            }
+        }
+    }
 
     impl From<Msg<SetMacAddr>> for RackdCmd {
         fn from(cmd: Msg<SetMacAddr>) -> Self {
@@ -59,11 +77,65 @@ pub mod casts {
     }
 }
 
+pub mod api {
+    use std::collections::HashMap;
+    use serde_json::Value;
+    use axum::{extract::{OriginalUri, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, net::MacAddr, util::api::{Error, Json, Response, TryFromJson}, wan::model::values::WanId};
+    use super::{SetMacAddr, SetMacAddrError, SetMacAddrFieldName};
+
+    #[utoipa::path(patch, path = "/wan/mac", tag = "wan",
+        request_body = SetMacAddr,
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn set_mac(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Json(cmd): Json<SetMacAddr>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.exec(cmd).await
+            .map(|_| Response::ok((), path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl TryFromJson for SetMacAddr {
+        fn try_from(mut map: HashMap<String, Value>) -> Result<Self, Vec<Error>> {
+            Self::check_keys(&map, SetMacAddr::as_field_name_array().map(|f| f.name()))?;
+            let id = map.remove(SetMacAddrFieldName::Id.name()).unwrap_or_default();
+            let mac = map.remove(SetMacAddrFieldName::Mac.name()).unwrap_or_default();
+
+            match (WanId::try_from(id), MacAddr::try_from(mac)) {
+                (Ok(id), Ok(mac)) => Ok(Self { id, mac }),
+                (r1, r2) => {
+                    let e1 = r1.map_err(Error::from).err();
+                    let e2 = r2.map_err(Error::from).err();
+
+                    let errors: Vec<Error> = [e1, e2].into_iter().flatten().collect();
+                    Err(errors)
+                }
+            }
+        }
+    }
+
+    impl From<SetMacAddrError> for Error {
+        fn from(error: SetMacAddrError) -> Self {
+            let msg = error.to_string();
+            match error {
+                SetMacAddrError::Db(_) => Error::new("SET_MAC_ADDR_DB_ERROR", msg),
+                SetMacAddrError::Conflict(_) => Error::new("SET_MAC_ADDR_CONFLICT", msg),
+                SetMacAddrError::Cluster(e) => Error::from(e),
+                SetMacAddrError::AlreadySet => Error::new("SET_MAC_ADDR_ALREADY_SET", msg),
+                SetMacAddrError::WanNotFound => Error::new("SET_MAC_ADDR_WAN_NOT_FOUND", msg)
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::str::FromStr;
-    use crate::{actors::system::Rackd, net::{MacAddr, NetName, VlanId}, trunk::{cmd::create::CreateTrunk, model::TrunkName}, wan::{cmd::{create::CreateWan, set_mac::SetMacAddrError}, model::values::{WanId, WanMode}}};
+    use macaddr::MacAddr6;
+    use crate::{actors::system::Rackd, net::MacAddr, wan::{cmd::set_mac::SetMacAddrError, model::values::{WanId, WanMode}, query::get_by_key::GetWanById}};
     use super::SetMacAddr;
+    use crate::util::test_support::create_wan;
 
     #[tokio::test]
     async fn cant_set_mac_addr_if_wan_doesnt_exist() {
@@ -77,22 +149,22 @@ mod tests {
 
     #[tokio::test]
     async fn cant_set_mac_addr_if_already_set() {
-        let rackd = Rackd::mock().unwrap();
-        let cmd = CreateTrunk {
-            name: TrunkName::from_str("trunk1").unwrap()
-        };
-        let trunk_id = rackd.exec(cmd).await.unwrap();
-        let cmd = CreateWan {
-            trunk: trunk_id,
-            vlan: VlanId::try_from(4001).unwrap(),
-            name: NetName::from_str("verizon").unwrap(),
-            mode: WanMode::IPoE
-        };
-        let wan_id = rackd.exec(cmd).await.unwrap();
+        let (rackd, _, wan_id) = create_wan(WanMode::IPoE).await;
         let cmd = SetMacAddr {
             id: wan_id,
             mac: MacAddr::Auto
         };
         assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, SetMacAddrError::AlreadySet)));
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn can_spoof_and_unspoof_mac_addr() {
+        let (rackd, _, wan_id) = create_wan(WanMode::IPoE).await;
+        let mac = MacAddr::Spoofed(MacAddr6::new(0x76, 0xdc, 0x3a, 0x78, 0xaf, 0xd0));
+        rackd.exec(SetMacAddr { id: wan_id, mac }).await.unwrap();
+        assert_eq!(rackd.query(GetWanById { id: wan_id }).await.unwrap().mac, mac);
+        assert!(rackd.exec(SetMacAddr { id: wan_id, mac }).await.is_err_and(|e| matches!(e, SetMacAddrError::AlreadySet)));
+        rackd.exec(SetMacAddr { id: wan_id, mac: MacAddr::Auto }).await.unwrap();
+        assert_eq!(rackd.query(GetWanById { id: wan_id }).await.unwrap().mac, MacAddr::Auto);
+    }
+}
@@ -0,0 +1,178 @@
+use field_types::FieldName;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::{EntityStore, VersionConflict}, raft::ClusterError, Tx}, net::Ipv4Params, util::{actor::{Payload, Process}, models::Entity}, wan::model::{entity::{Wan, WanEvent}, values::{WanId, WanIpv6, WanMode}}};
+
+/// Moves a wan between IPoE and PPPoE, the state that doesn't carry over to the new mode is reset:
+/// - **ipv4**: Back to DHCP, a static address on PPPoE comes from IPCP and on IPoE it changes with the ISP's migration
+/// - **ipv6**: Static hosts are reset to auto when switching to PPPoE
+/// - **pppoe**: Credentials are dropped when switching to IPoE, they must be set after switching to PPPoE
+/// - **dhcp6**: Kept as is, the DUID and IA_PD identify the rack to the ISP and keep the delegated prefix stable
+#[derive(Debug, Serialize, Deserialize, ToSchema, FieldName)]
+pub struct SetWanMode {
+    pub id: WanId,
+    pub mode: WanMode
+}
+
+#[derive(Debug, Error)]
+pub enum SetWanModeError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Conflict(#[from] VersionConflict),
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
+    #[error("Wan with id x can't be found")]
+    WanNotFound,
+    #[error("Wan mode already set")]
+    AlreadySet
+}
+
+impl Payload for SetWanMode {
+    type Ok = ();
+    type Err = SetWanModeError;
+}
+
+impl SetWanMode {
+    fn exec(&self, wan: Option<Wan>) -> Result<Wan, SetWanModeError> {
+        let mut wan = wan.filter(|wan| !wan.deleted).ok_or(SetWanModeError::WanNotFound)?;
+        if wan.mode == self.mode {
+            Err(SetWanModeError::AlreadySet)?
+        }
+        wan.process(WanEvent::ModeSwitched { from: wan.mode, to: self.mode });
+        if wan.ipv4 != Ipv4Params::DHCP {
+            wan.process(WanEvent::Ipv4ParamsSet { from: wan.ipv4, to: Ipv4Params::DHCP });
+        }
+        if self.mode == WanMode::PPPoE && wan.ipv6 != WanIpv6::Auto {
+            wan.process(WanEvent::Ipv6Set { from: wan.ipv6.clone(), to: WanIpv6::Auto });
+        }
+        Ok(wan)
+    }
+}
+
+impl Process for SetWanMode {
+    type Actor = RackdCmdActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let wan = tx.load(self.id)?;
+        self.exec(wan).map(|mut wan| {
+            tx.save(&mut wan)?;
+            tx.commit()?;
+            Ok(())
+        })?
+    }
+}
+
+pub mod casts {
+    use crate::{actors::cmd::RackdCmd, db::cmd::traits::EntityStoreError, util::actor::Msg, wan::cmd::WanCmd};
+    use super::{SetWanMode, SetWanModeError};
+
+    impl From<EntityStoreError> for SetWanModeError {
+        fn from(error: EntityStoreError) -> Self {
+            match error {
+                EntityStoreError::Db(e) => Self::Db(e),
+                EntityStoreError::Conflict(e) => Self::Conflict(e)
+            }
+        }
+    }
+
+    impl From<Msg<SetWanMode>> for RackdCmd {
+        fn from(cmd: Msg<SetWanMode>) -> Self {
+            Self::Wan(WanCmd::SetMode(cmd))
+        }
+    }
+}
+
+pub mod api {
+    use std::collections::HashMap;
+    use serde_json::Value;
+    use axum::{extract::{OriginalUri, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, util::api::{Error, Json, Response, TryFromJson}, wan::model::values::{WanId, WanMode}};
+    use super::{SetWanMode, SetWanModeError, SetWanModeFieldName};
+
+    #[utoipa::path(patch, path = "/wan/mode", tag = "wan",
+        request_body = SetWanMode,
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn set_mode(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Json(cmd): Json<SetWanMode>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.exec(cmd).await
+            .map(|_| Response::ok((), path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl TryFromJson for SetWanMode {
+        fn try_from(mut map: HashMap<String, Value>) -> Result<Self, Vec<Error>> {
+            Self::check_keys(&map, SetWanMode::as_field_name_array().map(|f| f.name()))?;
+            let id = map.remove(SetWanModeFieldName::Id.name()).unwrap_or_default();
+            let mode = map.remove(SetWanModeFieldName::Mode.name()).unwrap_or_default();
+
+            match (WanId::try_from(id), WanMode::try_from(mode)) {
+                (Ok(id), Ok(mode)) => Ok(Self { id, mode }),
+                (r1, r2) => {
+                    let e1 = r1.map_err(Error::from).err();
+                    let e2 = r2.map_err(Error::from).err();
+
+                    let errors: Vec<Error> = [e1, e2].into_iter().flatten().collect();
+                    Err(errors)
+                }
+            }
+        }
+    }
+
+    impl From<SetWanModeError> for Error {
+        fn from(error: SetWanModeError) -> Self {
+            let msg = error.to_string();
+            match error {
+                SetWanModeError::Db(_) => Error::new("SET_WAN_MODE_DB_ERROR", msg),
+                SetWanModeError::Conflict(_) => Error::new("SET_WAN_MODE_CONFLICT", msg),
+                SetWanModeError::Cluster(e) => Error::from(e),
+                SetWanModeError::WanNotFound => Error::new("SET_WAN_MODE_WAN_NOT_FOUND", msg),
+                SetWanModeError::AlreadySet => Error::new("SET_WAN_MODE_ALREADY_SET", msg)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::Ipv6Addr, str::FromStr};
+    use serde_json::Value;
+    use crate::{net::Ipv6HostAddr, wan::{cmd::{set_ipv6::SetIpv6, set_mode::{SetWanMode, SetWanModeError}, set_pppoe::SetPppoeCredentials}, model::values::{Ipv6Host, PppoePassword, PppoeUsername, WanIpv6, WanMode}, query::{get_by_key::GetWanById, get_pppoe_peer::{GetPppoePeer, GetPppoePeerError}}}};
+    use crate::util::test_support::create_wan;
+
+    #[tokio::test]
+    async fn switching_modes_resets_what_doesnt_carry_over() {
+        let (rackd, _, id) = create_wan(WanMode::IPoE).await;
+        let ip = WanIpv6::Static(Ipv6Host {
+            addr: Ipv6HostAddr { addr: Ipv6Addr::from_str("2800:200:44:8814::2").unwrap(), prefix_len: 64u8.try_into().unwrap() },
+            gateway: Ipv6Addr::from_str("fe80::1").unwrap()
+        });
+        rackd.exec(SetIpv6 { id, ip }).await.unwrap();
+        assert!(rackd.exec(SetWanMode { id, mode: WanMode::IPoE }).await.is_err_and(|e| matches!(e, SetWanModeError::AlreadySet)));
+
+        rackd.exec(SetWanMode { id, mode: WanMode::PPPoE }).await.unwrap();
+        let wan = rackd.query(GetWanById { id }).await.unwrap();
+        assert_eq!(wan.mode, WanMode::PPPoE);
+        assert_eq!(wan.ipv6, WanIpv6::Auto);
+
+        let cmd = SetPppoeCredentials {
+            id,
+            username: PppoeUsername::try_from(Value::from("user@movistar.pe")).unwrap(),
+            password: PppoePassword::try_from(Value::from("hunter2")).unwrap()
+        };
+        rackd.exec(cmd).await.unwrap();
+        rackd.exec(SetWanMode { id, mode: WanMode::IPoE }).await.unwrap();
+        let wan = rackd.query(GetWanById { id }).await.unwrap();
+        assert_eq!(wan.mode, WanMode::IPoE);
+        assert!(wan.pppoe_username.is_none());
+
+        // Credentials don't come back with the mode
+        rackd.exec(SetWanMode { id, mode: WanMode::PPPoE }).await.unwrap();
+        assert!(rackd.query(GetPppoePeer { id, node: 1 }).await.is_err_and(|e| matches!(e, GetPppoePeerError::CredentialsNotSet)));
+    }
+}
@@ -0,0 +1,181 @@
+use field_types::FieldName;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use crate::{actors::cmd::RackdCmdActor, db::{cmd::traits::{EntityStore, VersionConflict}, raft::ClusterError, Tx}, util::{actor::{Payload, Process}, models::Entity}, wan::model::{entity::{Wan, WanEvent}, values::{PppoePassword, PppoeUsername, WanId, WanMode}}};
+
+/// The password is sealed when the request is parsed, neither the replicated command nor its event carry it in plaintext
+#[derive(Debug, Serialize, Deserialize, ToSchema, FieldName)]
+pub struct SetPppoeCredentials {
+    pub id: WanId,
+    pub username: PppoeUsername,
+    pub password: PppoePassword
+}
+
+#[derive(Debug, Error)]
+pub enum SetPppoeCredentialsError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Conflict(#[from] VersionConflict),
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
+    #[error("Wan with id x can't be found")]
+    WanNotFound,
+    #[error("PPPoE credentials are only available for PPPoE Connections")]
+    ConnectionIsNotPPPoE,
+    #[error("PPPoE credentials already set")]
+    AlreadySet
+}
+
+impl Payload for SetPppoeCredentials {
+    type Ok = ();
+    type Err = SetPppoeCredentialsError;
+}
+
+impl SetPppoeCredentials {
+    fn exec(&self, wan: Option<Wan>) -> Result<Wan, SetPppoeCredentialsError> {
+        let mut wan = wan.filter(|wan| !wan.deleted).ok_or(SetPppoeCredentialsError::WanNotFound)?;
+        if wan.mode != WanMode::PPPoE {
+            Err(SetPppoeCredentialsError::ConnectionIsNotPPPoE)?
+        }
+        // Every seal uses a fresh nonce, passwords can only be compared once opened
+        if let Some(pppoe) = &wan.pppoe {
+            let same_password = pppoe.password.0.open().ok().is_some_and(|current| self.password.0.open().is_ok_and(|new| new == current));
+            if pppoe.username == self.username && same_password {
+                Err(SetPppoeCredentialsError::AlreadySet)?
+            }
+        }
+        wan.process(WanEvent::PppoeCredentialsSet { username: self.username.clone(), password: self.password.clone() });
+        Ok(wan)
+    }
+}
+
+impl Process for SetPppoeCredentials {
+    type Actor = RackdCmdActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let wan = tx.load(self.id)?;
+        self.exec(wan).map(|mut wan| {
+            tx.save(&mut wan)?;
+            tx.commit()?;
+            Ok(())
+        })?
+    }
+}
+
+pub mod casts {
+    use crate::{actors::cmd::RackdCmd, db::cmd::traits::EntityStoreError, util::actor::Msg, wan::cmd::WanCmd};
+    use super::{SetPppoeCredentials, SetPppoeCredentialsError};
+
+    impl From<EntityStoreError> for SetPppoeCredentialsError {
+        fn from(error: EntityStoreError) -> Self {
+            match error {
+                EntityStoreError::Db(e) => Self::Db(e),
+                EntityStoreError::Conflict(e) => Self::Conflict(e)
+            }
+        }
+    }
+
+    impl From<Msg<SetPppoeCredentials>> for RackdCmd {
+        fn from(cmd: Msg<SetPppoeCredentials>) -> Self {
+            Self::Wan(WanCmd::SetPppoeCredentials(cmd))
+        }
+    }
+}
+
+pub mod api {
+    use std::collections::HashMap;
+    use serde_json::Value;
+    use axum::{extract::{OriginalUri, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, util::api::{Error, Json, Response, TryFromJson}, wan::model::values::{PppoePassword, PppoeUsername, WanId}};
+    use super::{SetPppoeCredentials, SetPppoeCredentialsError, SetPppoeCredentialsFieldName};
+
+    #[utoipa::path(patch, path = "/wan/pppoe", tag = "wan",
+        request_body = SetPppoeCredentials,
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn set_pppoe_credentials(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Json(cmd): Json<SetPppoeCredentials>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.exec(cmd).await
+            .map(|_| Response::ok((), path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl TryFromJson for SetPppoeCredentials {
+        fn try_from(mut map: HashMap<String, Value>) -> Result<Self, Vec<Error>> {
+            Self::check_keys(&map, SetPppoeCredentials::as_field_name_array().map(|f| f.name()))?;
+            let id = map.remove(SetPppoeCredentialsFieldName::Id.name()).unwrap_or_default();
+            let username = map.remove(SetPppoeCredentialsFieldName::Username.name()).unwrap_or_default();
+            let password = map.remove(SetPppoeCredentialsFieldName::Password.name()).unwrap_or_default();
+
+            match (WanId::try_from(id), PppoeUsername::try_from(username), PppoePassword::try_from(password)) {
+                (Ok(id), Ok(username), Ok(password)) => Ok(Self { id, username, password }),
+                (r1, r2, r3) => {
+                    let e1 = r1.map_err(Error::from).err();
+                    let e2 = r2.map_err(Error::from).err();
+                    let e3 = r3.map_err(Error::from).err();
+
+                    let errors: Vec<Error> = [e1, e2, e3].into_iter().flatten().collect();
+                    Err(errors)
+                }
+            }
+        }
+    }
+
+    impl From<SetPppoeCredentialsError> for Error {
+        fn from(error: SetPppoeCredentialsError) -> Self {
+            let msg = error.to_string();
+            match error {
+                SetPppoeCredentialsError::Db(_) => Error::new("SET_PPPOE_CREDENTIALS_DB_ERROR", msg),
+                SetPppoeCredentialsError::Conflict(_) => Error::new("SET_PPPOE_CREDENTIALS_CONFLICT", msg),
+                SetPppoeCredentialsError::Cluster(e) => Error::from(e),
+                SetPppoeCredentialsError::WanNotFound => Error::new("SET_PPPOE_CREDENTIALS_WAN_NOT_FOUND", msg),
+                SetPppoeCredentialsError::ConnectionIsNotPPPoE => Error::new("SET_PPPOE_CREDENTIALS_CONNECTION_IS_NOT_PPPOE", msg),
+                SetPppoeCredentialsError::AlreadySet => Error::new("SET_PPPOE_CREDENTIALS_ALREADY_SET", msg)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+    use crate::wan::{cmd::set_pppoe::{SetPppoeCredentials, SetPppoeCredentialsError}, model::values::{PppoePassword, PppoeUsername, WanId, WanMode}, query::{get_by_key::GetWanById, get_history::GetWanHistory}};
+    use crate::util::test_support::create_wan;
+
+    fn credentials(id: WanId, username: &str, password: &str) -> SetPppoeCredentials {
+        SetPppoeCredentials {
+            id,
+            username: PppoeUsername::try_from(Value::from(username)).unwrap(),
+            password: PppoePassword::try_from(Value::from(password)).unwrap()
+        }
+    }
+
+    #[tokio::test]
+    async fn cant_set_credentials_on_ipoe() {
+        let (rackd, _, id) = create_wan(WanMode::IPoE).await;
+        let cmd = credentials(id, "user@movistar.pe", "hunter2");
+        assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, SetPppoeCredentialsError::ConnectionIsNotPPPoE)));
+    }
+
+    #[tokio::test]
+    async fn password_never_leaves_sealed() {
+        let (rackd, _, id) = create_wan(WanMode::PPPoE).await;
+        let cmd = credentials(id, "user@movistar.pe", "hunter2");
+        assert!(!format!("{:?}", cmd).contains("hunter2"));
+        rackd.exec(cmd).await.unwrap();
+
+        let wan = rackd.query(GetWanById { id }).await.unwrap();
+        assert_eq!(wan.pppoe_username.unwrap().to_string(), "user@movistar.pe");
+        let history = rackd.query(GetWanHistory { id }).await.unwrap();
+        assert!(!serde_json::to_string(&history).unwrap().contains("hunter2"));
+
+        let cmd = credentials(id, "user@movistar.pe", "hunter2");
+        assert!(rackd.exec(cmd).await.is_err_and(|e| matches!(e, SetPppoeCredentialsError::AlreadySet)));
+        rackd.exec(credentials(id, "user@movistar.pe", "hunter3")).await.unwrap();
+    }
+}
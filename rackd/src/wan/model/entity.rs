@@ -1,5 +1,6 @@
+use macaddr::MacAddr6;
 use serde::{Serialize, Deserialize};
-use crate::{net::{Ipv4Params, MacAddr, NetName, VlanId}, rack::{Rack, RackId}, trunk::model::{Trunk, TrunkId}, util::models::{Entity, Id, Metadata}};
+use crate::{net::{Ipv4Params, MacAddr, NetName, VlanId}, rack::{Rack, RackId}, trunk::model::{Trunk, TrunkId}, util::models::{Entity, Id, Metadata, SoftDelete}};
 use super::values::*;
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -13,9 +14,10 @@ pub struct Wan {
     pub mode: WanMode,
     pub mac: MacAddr,
     pub ipv4: Ipv4Params,
-    // pub ipv6: Ipv6Address,
-    pub pppoe: WanPPPoE,
-    pub dhcp6: WanDhcp6
+    pub ipv6: WanIpv6,
+    pub pppoe: Option<WanPPPoE>,
+    pub dhcp6: WanDhcp6,
+    pub deleted: bool
 }
 
 impl Entity for Wan {
@@ -42,26 +44,62 @@ impl Entity for Wan {
             WanEvent::Renamed { to, .. } => {
                 self.name = to.clone();
             },
+            WanEvent::Deleted => {
+                self.deleted = true;
+            },
+            WanEvent::Restored => {
+                self.deleted = false;
+            },
+            WanEvent::ModeSwitched { to, .. } => {
+                self.mode = *to;
+                if *to != WanMode::PPPoE {
+                    self.pppoe = None;
+                }
+            },
             WanEvent::MacAddrSet { to, .. } => {
                 self.mac = *to;
             },
             WanEvent::Ipv4ParamsSet { to, .. } => {
                 self.ipv4 = *to;
             },
-            // WanEvent::Ipv6Set { to, .. } => {
-            //     self.ip.ipv6 = to.clone();
-            // }
+            WanEvent::Ipv6Set { to, .. } => {
+                self.ipv6 = to.clone();
+            },
+            WanEvent::Dhcp6IapdSet { to, .. } => {
+                self.dhcp6.iapd = *to;
+            },
+            WanEvent::Dhcp6IanaSet { to, .. } => {
+                self.dhcp6.iana = *to;
+            },
+            WanEvent::PppoeCredentialsSet { username, password } => {
+                self.pppoe = Some(WanPPPoE { username: username.clone(), password: password.clone() });
+            },
+            e => if let Some(duid) = e.duid() {
+                self.dhcp6.duid = duid;
+            }
         }
     }
 }
 
+impl SoftDelete for Wan {
+    fn deleted(&self) -> bool {
+        self.deleted
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum WanEvent {
     Created { id: WanId, rack: Rack, trunk: Trunk, vlan: VlanId, name: NetName, mode: WanMode },
     Renamed { from: NetName, to: NetName },
+    Deleted,
+    Restored,
+    // Leaving PPPoE drops the credentials
+    ModeSwitched { from: WanMode, to: WanMode },
     MacAddrSet { from: MacAddr, to: MacAddr },
     Ipv4ParamsSet { from: Ipv4Params, to: Ipv4Params },
-    // Ipv6AddrSet { from: WanIpv6, to: WanIpv6 }
+    Ipv6Set { from: WanIpv6, to: WanIpv6 },
+    // The password is sealed, the previous credentials aren't kept around
+    PppoeCredentialsSet { username: PppoeUsername, password: PppoePassword },
     // Ipv6SetToRA(Ipv6SetToRA),
     // Ipv6SetToStatic(Ipv6SetToStatic),
 
@@ -71,20 +109,17 @@ pub enum WanEvent {
     // Ipv4Disabled(Ipv4Disabled),
 
     // DHCPv6 IA for Prefix Delegation
-    // Dhcp6IAPDAdded { iaid: u32, prefix_hint: Ipv6Prefix, preferred_lt: u32, valid_lt: u32 },
-    // Dhcp6IAPDPrefixHintUpdated { iaid: u32, prefix_hint: Ipv6Prefix },
-    // Dhcp6IAPDPreferredLTUpdated { iaid: u32, preferred_lt: u32 },
-    // Dhcp6IAPDValidLTUpdated { iaid: u32, valid_lt: u32 },
+    Dhcp6IapdSet { from: Dhcp6Iapd, to: Dhcp6Iapd },
     // DHCPv6 IA for Non-temporary Address
-    // Dhcp6IANAAdded { iaid: u32, preferred_lt: u32, valid_lt: u32 },
+    Dhcp6IanaSet { from: Dhcp6Iana, to: Dhcp6Iana },
     // DHCPv6 DUID
-    // Dhcp6DuidSwitchedToAutoEN,
-    // Dhcp6DuidSwitchedToEN { pen: u16, id: u128 },
-    // Dhcp6DuidSwitchedToAutoLL,
-    // Dhcp6DuidSwitchedToLL { hw_type: u16, mac: MacAddr6 },
-    // Dhcp6DuidSwitchedToAutoLLT,
-    // Dhcp6DuidSwitchedToLLT { hw_type: u16, mac: MacAddr6, time: u32 },
-    // Dhcp6DuidSwitchedToRaw { value: u128 },
+    Dhcp6DuidSwitchedToAutoEN,
+    Dhcp6DuidSwitchedToEN { pen: u32, #[serde(with = "hex_u128")] id: u128 },
+    Dhcp6DuidSwitchedToAutoLL,
+    Dhcp6DuidSwitchedToLL { hw_type: u16, mac: MacAddr6 },
+    Dhcp6DuidSwitchedToAutoLLT,
+    Dhcp6DuidSwitchedToLLT { hw_type: u16, mac: MacAddr6, time: u32 },
+    Dhcp6DuidSwitchedToRaw { #[serde(with = "hex_u128")] value: u128 },
 
 
 
@@ -93,8 +128,24 @@ pub enum WanEvent {
 //     LinkWentDown { down: LinkDown },
 }
 
+impl WanEvent {
+    /// DUID the wan switched to, for the Dhcp6DuidSwitchedTo* events
+    pub fn duid(&self) -> Option<Dhcp6Duid> {
+        match *self {
+            Self::Dhcp6DuidSwitchedToAutoEN => Some(Dhcp6Duid::AutoEN),
+            Self::Dhcp6DuidSwitchedToEN { pen, id } => Some(Dhcp6Duid::EN(DuidEN { pen, id })),
+            Self::Dhcp6DuidSwitchedToAutoLL => Some(Dhcp6Duid::AutoLL),
+            Self::Dhcp6DuidSwitchedToLL { hw_type, mac } => Some(Dhcp6Duid::LL(DuidLL { hw_type, mac })),
+            Self::Dhcp6DuidSwitchedToAutoLLT => Some(Dhcp6Duid::AutoLLT),
+            Self::Dhcp6DuidSwitchedToLLT { hw_type, mac, time } => Some(Dhcp6Duid::LLT(DuidLLT { hw_type, mac, time })),
+            Self::Dhcp6DuidSwitchedToRaw { value } => Some(Dhcp6Duid::Raw(value)),
+            _ => None
+        }
+    }
+}
+
 pub mod casts {
-    use crate::{trunk::model::TrunkEvent, util::models::EventData};
+    use crate::{trunk::model::TrunkEvent, util::models::{casts::UnexpectedEvent, EventData}};
     use super::WanEvent;
 
     impl From<WanEvent> for EventData {
@@ -103,9 +154,31 @@ pub mod casts {
         }
     }
 
+    impl TryFrom<EventData> for WanEvent {
+        type Error = UnexpectedEvent;
+
+        fn try_from(e: EventData) -> Result<Self, Self::Error> {
+            match e {
+                EventData::Wan(e) => Ok(e),
+                e => Err(UnexpectedEvent(e))
+            }
+        }
+    }
+
     impl From<TrunkEvent> for EventData {
         fn from(e: TrunkEvent) -> Self {
             Self::Trunk(e)
         }
     }
+
+    impl TryFrom<EventData> for TrunkEvent {
+        type Error = UnexpectedEvent;
+
+        fn try_from(e: EventData) -> Result<Self, Self::Error> {
+            match e {
+                EventData::Trunk(e) => Ok(e),
+                e => Err(UnexpectedEvent(e))
+            }
+        }
+    }
 }
\ No newline at end of file
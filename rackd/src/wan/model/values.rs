@@ -1,8 +1,10 @@
 use std::{fmt::Display, net::Ipv6Addr};
+use chrono::{DateTime, Utc};
 use macaddr::MacAddr6;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use utoipa::ToSchema;
-use crate::{net::{Ipv6HostAddr, Ipv6Prefix}, util::models::Id};
+use crate::{net::{Ipv6HostAddr, Ipv6Prefix}, rack::RackId, util::{models::Id, secret::Sealed}};
 
 #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, ToSchema)]
 pub struct WanId(pub Id);
@@ -56,12 +58,30 @@ pub struct ISPL2Conf {
 // ///             to configure the CPE's WAN interface and the Delegated Prefix should be statically configured on the PD daemon. 
 
 
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct WanPPPoE {
-    pub username: String,
-    pub password: String
+    pub username: PppoeUsername,
+    pub password: PppoePassword
 }
 
+/// Chars that would break out of the quoted strings of a pppd peer file
+pub const PPPOE_FORBIDDEN_CHARS: [char; 2] = ['"', '\\'];
+pub const PPPOE_MAX_LEN: usize = 255;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct PppoeUsername(String);
+
+impl Display for PppoeUsername {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Sealed as soon as it's parsed, see `util::secret`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[schema(value_type = String, format = Password)]
+pub struct PppoePassword(pub Sealed);
+
 // #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 // pub struct WanIp {
 //     pub ipv6: WanIpv6,
@@ -91,14 +111,14 @@ pub struct Ipv6Host {
     pub gateway: Ipv6Addr
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
 pub struct WanDhcp6 {
     pub duid: Dhcp6Duid,
     pub iana: Dhcp6Iana,
     pub iapd: Dhcp6Iapd
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub struct Dhcp6Iana {
     pub iaid: u32,
     pub valid_lt: u32,
@@ -115,9 +135,10 @@ impl Default for Dhcp6Iana {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub struct Dhcp6Iapd {
     pub iaid: u32,
+    /// ::/0 asks for whatever the ISP delegates
     pub prefix_hint: Ipv6Prefix,
     pub valid_lt: u32,
     pub preferred_lt: u32
@@ -134,8 +155,8 @@ impl Default for Dhcp6Iapd {
     }
 }
 
-/// Based on https://datatracker.ietf.org/doc/html/rfc3315#section-9
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+/// Based on https://datatracker.ietf.org/doc/html/rfc8415#section-11
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub enum Dhcp6Duid {
     LLT(DuidLLT),   // DUID-LLT (Type 1: Link-Layer Address + Time)
     AutoLLT,        // Use L2's MAC Address as the LLA + rackd installation time as the Time
@@ -143,7 +164,7 @@ pub enum Dhcp6Duid {
     AutoEN,         // Use 43793 as the Vendor and the Rack's ID as the Vendor Assigned ID
     LL(DuidLL),     // DUID-LL (Type 3: Link-Layer Address)     
     AutoLL,         // Use L2's MAC Address as the LLA
-    Raw(u128)
+    Raw(#[serde(with = "hex_u128")] u128)
 }
 
 impl Default for Dhcp6Duid {
@@ -152,23 +173,74 @@ impl Default for Dhcp6Duid {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+impl Dhcp6Duid {
+    /// Private Enterprise Number used by AutoEN
+    pub const PEN: u32 = 43793;
+    /// https://www.iana.org/assignments/arp-parameters/arp-parameters.xhtml
+    pub const HW_TYPE_ETHERNET: u16 = 1;
+
+    /// DUID as sent in the Client Identifier option, `mac` is the WAN's effective MAC and
+    /// `time` the rack's installation time, only the Auto variants that need them use them
+    pub fn to_wire(&self, rack: RackId, mac: Option<MacAddr6>, time: DateTime<Utc>) -> Result<Vec<u8>, DuidUnresolved> {
+        let ll = |hw_type: u16, mac: MacAddr6| [&hw_type.to_be_bytes()[..], mac.as_bytes()].concat();
+        let duid = match *self {
+            Self::LLT(llt) => [&1u16.to_be_bytes()[..], &llt.hw_type.to_be_bytes(), &llt.time.to_be_bytes(), llt.mac.as_bytes()].concat(),
+            Self::AutoLLT => {
+                let mac = mac.ok_or(DuidUnresolved)?;
+                [&1u16.to_be_bytes()[..], &Self::HW_TYPE_ETHERNET.to_be_bytes(), &Self::time(time).to_be_bytes(), mac.as_bytes()].concat()
+            },
+            Self::EN(en) => [&2u16.to_be_bytes()[..], &en.pen.to_be_bytes(), &en.id.to_be_bytes()].concat(),
+            Self::AutoEN => [&2u16.to_be_bytes()[..], &Self::PEN.to_be_bytes(), rack.0.as_bytes()].concat(),
+            Self::LL(ll_) => [&3u16.to_be_bytes()[..], &ll(ll_.hw_type, ll_.mac)].concat(),
+            Self::AutoLL => [&3u16.to_be_bytes()[..], &ll(Self::HW_TYPE_ETHERNET, mac.ok_or(DuidUnresolved)?)].concat(),
+            Self::Raw(value) => value.to_be_bytes().to_vec()
+        };
+        Ok(duid)
+    }
+
+    /// Seconds since midnight (UTC), January 1, 2000, modulo 2^32
+    pub fn time(timestamp: DateTime<Utc>) -> u32 {
+        const EPOCH_2000: i64 = 946684800;
+        (timestamp.timestamp() - EPOCH_2000).rem_euclid(1 << 32) as u32
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("The WAN's MAC is automatic and hasn't been resolved from its link")]
+pub struct DuidUnresolved;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub struct DuidLLT {
-    hw_type: u16, // Set it to 1 for ethernet: https://www.iana.org/assignments/arp-parameters/arp-parameters.xhtml
-    time: u32,
-    address: u128
+    pub hw_type: u16, // Set it to 1 for ethernet: https://www.iana.org/assignments/arp-parameters/arp-parameters.xhtml
+    pub time: u32,
+    pub mac: MacAddr6
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub struct DuidEN {
-    pub pen: u16, // Private Enterprise Number
+    pub pen: u32, // Private Enterprise Number
+    #[serde(with = "hex_u128")]
     pub id: u128 // Vendor Assigned ID
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub struct DuidLL {
-    hw_type: u16, // Set it to 1 for ethernet
-    address: u128
+    pub hw_type: u16, // Set it to 1 for ethernet
+    pub mac: MacAddr6
+}
+
+/// u128s go out as hex strings, JSON numbers (and sqlite's json functions) can't hold them
+pub mod hex_u128 {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(value: &u128, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        serializer.serialize_str(&format!("{:032x}", value))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<u128, D::Error> where D: Deserializer<'de> {
+        let s = String::deserialize(deserializer)?;
+        u128::from_str_radix(&s, 16).map_err(serde::de::Error::custom)
+    }
 }
 
 // #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
@@ -178,10 +250,14 @@ pub struct DuidLL {
 // }
 
 pub mod casts {
+    use std::{net::Ipv6Addr, str::FromStr};
     use serde_json::Value;
     use thiserror::Error;
-    use crate::util::models::{casts::IdError, Id};
-    use super::{WanId, WanMode};
+    use macaddr::MacAddr6;
+    use serde_json::Map;
+    use crate::{net::{Ipv6HostAddr, Ipv6Prefix, Ipv6PrefixLen}, util::models::{casts::IdError, Id}};
+    use crate::util::secret::{Sealed, SealedError};
+    use super::{Dhcp6Duid, Dhcp6Iana, Dhcp6Iapd, DuidEN, DuidLL, DuidLLT, Ipv6Host, PppoePassword, PppoeUsername, WanId, WanIpv6, WanMode, PPPOE_FORBIDDEN_CHARS, PPPOE_MAX_LEN};
 
     impl From<WanId> for Id {
         fn from(value: WanId) -> Self {
@@ -226,11 +302,190 @@ pub mod casts {
             }
         }
     }
+
+    #[derive(Debug, Error)]
+    pub enum WanIpv6Error {
+        #[error("Value is not \"auto\" or an Object [{}]", .0)]
+        InvalidType(Value),
+        #[error("Value is not a valid IPv6 address [{}]", .0)]
+        InvalidAddr(Value),
+        #[error("Value is not a valid IPv6 prefix length [{}]", .0)]
+        InvalidPrefixLen(Value),
+        #[error("No value provided")]
+        MissingValue
+    }
+
+    /// Either "auto" or {"addr": "2001:db8::2", "prefix_len": 64, "gateway": "fe80::1"}
+    impl TryFrom<Value> for WanIpv6 {
+        type Error = WanIpv6Error;
+
+        fn try_from(value: Value) -> Result<Self, Self::Error> {
+            let addr = |value: Option<Value>| match value {
+                Some(Value::String(s)) => Ipv6Addr::from_str(&s).map_err(|_| WanIpv6Error::InvalidAddr(Value::String(s))),
+                Some(value) => Err(WanIpv6Error::InvalidAddr(value)),
+                None => Err(WanIpv6Error::MissingValue)
+            };
+            match value {
+                Value::String(s) if s.eq_ignore_ascii_case("auto") => Ok(WanIpv6::Auto),
+                Value::Object(mut map) => {
+                    let prefix_len = match map.remove("prefix_len") {
+                        Some(Value::Number(n)) => n.as_u64()
+                            .and_then(|len| u8::try_from(len).ok())
+                            .and_then(|len| Ipv6PrefixLen::try_from(len).ok())
+                            .ok_or(WanIpv6Error::InvalidPrefixLen(Value::Number(n)))?,
+                        Some(value) => Err(WanIpv6Error::InvalidPrefixLen(value))?,
+                        None => Err(WanIpv6Error::MissingValue)?
+                    };
+                    let (addr, gateway) = (addr(map.remove("addr"))?, addr(map.remove("gateway"))?);
+                    Ok(WanIpv6::Static(Ipv6Host { addr: Ipv6HostAddr { addr, prefix_len }, gateway }))
+                },
+                Value::Null => Err(WanIpv6Error::MissingValue),
+                _ => Err(WanIpv6Error::InvalidType(value))
+            }
+        }
+    }
+
+    #[derive(Debug, Error)]
+    pub enum PppoeCredentialError {
+        #[error("PPPoE {0} is not a String")]
+        InvalidType(&'static str),
+        #[error("PPPoE {0} is empty or longer than {max} characters", max = PPPOE_MAX_LEN)]
+        InvalidLength(&'static str),
+        #[error("PPPoE {0} contains control characters or any of: {chars}", chars = PPPOE_FORBIDDEN_CHARS.iter().collect::<String>())]
+        InvalidCharacters(&'static str),
+        #[error("PPPoE {0} not provided")]
+        MissingValue(&'static str),
+        #[error(transparent)]
+        Sealed(#[from] SealedError)
+    }
+
+    /// Errors never echo the value back, it might be a mistyped password
+    fn pppoe_credential(field: &'static str, value: Value) -> Result<String, PppoeCredentialError> {
+        match value {
+            Value::String(s) if s.is_empty() || s.chars().count() > PPPOE_MAX_LEN => Err(PppoeCredentialError::InvalidLength(field)),
+            Value::String(s) if s.chars().any(|c| c.is_control() || PPPOE_FORBIDDEN_CHARS.contains(&c)) => Err(PppoeCredentialError::InvalidCharacters(field)),
+            Value::String(s) => Ok(s),
+            Value::Null => Err(PppoeCredentialError::MissingValue(field)),
+            _ => Err(PppoeCredentialError::InvalidType(field))
+        }
+    }
+
+    impl TryFrom<Value> for PppoeUsername {
+        type Error = PppoeCredentialError;
+
+        fn try_from(value: Value) -> Result<Self, Self::Error> {
+            Ok(PppoeUsername(pppoe_credential("username", value)?))
+        }
+    }
+
+    impl TryFrom<Value> for PppoePassword {
+        type Error = PppoeCredentialError;
+
+        fn try_from(value: Value) -> Result<Self, Self::Error> {
+            Ok(PppoePassword(Sealed::seal(&pppoe_credential("password", value)?)?))
+        }
+    }
+
+    #[derive(Debug, Error)]
+    pub enum Dhcp6Error {
+        #[error("Value is not an Object [{}]", .0)]
+        InvalidType(Value),
+        #[error("DUID type is not valid [{}]", .0)]
+        InvalidOption(String),
+        #[error("Field {} is not valid [{}]", .0, .1)]
+        InvalidField(&'static str, Value),
+        #[error("No value provided for {}", .0)]
+        MissingValue(&'static str)
+    }
+
+    fn field<T, F>(map: &mut Map<String, Value>, key: &'static str, parse: F) -> Result<T, Dhcp6Error> where F: Fn(&Value) -> Option<T> {
+        match map.remove(key) {
+            Some(Value::Null) | None => Err(Dhcp6Error::MissingValue(key)),
+            Some(value) => parse(&value).ok_or(Dhcp6Error::InvalidField(key, value))
+        }
+    }
+
+    fn u16_field(map: &mut Map<String, Value>, key: &'static str) -> Result<u16, Dhcp6Error> {
+        field(map, key, |v| v.as_u64().and_then(|n| u16::try_from(n).ok()))
+    }
+
+    fn u32_field(map: &mut Map<String, Value>, key: &'static str) -> Result<u32, Dhcp6Error> {
+        field(map, key, |v| v.as_u64().and_then(|n| u32::try_from(n).ok()))
+    }
+
+    fn hex_field(map: &mut Map<String, Value>, key: &'static str) -> Result<u128, Dhcp6Error> {
+        field(map, key, |v| v.as_str().and_then(|s| u128::from_str_radix(&s.replace(':', ""), 16).ok()))
+    }
+
+    fn mac_field(map: &mut Map<String, Value>, key: &'static str) -> Result<MacAddr6, Dhcp6Error> {
+        field(map, key, |v| v.as_str().and_then(|s| MacAddr6::from_str(s).ok()))
+    }
+
+    /// Either "auto_en", "auto_ll", "auto_llt" or an Object with a "type" of
+    /// "en" (pen, id), "ll" (hw_type, mac), "llt" (hw_type, mac, time) or "raw" (value), ids and values in hex
+    impl TryFrom<Value> for Dhcp6Duid {
+        type Error = Dhcp6Error;
+
+        fn try_from(value: Value) -> Result<Self, Self::Error> {
+            let mut map = match value {
+                Value::String(kind) => Map::from_iter([(String::from("type"), Value::String(kind))]),
+                Value::Object(map) => map,
+                Value::Null => Err(Dhcp6Error::MissingValue("duid"))?,
+                _ => Err(Dhcp6Error::InvalidType(value))?
+            };
+            let kind = field(&mut map, "type", |v| v.as_str().map(|s| s.to_lowercase()))?;
+            match kind.as_str() {
+                "auto_en" => Ok(Dhcp6Duid::AutoEN),
+                "auto_ll" => Ok(Dhcp6Duid::AutoLL),
+                "auto_llt" => Ok(Dhcp6Duid::AutoLLT),
+                "en" => Ok(Dhcp6Duid::EN(DuidEN { pen: u32_field(&mut map, "pen")?, id: hex_field(&mut map, "id")? })),
+                "ll" => Ok(Dhcp6Duid::LL(DuidLL { hw_type: u16_field(&mut map, "hw_type")?, mac: mac_field(&mut map, "mac")? })),
+                "llt" => Ok(Dhcp6Duid::LLT(DuidLLT { hw_type: u16_field(&mut map, "hw_type")?, mac: mac_field(&mut map, "mac")?, time: u32_field(&mut map, "time")? })),
+                "raw" => Ok(Dhcp6Duid::Raw(hex_field(&mut map, "value")?)),
+                _ => Err(Dhcp6Error::InvalidOption(kind))
+            }
+        }
+    }
+
+    /// {"iaid": 1, "valid_lt": 2000, "preferred_lt": 1500}
+    impl TryFrom<Value> for Dhcp6Iana {
+        type Error = Dhcp6Error;
+
+        fn try_from(value: Value) -> Result<Self, Self::Error> {
+            match value {
+                Value::Object(mut map) => Ok(Dhcp6Iana {
+                    iaid: u32_field(&mut map, "iaid")?,
+                    valid_lt: u32_field(&mut map, "valid_lt")?,
+                    preferred_lt: u32_field(&mut map, "preferred_lt")?
+                }),
+                Value::Null => Err(Dhcp6Error::MissingValue("iana")),
+                _ => Err(Dhcp6Error::InvalidType(value))
+            }
+        }
+    }
+
+    /// {"iaid": 1, "prefix_hint": "::/56", "valid_lt": 0, "preferred_lt": 0}
+    impl TryFrom<Value> for Dhcp6Iapd {
+        type Error = Dhcp6Error;
+
+        fn try_from(value: Value) -> Result<Self, Self::Error> {
+            match value {
+                Value::Object(mut map) => Ok(Dhcp6Iapd {
+                    iaid: u32_field(&mut map, "iaid")?,
+                    prefix_hint: field(&mut map, "prefix_hint", |v| v.as_str().and_then(|s| Ipv6Prefix::from_str(s).ok()))?,
+                    valid_lt: u32_field(&mut map, "valid_lt")?,
+                    preferred_lt: u32_field(&mut map, "preferred_lt")?
+                }),
+                Value::Null => Err(Dhcp6Error::MissingValue("iapd")),
+                _ => Err(Dhcp6Error::InvalidType(value))
+            }
+        }
+    }
 }
 
 pub mod api {
     use crate::util::api::Error;
-    use super::casts::{WanIdError, WanModeError};
+    use super::casts::{Dhcp6Error, PppoeCredentialError, WanIdError, WanIpv6Error, WanModeError};
 
     impl From<WanIdError> for Error {
         fn from(error: WanIdError) -> Self {
@@ -243,6 +498,24 @@ pub mod api {
             Error::new("WAN_MODE_ERROR", error.to_string())
         }
     }
+
+    impl From<WanIpv6Error> for Error {
+        fn from(error: WanIpv6Error) -> Self {
+            Error::new("WAN_IPV6_ERROR", error.to_string())
+        }
+    }
+
+    impl From<Dhcp6Error> for Error {
+        fn from(error: Dhcp6Error) -> Self {
+            Error::new("WAN_DHCP6_ERROR", error.to_string())
+        }
+    }
+
+    impl From<PppoeCredentialError> for Error {
+        fn from(error: PppoeCredentialError) -> Self {
+            Error::new("WAN_PPPOE_CREDENTIAL_ERROR", error.to_string())
+        }
+    }
 }
 
 pub mod sqlite {
@@ -274,4 +547,44 @@ pub mod sqlite {
             Ok(value)
         }
     }
-}
\ No newline at end of file
+
+    impl ToSql for WanIpv6 {
+        fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+            let json = serde_json::to_string(self).map_err(|e| Error::ToSqlConversionFailure(Box::new(e)))?;
+            Ok(json.into())
+        }
+    }
+
+    impl FromSql for WanIpv6 {
+        fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+            let value: Self = serde_json::from_str(value.as_str()?).map_err(|e| FromSqlError::Other(Box::new(e)))?;
+            Ok(value)
+        }
+    }
+
+    impl ToSql for PppoeUsername {
+        fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+            Ok(self.0.as_str().into())
+        }
+    }
+
+    impl FromSql for PppoeUsername {
+        fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+            Ok(PppoeUsername(String::from(value.as_str()?)))
+        }
+    }
+
+    impl ToSql for WanDhcp6 {
+        fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+            let json = serde_json::to_string(self).map_err(|e| Error::ToSqlConversionFailure(Box::new(e)))?;
+            Ok(json.into())
+        }
+    }
+
+    impl FromSql for WanDhcp6 {
+        fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+            let value: Self = serde_json::from_str(value.as_str()?).map_err(|e| FromSqlError::Other(Box::new(e)))?;
+            Ok(value)
+        }
+    }
+}
@@ -1,10 +1,20 @@
 use crate::util::actor::Msg;
+pub mod get_all;
 pub mod get_by_key;
+pub mod get_duid;
+pub mod get_history;
+pub mod get_host_links;
+pub mod get_pppoe_peer;
 
 #[derive(Debug)]
 pub enum WanQuery {
+    GetAllWans(Msg<get_all::GetAllWans>),
     GetWanById(Msg<get_by_key::GetWanById>),
-    GetWanByName(Msg<get_by_key::GetWanByName>)
+    GetWanByName(Msg<get_by_key::GetWanByName>),
+    GetWanDuid(Msg<get_duid::GetWanDuid>),
+    GetWanHistory(Msg<get_history::GetWanHistory>),
+    GetPppoePeer(Msg<get_pppoe_peer::GetPppoePeer>),
+    GetWanHostLinks(Msg<get_host_links::GetWanHostLinks>)
 }
 
 
@@ -0,0 +1,265 @@
+use std::marker::PhantomData;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use crate::{actors::query::RackdQueryActor, db::{query::traits::{DbQuery, Filter, GetPage, Order, Page}, Tx}, trunk::model::TrunkId, util::actor::{Payload, Process}, wan::{model::values::{WanId, WanMode}, views::WanView}};
+
+pub const MAX_LIMIT: usize = 1000;
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum WanSort {
+    #[default]
+    Name,
+    Vlan,
+    Trunk
+}
+
+impl WanSort {
+    fn column(&self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::Vlan => "vlan",
+            Self::Trunk => "trunk_name"
+        }
+    }
+}
+
+/// Page of the wans matching every filter given, resuming after `cursor`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetAllWans {
+    pub trunk: Option<TrunkId>,
+    pub mode: Option<WanMode>,
+    /// Only wans whose name starts with this, ignoring case
+    pub name: Option<String>,
+    pub sort: WanSort,
+    pub order: Order,
+    pub cursor: Option<WanId>,
+    pub limit: usize,
+    /// Deleted wans are left out unless asked for
+    pub include_deleted: bool
+}
+
+impl Default for GetAllWans {
+    fn default() -> Self {
+        Self {
+            trunk: None,
+            mode: None,
+            name: None,
+            sort: WanSort::default(),
+            order: Order::default(),
+            cursor: None,
+            limit: 100,
+            include_deleted: false
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum GetAllWansError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error("Limit must be between 1 and {}", MAX_LIMIT)]
+    InvalidLimit
+}
+
+impl Payload for GetAllWans {
+    type Ok = Page<WanView>;
+    type Err = GetAllWansError;
+}
+
+impl DbQuery for GetAllWans {
+    type Ok = Page<WanView>;
+
+    fn run(&self, tx: &rusqlite::Transaction) -> Result<Self::Ok, rusqlite::Error> {
+        let mut filters = vec![];
+        if let Some(trunk) = self.trunk {
+            filters.push(Filter::Eq("trunk_id", Box::new(trunk)));
+        }
+        if let Some(mode) = self.mode {
+            filters.push(Filter::Eq("mode", Box::new(mode)));
+        }
+        if let Some(name) = &self.name {
+            filters.push(Filter::Prefix("name", name.clone()));
+        }
+        let query = GetPage {
+            filters,
+            sort: self.sort.column(),
+            order: self.order,
+            cursor: self.cursor.map(|cursor| cursor.0),
+            limit: self.limit,
+            include_deleted: self.include_deleted,
+            view: PhantomData::<WanView>
+        };
+        query.run(tx)
+    }
+}
+
+impl Process for GetAllWans {
+    type Actor = RackdQueryActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        if self.limit == 0 || self.limit > MAX_LIMIT {
+            Err(GetAllWansError::InvalidLimit)?
+        }
+        let tx = actor.conn.tx()?;
+        Ok(self.run(&tx)?)
+    }
+}
+
+pub mod casts {
+    use crate::{actors::query::RackdQuery, util::actor::Msg, wan::query::WanQuery};
+    use super::GetAllWans;
+
+    impl From<Msg<GetAllWans>> for RackdQuery {
+        fn from(query: Msg<GetAllWans>) -> Self {
+            Self::Wan(WanQuery::GetAllWans(query))
+        }
+    }
+}
+
+pub mod api {
+    use axum::{extract::{OriginalUri, Query, State}, response::IntoResponse};
+    use serde::Deserialize;
+    use utoipa::IntoParams;
+    use crate::{actors::system::Rackd, db::query::traits::Order, trunk::model::TrunkId, util::api::{Error, Response}, wan::model::values::{WanId, WanMode}};
+    use super::{GetAllWans, GetAllWansError, WanSort};
+
+    #[derive(Debug, Deserialize, IntoParams)]
+    #[into_params(parameter_in = Query)]
+    pub struct WanParams {
+        /// Only wans riding this trunk
+        #[param(value_type = Option<String>)]
+        pub trunk: Option<TrunkId>,
+        /// Only wans in this mode
+        pub mode: Option<WanMode>,
+        /// Only wans whose name starts with this, ignoring case
+        pub name: Option<String>,
+        /// Defaults to name
+        pub sort: Option<WanSort>,
+        /// Defaults to asc
+        pub order: Option<Order>,
+        /// Use the `next` of the previous page
+        #[param(value_type = Option<String>)]
+        pub cursor: Option<WanId>,
+        /// Defaults to 100, at most 1000
+        pub limit: Option<usize>,
+        /// Defaults to false
+        pub include_deleted: Option<bool>
+    }
+
+    #[utoipa::path(get, path = "/wan", tag = "wan",
+        params(WanParams),
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn get_all_wans(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Query(params): Query<WanParams>) -> impl IntoResponse {
+        let path = uri.path();
+        let query = GetAllWans {
+            trunk: params.trunk,
+            mode: params.mode,
+            name: params.name,
+            sort: params.sort.unwrap_or_default(),
+            order: params.order.unwrap_or_default(),
+            cursor: params.cursor,
+            limit: params.limit.unwrap_or(100),
+            include_deleted: params.include_deleted.unwrap_or_default()
+        };
+        let response = rackd.query_hydrated(query).await
+            .map(|page| Response::ok(page, path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl From<GetAllWansError> for Error {
+        fn from(error: GetAllWansError) -> Self {
+            let msg = error.to_string();
+            match error {
+                GetAllWansError::Db(_) => Error::new("GET_ALL_WANS_DB_ERROR", msg),
+                GetAllWansError::InvalidLimit => Error::new("GET_ALL_WANS_INVALID_LIMIT", msg)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::{actors::system::Rackd, db::query::traits::Order, net::{LinkName, NetName, VlanId}, sys::link::domain::{LinkDown, LinkStatus}, trunk::{cmd::{bind::BindTrunkInterface, create::CreateTrunk}, model::TrunkName}, wan::{cmd::create::CreateWan, model::values::{WanId, WanMode}, query::get_all::{GetAllWans, GetAllWansError, WanSort}}};
+    use crate::util::test_support::init_rack;
+
+    async fn rackd_with_wans() -> Rackd {
+        let rackd = Rackd::mock().unwrap();
+        let trunk1 = init_rack(&rackd).await;
+        let trunk2 = rackd.exec(CreateTrunk { name: TrunkName::from_str("trunk2").unwrap() }).await.unwrap();
+        let wans = [(trunk1, 10, "isp1", WanMode::IPoE), (trunk1, 11, "isp2", WanMode::PPPoE), (trunk2, 12, "isp3", WanMode::IPoE), (trunk2, 13, "backup", WanMode::IPoE)];
+        for (trunk, vlan, name, mode) in wans {
+            let cmd = CreateWan {
+                trunk,
+                vlan: VlanId::try_from(vlan).unwrap(),
+                name: NetName::from_str(name).unwrap(),
+                mode
+            };
+            rackd.exec(cmd).await.unwrap();
+        }
+        rackd
+    }
+
+    #[tokio::test]
+    async fn can_filter_wans() {
+        let rackd = rackd_with_wans().await;
+        let query = GetAllWans { mode: Some(WanMode::IPoE), name: Some(String::from("isp")), ..Default::default() };
+        let page = rackd.query(query).await.unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(page.items.iter().map(|wan| wan.name.to_string()).collect::<Vec<_>>(), vec!["isp1", "isp3"]);
+        assert!(page.next.is_none());
+
+        // Prefixes ignore case and their wildcards are matched as they are
+        let trunk = page.items[0].trunk.id;
+        let cmd = CreateWan { trunk, vlan: VlanId::try_from(4001).unwrap(), name: NetName::from_str("movistar").unwrap(), mode: WanMode::IPoE };
+        rackd.exec(cmd).await.unwrap();
+        let page = rackd.query(GetAllWans { name: Some(String::from("Mov")), ..Default::default() }).await.unwrap();
+        assert_eq!(page.items.iter().map(|wan| wan.name.to_string()).collect::<Vec<_>>(), vec!["movistar"]);
+        for prefix in ["%", "_", "isp_", "\\"] {
+            let page = rackd.query(GetAllWans { name: Some(String::from(prefix)), ..Default::default() }).await.unwrap();
+            assert_eq!(page.total, 0);
+        }
+    }
+
+    #[tokio::test]
+    async fn can_page_through_sorted_wans() {
+        let rackd = rackd_with_wans().await;
+        let query = GetAllWans { sort: WanSort::Vlan, order: Order::Desc, limit: 3, ..Default::default() };
+        let first = rackd.query(query).await.unwrap();
+        assert_eq!(first.total, 4);
+        assert_eq!(first.items.iter().map(|wan| wan.name.to_string()).collect::<Vec<_>>(), vec!["backup", "isp3", "isp2"]);
+        let cursor = first.next.map(WanId);
+        assert!(cursor.is_some());
+
+        let query = GetAllWans { sort: WanSort::Vlan, order: Order::Desc, limit: 3, cursor, ..Default::default() };
+        let second = rackd.query(query).await.unwrap();
+        assert_eq!(second.items.iter().map(|wan| wan.name.to_string()).collect::<Vec<_>>(), vec!["isp1"]);
+        assert!(second.next.is_none());
+
+        let query = GetAllWans { limit: 0, ..Default::default() };
+        assert!(rackd.query(query).await.is_err_and(|e| matches!(e, GetAllWansError::InvalidLimit)));
+    }
+
+    #[tokio::test]
+    async fn pages_are_hydrated_with_the_vlan_links_of_the_node() {
+        let rackd = rackd_with_wans().await;
+        let page = rackd.query(GetAllWans::default()).await.unwrap();
+        assert!(page.items.iter().all(|wan| wan.telemetry.is_none()));
+
+        // Only the wans of a trunk bound on the node have a link, none of them exists on the host running the tests
+        let trunk1 = page.items.iter().find(|wan| wan.name.to_string() == "isp1").unwrap().trunk.id;
+        rackd.exec(BindTrunkInterface { id: trunk1, node: rackd.node, link: LinkName::from_str("rkdtrunk0").unwrap() }).await.unwrap();
+        let page = rackd.query_hydrated(GetAllWans::default()).await.unwrap();
+        for wan in page.items {
+            match wan.name.to_string().as_str() {
+                "isp1" | "isp2" => assert_eq!(wan.telemetry.unwrap().status, LinkStatus::Down(LinkDown::WentMissing)),
+                _ => assert!(wan.telemetry.is_none())
+            }
+        }
+    }
+}
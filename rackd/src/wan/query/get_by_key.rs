@@ -35,7 +35,7 @@ impl Process for GetWanById {
             Some(wan) => wan,
             None => Err(GetByKeyError::NotFound(self.id))?
         };
-        // hydrate with data from NetLink
+        // Telemetry is hydrated from the host once the wan is returned, see Rackd::query_hydrated
         Ok(wan)
     }
 }
@@ -68,7 +68,7 @@ impl Process for GetWanByName {
             Some(wan) => wan,
             None => Err(GetByKeyError::NotFound(self.name))?
         };
-        // hydrate with data from NetLink
+        // Telemetry is hydrated from the host once the wan is returned, see Rackd::query_hydrated
         Ok(wan)
     }
 }
@@ -101,7 +101,7 @@ pub mod api {
     #[axum::debug_handler]
     pub async fn get_wan_by_id(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Path(wan_id): Path<WanId>) -> impl IntoResponse {
         let path = uri.path();
-        let response = rackd.query(super::GetWanById { id: wan_id }).await
+        let response = rackd.query_hydrated(super::GetWanById { id: wan_id }).await
             .map(|wan_id| Response::ok(wan_id, path).to_axum_json())
             .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
         (axum::http::StatusCode::OK, response).into_response()    
@@ -0,0 +1,155 @@
+use rusqlite::{named_params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use crate::{actors::query::RackdQueryActor, db::{query::traits::{DbQuery, QueryRunner}, Tx}, net::MacAddr, rack::RackId, util::{actor::{Payload, Process}, models::{now, sqlite::timestamp_from_sql}}, wan::{model::values::{Dhcp6Duid, DuidUnresolved, WanId}, query::get_by_key::GetWanById}};
+
+/// DUID the wan's DHCPv6 client sends, with the Auto variants resolved
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetWanDuid {
+    pub id: WanId
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct WanDuid {
+    pub duid: Dhcp6Duid,
+    /// RFC 8415 wire format, as carried in the Client Identifier option
+    pub bytes: Vec<u8>,
+    /// Same bytes colon separated, the way dhcp clients print them
+    pub hex: String
+}
+
+#[derive(Debug, Error)]
+pub enum GetWanDuidError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error("{} not found", .0)]
+    WanNotFound(WanId),
+    #[error(transparent)]
+    Unresolved(#[from] DuidUnresolved)
+}
+
+impl Payload for GetWanDuid {
+    type Ok = WanDuid;
+    type Err = GetWanDuidError;
+}
+
+/// When the rack was initialized, used as the time of AutoLLT DUIDs
+struct GetRackInstallTime {
+    rack: RackId
+}
+
+impl DbQuery for GetRackInstallTime {
+    type Ok = Option<i64>;
+
+    fn run(&self, tx: &rusqlite::Transaction) -> Result<Self::Ok, rusqlite::Error> {
+        let sql = "SELECT timestamp FROM event WHERE stream_id = :rack ORDER BY version LIMIT 1";
+        tx.query_row(sql, named_params! { ":rack": self.rack }, |row| row.get(0)).optional()
+    }
+}
+
+impl Process for GetWanDuid {
+    type Actor = RackdQueryActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let wan = tx.run(GetWanById { id: self.id })?.ok_or(GetWanDuidError::WanNotFound(self.id))?;
+        // Until the link reports its own MAC only a spoofed one is known
+        let mac = match wan.mac {
+            MacAddr::Spoofed(mac) => Some(mac),
+            MacAddr::Auto => None
+        };
+        let installed = match tx.run(GetRackInstallTime { rack: wan.rack.id })? {
+            Some(micros) => timestamp_from_sql(micros)?,
+            None => now()
+        };
+        let duid = wan.dhcp6.duid;
+        let bytes = duid.to_wire(wan.rack.id, mac, installed)?;
+        let hex = bytes.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(":");
+        Ok(WanDuid { duid, bytes, hex })
+    }
+}
+
+pub mod casts {
+    use crate::{actors::query::RackdQuery, util::actor::Msg, wan::query::WanQuery};
+    use super::GetWanDuid;
+
+    impl From<Msg<GetWanDuid>> for RackdQuery {
+        fn from(query: Msg<GetWanDuid>) -> Self {
+            Self::Wan(WanQuery::GetWanDuid(query))
+        }
+    }
+}
+
+pub mod api {
+    use axum::{extract::{OriginalUri, Path, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, util::api::{Error, Response}, wan::model::values::WanId};
+    use super::{GetWanDuid, GetWanDuidError};
+
+    #[utoipa::path(get, path = "/wan/{wan_id}/dhcp6/duid", tag = "wan",
+        params(("wan_id" = WanId, Path, description = "Wan UUID")),
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn get_wan_duid(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Path(wan_id): Path<WanId>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.query(GetWanDuid { id: wan_id }).await
+            .map(|duid| Response::ok(duid, path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+
+    impl From<GetWanDuidError> for Error {
+        fn from(error: GetWanDuidError) -> Self {
+            let msg = error.to_string();
+            match error {
+                GetWanDuidError::Db(_) => Error::new("GET_WAN_DUID_DB_ERROR", msg),
+                GetWanDuidError::WanNotFound(_) => Error::new("GET_WAN_DUID_WAN_NOT_FOUND", msg),
+                GetWanDuidError::Unresolved(_) => Error::new("GET_WAN_DUID_UNRESOLVED", msg)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use macaddr::MacAddr6;
+    use crate::{actors::system::Rackd, net::{MacAddr, NetName, VlanId}, wan::{cmd::{create::CreateWan, set_dhcp6_duid::{SetDhcp6Duid, SetDhcp6DuidError}, set_mac::SetMacAddr}, model::values::{Dhcp6Duid, DuidEN, WanMode}, query::get_duid::{GetWanDuid, GetWanDuidError}}};
+    use crate::util::test_support::lim15109;
+
+    #[tokio::test]
+    async fn can_resolve_duids_to_wire_format() {
+        let rackd = Rackd::mock().unwrap();
+        let cmd = lim15109();
+        let initialized = rackd.exec(cmd).await.unwrap();
+        let cmd = CreateWan {
+            trunk: initialized.trunk,
+            vlan: VlanId::try_from(4001).unwrap(),
+            name: NetName::from_str("verizon").unwrap(),
+            mode: WanMode::IPoE
+        };
+        let id = rackd.exec(cmd).await.unwrap();
+
+        // AutoEN: type 2 + PEN 43793 + rack id
+        let duid = rackd.query(GetWanDuid { id }).await.unwrap();
+        assert_eq!(duid.duid, Dhcp6Duid::AutoEN);
+        assert_eq!(&duid.bytes[..6], &[0, 2, 0, 0, 0xab, 0x11]);
+        assert_eq!(&duid.bytes[6..], initialized.rack.0.as_bytes());
+
+        // AutoLL needs the effective MAC
+        rackd.exec(SetDhcp6Duid { id, duid: Dhcp6Duid::AutoLL }).await.unwrap();
+        assert!(rackd.query(GetWanDuid { id }).await.is_err_and(|e| matches!(e, GetWanDuidError::Unresolved(_))));
+        let mac = MacAddr6::new(0x76, 0xdc, 0x3a, 0x78, 0xaf, 0xd0);
+        rackd.exec(SetMacAddr { id, mac: MacAddr::Spoofed(mac) }).await.unwrap();
+        assert_eq!(rackd.query(GetWanDuid { id }).await.unwrap().hex, "00:03:00:01:76:dc:3a:78:af:d0");
+
+        let duid = Dhcp6Duid::EN(DuidEN { pen: 0, id: 1 });
+        assert!(rackd.exec(SetDhcp6Duid { id, duid }).await.is_err_and(|e| matches!(e, SetDhcp6DuidError::InvalidDuid(_))));
+        let duid = Dhcp6Duid::EN(DuidEN { pen: 9, id: 0x0102 });
+        rackd.exec(SetDhcp6Duid { id, duid }).await.unwrap();
+        let bytes = rackd.query(GetWanDuid { id }).await.unwrap().bytes;
+        assert_eq!(bytes.len(), 2 + 4 + 16);
+        assert_eq!(&bytes[..6], &[0, 2, 0, 0, 0, 9]);
+        assert_eq!(&bytes[20..], &[1, 2]);
+    }
+}
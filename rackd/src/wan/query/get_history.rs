@@ -0,0 +1,124 @@
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+use serde::Serialize;
+use crate::{actors::query::RackdQueryActor, db::{query::traits::DbQuery, Tx}, util::{actor::{Payload, Process}, models::{Event, Id}, query::GetByKeyError}, wan::model::{entity::WanEvent, values::WanId}};
+
+/// Every change made to a Wan, oldest first
+#[derive(Debug)]
+pub struct GetWanHistory {
+    pub id: WanId
+}
+
+#[derive(Debug, Serialize)]
+pub struct WanHistoryEntry {
+    pub seq: u64,
+    pub version: u32,
+    pub timestamp: DateTime<Utc>,
+    pub origin: Option<Id>,
+    pub event: WanEvent
+}
+
+impl Payload for GetWanHistory {
+    type Ok = Vec<WanHistoryEntry>;
+    type Err = GetByKeyError<WanId>;
+}
+
+impl DbQuery for GetWanHistory {
+    type Ok = Vec<WanHistoryEntry>;
+
+    fn run(&self, tx: &rusqlite::Transaction) -> Result<Self::Ok, rusqlite::Error> {
+        let sql = format!("SELECT {} FROM event WHERE stream_id = ?1 ORDER BY version", Event::select_fields());
+        let mut stmt = tx.prepare(&sql)?;
+        let mut rows = stmt.query(params![self.id.0])?;
+        let mut history = Vec::new();
+        while let Some(row) = rows.next()? {
+            let e = Event::try_from(row)?;
+            // The stream id of a Wan only holds WanEvents
+            let Ok(event) = WanEvent::try_from(e.data) else { continue };
+            history.push(WanHistoryEntry { seq: e.seq, version: e.version, timestamp: e.timestamp, origin: e.origin, event });
+        }
+        Ok(history)
+    }
+}
+
+impl Process for GetWanHistory {
+    type Actor = RackdQueryActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let history = self.run(&tx)?;
+        if history.is_empty() {
+            Err(GetByKeyError::NotFound(self.id))?
+        }
+        Ok(history)
+    }
+}
+
+pub mod casts {
+    use crate::{actors::query::RackdQuery, util::actor::Msg, wan::query::WanQuery};
+    use super::GetWanHistory;
+
+    impl From<Msg<GetWanHistory>> for RackdQuery {
+        fn from(query: Msg<GetWanHistory>) -> Self {
+            Self::Wan(WanQuery::GetWanHistory(query))
+        }
+    }
+}
+
+pub mod api {
+    use axum::{extract::{OriginalUri, Path, State}, response::IntoResponse};
+    use crate::{actors::system::Rackd, util::api::Response, wan::model::values::WanId};
+
+    #[utoipa::path(get, path = "/wan/{wan_id}/history", tag = "wan",
+        params(("wan_id" = WanId, Path, description = "Wan UUID")),
+        responses((status = OK, body = Response))
+    )]
+    #[axum::debug_handler]
+    pub async fn get_wan_history(State(rackd): State<Rackd>, OriginalUri(uri): OriginalUri, Path(wan_id): Path<WanId>) -> impl IntoResponse {
+        let path = uri.path();
+        let response = rackd.query(super::GetWanHistory { id: wan_id }).await
+            .map(|history| Response::ok(history, path).to_axum_json())
+            .unwrap_or_else(|error| Response::<()>::error(error, path).to_axum_json());
+        (axum::http::StatusCode::OK, response).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::{actors::system::Rackd, net::{NetName, VlanId}, util::query::GetByKeyError, wan::{cmd::{create::CreateWan, rename::RenameWan}, model::{entity::WanEvent, values::{WanId, WanMode}}, query::get_history::GetWanHistory}};
+    use crate::util::test_support::init_rack;
+
+    #[tokio::test]
+    async fn cant_get_history_if_wan_doesnt_exist() {
+        let rackd = Rackd::mock().unwrap();
+        let query = GetWanHistory {
+            id: WanId::new()
+        };
+        assert!(rackd.query(query).await.is_err_and(|e| matches!(e, GetByKeyError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn can_get_wan_history_in_order() {
+        let rackd = Rackd::mock().unwrap();
+        let trunk_id = init_rack(&rackd).await;
+        let cmd = CreateWan {
+            trunk: trunk_id,
+            vlan: VlanId::try_from(4000).unwrap(),
+            name: NetName::from_str("att").unwrap(),
+            mode: WanMode::IPoE
+        };
+        let wan_id = rackd.exec(cmd).await.unwrap();
+        let cmd = RenameWan {
+            id: wan_id,
+            name: NetName::from_str("verizon").unwrap()
+        };
+        rackd.exec(cmd).await.unwrap();
+
+        let history = rackd.query(GetWanHistory { id: wan_id }).await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert!(matches!(history[0].event, WanEvent::Created { .. }));
+        assert!(matches!(&history[1].event, WanEvent::Renamed { from, to } if from.to_string() == "att" && to.to_string() == "verizon"));
+        assert!(history[0].timestamp <= history[1].timestamp);
+    }
+}
@@ -0,0 +1,92 @@
+use thiserror::Error;
+use crate::{actors::query::RackdQueryActor, db::{cmd::traits::EntityStore, raft::NodeId, Tx}, net::{Ipv4Params, LinkName, MacAddr, VlanId}, trunk::model::Trunk, util::actor::{Payload, Process}, wan::model::{entity::Wan, values::WanId}};
+
+/// State the host links of the wans should converge to on the given node, every wan is returned when no `id` is given
+#[derive(Debug)]
+pub struct GetWanHostLinks {
+    pub node: NodeId,
+    pub id: Option<WanId>
+}
+
+/// 802.1Q link of a wan on top of the interface its trunk is bound to on a node
+#[derive(Debug, Clone)]
+pub struct WanHostLink {
+    pub id: WanId,
+    pub name: String,
+    /// None while the trunk isn't bound to an interface on the node
+    pub parent: Option<LinkName>,
+    pub vlan: VlanId,
+    pub mac: MacAddr,
+    pub ipv4: Ipv4Params,
+    /// The link must be removed
+    pub deleted: bool
+}
+
+#[derive(Debug, Error)]
+pub enum GetWanHostLinksError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error)
+}
+
+impl Payload for GetWanHostLinks {
+    type Ok = Vec<WanHostLink>;
+    type Err = GetWanHostLinksError;
+}
+
+impl Process for GetWanHostLinks {
+    type Actor = RackdQueryActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        let ids = match self.id {
+            Some(id) => vec![id],
+            None => {
+                let mut stmt = tx.prepare("SELECT id FROM wan_view")?;
+                stmt.query_map([], |row| row.get::<_, WanId>(0))?.collect::<Result<Vec<_>, _>>()?
+            }
+        };
+        let mut links = Vec::with_capacity(ids.len());
+        for id in ids {
+            let Some(wan) = tx.load::<Wan, _>(id)? else { continue };
+            let trunk: Option<Trunk> = tx.load(wan.trunk)?;
+            let parent = trunk.as_ref().and_then(|trunk| trunk.links.get(&self.node).cloned());
+            let deleted = wan.deleted || trunk.is_none_or(|trunk| trunk.deleted);
+            links.push(WanHostLink { id: wan.id, name: wan.name.to_string(), parent, vlan: wan.vlan, mac: wan.mac, ipv4: wan.ipv4, deleted });
+        }
+        Ok(links)
+    }
+}
+
+pub mod casts {
+    use crate::{actors::query::RackdQuery, util::actor::Msg, wan::query::WanQuery};
+    use super::GetWanHostLinks;
+
+    impl From<Msg<GetWanHostLinks>> for RackdQuery {
+        fn from(query: Msg<GetWanHostLinks>) -> Self {
+            Self::Wan(WanQuery::GetWanHostLinks(query))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::{net::LinkName, trunk::cmd::bind::BindTrunkInterface, wan::{cmd::delete::DeleteWan, model::values::WanMode, query::get_host_links::GetWanHostLinks}};
+    use crate::util::test_support::create_wan;
+
+    #[tokio::test]
+    async fn links_sit_on_the_trunk_interface_of_the_node() {
+        let (rackd, trunk, id) = create_wan(WanMode::IPoE).await;
+        let links = rackd.query(GetWanHostLinks { node: 1, id: None }).await.unwrap();
+        assert!(links.len() == 1 && links[0].parent.is_none());
+
+        rackd.exec(BindTrunkInterface { id: trunk, node: 1, link: LinkName::from_str("enp1s0").unwrap() }).await.unwrap();
+        let link = rackd.query(GetWanHostLinks { node: 1, id: Some(id) }).await.unwrap().remove(0);
+        assert_eq!(link.parent, Some(LinkName::from_str("enp1s0").unwrap()));
+        assert!(rackd.query(GetWanHostLinks { node: 2, id: Some(id) }).await.unwrap()[0].parent.is_none());
+
+        // Deleted wans are kept so their links get removed
+        rackd.exec(DeleteWan { id }).await.unwrap();
+        assert!(rackd.query(GetWanHostLinks { node: 1, id: None }).await.unwrap()[0].deleted);
+    }
+}
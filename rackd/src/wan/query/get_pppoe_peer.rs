@@ -0,0 +1,115 @@
+use serde::Serialize;
+use thiserror::Error;
+use crate::{actors::query::RackdQueryActor, db::{cmd::traits::EntityStore, raft::NodeId, Tx}, trunk::model::Trunk, util::{actor::{Payload, Process}, secret::SealedError}, wan::model::{entity::Wan, values::{WanId, WanMode}}};
+
+/// pppd peer file of a PPPoE wan on the given node, the only way to get the PPPoE password back.
+/// It's only meant for the node bringing the session up and isn't exposed through the API
+#[derive(Debug)]
+pub struct GetPppoePeer {
+    pub id: WanId,
+    pub node: NodeId
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct PppoePeer {
+    /// File name under /etc/ppp/peers
+    pub name: String,
+    pub conf: String
+}
+
+#[derive(Debug, Error)]
+pub enum GetPppoePeerError {
+    #[error("Db Error")]
+    Db(#[from] rusqlite::Error),
+    #[error("{} not found", .0)]
+    WanNotFound(WanId),
+    #[error("PPPoE peers are only available for PPPoE Connections")]
+    ConnectionIsNotPPPoE,
+    #[error("PPPoE credentials haven't been set")]
+    CredentialsNotSet,
+    #[error("Trunk of the wan isn't bound to an interface of node {}", .0)]
+    TrunkNotBound(NodeId),
+    #[error(transparent)]
+    Sealed(#[from] SealedError)
+}
+
+impl Payload for GetPppoePeer {
+    type Ok = PppoePeer;
+    type Err = GetPppoePeerError;
+}
+
+impl Process for GetPppoePeer {
+    type Actor = RackdQueryActor;
+
+    fn process(self, actor: &mut Self::Actor) -> Result<Self::Ok, Self::Err> {
+        let tx = actor.conn.tx()?;
+        // The password isn't projected into any view, it's read from the entity
+        let wan = tx.load::<Wan, _>(self.id)?.filter(|wan| !wan.deleted).ok_or(GetPppoePeerError::WanNotFound(self.id))?;
+        if wan.mode != WanMode::PPPoE {
+            Err(GetPppoePeerError::ConnectionIsNotPPPoE)?
+        }
+        let pppoe = wan.pppoe.ok_or(GetPppoePeerError::CredentialsNotSet)?;
+        // pppd runs on the same 802.1Q link the reconciler brings up for the wan
+        let nic = tx.load::<Trunk, _>(wan.trunk)?
+            .and_then(|trunk| trunk.links.get(&self.node).cloned())
+            .ok_or(GetPppoePeerError::TrunkNotBound(self.node))?
+            .vlan(wan.vlan);
+        let password = pppoe.password.0.open()?;
+        let conf = [
+            format!("# {} ({})", wan.name, wan.id.0),
+            String::from("plugin pppoe.so"),
+            format!("nic-{}", nic),
+            format!("user \"{}\"", pppoe.username),
+            format!("password \"{}\"", password),
+            String::from("hide-password"),
+            String::from("noauth"),
+            String::from("noipdefault"),
+            String::from("defaultroute"),
+            String::from("+ipv6"),
+            String::from("persist"),
+            String::from("maxfail 0"),
+            String::from("lcp-echo-interval 10"),
+            String::from("lcp-echo-failure 3")
+        ].join("\n");
+        Ok(PppoePeer { name: wan.name.to_string(), conf })
+    }
+}
+
+pub mod casts {
+    use crate::{actors::query::RackdQuery, util::actor::Msg, wan::query::WanQuery};
+    use super::GetPppoePeer;
+
+    impl From<Msg<GetPppoePeer>> for RackdQuery {
+        fn from(query: Msg<GetPppoePeer>) -> Self {
+            Self::Wan(WanQuery::GetPppoePeer(query))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use serde_json::Value;
+    use crate::{net::LinkName, trunk::cmd::bind::BindTrunkInterface, wan::{cmd::set_pppoe::SetPppoeCredentials, model::values::{PppoePassword, PppoeUsername, WanMode}, query::get_pppoe_peer::{GetPppoePeer, GetPppoePeerError}}};
+    use crate::util::test_support::create_wan;
+
+    #[tokio::test]
+    async fn peer_conf_carries_the_opened_password() {
+        let (rackd, trunk, id) = create_wan(WanMode::PPPoE).await;
+        assert!(rackd.query(GetPppoePeer { id, node: 1 }).await.is_err_and(|e| matches!(e, GetPppoePeerError::CredentialsNotSet)));
+
+        let cmd = SetPppoeCredentials {
+            id,
+            username: PppoeUsername::try_from(Value::from("user@movistar.pe")).unwrap(),
+            password: PppoePassword::try_from(Value::from("hunter2")).unwrap()
+        };
+        rackd.exec(cmd).await.unwrap();
+        assert!(rackd.query(GetPppoePeer { id, node: 1 }).await.is_err_and(|e| matches!(e, GetPppoePeerError::TrunkNotBound(1))));
+
+        rackd.exec(BindTrunkInterface { id: trunk, node: 1, link: LinkName::from_str("enp1s0").unwrap() }).await.unwrap();
+        let peer = rackd.query(GetPppoePeer { id, node: 1 }).await.unwrap();
+        assert_eq!(peer.name, "movistar");
+        assert!(peer.conf.contains("nic-enp1s0.4001\n"));
+        assert!(peer.conf.contains("user \"user@movistar.pe\"\npassword \"hunter2\""));
+    }
+}
@@ -1,9 +1,10 @@
-use log::error;
+use log::warn;
 use rusqlite::{named_params, params, Row};
 use serde::{Deserialize, Serialize};
-use crate::{db::query::traits::DbView, net::{NetName, VlanId}, org::model::Asn, rack::RackId, trunk::{model::{TrunkEvent, TrunkId, TrunkName}, views::TrunkIdView}, util::models::{Event, EventData}};
+use crate::{actors::system::Rackd, db::query::traits::{DbView, Page}, net::{Ipv4Params, LinkName, MacAddr, NetName, VlanId}, org::model::Asn, rack::RackId, trunk::{model::{TrunkEvent, TrunkId}, query::get_all::GetAllTrunks, views::TrunkIdView}, util::models::{Event, EventData}};
+use crate::sys::{hydrate::Hydrate, link::{domain::LinkTelemetry, query::GetLinksTelemetry}};
 use rusqlite::Transaction;
-use super::model::{entity::WanEvent, values::{WanId, WanMode}};
+use super::model::{entity::WanEvent, values::{PppoeUsername, WanDhcp6, WanId, WanIpv6, WanMode}};
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct WanView {
@@ -13,14 +14,20 @@ pub struct WanView {
     pub vlan: VlanId,
     pub name: NetName,
     pub mode: WanMode,
+    pub mac: MacAddr,
+    pub ipv4: Ipv4Params,
+    pub ipv6: WanIpv6,
+    pub dhcp6: WanDhcp6,
+    /// PPPoE passwords are only available to `GetPppoePeer`
+    pub pppoe_username: Option<PppoeUsername>,
+    /// Only ever true when deleted wans are asked for
+    pub deleted: bool,
     pub telemetry: Option<WanTelemetry>
     // pub prefixes: Vec<DelegatedPrefix>
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct WanTelemetry {
-    pub status: WanStatus
-}
+/// Telemetry of the 802.1Q link of the wan on the node answering the query
+pub type WanTelemetry = LinkTelemetry;
 
 #[derive(Default, Debug, Serialize, Deserialize)]
 pub struct RackIdView {
@@ -28,14 +35,48 @@ pub struct RackIdView {
     pub asn: Asn
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub enum WanStatus {
-    Up, Down
+impl Hydrate for WanView {
+    async fn hydrate(self, rackd: &Rackd) -> Self {
+        let mut wans = vec![self];
+        hydrate_wans(&mut wans, rackd).await;
+        wans.remove(0)
+    }
+}
+
+impl Hydrate for Page<WanView> {
+    async fn hydrate(mut self, rackd: &Rackd) -> Self {
+        hydrate_wans(&mut self.items, rackd).await;
+        self
+    }
 }
 
-impl Default for WanStatus {
-    fn default() -> Self {
-        Self::Down
+/// Links are the ones the WanReconciler brings up on the node (see LinkName::vlan), deleted wans
+/// and wans whose trunk isn't bound on the node have no link to report on
+async fn hydrate_wans(wans: &mut [WanView], rackd: &Rackd) {
+    let trunks = match rackd.query(GetAllTrunks).await {
+        Ok(trunks) => trunks,
+        Err(e) => {
+            warn!("[WAN] Failed to read the trunks of the wans: {}", e);
+            return;
+        }
+    };
+    let link = |wan: &WanView| -> Option<LinkName> {
+        let trunk = trunks.iter().find(|trunk| trunk.id.0 == wan.trunk.id.0)?;
+        trunk.links.get(&rackd.node).map(|parent| parent.vlan(wan.vlan))
+    };
+    let names = wans.iter().filter(|wan| !wan.deleted).filter_map(&link).collect::<Vec<_>>();
+    if names.is_empty() {
+        return;
+    }
+    let mut telemetry = match rackd.sys.send(GetLinksTelemetry { names }).await {
+        Ok(telemetry) => telemetry,
+        Err(e) => {
+            warn!("[WAN] Failed to read the telemetry of the wan links: {}", e);
+            return;
+        }
+    };
+    for wan in wans.iter_mut().filter(|wan| !wan.deleted) {
+        wan.telemetry = link(wan).and_then(|name| telemetry.remove(&name));
     }
 }
 
@@ -44,36 +85,100 @@ impl DbView for WanView {
         "wan_view"
     }
 
-    fn update(tx: &Transaction, e: &Event) {
+    fn update(tx: &Transaction, e: &Event) -> Result<(), rusqlite::Error> {
         match &e.data {
             EventData::Wan(data) => match data {
                 WanEvent::Created { id, rack, trunk, vlan, name, mode } => {
-                    let sql = format!("INSERT INTO {} (id, rack_id, rack_asn, trunk_id, trunk_name, vlan, name, mode) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)", Self::name());
-                    tx.execute(&sql, params![e.stream_id, rack.id, rack.asn, trunk.id, trunk.name, vlan, name, mode]).map_err(|e| error!("{e}")).unwrap();
+                    let sql = format!("INSERT INTO {} (id, rack_id, rack_asn, trunk_id, trunk_name, vlan, name, mode, dhcp6) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)", Self::name());
+                    tx.execute(&sql, params![e.stream_id, rack.id, rack.asn, trunk.id, trunk.name, vlan, name, mode, WanDhcp6::default()])?;
                 },
                 WanEvent::Renamed { to, .. } => {
                     let sql = format!("UPDATE {} SET name = :name WHERE id = :id", Self::name());
-                    tx.execute(&sql, named_params! { ":id": WanId(e.stream_id), ":name": to }).map_err(|e| error!("{e}")).unwrap(); 
+                    tx.execute(&sql, named_params! { ":id": WanId(e.stream_id), ":name": to })?; 
+                },
+                WanEvent::Deleted => {
+                    let sql = format!("UPDATE {} SET deleted = 1 WHERE id = :id", Self::name());
+                    tx.execute(&sql, named_params! { ":id": WanId(e.stream_id) })?;
+                },
+                WanEvent::Restored => {
+                    let sql = format!("UPDATE {} SET deleted = 0 WHERE id = :id", Self::name());
+                    tx.execute(&sql, named_params! { ":id": WanId(e.stream_id) })?;
+                },
+                WanEvent::ModeSwitched { to, .. } => {
+                    let sql = format!("UPDATE {} SET mode = :mode WHERE id = :id", Self::name());
+                    tx.execute(&sql, named_params! { ":id": WanId(e.stream_id), ":mode": to })?;
+                    if *to != WanMode::PPPoE {
+                        let sql = format!("UPDATE {} SET pppoe_username = NULL WHERE id = :id", Self::name());
+                        tx.execute(&sql, named_params! { ":id": WanId(e.stream_id) })?;
+                    }
                 },
                 WanEvent::MacAddrSet { to, .. } => {
                     let sql = format!("UPDATE {} SET mac = :mac WHERE id = :id", Self::name());
-                    tx.execute(&sql, named_params! { ":id": WanId(e.stream_id), ":mac": to }).map_err(|e| error!("{e}")).unwrap();
+                    tx.execute(&sql, named_params! { ":id": WanId(e.stream_id), ":mac": to })?;
                 },
-                // TBD
-                _ => {}
+                WanEvent::Ipv4ParamsSet { to, .. } => {
+                    let sql = format!("UPDATE {} SET ipv4 = :ipv4 WHERE id = :id", Self::name());
+                    tx.execute(&sql, named_params! { ":id": WanId(e.stream_id), ":ipv4": to })?;
+                },
+                WanEvent::Ipv6Set { to, .. } => {
+                    let sql = format!("UPDATE {} SET ipv6 = :ipv6 WHERE id = :id", Self::name());
+                    tx.execute(&sql, named_params! { ":id": WanId(e.stream_id), ":ipv6": to })?;
+                },
+                WanEvent::PppoeCredentialsSet { username, .. } => {
+                    let sql = format!("UPDATE {} SET pppoe_username = :pppoe_username WHERE id = :id", Self::name());
+                    tx.execute(&sql, named_params! { ":id": WanId(e.stream_id), ":pppoe_username": username })?;
+                },
+                WanEvent::Dhcp6IapdSet { to, .. } => {
+                    let sql = format!("UPDATE {} SET dhcp6 = json_set(dhcp6, '$.iapd', json(:iapd)) WHERE id = :id", Self::name());
+                    let iapd = serde_json::to_string(to).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+                    tx.execute(&sql, named_params! { ":id": WanId(e.stream_id), ":iapd": iapd })?;
+                },
+                WanEvent::Dhcp6IanaSet { to, .. } => {
+                    let sql = format!("UPDATE {} SET dhcp6 = json_set(dhcp6, '$.iana', json(:iana)) WHERE id = :id", Self::name());
+                    let iana = serde_json::to_string(to).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+                    tx.execute(&sql, named_params! { ":id": WanId(e.stream_id), ":iana": iana })?;
+                },
+                data => if let Some(duid) = data.duid() {
+                    let sql = format!("UPDATE {} SET dhcp6 = json_set(dhcp6, '$.duid', json(:duid)) WHERE id = :id", Self::name());
+                    let duid = serde_json::to_string(&duid).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+                    tx.execute(&sql, named_params! { ":id": WanId(e.stream_id), ":duid": duid })?;
+                }
             },
             EventData::Trunk(data) => match data {
                 TrunkEvent::Renamed { to, .. } => {
-                    let sql = format!("UPDATE {} SET trunk_name := trunk_name WHERE trunk_id = :trunk_id", Self::name());
-                    tx.execute(&sql, named_params! { ":trunk_id": TrunkId(e.stream_id), ":trunk_name": to  }).map_err(|e| error!("{e}")).unwrap();
+                    let sql = format!("UPDATE {} SET trunk_name = :trunk_name WHERE trunk_id = :trunk_id", Self::name());
+                    tx.execute(&sql, named_params! { ":trunk_id": TrunkId(e.stream_id), ":trunk_name": to  })?;
                 },
                 _ => {}
-            }
+            },
+            _ => {}
         }
+        Ok(())
+    }
+
+    fn sql_create() -> &'static str {
+        r#"
+        CREATE TABLE IF NOT EXISTS wan_view (
+            id              TEXT        PRIMARY KEY,
+            rack_id         TEXT        NOT NULL,
+            rack_asn        INTEGER     NOT NULL,
+            trunk_id        TEXT        NOT NULL,
+            trunk_name      TEXT        NOT NULL,
+            vlan            INTEGER     NOT NULL,
+            name            TEXT        NOT NULL,
+            mode            TEXT        NOT NULL,
+            mac             TEXT        NOT NULL DEFAULT '{"mode":"auto"}',
+            ipv4            TEXT        NOT NULL DEFAULT '"DHCP"',
+            ipv6            TEXT        NOT NULL DEFAULT '{"mode":"auto"}',
+            dhcp6           TEXT        NOT NULL,
+            pppoe_username  TEXT,
+            deleted         INTEGER     NOT NULL DEFAULT 0
+        )
+        "#
     }
 
     fn select_fields() -> &'static str {
-        "id, rack_id, rack_asn, trunk_id, trunk_name, vlan, name, mode"
+        "id, rack_id, rack_asn, trunk_id, trunk_name, vlan, name, mode, mac, ipv4, ipv6, dhcp6, pppoe_username, deleted"
     }
 
     fn try_from(row: &Row) -> Result<Self, rusqlite::Error> {
@@ -90,6 +195,12 @@ impl DbView for WanView {
             vlan: row.get(5)?,
             name: row.get(6)?,
             mode: row.get(7)?,
+            mac: row.get(8)?,
+            ipv4: row.get(9)?,
+            ipv6: row.get(10)?,
+            dhcp6: row.get(11)?,
+            pppoe_username: row.get(12)?,
+            deleted: row.get(13)?,
             ..Default::default()
         })
     }